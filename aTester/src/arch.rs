@@ -1,6 +1,11 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
-use crate::types::{ElfTarget, RunOptions};
+use types::KernelCrash;
+use vm::metering::MemoryAccessCounts;
+use vm::vm::VmState;
+
+use crate::types::{ElfTarget, RunOptions, Termination};
 
 #[derive(Debug, Clone)]
 pub struct RunResult {
@@ -9,9 +14,30 @@ pub struct RunResult {
     pub stderr: String,
     pub output: Vec<u8>,
     pub instruction_count: u64,
+    pub memory_accesses: MemoryAccessCounts,
     pub stack_used_bytes: u64,
     pub heap_used_bytes: u64,
     pub code_size_bytes: u64,
+    /// Kernel heap bytes in use at exit, as reported in the kernel result header.
+    pub kernel_heap_used_bytes: u64,
+    /// Kernel stack high-water mark, as reported in the kernel result header.
+    pub kernel_stack_used_bytes: u64,
+    /// Hottest taken back-edges as `(branch pc, count)`, most frequent first.
+    pub hot_back_edges: Vec<(u32, u64)>,
+    /// `ecall`s executed per syscall id, kernel and guest alike.
+    pub syscall_counts: BTreeMap<u32, u64>,
+    /// Registers, PC and privilege mode when the VM stopped.
+    pub final_state: VmState,
+    /// Why the VM stopped.
+    pub termination: Termination,
+    /// Distinct user-mode (guest program) PCs executed; empty unless
+    /// `RunOptions::coverage` is set.
+    pub executed_pcs: BTreeSet<u32>,
+    /// Crash record the kernel panic handler left in the result region, if any.
+    pub kernel_crash: Option<KernelCrash>,
+    /// Hexdump of the head of the result region; `Suite::run` attaches it to the
+    /// report's `stderr` when the evaluator fails the case.
+    pub result_dump: String,
 }
 
 #[derive(Debug)]
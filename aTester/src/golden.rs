@@ -0,0 +1,183 @@
+//! Golden files for a case's final result.
+//!
+//! A golden file records the observed `success`, `error_code` and result data of a
+//! case. Normal runs compare against it; with `ATESTER_BLESS=1` the evaluator
+//! rewrites it from the run instead, so an intentional behavior change is one
+//! command: `ATESTER_BLESS=1 cargo test -p a_tests --test examples`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that switches evaluators from comparing to rewriting goldens.
+pub const BLESS_ENV: &str = "ATESTER_BLESS";
+
+/// Returns true when `ATESTER_BLESS=1` is set.
+pub fn bless_enabled() -> bool {
+    std::env::var(BLESS_ENV).is_ok_and(|value| value == "1")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenResult {
+    pub success: bool,
+    pub error_code: u32,
+    pub data: Vec<u8>,
+}
+
+impl GoldenResult {
+    /// Text form written to golden files: one `key=value` per line, data as hex.
+    pub fn render(&self) -> String {
+        let data: String = self.data.iter().map(|b| format!("{b:02x}")).collect();
+        format!(
+            "success={}\nerror_code=0x{:x}\ndata={data}\n",
+            self.success, self.error_code
+        )
+    }
+
+    /// Parses `render` output. Blank lines and `#` comments are ignored.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (mut success, mut error_code, mut data) = (None, None, None);
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("malformed line `{line}`"))?;
+            match key {
+                "success" => success = Some(value.parse::<bool>().map_err(|e| e.to_string())?),
+                "error_code" => {
+                    let hex = value.strip_prefix("0x").unwrap_or(value);
+                    error_code = Some(u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?);
+                }
+                "data" => data = Some(decode_hex(value)?),
+                other => return Err(format!("unknown key `{other}`")),
+            }
+        }
+        Ok(Self {
+            success: success.ok_or("missing success")?,
+            error_code: error_code.ok_or("missing error_code")?,
+            data: data.ok_or("missing data")?,
+        })
+    }
+}
+
+/// Golden file path for `case` under `dir`: the name lowercased, with every
+/// non-alphanumeric run collapsed to `_`.
+pub fn golden_path(dir: &Path, case: &str) -> PathBuf {
+    let mut slug = String::new();
+    for ch in case.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    dir.join(format!("{}.golden", slug.trim_matches('_')))
+}
+
+/// Compares `actual` with the golden file at `path`, or rewrites the file when
+/// `bless` is set. Blessing an unchanged result leaves the file byte-identical.
+pub fn check_golden(path: &Path, actual: &GoldenResult, bless: bool) -> Result<(), String> {
+    if bless {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+        }
+        return fs::write(path, actual.render())
+            .map_err(|e| format!("write {}: {e}", path.display()));
+    }
+
+    let text = fs::read_to_string(path).map_err(|e| {
+        format!(
+            "read {}: {e} (run with {BLESS_ENV}=1 to create it)",
+            path.display()
+        )
+    })?;
+    let expected =
+        GoldenResult::parse(&text).map_err(|e| format!("parse {}: {e}", path.display()))?;
+    if expected.success != actual.success {
+        return Err(format!(
+            "expected success={}, got {}",
+            expected.success, actual.success
+        ));
+    }
+    if expected.error_code != actual.error_code {
+        return Err(format!(
+            "expected error_code={}, got {}",
+            expected.error_code, actual.error_code
+        ));
+    }
+    if let Some(detail) = describe_data_mismatch(&expected.data, &actual.data) {
+        return Err(detail);
+    }
+    Ok(())
+}
+
+/// Bytes of context shown on each side of the first differing byte.
+const MISMATCH_CONTEXT: usize = 4;
+
+/// Offset of the first byte where `expected` and `actual` differ, counting a
+/// length difference as a mismatch at the end of the shorter one.
+pub fn first_mismatch(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    if expected == actual {
+        return None;
+    }
+    Some(
+        expected
+            .iter()
+            .zip(actual)
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| expected.len().min(actual.len())),
+    )
+}
+
+/// Describes the first difference between two result payloads, e.g.
+/// `data mismatch at byte 16: expected 0x05 got 0x06 (expected ..0a0b[05]0c.., got ..0a0b[06]0c..)`.
+/// Returns `None` when they are equal.
+pub fn describe_data_mismatch(expected: &[u8], actual: &[u8]) -> Option<String> {
+    let offset = first_mismatch(expected, actual)?;
+    let byte = |data: &[u8]| match data.get(offset) {
+        Some(b) => format!("0x{b:02x}"),
+        None => format!("end of data (len {})", data.len()),
+    };
+    Some(format!(
+        "data mismatch at byte {offset}: expected {} got {} (expected {}, got {})",
+        byte(expected),
+        byte(actual),
+        hex_window(expected, offset),
+        hex_window(actual, offset)
+    ))
+}
+
+/// Hex of `data` around `offset`, with the byte at `offset` bracketed.
+fn hex_window(data: &[u8], offset: usize) -> String {
+    let start = offset.saturating_sub(MISMATCH_CONTEXT);
+    let end = (offset + 1 + MISMATCH_CONTEXT).min(data.len());
+    let mut out = String::new();
+    if start > 0 {
+        out.push_str("..");
+    }
+    for (i, b) in data.iter().enumerate().take(end).skip(start) {
+        if i == offset {
+            out.push_str(&format!("[{b:02x}]"));
+        } else {
+            out.push_str(&format!("{b:02x}"));
+        }
+    }
+    if offset >= data.len() {
+        out.push_str("[]");
+    }
+    if end < data.len() {
+        out.push_str("..");
+    }
+    out
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, String> {
+    if !value.len().is_multiple_of(2) {
+        return Err(format!("odd-length hex `{value}`"));
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
@@ -1,9 +1,13 @@
 mod arch;
+pub mod golden;
 mod runners;
 mod suite;
 mod types;
 
 pub use arch::{ArchRegistry, ArchRunner, RunError, RunResult};
 pub use runners::AvmRunner;
-pub use suite::{Suite, TestCase, TestEvaluator, TestKind, TestReport};
-pub use types::{ElfTarget, RunOptions, TestOutcome};
+pub use suite::{
+    LIST_ENV, StabilityReport, Suite, TestCase, TestEvaluator, TestKind, TestReport, list_enabled,
+    print_case_list,
+};
+pub use types::{DirectMap, ElfTarget, RunOptions, Termination, TestOutcome};
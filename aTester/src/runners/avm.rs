@@ -1,4 +1,5 @@
 use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
 use std::fmt::Write as FmtWrite;
 use std::fs;
 use std::mem;
@@ -6,23 +7,36 @@ use std::rc::Rc;
 
 use compiler::elf::parse_elf_from_bytes;
 use goblin::elf::Elf;
-use types::SV32_DIRECT_MAP_BASE;
-use types::boot::BootInfo;
-use types::kernel_result::KERNEL_RESULT_ADDR;
+use types::boot::{BootInfo, LogLevel};
+use types::kernel_result::{KERNEL_RESULT_ADDR, KernelResult};
+use types::{KernelCrash, SV32_DIRECT_MAP_BASE};
 use vm::instruction::Instruction;
-use vm::memory::{API, HEAP_PTR_OFFSET, MMU, PAGE_SIZE, Perms, Sv32Memory, VirtualAddress};
-use vm::metering::{MeterResult, Metering};
+use vm::memory::{
+    API, HEAP_PTR_OFFSET, MMU, MmioHandle, PAGE_SIZE, Perms, Sv32Memory, VirtualAddress,
+};
+use vm::metering::{BranchProfiler, MemoryAccessKind, MemoryProfiler, MeterResult, Metering};
 use vm::registers::Register;
-use vm::vm::VM;
+use vm::vm::{ExitReason, VM};
 
 use crate::arch::{ArchRunner, RunError, RunResult};
-use crate::types::{ElfTarget, RunOptions};
+use crate::types::{DirectMap, ElfTarget, RunOptions, Termination};
 
-pub struct AvmRunner;
+#[derive(Clone)]
+pub struct AvmRunner {
+    /// Host devices mapped into every run as `(base VA, len, device)`.
+    mmio: Vec<(u32, u32, MmioHandle)>,
+}
 
 impl AvmRunner {
     pub fn new() -> Self {
-        Self
+        Self { mmio: Vec::new() }
+    }
+
+    /// Map `device` at `[base, base + len)` in every VM this runner starts, e.g. to give
+    /// a test guest a host-side oracle without adding a syscall.
+    pub fn with_mmio(mut self, base: u32, len: u32, device: MmioHandle) -> Self {
+        self.mmio.push((base, len, device));
+        self
     }
 }
 
@@ -35,21 +49,48 @@ impl Default for AvmRunner {
 #[derive(Debug)]
 struct InstructionCounter {
     count: Rc<Cell<u64>>,
+    gas_limit: Option<u64>,
+    out_of_gas: Rc<Cell<bool>>,
     kernel_min_sp: Rc<Cell<u32>>,
     user_base_sp: Rc<Cell<Option<u32>>>,
     user_min_sp: Rc<Cell<Option<u32>>>,
     heap_current: Rc<Cell<u64>>,
     heap_peak: Rc<Cell<u64>>,
+    syscall_counts: Rc<RefCell<BTreeMap<u32, u64>>>,
+    memory: MemoryProfiler,
+    branches: BranchProfiler,
 }
 
+/// Number of back-edges reported per run.
+const HOT_BACK_EDGES: usize = 5;
 const SYSCALL_ALLOC: u32 = 7;
 
 impl Metering for InstructionCounter {
     fn on_instruction(&mut self, _pc: u32, _instr: &Instruction, _size: u8) -> MeterResult {
+        if self
+            .gas_limit
+            .is_some_and(|limit| self.count.get() >= limit)
+        {
+            self.out_of_gas.set(true);
+            return MeterResult::Halt;
+        }
         self.count.set(self.count.get().saturating_add(1));
         MeterResult::Continue
     }
 
+    fn on_memory_access(
+        &mut self,
+        kind: MemoryAccessKind,
+        addr: usize,
+        bytes: usize,
+    ) -> MeterResult {
+        self.memory.on_memory_access(kind, addr, bytes)
+    }
+
+    fn on_branch(&mut self, pc: u32, target: u32, taken: bool) -> MeterResult {
+        self.branches.on_branch(pc, target, taken)
+    }
+
     fn on_register_write(
         &mut self,
         reg: usize,
@@ -80,6 +121,7 @@ impl Metering for InstructionCounter {
     }
 
     fn on_syscall(&mut self, call_id: u32, args: &[u32; 6]) -> MeterResult {
+        *self.syscall_counts.borrow_mut().entry(call_id).or_default() += 1;
         if call_id == SYSCALL_ALLOC {
             let size = args[0] as u64;
             let next = self.heap_current.get().saturating_add(size);
@@ -98,14 +140,23 @@ impl ArchRunner for AvmRunner {
     }
 
     fn run(&self, elf: &ElfTarget, options: &RunOptions) -> Result<RunResult, RunError> {
+        let total_size = options.vm_memory_size.unwrap_or(16 * 1024 * 1024);
+        check_memory_size(total_size, options.direct_map)?;
         let elf_bytes = fs::read(&elf.path).map_err(|e| RunError {
             message: format!("failed to read elf {}: {e}", elf.path.display()),
         })?;
 
-        let total_size = options.vm_memory_size.unwrap_or(16 * 1024 * 1024);
         let memory = Rc::new(Sv32Memory::new(total_size, PAGE_SIZE));
+        for (base, len, device) in &self.mmio {
+            if !memory.register_mmio(VirtualAddress(*base), *len, Rc::clone(device)) {
+                return Err(RunError {
+                    message: format!("failed to register MMIO region at 0x{base:08x}"),
+                });
+            }
+        }
         let heap_ptr = Rc::new(Cell::new(0u32));
-        let (entry_point, code_size_bytes) = load_kernel(&elf_bytes, &memory, heap_ptr.as_ref())?;
+        let (entry_point, code_size_bytes) =
+            load_kernel(&elf_bytes, &memory, heap_ptr.as_ref(), options.direct_map)?;
 
         if options.input.len() > 3usize {
             return Err(RunError {
@@ -120,34 +171,52 @@ impl ArchRunner for AvmRunner {
                 .get(idx)
                 .map(|input| input.as_slice())
                 .unwrap_or(&[]);
-            let ptr = alloc_on_heap(memory.as_ref(), heap_ptr.as_ref(), bytes);
+            let ptr = alloc_on_heap(memory.as_ref(), heap_ptr.as_ref(), bytes)?;
             input_ptrs[idx] = ptr;
             input_lens[idx] = bytes.len() as u32;
         }
-        let boot_info_ptr = place_boot_info(memory.as_ref(), heap_ptr.as_ref(), total_size)?;
+        let boot_info_ptr = place_boot_info(
+            memory.as_ref(),
+            heap_ptr.as_ref(),
+            total_size,
+            options.kernel_log_level,
+            options.direct_map,
+        )?;
 
         let mut vm = VM::new(memory.clone());
         vm.set_reg_u32(Register::Sp, KERNEL_STACK_TOP);
         vm.cpu.verbose = options.verbose;
         let instruction_count = Rc::new(Cell::new(0u64));
+        let out_of_gas = Rc::new(Cell::new(false));
         let kernel_base_sp = vm.cpu.regs[Register::Sp as usize];
         let kernel_min_sp = Rc::new(Cell::new(kernel_base_sp));
         let user_base_sp = Rc::new(Cell::new(None));
         let user_min_sp = Rc::new(Cell::new(None));
         let heap_current = Rc::new(Cell::new(0u64));
         let heap_peak = Rc::new(Cell::new(0u64));
+        let syscall_counts = Rc::new(RefCell::new(BTreeMap::new()));
+        let memory_profiler = MemoryProfiler::new();
+        let branch_profiler = BranchProfiler::new();
         vm.set_metering(Box::new(InstructionCounter {
             count: Rc::clone(&instruction_count),
+            gas_limit: options.gas_limit,
+            out_of_gas: Rc::clone(&out_of_gas),
             kernel_min_sp: Rc::clone(&kernel_min_sp),
             user_base_sp: Rc::clone(&user_base_sp),
             user_min_sp: Rc::clone(&user_min_sp),
             heap_current: Rc::clone(&heap_current),
             heap_peak: Rc::clone(&heap_peak),
+            syscall_counts: Rc::clone(&syscall_counts),
+            memory: memory_profiler.clone(),
+            branches: branch_profiler.clone(),
         }));
 
         let writer = Rc::new(RefCell::new(StringWriter::default()));
         vm.cpu.set_verbose_writer(writer.clone());
         vm.cpu.pc = entry_point;
+        if options.coverage {
+            vm.cpu.enable_coverage();
+        }
 
         // set input regs
         const ARG_REGS: [Register; 8] = [
@@ -176,40 +245,79 @@ impl ArchRunner for AvmRunner {
             vm.set_reg_u32(ARG_REGS[boot_reg_idx + 1], 0);
         }
 
-        vm.raw_run();
+        let summary = vm.run_with_limit(options.step_limit.unwrap_or(usize::MAX));
+        // Classify before reading the result blob, which goes through the MMU too.
+        let termination = if out_of_gas.get() {
+            Termination::OutOfGas
+        } else if summary.exit == ExitReason::StepLimit {
+            Termination::StepLimit
+        } else if let Some(fault) = memory.take_fault() {
+            Termination::Fault(fault)
+        } else {
+            Termination::Halted
+        };
 
         let stdout = writer.borrow().buffer.clone();
         let output = read_kernel_blob(memory.as_ref()).unwrap_or_default();
         let exit_code = 0;
-        let stderr = String::new();
         let instruction_count = instruction_count.get();
         let stack_used_bytes = match (user_base_sp.get(), user_min_sp.get()) {
             (Some(base), Some(min)) => base.saturating_sub(min) as u64,
             _ => kernel_base_sp.saturating_sub(kernel_min_sp.get()) as u64,
         };
         let heap_used_bytes = heap_peak.get();
+        // A crash record replaces the header, so there is no usage to read.
+        let kernel_crash = read_kernel_crash(memory.as_ref());
+        let (kernel_heap_used_bytes, kernel_stack_used_bytes) = match kernel_crash {
+            Some(_) => (0, 0),
+            None => kernel_memory_usage(&output).unwrap_or_default(),
+        };
+        let result_dump = format!(
+            "result region ({termination:?}):\n{}",
+            memory.hexdump(VirtualAddress(KERNEL_RESULT_ADDR), RESULT_HEXDUMP_BYTES)
+        );
 
         Ok(RunResult {
             exit_code,
             stdout,
-            stderr,
+            stderr: String::new(),
             output,
             instruction_count,
+            memory_accesses: memory_profiler.counts(),
             stack_used_bytes,
             heap_used_bytes,
             code_size_bytes,
+            kernel_heap_used_bytes,
+            kernel_stack_used_bytes,
+            hot_back_edges: branch_profiler.top_back_edges(HOT_BACK_EDGES),
+            syscall_counts: syscall_counts.take(),
+            final_state: vm.final_state(),
+            termination,
+            executed_pcs: vm
+                .cpu
+                .coverage
+                .take()
+                .map(|coverage| coverage.user)
+                .unwrap_or_default(),
+            kernel_crash,
+            result_dump,
         })
     }
 }
 
 const KERNEL_WINDOW_BYTES: usize = 4 * 1024 * 1024;
 const KERNEL_STACK_TOP: u32 = KERNEL_WINDOW_BYTES as u32;
-const KERNEL_RESULT_DUMP_BYTES: u32 = 1024 * 1024;
+// The kernel heap (and the receipts/state it hands back) can sit anywhere in its
+// window, e.g. well past 1 MiB after decoding a bundle that deploys large code.
+const KERNEL_RESULT_DUMP_BYTES: u32 = KERNEL_WINDOW_BYTES as u32 - KERNEL_RESULT_ADDR;
+/// Bytes of the result region hexdumped into `RunResult::result_dump`.
+const RESULT_HEXDUMP_BYTES: usize = 128;
 
 fn load_kernel(
     elf_bytes: &[u8],
     memory: &Rc<Sv32Memory>,
     heap_ptr: &Cell<u32>,
+    direct_map: DirectMap,
 ) -> Result<(u32, u64), RunError> {
     let elf = parse_elf_from_bytes(elf_bytes).map_err(|e| RunError {
         message: format!("failed to parse kernel elf: {e}"),
@@ -226,14 +334,21 @@ fn load_kernel(
     let code_size_bytes = code.len() as u64;
     let (rodata, ro_base) = elf.get_flat_rodata().unwrap_or((Vec::new(), code_base));
     let (bss, bss_base) = elf.get_flat_bss().unwrap_or((Vec::new(), code_base));
+    let (data, data_base) = elf.get_flat_data().unwrap_or((Vec::new(), code_base));
 
     let mut min_base = core::cmp::min(code_base, ro_base) as usize;
+    if !data.is_empty() {
+        min_base = core::cmp::min(min_base, data_base as usize);
+    }
     if !bss.is_empty() {
         min_base = core::cmp::min(min_base, bss_base as usize);
     }
     let code_end = (code_base + code.len() as u64) as usize;
     let ro_end = (ro_base + rodata.len() as u64) as usize;
     let mut image_end = core::cmp::max(code_end, ro_end);
+    if !data.is_empty() {
+        image_end = core::cmp::max(image_end, (data_base + data.len() as u64) as usize);
+    }
     if !bss.is_empty() {
         let bss_end = bss_base
             .checked_add(bss.len() as u64)
@@ -246,24 +361,14 @@ fn load_kernel(
         message: "invalid image size".to_string(),
     })?;
 
-    if image_end > memory.size() {
+    if image_end > KERNEL_WINDOW_BYTES {
         return Err(RunError {
             message: format!(
-                "elf image does not fit in mapped memory (need {}, have {})",
-                image_end,
-                memory.size()
-            ),
-        });
-    }
-    if KERNEL_WINDOW_BYTES > memory.size() {
-        return Err(RunError {
-            message: format!(
-                "kernel window exceeds physical memory (need {}, have {})",
-                KERNEL_WINDOW_BYTES,
-                memory.size()
+                "kernel image ends at 0x{image_end:x}, past the {KERNEL_WINDOW_BYTES}-byte kernel window"
             ),
         });
     }
+    check_memory_size(memory.size(), direct_map)?;
 
     let mut image = vec![0u8; image_size];
     let code_off = (code_base as usize).saturating_sub(min_base);
@@ -272,23 +377,33 @@ fn load_kernel(
         let ro_off = (ro_base as usize).saturating_sub(min_base);
         image[ro_off..ro_off + rodata.len()].copy_from_slice(&rodata);
     }
+    if !data.is_empty() {
+        let data_off = (data_base as usize).saturating_sub(min_base);
+        image[data_off..data_off + data.len()].copy_from_slice(&data);
+    }
     if !bss.is_empty() {
         let bss_off = (bss_base as usize).saturating_sub(min_base);
         image[bss_off..bss_off + bss.len()].copy_from_slice(&bss);
     }
 
-    memory.map_range(VirtualAddress(0), KERNEL_WINDOW_BYTES, Perms::rwx_kernel());
+    if !memory.map_range(VirtualAddress(0), KERNEL_WINDOW_BYTES, Perms::rwx_kernel()) {
+        return Err(RunError {
+            message: "failed to map kernel window: out of physical memory".to_string(),
+        });
+    }
     memory.write_bytes(VirtualAddress(min_base as u32), &image);
 
     let heap_start = ((image_end + HEAP_PTR_OFFSET as usize + 7) & !7) as u32;
     heap_ptr.set(heap_start);
 
-    let mapped = memory.map_physical_range(
-        VirtualAddress(SV32_DIRECT_MAP_BASE),
-        0,
-        memory.size(),
-        Perms::rw_kernel(),
-    );
+    let (direct_map_len, direct_map_perms) = direct_map_layout(direct_map, memory.size());
+    let mapped = direct_map_len == 0
+        || memory.map_physical_range(
+            VirtualAddress(SV32_DIRECT_MAP_BASE),
+            0,
+            direct_map_len,
+            direct_map_perms,
+        );
     if !mapped {
         return Err(RunError {
             message: "failed to map kernel direct physical window".to_string(),
@@ -298,6 +413,38 @@ fn load_kernel(
     Ok((entry_point, code_size_bytes))
 }
 
+/// Physical bytes the kernel needs before it starts: the reserved frame and root
+/// table, the kernel window (which holds the ELF image) and the L2 tables mapping
+/// both the window and the direct map.
+fn required_memory_size(memory_size: usize, direct_map: DirectMap) -> usize {
+    let l2_span = PAGE_SIZE * 1024;
+    let (direct_map_len, _) = direct_map_layout(direct_map, memory_size);
+    let table_pages = 2 + KERNEL_WINDOW_BYTES.div_ceil(l2_span) + direct_map_len.div_ceil(l2_span);
+    KERNEL_WINDOW_BYTES + table_pages * PAGE_SIZE
+}
+
+fn check_memory_size(memory_size: usize, direct_map: DirectMap) -> Result<(), RunError> {
+    let required = required_memory_size(memory_size, direct_map);
+    if memory_size < required {
+        return Err(RunError {
+            message: format!(
+                "vm_memory_size of {memory_size} bytes is too small: the kernel needs {required} bytes ({} short)",
+                required - memory_size
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Bytes of physical memory to direct-map (0 for none) and their permissions.
+fn direct_map_layout(direct_map: DirectMap, memory_size: usize) -> (usize, Perms) {
+    match direct_map {
+        DirectMap::Full => (memory_size, Perms::rw_kernel()),
+        DirectMap::Limited { len, perms } => (len.min(memory_size), perms),
+        DirectMap::Disabled => (0, Perms::rw_kernel()),
+    }
+}
+
 fn read_kernel_blob(memory: &Sv32Memory) -> Option<Vec<u8>> {
     let start = VirtualAddress(KERNEL_RESULT_ADDR);
     let end = start.checked_add(KERNEL_RESULT_DUMP_BYTES)?;
@@ -305,10 +452,39 @@ fn read_kernel_blob(memory: &Sv32Memory) -> Option<Vec<u8>> {
     Some(slice.as_ref().to_vec())
 }
 
+/// Read the crash record the kernel panic handler left at `KERNEL_RESULT_ADDR`, if any.
+/// The message is read as a C string, so it does not depend on the result region
+/// being one physically contiguous slice.
+fn read_kernel_crash(memory: &Sv32Memory) -> Option<KernelCrash> {
+    let start = VirtualAddress(KERNEL_RESULT_ADDR);
+    let header = memory.mem_slice(start, start.checked_add(KernelCrash::HEADER_LEN as u32)?)?;
+    let (tx_index, len) = KernelCrash::decode_header(&header)?;
+    drop(header);
+    let message = memory.read_cstr(start.wrapping_add(KernelCrash::HEADER_LEN as u32), len + 1)?;
+    Some(KernelCrash {
+        tx_index,
+        message: message.into_bytes(),
+    })
+}
+
+/// Read the kernel's `(heap_used, stack_used)` from the `KernelResult` header at the
+/// start of the dump.
+fn kernel_memory_usage(dump: &[u8]) -> Option<(u64, u64)> {
+    let field = |offset: usize| -> Option<u64> {
+        let bytes = dump.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?) as u64)
+    };
+    let heap_offset = mem::offset_of!(KernelResult, heap_used);
+    let stack_offset = mem::offset_of!(KernelResult, stack_used);
+    Some((field(heap_offset)?, field(stack_offset)?))
+}
+
 fn place_boot_info(
     memory: &Sv32Memory,
     heap_ptr: &Cell<u32>,
     memory_size: usize,
+    log_level: LogLevel,
+    direct_map: DirectMap,
 ) -> Result<u32, RunError> {
     let heap_start = ensure_heap_ptr(heap_ptr);
     let aligned_heap = (heap_start + 7) & !7;
@@ -327,23 +503,28 @@ fn place_boot_info(
         memory.next_free_ppn() as u32,
         0,
         KERNEL_WINDOW_BYTES as u32,
-    );
+    )
+    .with_log_level(log_level)
+    .with_direct_map_len(direct_map_layout(direct_map, memory_size).0 as u32);
     let bytes = unsafe {
         core::slice::from_raw_parts(
             &boot_info as *const BootInfo as *const u8,
             mem::size_of::<BootInfo>(),
         )
     };
-    let addr = alloc_on_heap(memory, heap_ptr, bytes);
-    Ok(addr)
+    alloc_on_heap(memory, heap_ptr, bytes)
 }
 
-fn alloc_on_heap(memory: &Sv32Memory, heap_ptr: &Cell<u32>, data: &[u8]) -> u32 {
+fn alloc_on_heap(memory: &Sv32Memory, heap_ptr: &Cell<u32>, data: &[u8]) -> Result<u32, RunError> {
     let addr = ensure_heap_ptr(heap_ptr);
-    memory.write_bytes(VirtualAddress(addr), data);
+    memory
+        .try_write_bytes(VirtualAddress(addr), data)
+        .map_err(|e| RunError {
+            message: format!("heap allocation failed: {e}"),
+        })?;
     let next = (addr + data.len() as u32 + HEAP_PTR_OFFSET + 7) & !7;
     heap_ptr.set(next);
-    addr
+    Ok(addr)
 }
 
 fn ensure_heap_ptr(heap_ptr: &Cell<u32>) -> u32 {
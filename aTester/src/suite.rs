@@ -1,7 +1,26 @@
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 
+use vm::metering::MemoryAccessCounts;
+
 use crate::arch::{ArchRunner, RunResult};
-use crate::types::{ElfTarget, RunOptions, TestOutcome};
+use crate::types::{ElfTarget, RunOptions, Termination, TestOutcome};
+
+/// Environment variable that makes the `list` test target print every case name
+/// and the VM-running tests return early, e.g. so CI can shard a suite.
+pub const LIST_ENV: &str = "ATESTER_LIST";
+
+/// Returns true when `ATESTER_LIST=1` is set.
+pub fn list_enabled() -> bool {
+    std::env::var(LIST_ENV).is_ok_and(|value| value == "1")
+}
+
+/// Print one `<suite>\t<case>` line per case name.
+pub fn print_case_list<S: AsRef<str>>(suite: &str, names: impl IntoIterator<Item = S>) {
+    for name in names {
+        println!("{suite}\t{}", name.as_ref());
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum TestKind {
@@ -27,10 +46,55 @@ pub struct TestReport {
     pub stdout: String,
     pub stderr: String,
     pub instruction_count: u64,
+    pub memory_accesses: MemoryAccessCounts,
     pub duration_ms: u128,
     pub stack_used_bytes: u64,
     pub heap_used_bytes: u64,
     pub code_size_bytes: u64,
+    pub hot_back_edges: Vec<(u32, u64)>,
+    /// How the VM stopped; `None` when the run never started.
+    pub termination: Option<Termination>,
+    /// Guest PCs executed, when the case ran with `RunOptions::coverage`.
+    pub executed_pcs: BTreeSet<u32>,
+}
+
+/// One case's reports from `Suite::run_repeated`, in run order.
+#[derive(Debug, Clone)]
+pub struct StabilityReport {
+    pub name: String,
+    pub runs: Vec<TestReport>,
+}
+
+impl StabilityReport {
+    /// True when any run ended differently from the first. Runs are deterministic,
+    /// so this points at a real bug (uninitialized memory, host-dependent state).
+    pub fn is_flaky(&self) -> bool {
+        !self.differing_runs().is_empty()
+    }
+
+    /// Indices of the runs whose outcome, exit code, output, instruction count or
+    /// termination differ from the first run. Timing is ignored.
+    pub fn differing_runs(&self) -> Vec<usize> {
+        let Some(first) = self.runs.first() else {
+            return Vec::new();
+        };
+        self.runs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, run)| !same_result(first, run))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
+
+fn same_result(a: &TestReport, b: &TestReport) -> bool {
+    a.outcome == b.outcome
+        && a.exit_code == b.exit_code
+        && a.stdout == b.stdout
+        && a.stderr == b.stderr
+        && a.instruction_count == b.instruction_count
+        && a.termination == b.termination
 }
 
 pub trait TestEvaluator {
@@ -57,21 +121,37 @@ impl<'a> Suite<'a> {
                 stdout,
                 stderr,
                 instruction_count,
+                memory_accesses,
                 stack_used_bytes,
                 heap_used_bytes,
                 code_size_bytes,
+                hot_back_edges,
+                termination,
+                executed_pcs,
             ) = match runner.run(&elf, &case.options) {
                 Ok(result) => {
                     let outcome = self.evaluator.evaluate(case, &result);
+                    let mut stderr = result.stderr;
+                    // Show what the kernel left behind whenever the case failed.
+                    if matches!(outcome, TestOutcome::Failed(_)) && !result.result_dump.is_empty() {
+                        if !stderr.is_empty() && !stderr.ends_with('\n') {
+                            stderr.push('\n');
+                        }
+                        stderr.push_str(&result.result_dump);
+                    }
                     (
                         outcome,
                         result.exit_code,
                         result.stdout,
-                        result.stderr,
+                        stderr,
                         result.instruction_count,
+                        result.memory_accesses,
                         result.stack_used_bytes,
                         result.heap_used_bytes,
                         result.code_size_bytes,
+                        result.hot_back_edges,
+                        Some(result.termination),
+                        result.executed_pcs,
                     )
                 }
                 Err(err) => (
@@ -80,9 +160,13 @@ impl<'a> Suite<'a> {
                     String::new(),
                     err.message,
                     0,
+                    MemoryAccessCounts::default(),
                     0,
                     0,
                     0,
+                    Vec::new(),
+                    None,
+                    BTreeSet::new(),
                 ),
             };
             let duration_ms = start.elapsed().as_millis();
@@ -94,12 +178,35 @@ impl<'a> Suite<'a> {
                 stdout,
                 stderr,
                 instruction_count,
+                memory_accesses,
                 duration_ms,
                 stack_used_bytes,
                 heap_used_bytes,
                 code_size_bytes,
+                hot_back_edges,
+                termination,
+                executed_pcs,
             });
         }
         reports
     }
+
+    /// Run every case `runs` times and group the reports per case, to check that
+    /// repeated runs agree (see `StabilityReport::is_flaky`).
+    pub fn run_repeated(&self, runner: &dyn ArchRunner, runs: usize) -> Vec<StabilityReport> {
+        let mut stability: Vec<StabilityReport> = self
+            .cases
+            .iter()
+            .map(|case| StabilityReport {
+                name: case.name.clone(),
+                runs: Vec::with_capacity(runs),
+            })
+            .collect();
+        for _ in 0..runs {
+            for (entry, report) in stability.iter_mut().zip(self.run(runner)) {
+                entry.runs.push(report);
+            }
+        }
+        stability
+    }
 }
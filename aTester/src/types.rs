@@ -1,5 +1,8 @@
 use std::path::PathBuf;
 
+use types::boot::LogLevel;
+use vm::memory::{MemoryFault, Perms};
+
 #[derive(Debug, Clone)]
 pub struct ElfTarget {
     pub path: PathBuf,
@@ -8,12 +11,51 @@ pub struct ElfTarget {
 #[derive(Debug, Clone, Default)]
 pub struct RunOptions {
     pub timeout_ms: Option<u64>,
+    /// Physical memory in bytes (16 MiB when unset). Runs fail up front if this
+    /// cannot hold the kernel window and its page tables.
     pub vm_memory_size: Option<usize>,
     pub verbose: bool,
     pub input: Vec<Vec<u8>>,
+    /// Kernel log verbosity handed over in `BootInfo`.
+    pub kernel_log_level: LogLevel,
+    /// How physical memory is mapped at `SV32_DIRECT_MAP_BASE` for the kernel.
+    pub direct_map: DirectMap,
+    /// Instructions the run may retire before it stops with `Termination::OutOfGas`.
+    pub gas_limit: Option<u64>,
+    /// VM steps before the run stops with `Termination::StepLimit`.
+    pub step_limit: Option<usize>,
+    /// Record the guest PCs executed into `RunResult::executed_pcs`.
+    pub coverage: bool,
 }
 
-#[derive(Debug, Clone)]
+/// How a run stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Termination {
+    /// The program stopped on its own (`ebreak`, `wfi`).
+    #[default]
+    Halted,
+    /// `RunOptions::gas_limit` was used up.
+    OutOfGas,
+    /// A memory access failed with no trap handler to take it.
+    Fault(MemoryFault),
+    /// `RunOptions::step_limit` steps ran without the program stopping.
+    StepLimit,
+}
+
+/// Kernel direct-map setup, narrowed or dropped to test isolation without the
+/// kernel's physical-memory shortcut.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum DirectMap {
+    /// All of physical memory, kernel read/write.
+    #[default]
+    Full,
+    /// Only the first `len` bytes of physical memory, with `perms`.
+    Limited { len: usize, perms: Perms },
+    /// No direct map at all.
+    Disabled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TestOutcome {
     Passed,
     Failed(String),
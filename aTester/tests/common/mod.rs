@@ -0,0 +1,73 @@
+//! Helpers shared by the tests that run a single transfer through the real kernel.
+// Each test binary uses a different subset.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use a_tests::{ArchRunner, AvmRunner, ElfTarget, RunOptions, RunResult};
+use types::address::Address;
+use types::transaction::{Transaction, TransactionBundle, TransactionType};
+
+pub const FUNDED: Address = Address([0xd3; 20]);
+pub const RECIPIENT: Address = Address([0xd0; 20]);
+
+/// Run a single native transfer through the kernel with `options` plus the bundle input.
+pub fn run_transfer(options: RunOptions) -> RunResult {
+    AvmRunner::new()
+        .run(&kernel_elf(), &transfer_options(options))
+        .expect("kernel run failed")
+}
+
+/// The kernel ELF built by `build_kernel`.
+pub fn kernel_elf() -> ElfTarget {
+    ElfTarget {
+        path: kernel_elf_dir().join("kernel.elf"),
+    }
+}
+
+/// `options` with the input set to a single native transfer from a funded account.
+pub fn transfer_options(options: RunOptions) -> RunOptions {
+    let bundle = TransactionBundle::new(vec![Transaction {
+        tx_type: TransactionType::Transfer,
+        to: RECIPIENT,
+        from: FUNDED,
+        data: vec![],
+        value: 10,
+        nonce: 0,
+    }]);
+    let mut state = state::State::new();
+    state.get_account_mut(&FUNDED).balance = 1_000;
+
+    RunOptions {
+        input: vec![bundle.encode(), state.encode()],
+        ..options
+    }
+}
+
+pub fn kernel_elf_dir() -> PathBuf {
+    std::env::var("KERNEL_ELF_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| workspace_root().join("crates/bootloader/bin"))
+}
+
+pub fn build_kernel() -> Result<(), String> {
+    let status = std::process::Command::new("make")
+        .args(["kernel"])
+        .current_dir(workspace_root())
+        .status()
+        .map_err(|e| format!("failed to spawn kernel make: {e}"))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("kernel build failed with status: {status}"))
+    }
+}
+
+pub fn workspace_root() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir
+        .parent()
+        .map(PathBuf::from)
+        .expect("missing workspace root")
+}
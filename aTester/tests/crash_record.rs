@@ -0,0 +1,57 @@
+use std::mem;
+
+use types::kernel_result::{KERNEL_CRASH_MESSAGE_MAX, KERNEL_CRASH_NO_TX};
+use types::{KernelCrash, KernelResult};
+
+#[test]
+fn crash_record_round_trips_and_reports_the_tx() {
+    let mut region = [0u8; 64];
+    let written = KernelCrash::encode_into(2, b"out of pages", &mut region);
+    assert_eq!(written, KernelCrash::HEADER_LEN + 12 + 1);
+    assert_eq!(region[written - 1], 0, "message is NUL-terminated");
+
+    let crash = KernelCrash::decode(&region).expect("crash record");
+    assert_eq!(crash.tx_index, 2);
+    assert_eq!(crash.message, b"out of pages");
+    assert_eq!(
+        crash.to_string(),
+        "kernel panicked: out of pages during tx 2"
+    );
+
+    KernelCrash::encode_into(KERNEL_CRASH_NO_TX, b"boot", &mut region);
+    assert_eq!(
+        KernelCrash::decode(&region).unwrap().to_string(),
+        "kernel panicked: boot outside any transaction"
+    );
+}
+
+#[test]
+fn long_messages_are_truncated() {
+    let message = vec![b'x'; KERNEL_CRASH_MESSAGE_MAX + 40];
+    let mut region = vec![0u8; KernelCrash::MAX_LEN + 40];
+    KernelCrash::encode_into(0, &message, &mut region);
+    let crash = KernelCrash::decode(&region).unwrap();
+    assert_eq!(crash.message.len(), KERNEL_CRASH_MESSAGE_MAX);
+
+    let mut small = [0u8; KernelCrash::HEADER_LEN + 4];
+    KernelCrash::encode_into(0, b"too long", &mut small);
+    assert_eq!(KernelCrash::decode(&small).unwrap().message, b"too");
+    assert_eq!(KernelCrash::encode_into(0, b"x", &mut [0u8; 8]), 0);
+    assert_eq!(
+        KernelCrash::encode_into(0, b"x", &mut [0u8; KernelCrash::HEADER_LEN]),
+        0
+    );
+
+    // Truncation backs off to a character boundary.
+    let mut small = [0u8; KernelCrash::HEADER_LEN + 3];
+    KernelCrash::encode_into(0, "a\u{e9}".as_bytes(), &mut small);
+    assert_eq!(KernelCrash::decode(&small).unwrap().message, b"a");
+}
+
+#[test]
+fn a_normal_result_header_is_not_a_crash() {
+    let mut region = vec![0u8; mem::size_of::<KernelResult>()];
+    region[..4].copy_from_slice(&0x0020_0000u32.to_le_bytes());
+    assert_eq!(KernelCrash::decode(&region), None);
+    assert_eq!(KernelCrash::decode(&[]), None);
+}
@@ -1,19 +1,37 @@
 use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
-use a_tests::{AvmRunner, RunOptions, Suite, TestCase, TestEvaluator, TestKind, TestOutcome};
-use types::TransactionReceipt;
-use types::transaction::TransactionType;
+use a_tests::golden::{self, GoldenResult, check_golden, golden_path};
+use a_tests::{
+    ArchRunner, AvmRunner, DirectMap, ElfTarget, RunOptions, RunResult, Suite, Termination,
+    TestCase, TestEvaluator, TestKind, TestOutcome, list_enabled,
+};
+use goblin::elf::Elf;
+use types::boot::LogLevel;
+use types::transaction::{TransactionBundle, TransactionType};
+use types::{BundleSummary, KernelResult, TransactionReceipt};
 
 #[path = "fixtures/examples.rs"]
 mod fixtures;
 
-use fixtures::{all_example_cases, expected_for, test_state_bytes};
+use fixtures::{
+    ExpectedAccount, ExpectedResult, all_example_cases, build_deploy_copies_bundle,
+    expected_call_traces_for, expected_logs_for, expected_receipts_for, expected_state_for,
+    memory_size_for, test_state_bytes,
+};
 
 struct ExampleEvaluator;
 
 impl TestEvaluator for ExampleEvaluator {
     fn evaluate(&self, case: &TestCase, result: &a_tests::RunResult) -> TestOutcome {
+        if result.termination != Termination::Halted {
+            return TestOutcome::Failed(format!("kernel stopped with {:?}", result.termination));
+        }
+        if let Some(crash) = &result.kernel_crash {
+            return TestOutcome::Failed(crash.to_string());
+        }
         let receipts_slice = match kernel_receipts_slice(&result.output) {
             Some(slice) => slice,
             None => return TestOutcome::Failed("kernel receipts not in dump".to_string()),
@@ -26,55 +44,206 @@ impl TestEvaluator for ExampleEvaluator {
             Some(receipt) => receipt,
             None => return TestOutcome::Failed("missing transaction receipt".to_string()),
         };
-        let expected = match expected_for(case.name.as_str()) {
-            Some(expected) => expected,
-            None => {
-                return TestOutcome::Failed(format!("missing expected result for {}", case.name));
-            }
+        let data_len = receipt.result.data_len as usize;
+        let observed = GoldenResult {
+            success: receipt.result.success,
+            error_code: receipt.result.error_code,
+            data: receipt.result.data[..data_len.min(receipt.result.data.len())].to_vec(),
         };
-        let success = receipt.result.success;
-        let error_code = receipt.result.error_code;
-        let data_len = receipt.result.data_len;
-        let data = receipt.result.data;
-        if success != expected.success {
-            return TestOutcome::Failed(format!(
-                "expected success={}, got {}",
-                expected.success, success
-            ));
+        let golden = golden_path(&golden_dir(), &case.name);
+        if let Err(detail) = check_golden(&golden, &observed, golden::bless_enabled()) {
+            return TestOutcome::Failed(detail);
         }
-        if error_code != expected.error_code {
-            return TestOutcome::Failed(format!(
-                "expected error_code={}, got {}",
-                expected.error_code, error_code
-            ));
+        for expected_receipt in expected_receipts_for(case.name.as_str()) {
+            let receipt = match receipts.get(expected_receipt.index) {
+                Some(receipt) => receipt,
+                None => {
+                    return TestOutcome::Failed(format!(
+                        "missing receipt {}",
+                        expected_receipt.index
+                    ));
+                }
+            };
+            if let Err(detail) = check_result(receipt, &expected_receipt.result) {
+                return TestOutcome::Failed(format!(
+                    "receipt {}: {detail}",
+                    expected_receipt.index
+                ));
+            }
+            if receipt.logs.len() != expected_receipt.logs {
+                return TestOutcome::Failed(format!(
+                    "receipt {}: expected {} logs, got {}",
+                    expected_receipt.index,
+                    expected_receipt.logs,
+                    receipt.logs.len()
+                ));
+            }
         }
-        let data_len = data_len as usize;
-        let actual = &data[..data_len.min(data.len())];
-        if actual != expected.data.as_slice() {
-            return TestOutcome::Failed(format!(
-                "expected data {:?}, got {:?}",
-                expected.data, actual
-            ));
+        for expected_trace in expected_call_traces_for(case.name.as_str()) {
+            let trace = receipts.get(expected_trace.index).map(|r| &r.call_trace);
+            if trace != Some(&expected_trace.programs) {
+                return TestOutcome::Failed(format!(
+                    "receipt {}: expected call trace {:?}, got {:?}",
+                    expected_trace.index, expected_trace.programs, trace
+                ));
+            }
+        }
+        for expected_log in expected_logs_for(case.name.as_str()) {
+            let found = receipts
+                .iter()
+                .flat_map(|r| &r.logs)
+                .any(|log| log.emitter == expected_log.emitter && log.data == expected_log.data);
+            if !found {
+                return TestOutcome::Failed(format!(
+                    "missing log from {} with data {:?}",
+                    expected_log.emitter, expected_log.data
+                ));
+            }
+        }
+        let expected_state = expected_state_for(case.name.as_str());
+        if !expected_state.is_empty() {
+            let state = match kernel_state_slice(&result.output).and_then(state::State::decode) {
+                Some(state) => state,
+                None => return TestOutcome::Failed("failed to decode final state".to_string()),
+            };
+            for expected_account in &expected_state {
+                if let Err(detail) = check_account(&state, expected_account) {
+                    return TestOutcome::Failed(format!(
+                        "account {}: {detail}",
+                        expected_account.address
+                    ));
+                }
+            }
+        }
+        if let Err(detail) = check_kernel_usage(result) {
+            return TestOutcome::Failed(detail);
+        }
+        if let Err(detail) = check_bundle_summary(&result.output, &receipts) {
+            return TestOutcome::Failed(detail);
         }
         TestOutcome::Passed
     }
 }
 
+/// The kernel's self-reported memory usage must cover what it handed back: the
+/// receipts and state buffers both come from its heap.
+fn check_kernel_usage(result: &a_tests::RunResult) -> Result<(), String> {
+    let receipts_len = kernel_receipts_slice(&result.output).map_or(0, <[u8]>::len);
+    let state_len = kernel_state_slice(&result.output).map_or(0, <[u8]>::len);
+    let handed_back = (receipts_len + state_len) as u64;
+    if result.kernel_heap_used_bytes < handed_back {
+        return Err(format!(
+            "kernel reported {} heap bytes but returned {handed_back} bytes of receipts/state",
+            result.kernel_heap_used_bytes
+        ));
+    }
+    if result.kernel_stack_used_bytes == 0 {
+        return Err("kernel reported no stack usage".to_string());
+    }
+    Ok(())
+}
+
+/// The kernel's bundle summary must agree with the receipts it summarises.
+fn check_bundle_summary(dump: &[u8], receipts: &[TransactionReceipt]) -> Result<(), String> {
+    let offset = mem::offset_of!(KernelResult, summary);
+    let summary = dump
+        .get(offset..)
+        .and_then(BundleSummary::decode)
+        .ok_or_else(|| "bundle summary not in dump".to_string())?;
+    let expected = BundleSummary::from_receipts(receipts, summary.total_gas);
+    if summary != expected {
+        return Err(format!(
+            "bundle summary {summary:?} does not match receipts {expected:?}"
+        ));
+    }
+    if summary.total_gas == 0 {
+        return Err("bundle summary reported no gas".to_string());
+    }
+    Ok(())
+}
+
+fn check_account(state: &state::State, expected: &ExpectedAccount) -> Result<(), String> {
+    let account = state
+        .get_account(&expected.address)
+        .ok_or_else(|| "missing from final state".to_string())?;
+    if let Some(balance) = expected.balance
+        && account.balance != balance
+    {
+        return Err(format!(
+            "expected balance {balance}, got {}",
+            account.balance
+        ));
+    }
+    for (key, value) in &expected.storage {
+        match account.storage.get(key) {
+            Some(actual) if actual == value => {}
+            Some(actual) => {
+                return Err(format!("storage {key}: expected {value:?}, got {actual:?}"));
+            }
+            None => return Err(format!("storage {key}: missing")),
+        }
+    }
+    if let Some(key) = expected
+        .absent_storage
+        .iter()
+        .find(|key| account.storage.contains_key(*key))
+    {
+        return Err(format!("storage {key}: expected absent"));
+    }
+    Ok(())
+}
+
+fn check_result(receipt: &TransactionReceipt, expected: &ExpectedResult) -> Result<(), String> {
+    let success = receipt.result.success;
+    let error_code = receipt.result.error_code;
+    let data_len = receipt.result.data_len;
+    let data = receipt.result.data;
+    if success != expected.success {
+        return Err(format!(
+            "expected success={}, got {}",
+            expected.success, success
+        ));
+    }
+    if error_code != expected.error_code {
+        return Err(format!(
+            "expected error_code={}, got {}",
+            expected.error_code, error_code
+        ));
+    }
+    let data_len = data_len as usize;
+    let actual = &data[..data_len.min(data.len())];
+    if let Some(detail) = golden::describe_data_mismatch(&expected.data, actual) {
+        return Err(detail);
+    }
+    Ok(())
+}
+
 #[test]
 fn examples_tests() {
+    // `tests/list.rs` prints the cases; listing must not build or run anything.
+    if list_enabled() {
+        return;
+    }
     build_kernel().expect("failed to build kernel");
     build_examples().expect("failed to build example programs");
 
     let target_dir = kernel_elf_dir();
     let state_bytes = test_state_bytes();
-    let example_cases = all_example_cases().expect("failed to build example bundles");
+    let example_cases = all_example_cases()
+        .into_iter()
+        .map(|case| {
+            let bundle = (case.build)()
+                .unwrap_or_else(|e| panic!("failed to build bundle for {}: {e}", case.name));
+            (case, bundle)
+        })
+        .collect::<Vec<_>>();
     let code_sizes = example_cases
         .iter()
-        .map(|case| (case.name.to_string(), bundle_code_size(&case.bundle)))
+        .map(|(case, bundle)| (case.name.to_string(), bundle_code_size(bundle)))
         .collect::<HashMap<_, _>>();
     let cases = example_cases
         .into_iter()
-        .map(|case| {
+        .map(|(case, bundle)| {
             println!("Running example: {} - {}", case.name, case.description);
             TestCase {
                 name: case.name.to_string(),
@@ -82,9 +251,14 @@ fn examples_tests() {
                 elf: target_dir.join("kernel.elf"),
                 options: RunOptions {
                     timeout_ms: None,
-                    vm_memory_size: None,
+                    vm_memory_size: memory_size_for(case.name),
                     verbose: false,
-                    input: vec![case.bundle.encode(), state_bytes.clone()],
+                    input: vec![bundle.encode(), state_bytes.clone()],
+                    kernel_log_level: LogLevel::Error,
+                    direct_map: DirectMap::Full,
+                    gas_limit: None,
+                    step_limit: None,
+                    coverage: true,
                 },
             }
         })
@@ -127,6 +301,185 @@ fn examples_tests() {
     }
 }
 
+#[test]
+fn example_cases_are_listed_without_building() {
+    // Listing only needs names, so it must not require the kernel or example ELFs.
+    let names: Vec<_> = all_example_cases().iter().map(|case| case.name).collect();
+    for known in [
+        "erc20",
+        "erc20 panic",
+        "dex amm",
+        "ecdsa verify",
+        "native transfer",
+    ] {
+        assert!(names.contains(&known), "missing example case {known}");
+    }
+    let mut unique = names.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    assert_eq!(unique.len(), names.len(), "duplicate example case names");
+}
+
+#[test]
+fn erc20_transfer_coverage() {
+    if list_enabled() {
+        return;
+    }
+    build_kernel().expect("failed to build kernel");
+    build_examples().expect("failed to build example programs");
+
+    // The erc20 bundle inits, transfers and reads a balance, but never spends an allowance.
+    let case = all_example_cases()
+        .into_iter()
+        .find(|case| case.name == "erc20")
+        .expect("missing erc20 case");
+    let bundle = (case.build)().expect("failed to build erc20 bundle");
+    let options = RunOptions {
+        vm_memory_size: memory_size_for(case.name),
+        input: vec![bundle.encode(), test_state_bytes()],
+        kernel_log_level: LogLevel::Error,
+        coverage: true,
+        ..RunOptions::default()
+    };
+    let elf = ElfTarget {
+        path: kernel_elf_dir().join("kernel.elf"),
+    };
+    let result = AvmRunner::new()
+        .run(&elf, &options)
+        .expect("kernel run failed");
+
+    let erc20 = std::fs::read(workspace_root().join("crates/examples/bin/erc20.elf"))
+        .expect("failed to read erc20.elf");
+    let transfer = erc20_function_range(&erc20, "transfer");
+    let transfer_from = erc20_function_range(&erc20, "transfer_from");
+    assert!(
+        result.executed_pcs.range(transfer).next().is_some(),
+        "transfer never ran"
+    );
+    assert!(
+        result.executed_pcs.range(transfer_from).next().is_none(),
+        "transfer_from should not run"
+    );
+}
+
+#[test]
+fn storage_batch_saves_syscalls() {
+    if list_enabled() {
+        return;
+    }
+    build_kernel().expect("failed to build kernel");
+    build_examples().expect("failed to build example programs");
+
+    // Both cases write the same three keys (checked against `expected_state_for` by
+    // `examples_tests`); only the number of storage traps may differ.
+    let individual = run_example_case("storage batch (individual writes)");
+    let batched = run_example_case("storage batch");
+    assert_eq!(individual.termination, Termination::Halted);
+    assert_eq!(batched.termination, Termination::Halted);
+
+    let count = |result: &RunResult, id: u32| result.syscall_counts.get(&id).copied();
+    assert_eq!(count(&individual, SYSCALL_STORAGE_SET), Some(3));
+    assert_eq!(count(&individual, SYSCALL_STORAGE_SET_BATCH), None);
+    assert_eq!(count(&batched, SYSCALL_STORAGE_SET), None);
+    assert_eq!(count(&batched, SYSCALL_STORAGE_SET_BATCH), Some(1));
+
+    let total = |result: &RunResult| result.syscall_counts.values().sum::<u64>();
+    assert!(
+        total(&batched) < total(&individual),
+        "batched run made {} syscalls, individual writes {}",
+        total(&batched),
+        total(&individual)
+    );
+}
+
+#[test]
+fn kernel_heap_usage_tracks_deployed_code() {
+    if list_enabled() {
+        return;
+    }
+    build_kernel().expect("failed to build kernel");
+    build_examples().expect("failed to build example programs");
+
+    // Every deployment keeps its own copy of the code in the kernel's bump heap, so
+    // each one must cost at least the code it was handed, and every copy the same.
+    let usage = |copies: u8| {
+        let bundle = build_deploy_copies_bundle(copies).expect("failed to build deploy bundle");
+        let result = run_bundle(&bundle, None);
+        assert_eq!(result.termination, Termination::Halted);
+        assert!(result.kernel_crash.is_none(), "kernel crashed");
+        (
+            bundle.transactions[0].data.len() as u64,
+            result.kernel_heap_used_bytes,
+        )
+    };
+    let (code_len, one) = usage(1);
+    let (_, two) = usage(2);
+    let (_, many) = usage(DEPLOY_COPIES);
+
+    let per_copy = two - one;
+    assert!(
+        per_copy >= code_len,
+        "one more deployment grew the heap {per_copy} bytes for {code_len} bytes of code"
+    );
+    let expected = per_copy * u64::from(DEPLOY_COPIES - 2);
+    let grown = many - two;
+    assert!(
+        grown.abs_diff(expected) <= expected / 10,
+        "{} more deployments grew the heap {grown} bytes, expected about {expected}",
+        DEPLOY_COPIES - 2
+    );
+}
+
+const DEPLOY_COPIES: u8 = 8;
+
+const SYSCALL_STORAGE_SET: u32 = 2;
+const SYSCALL_STORAGE_SET_BATCH: u32 = 20;
+
+/// Runs example case `name` through the kernel and returns the raw run result.
+fn run_example_case(name: &str) -> RunResult {
+    let case = all_example_cases()
+        .into_iter()
+        .find(|case| case.name == name)
+        .unwrap_or_else(|| panic!("missing {name} case"));
+    let bundle = (case.build)().unwrap_or_else(|e| panic!("failed to build {name}: {e}"));
+    run_bundle(&bundle, memory_size_for(case.name))
+}
+
+/// Runs `bundle` through the kernel against the test state and returns the raw run result.
+fn run_bundle(bundle: &TransactionBundle, vm_memory_size: Option<usize>) -> RunResult {
+    let options = RunOptions {
+        vm_memory_size,
+        input: vec![bundle.encode(), test_state_bytes()],
+        kernel_log_level: LogLevel::Error,
+        ..RunOptions::default()
+    };
+    let elf = ElfTarget {
+        path: kernel_elf_dir().join("kernel.elf"),
+    };
+    AvmRunner::new()
+        .run(&elf, &options)
+        .expect("kernel run failed")
+}
+
+/// Address range of `erc20::<name>` in the example ELF. Flat program images keep
+/// their link addresses, so these are also the guest PCs.
+fn erc20_function_range(elf: &[u8], name: &str) -> Range<u32> {
+    let elf = Elf::parse(elf).expect("failed to parse erc20.elf");
+    // Both mangling schemes spell the path as length-prefixed identifiers.
+    let path = format!("5erc20{}{name}", name.len());
+    elf.syms
+        .iter()
+        .find(|sym| {
+            sym.is_function()
+                && elf
+                    .strtab
+                    .get_at(sym.st_name)
+                    .is_some_and(|sym_name| sym_name.contains(&path))
+        })
+        .map(|sym| sym.st_value as u32..(sym.st_value + sym.st_size) as u32)
+        .unwrap_or_else(|| panic!("erc20.elf has no symbol for {name}"))
+}
+
 fn print_summary(reports: &[a_tests::TestReport], code_sizes: &HashMap<String, u64>) {
     let total_tests = reports.len();
     let passed = reports
@@ -142,16 +495,27 @@ fn print_summary(reports: &[a_tests::TestReport], code_sizes: &HashMap<String, u
         .filter(|report| matches!(report.outcome, TestOutcome::Skipped(_)))
         .count();
     let instruction_count: u64 = reports.iter().map(|report| report.instruction_count).sum();
+    let data_accesses: u64 = reports
+        .iter()
+        .map(|report| report.memory_accesses.data_accesses())
+        .sum();
     let code_size_bytes: u64 = code_sizes.values().sum();
 
     println!("\n=== examples_tests summary ===");
     println!(
-        "{:<32} {:<7} {:>16} {:>10} {:>12} {:>12} {:>10}",
-        "Test", "Result", "Instructions", "Time(ms)", "Stack(B)", "Heap(B)", "Code(B)"
+        "{:<32} {:<7} {:>16} {:>14} {:>10} {:>12} {:>12} {:>10}",
+        "Test",
+        "Result",
+        "Instructions",
+        "MemAccesses",
+        "Time(ms)",
+        "Stack(B)",
+        "Heap(B)",
+        "Code(B)"
     );
     println!(
-        "{:-<32} {:-<7} {:-<16} {:-<10} {:-<12} {:-<12} {:-<10}",
-        "", "", "", "", "", "", ""
+        "{:-<32} {:-<7} {:-<16} {:-<14} {:-<10} {:-<12} {:-<12} {:-<10}",
+        "", "", "", "", "", "", "", ""
     );
     for report in reports {
         let result = match report.outcome {
@@ -160,6 +524,7 @@ fn print_summary(reports: &[a_tests::TestReport], code_sizes: &HashMap<String, u
             TestOutcome::Skipped(_) => "skipped",
         };
         let instruction_count = format_u64(report.instruction_count);
+        let data_accesses = format_u64(report.memory_accesses.data_accesses());
         let duration_ms = format_u128(report.duration_ms);
         let stack_used = format_u64(report.stack_used_bytes);
         let heap_used = format_u64(report.heap_used_bytes);
@@ -170,34 +535,65 @@ fn print_summary(reports: &[a_tests::TestReport], code_sizes: &HashMap<String, u
                 .unwrap_or(report.code_size_bytes),
         );
         println!(
-            "{:<32} {:<7} {:>16} {:>10} {:>12} {:>12} {:>10}",
-            report.name, result, instruction_count, duration_ms, stack_used, heap_used, code_size
+            "{:<32} {:<7} {:>16} {:>14} {:>10} {:>12} {:>12} {:>10}",
+            report.name,
+            result,
+            instruction_count,
+            data_accesses,
+            duration_ms,
+            stack_used,
+            heap_used,
+            code_size
         );
     }
     println!(
-        "{:-<32} {:-<7} {:-<16} {:-<10} {:-<12} {:-<12} {:-<10}",
-        "", "", "", "", "", "", ""
+        "{:-<32} {:-<7} {:-<16} {:-<14} {:-<10} {:-<12} {:-<12} {:-<10}",
+        "", "", "", "", "", "", "", ""
     );
     let instruction_count = format_u64(instruction_count);
+    let data_accesses = format_u64(data_accesses);
     let code_size_bytes = format_u64(code_size_bytes);
     println!(
-        "{:<32} {:<7} {:>16} {:>10} {:>12} {:>12} {:>10}",
+        "{:<32} {:<7} {:>16} {:>14} {:>10} {:>12} {:>12} {:>10}",
         "Total",
         format!("{passed}/{failed}/{skipped}/{total_tests}"),
         instruction_count,
+        data_accesses,
         "",
         "",
         "",
         code_size_bytes
     );
+
+    println!("\nHottest back-edges (pc x taken):");
+    for report in reports.iter().filter(|r| !r.hot_back_edges.is_empty()) {
+        let edges: Vec<String> = report
+            .hot_back_edges
+            .iter()
+            .map(|(pc, count)| format!("0x{pc:08x} x{}", format_u64(*count)))
+            .collect();
+        println!("  {:<30} {}", report.name, edges.join(", "));
+    }
+
+    println!("\nGuest coverage (distinct PCs executed):");
+    for report in reports.iter().filter(|r| !r.executed_pcs.is_empty()) {
+        println!(
+            "  {:<30} {}",
+            report.name,
+            format_u64(report.executed_pcs.len() as u64)
+        );
+    }
 }
 
 fn bundle_code_size(bundle: &types::transaction::TransactionBundle) -> u64 {
     bundle
         .transactions
         .iter()
-        .filter(|tx| matches!(tx.tx_type, TransactionType::CreateAccount))
-        .map(|tx| tx.data.len() as u64)
+        .map(|tx| match tx.tx_type {
+            TransactionType::CreateAccount | TransactionType::Redeploy => tx.data.len() as u64,
+            TransactionType::Create2 => tx.data.len().saturating_sub(32) as u64,
+            _ => 0,
+        })
         .sum()
 }
 
@@ -223,6 +619,11 @@ fn format_number(mut digits: String) -> String {
     out.chars().rev().collect()
 }
 
+/// Golden final results, one file per case (see `a_tests::golden`).
+fn golden_dir() -> PathBuf {
+    workspace_root().join("aTester/tests/golden/examples")
+}
+
 fn kernel_elf_dir() -> PathBuf {
     std::env::var("KERNEL_ELF_DIR")
         .map(PathBuf::from)
@@ -266,17 +667,26 @@ fn workspace_root() -> PathBuf {
 }
 
 fn kernel_receipts_slice(dump: &[u8]) -> Option<&[u8]> {
+    kernel_dump_slice(dump, 0)
+}
+
+fn kernel_state_slice(dump: &[u8]) -> Option<&[u8]> {
+    kernel_dump_slice(dump, 8)
+}
+
+/// Resolve the `(ptr, len)` pair at `header_offset` of the `KernelResult` header.
+fn kernel_dump_slice(dump: &[u8], header_offset: usize) -> Option<&[u8]> {
     if dump.len() < 16 {
         return None;
     }
-    let receipts_ptr = u32::from_le_bytes(dump[0..4].try_into().ok()?);
-    let receipts_len = u32::from_le_bytes(dump[4..8].try_into().ok()?);
-    if receipts_ptr == 0 || receipts_len == 0 {
+    let ptr = u32::from_le_bytes(dump[header_offset..header_offset + 4].try_into().ok()?);
+    let len = u32::from_le_bytes(dump[header_offset + 4..header_offset + 8].try_into().ok()?);
+    if ptr == 0 || len == 0 {
         return None;
     }
     let base = types::kernel_result::KERNEL_RESULT_ADDR;
-    let start = receipts_ptr.checked_sub(base)? as usize;
-    let end = start.checked_add(receipts_len as usize)?;
+    let start = ptr.checked_sub(base)? as usize;
+    let end = start.checked_add(len as usize)?;
     if end > dump.len() {
         return None;
     }
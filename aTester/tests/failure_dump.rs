@@ -0,0 +1,84 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use a_tests::{
+    ArchRunner, ElfTarget, RunError, RunOptions, RunResult, Suite, Termination, TestCase,
+    TestEvaluator, TestKind, TestOutcome,
+};
+use vm::cpu::PrivilegeMode;
+use vm::metering::MemoryAccessCounts;
+use vm::vm::VmState;
+
+const DUMP: &str = "result region (Halted):\n00000100: 4b50 4e43  KPNC\n";
+
+/// Stands in for a VM run that halted normally and left `DUMP` behind.
+struct StubRunner;
+
+impl ArchRunner for StubRunner {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    fn run(&self, _elf: &ElfTarget, _options: &RunOptions) -> Result<RunResult, RunError> {
+        Ok(RunResult {
+            exit_code: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            output: Vec::new(),
+            instruction_count: 0,
+            memory_accesses: MemoryAccessCounts::default(),
+            stack_used_bytes: 0,
+            heap_used_bytes: 0,
+            code_size_bytes: 0,
+            kernel_heap_used_bytes: 0,
+            kernel_stack_used_bytes: 0,
+            hot_back_edges: Vec::new(),
+            syscall_counts: BTreeMap::new(),
+            final_state: VmState {
+                regs: [0; 32],
+                pc: 0,
+                priv_mode: PrivilegeMode::Supervisor,
+            },
+            termination: Termination::Halted,
+            executed_pcs: BTreeSet::new(),
+            kernel_crash: None,
+            result_dump: DUMP.to_string(),
+        })
+    }
+}
+
+/// Passes or fails every case, regardless of the run.
+struct FixedEvaluator(TestOutcome);
+
+impl TestEvaluator for FixedEvaluator {
+    fn evaluate(&self, _case: &TestCase, _result: &RunResult) -> TestOutcome {
+        self.0.clone()
+    }
+}
+
+fn run_with(outcome: TestOutcome) -> String {
+    let evaluator = FixedEvaluator(outcome);
+    let suite = Suite {
+        name: "failure_dump".to_string(),
+        cases: vec![TestCase {
+            name: "stub case".to_string(),
+            kind: TestKind::Smoke,
+            elf: PathBuf::from("stub.elf"),
+            options: RunOptions::default(),
+        }],
+        evaluator: &evaluator,
+    };
+    suite.run(&StubRunner).remove(0).stderr
+}
+
+#[test]
+fn failed_case_carries_the_result_region_dump() {
+    // The run halted normally; only the evaluator's verdict decides.
+    let stderr = run_with(TestOutcome::Failed("receipt mismatch".to_string()));
+    assert_eq!(stderr, DUMP);
+}
+
+#[test]
+fn passing_case_has_no_dump() {
+    assert_eq!(run_with(TestOutcome::Passed), "");
+}
@@ -1,5 +1,13 @@
 use compiler::elf::parse_elf_from_bytes;
+use state::TransferError;
+use types::ProgramImageHeader;
 use types::address::Address;
+use types::program_image::{CODE_SIZE_LIMIT, RO_DATA_SIZE_LIMIT};
+use types::result::{
+    ADDRESS_OCCUPIED_ERROR_CODE, CODE_SIZE_EXCEEDED_ERROR_CODE, CREATE2_MALFORMED_ERROR_CODE,
+    FAULT_ERROR_CODE, INPUT_TOO_LARGE_ERROR_CODE, INVALID_CALL_ARGS_ERROR_CODE,
+    MALFORMED_INPUT_ERROR_CODE, PANIC_ERROR_CODE, PROGRAM_LOAD_ERROR_CODE, STATIC_WRITE_ERROR_CODE,
+};
 use types::transaction::{Transaction, TransactionBundle, TransactionType};
 
 pub struct ExpectedResult {
@@ -8,10 +16,38 @@ pub struct ExpectedResult {
     pub data: Vec<u8>,
 }
 
+pub struct ExpectedLog {
+    pub emitter: Address,
+    pub data: Vec<u8>,
+}
+
+/// Programs a receipt's `call_trace` must list, in call order.
+pub struct ExpectedCallTrace {
+    pub index: usize,
+    pub programs: Vec<Address>,
+}
+
+pub struct ExpectedReceipt {
+    pub index: usize,
+    pub result: ExpectedResult,
+    pub logs: usize,
+}
+
+/// Account fields that must hold in the kernel's dumped post-bundle `State`.
+pub struct ExpectedAccount {
+    pub address: Address,
+    pub balance: Option<u128>,
+    /// `(composite storage key, raw value)` pairs, see [`map_key`].
+    pub storage: Vec<(String, Vec<u8>)>,
+    /// Composite storage keys that must not be present.
+    pub absent_storage: Vec<String>,
+}
+
 pub struct ExampleCase {
     pub name: &'static str,
     pub description: &'static str,
-    pub bundle: TransactionBundle,
+    /// Builds the bundle; kept lazy so cases can be listed without the example ELFs.
+    pub build: fn() -> Result<TransactionBundle, String>,
 }
 
 pub fn test_state_bytes() -> Vec<u8> {
@@ -27,260 +63,1799 @@ pub fn test_state_bytes() -> Vec<u8> {
     state.encode()
 }
 
-pub fn all_example_cases() -> Result<Vec<ExampleCase>, String> {
-    Ok(vec![
+pub fn all_example_cases() -> Vec<ExampleCase> {
+    vec![
         ExampleCase {
             name: "erc20",
             description: "ERC-20 init, transfer, and balance query flow",
-            bundle: build_erc20_bundle()?,
+            build: build_erc20_bundle,
+        },
+        ExampleCase {
+            name: "erc20 panic",
+            description: "Guest panic fails its transaction and rolls back its writes",
+            build: build_erc20_panic_bundle,
+        },
+        ExampleCase {
+            name: "erc20 overflow",
+            description: "Minting past a u32::MAX supply panics instead of wrapping",
+            build: build_erc20_overflow_bundle,
+        },
+        ExampleCase {
+            name: "erc20 malformed input",
+            description: "Truncated router frame fails its receipt without running any call",
+            build: build_erc20_malformed_input_bundle,
+        },
+        ExampleCase {
+            name: "erc20 approval cleared",
+            description: "Approving zero deletes the allowance storage entry",
+            build: build_erc20_approval_cleared_bundle,
+        },
+        ExampleCase {
+            name: "static call",
+            description: "Static-called erc20 answers a balance query but cannot approve",
+            build: build_static_call_bundle,
+        },
+        ExampleCase {
+            name: "checked withdraw",
+            description: "Failed require_ge reports both operands in the receipt",
+            build: build_checked_withdraw_bundle,
+        },
+        ExampleCase {
+            name: "high base",
+            description: "Program linked at 0x2000 loads at its link address and entry",
+            build: build_high_base_bundle,
+        },
+        ExampleCase {
+            name: "hash store",
+            description: "32-byte hash persists across calls and rejects a wrong-length load",
+            build: build_hash_store_bundle,
+        },
+        ExampleCase {
+            name: "storage batch",
+            description: "Three storage writes applied by one StorageBatch syscall",
+            build: || build_storage_batch_bundle(STORAGE_BATCH_BATCHED),
+        },
+        ExampleCase {
+            name: "storage batch (individual writes)",
+            description: "The same three storage writes made one syscall at a time",
+            build: || build_storage_batch_bundle(STORAGE_BATCH_INDIVIDUAL),
+        },
+        ExampleCase {
+            name: "code inspect",
+            description: "Another program reads a deployed contract's code hash and size",
+            build: build_code_inspect_bundle,
+        },
+        ExampleCase {
+            name: "fee quote",
+            description: "Program returns a 40-byte composite result built field by field",
+            build: build_fee_quote_bundle,
+        },
+        ExampleCase {
+            name: "tagged input",
+            description: "Program peeks a tag byte to pick between two input layouts",
+            build: build_tagged_input_bundle,
+        },
+        ExampleCase {
+            name: "malformed program",
+            description: "Calling a contract with no entry point fails its receipt only",
+            build: build_malformed_program_bundle,
+        },
+        ExampleCase {
+            name: "oversized code",
+            description: "Deploying code one byte over the size limit fails its receipt only",
+            build: build_oversized_code_bundle,
+        },
+        ExampleCase {
+            name: "oversized input",
+            description: "Call input one byte over the limit fails with the actual and max lengths",
+            build: build_oversized_input_bundle,
+        },
+        ExampleCase {
+            name: "page fault",
+            description: "A load from an unmapped address fails its receipt and rolls back",
+            build: build_page_fault_bundle,
+        },
+        ExampleCase {
+            name: "oversized call input",
+            description: "Nested call and static_call one byte over max_input_len both fail",
+            build: build_oversized_call_bundle,
+        },
+        ExampleCase {
+            name: "entropy",
+            description: "SYSCALL_RANDOM returns the stream seeded by the pre-bundle state",
+            build: build_entropy_bundle,
+        },
+        ExampleCase {
+            name: "bad call args",
+            description: "A nested call with an unmapped input pointer fails without running",
+            build: build_bad_call_args_bundle,
         },
         ExampleCase {
             name: "call program",
             description: "Cross-contract call with nested program execution",
-            bundle: build_call_program_bundle()?,
+            build: build_call_program_bundle,
+        },
+        ExampleCase {
+            name: "call resume",
+            description: "Caller resumes after each nested call and combines results",
+            build: build_call_resume_bundle,
+        },
+        ExampleCase {
+            name: "repeated calls",
+            description: "More completed nested calls than task slots",
+            build: build_repeated_calls_bundle,
         },
         ExampleCase {
             name: "account create (storage)",
             description: "Create a contract and invoke a storage call",
-            bundle: build_account_create_storage_bundle()?,
+            build: build_account_create_storage_bundle,
+        },
+        ExampleCase {
+            name: "create2 deploy",
+            description: "Salted deployment derives its address and refuses to redeploy",
+            build: build_create2_bundle,
+        },
+        ExampleCase {
+            name: "account redeploy",
+            description: "Deploying over existing code fails unless sent as a redeploy",
+            build: build_account_redeploy_bundle,
         },
         ExampleCase {
             name: "account create (simple)",
             description: "Create a simple contract and verify return data",
-            bundle: build_account_create_simple_bundle()?,
+            build: build_account_create_simple_bundle,
         },
         ExampleCase {
             name: "multi function (simple)",
             description: "Router-style call into a multi-function contract",
-            bundle: build_multi_function_simple_bundle()?,
+            build: build_multi_function_simple_bundle,
         },
         ExampleCase {
             name: "allocator demo",
             description: "Heap allocation and collection usage in guest code",
-            bundle: build_allocator_demo_bundle()?,
+            build: build_allocator_demo_bundle,
         },
         ExampleCase {
             name: "native transfer",
             description: "Native value transfer without a contract call",
-            bundle: build_native_transfer_bundle(),
+            build: || Ok(build_native_transfer_bundle()),
+        },
+        ExampleCase {
+            name: "native transfer failures",
+            description: "Rejected native transfers fail with the transfer error code",
+            build: || Ok(build_native_transfer_failures_bundle()),
         },
         ExampleCase {
             name: "guest transfer syscall",
             description: "Program issues a native transfer syscall",
-            bundle: build_guest_transfer_syscall_bundle()?,
+            build: build_guest_transfer_syscall_bundle,
+        },
+        ExampleCase {
+            name: "payable call",
+            description: "Program call carrying value credits the program or fails atomically",
+            build: build_payable_call_bundle,
+        },
+        ExampleCase {
+            name: "ledger u128",
+            description: "u128 persist_struct field survives store and reload across calls",
+            build: build_ledger_bundle,
+        },
+        ExampleCase {
+            name: "guest transfer many",
+            description: "Program pays several recipients with one batched syscall",
+            build: build_guest_transfer_many_bundle,
         },
         ExampleCase {
             name: "dex amm",
             description: "AMM lifecycle: init, approve, add/remove liquidity, swap",
-            bundle: build_dex_amm_bundle()?,
+            build: build_dex_amm_bundle,
         },
         ExampleCase {
             name: "ecdsa verify",
             description: "ECDSA signature verification within the VM",
-            bundle: build_ecdsa_verify_bundle()?,
+            build: build_ecdsa_verify_bundle,
         },
-    ])
+    ]
 }
 
-pub fn expected_for(name: &str) -> Option<ExpectedResult> {
+/// Logs that must appear somewhere in the bundle's receipts.
+pub fn expected_logs_for(name: &str) -> Vec<ExpectedLog> {
     match name {
-        "erc20" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: vec![128, 240, 250, 2],
-        }),
-        "call program" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: vec![100, 0, 0, 0],
-        }),
-        "account create (storage)" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: Vec::new(),
-        }),
-        "account create (simple)" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: vec![100, 0, 0, 0],
-        }),
-        "multi function (simple)" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: vec![100, 0, 0, 0],
-        }),
-        "allocator demo" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: Vec::new(),
-        }),
-        "native transfer" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: Vec::new(),
-        }),
-        "guest transfer syscall" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: 42u128.to_le_bytes().to_vec(),
-        }),
-        "dex amm" => {
-            let mut buf = Vec::new();
-            buf.extend_from_slice(&101000u128.to_le_bytes());
-            buf.extend_from_slice(&495050u128.to_le_bytes());
-            Some(ExpectedResult {
+        "erc20" => {
+            let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+            let recipient = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+            let mut data = event_id("Transfer").to_vec();
+            data.extend_from_slice(&deployer.0);
+            data.extend_from_slice(&recipient.0);
+            data.extend_from_slice(&50000000u32.to_le_bytes());
+            vec![ExpectedLog {
+                emitter: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                data,
+            }]
+        }
+        "static call" => {
+            let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+            let mut data = event_id("QueryFailed").to_vec();
+            data.extend_from_slice(&contract.0);
+            data.extend_from_slice(&STATIC_WRITE_ERROR_CODE.to_le_bytes());
+            vec![ExpectedLog {
+                emitter: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d7"),
+                data,
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Physical memory to give the VM for a case, or `None` for the runner default.
+pub fn memory_size_for(name: &str) -> Option<usize> {
+    match name {
+        // A single small program: run it in half the default memory.
+        "account create (simple)" => Some(8 * 1024 * 1024),
+        _ => None,
+    }
+}
+
+/// Receipts whose call trace must match exactly.
+pub fn expected_call_traces_for(name: &str) -> Vec<ExpectedCallTrace> {
+    match name {
+        "call program" => vec![ExpectedCallTrace {
+            index: 2,
+            programs: vec![
+                to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+            ],
+        }],
+        "static call" => vec![ExpectedCallTrace {
+            index: 4,
+            programs: vec![
+                to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d7"),
+                to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+            ],
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Receipts other than the last one that must match, with their expected log counts.
+pub fn expected_receipts_for(name: &str) -> Vec<ExpectedReceipt> {
+    match name {
+        "erc20 panic" => vec![ExpectedReceipt {
+            index: 2,
+            result: ExpectedResult {
+                success: false,
+                error_code: PANIC_ERROR_CODE,
+                data: b"insufficient".to_vec(),
+            },
+            logs: 0,
+        }],
+        "erc20 overflow" => vec![
+            ExpectedReceipt {
+                index: 2,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: PANIC_ERROR_CODE,
+                    data: b"u32 addition overflow".to_vec(),
+                },
+                logs: 0,
+            },
+            ExpectedReceipt {
+                index: 3,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data: Vec::new(),
+                },
+                logs: 1,
+            },
+        ],
+        "native transfer failures" => vec![
+            ExpectedReceipt {
+                index: 0,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data: Vec::new(),
+                },
+                logs: 0,
+            },
+            ExpectedReceipt {
+                index: 1,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: TransferError::InsufficientFunds.code(),
+                    data: Vec::new(),
+                },
+                logs: 0,
+            },
+            ExpectedReceipt {
+                index: 2,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: TransferError::SenderMissing.code(),
+                    data: Vec::new(),
+                },
+                logs: 0,
+            },
+        ],
+        "payable call" => vec![
+            ExpectedReceipt {
+                index: 1,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data: payable_result(500, 500),
+                },
+                logs: 0,
+            },
+            // The unfunded sender has no account, so the program never runs and the
+            // receipt carries the transfer's own error code.
+            ExpectedReceipt {
+                index: 2,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: TransferError::SenderMissing.code(),
+                    data: Vec::new(),
+                },
+                logs: 0,
+            },
+            ExpectedReceipt {
+                index: 3,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: PANIC_ERROR_CODE,
+                    data: b"call value mismatch".to_vec(),
+                },
+                logs: 0,
+            },
+            ExpectedReceipt {
+                index: 4,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data: payable_result(0, 500),
+                },
+                logs: 0,
+            },
+        ],
+        "erc20 malformed input" => vec![ExpectedReceipt {
+            index: 2,
+            result: ExpectedResult {
+                success: false,
+                error_code: MALFORMED_INPUT_ERROR_CODE,
+                data: b"router: bad arg len".to_vec(),
+            },
+            logs: 0,
+        }],
+        "ledger u128" => vec![ExpectedReceipt {
+            index: 1,
+            result: ExpectedResult {
+                success: true,
+                error_code: 0,
+                data: ledger_result(1u128 << 100, 1),
+            },
+            logs: 0,
+        }],
+        "static call" => vec![ExpectedReceipt {
+            index: 3,
+            result: ExpectedResult {
+                success: false,
+                error_code: STATIC_WRITE_ERROR_CODE,
+                data: Vec::new(),
+            },
+            // The querier survives the refused write and fires `QueryFailed`.
+            logs: 1,
+        }],
+        "erc20 approval cleared" => vec![ExpectedReceipt {
+            index: 3,
+            result: ExpectedResult {
+                success: true,
+                error_code: 0,
+                data: 1u32.to_le_bytes().to_vec(),
+            },
+            logs: 0,
+        }],
+        "checked withdraw" => vec![ExpectedReceipt {
+            index: 1,
+            result: ExpectedResult {
+                success: false,
+                error_code: PANIC_ERROR_CODE,
+                data: b"withdraw: expected a >= b: 5 < 10".to_vec(),
+            },
+            logs: 0,
+        }],
+        "create2 deploy" => {
+            let derived = create2_simple_address()
+                .expect("simple program built")
+                .0
+                .to_vec();
+            vec![
+                ExpectedReceipt {
+                    index: 0,
+                    result: ExpectedResult {
+                        success: true,
+                        error_code: 0,
+                        data: derived.clone(),
+                    },
+                    logs: 0,
+                },
+                ExpectedReceipt {
+                    index: 1,
+                    result: ExpectedResult {
+                        success: false,
+                        error_code: ADDRESS_OCCUPIED_ERROR_CODE,
+                        data: derived,
+                    },
+                    logs: 0,
+                },
+                ExpectedReceipt {
+                    index: 2,
+                    result: ExpectedResult {
+                        success: false,
+                        error_code: CREATE2_MALFORMED_ERROR_CODE,
+                        data: Vec::new(),
+                    },
+                    logs: 0,
+                },
+            ]
+        }
+        "account redeploy" => {
+            let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+            vec![
+                ExpectedReceipt {
+                    index: 1,
+                    result: ExpectedResult {
+                        success: false,
+                        error_code: ADDRESS_OCCUPIED_ERROR_CODE,
+                        data: addr.0.to_vec(),
+                    },
+                    logs: 0,
+                },
+                // The refused deployment left `simple` in place.
+                ExpectedReceipt {
+                    index: 2,
+                    result: ExpectedResult {
+                        success: true,
+                        error_code: 0,
+                        data: 100u32.to_le_bytes().to_vec(),
+                    },
+                    logs: 0,
+                },
+                ExpectedReceipt {
+                    index: 3,
+                    result: ExpectedResult {
+                        success: true,
+                        error_code: 0,
+                        data: vec![],
+                    },
+                    logs: 0,
+                },
+            ]
+        }
+        "hash store" => vec![ExpectedReceipt {
+            index: 2,
+            result: ExpectedResult {
                 success: true,
                 error_code: 0,
-                data: buf,
+                data: stored_hash().to_vec(),
+            },
+            logs: 0,
+        }],
+        "code inspect" => {
+            let code = get_program_code("simple").expect("simple program built");
+            let mut data = state::code_hash(&code).to_vec();
+            data.extend((code.len() as u32).to_le_bytes());
+            vec![ExpectedReceipt {
+                index: 2,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data,
+                },
+                logs: 0,
+            }]
+        }
+        "fee quote" => {
+            // Caller, then 10_000 - 1% as a u128, then the 1% fee as a u32.
+            let user = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+            let mut data = user.0.to_vec();
+            data.extend(9_900u128.to_le_bytes());
+            data.extend(100u32.to_le_bytes());
+            vec![ExpectedReceipt {
+                index: 1,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data,
+                },
+                logs: 0,
+            }]
+        }
+        "tagged input" => {
+            // Cursor position after decoding, then the decoded amount.
+            let result = |end: u32, amount: u64| {
+                let mut data = end.to_le_bytes().to_vec();
+                data.extend(amount.to_le_bytes());
+                ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data,
+                }
+            };
+            vec![
+                ExpectedReceipt {
+                    index: 1,
+                    result: result(5, 500),
+                    logs: 0,
+                },
+                ExpectedReceipt {
+                    index: 2,
+                    result: result(29, 7),
+                    logs: 0,
+                },
+            ]
+        }
+        "oversized input" => {
+            // Actual length, then the kernel's MAX_INPUT_LEN.
+            let mut data = (OVERSIZED_INPUT_LEN as u32).to_le_bytes().to_vec();
+            data.extend(1024u32.to_le_bytes());
+            vec![ExpectedReceipt {
+                index: 1,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: INPUT_TOO_LARGE_ERROR_CODE,
+                    data,
+                },
+                logs: 0,
+            }]
+        }
+        "malformed program" => vec![ExpectedReceipt {
+            index: 2,
+            result: ExpectedResult {
+                success: false,
+                error_code: PROGRAM_LOAD_ERROR_CODE,
+                data: Vec::new(),
+            },
+            logs: 0,
+        }],
+        "oversized code" => vec![ExpectedReceipt {
+            index: 0,
+            result: ExpectedResult {
+                success: false,
+                error_code: CODE_SIZE_EXCEEDED_ERROR_CODE,
+                data: Vec::new(),
+            },
+            logs: 0,
+        }],
+        "oversized call input" => {
+            // Actual length, then the kernel's MAX_INPUT_LEN, for the call and the
+            // static call alike.
+            let receipt = |index: usize| {
+                let mut data = (OVERSIZED_INPUT_LEN as u32).to_le_bytes().to_vec();
+                data.extend(1024u32.to_le_bytes());
+                ExpectedReceipt {
+                    index,
+                    result: ExpectedResult {
+                        success: false,
+                        error_code: INPUT_TOO_LARGE_ERROR_CODE,
+                        data,
+                    },
+                    logs: 0,
+                }
+            };
+            vec![receipt(2), receipt(3)]
+        }
+        // Each call draws its own transaction's stream, so the two answers differ.
+        "entropy" => [1, 2]
+            .into_iter()
+            .map(|index| ExpectedReceipt {
+                index,
+                result: ExpectedResult {
+                    success: true,
+                    error_code: 0,
+                    data: entropy_result(index as u32),
+                },
+                logs: 0,
             })
+            .collect(),
+        "bad call args" => {
+            // Argument 2 (input), then the unmapped pointer the guest passed.
+            let mut data = 2u32.to_le_bytes().to_vec();
+            data.extend(0x0080_0000u32.to_le_bytes());
+            vec![ExpectedReceipt {
+                index: 2,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: INVALID_CALL_ARGS_ERROR_CODE,
+                    data,
+                },
+                logs: 0,
+            }]
         }
-        "ecdsa verify" => Some(ExpectedResult {
-            success: true,
-            error_code: 0,
-            data: Vec::new(),
-        }),
-        _ => None,
+        "page fault" => {
+            // scause 13 (load page fault), stval = the unmapped address.
+            let mut data = 13u32.to_le_bytes().to_vec();
+            data.extend(0x0080_0000u32.to_le_bytes());
+            vec![ExpectedReceipt {
+                index: 1,
+                result: ExpectedResult {
+                    success: false,
+                    error_code: FAULT_ERROR_CODE,
+                    data,
+                },
+                logs: 0,
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Accounts whose final balance/storage must match after the bundle runs.
+pub fn expected_state_for(name: &str) -> Vec<ExpectedAccount> {
+    match name {
+        // Only the first transfer applies.
+        "native transfer failures" => vec![
+            ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
+                balance: Some(1_000_000_000 - 10),
+                storage: Vec::new(),
+                absent_storage: Vec::new(),
+            },
+            ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                balance: Some(10),
+                storage: Vec::new(),
+                absent_storage: Vec::new(),
+            },
+        ],
+        "payable call" => vec![
+            ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4"),
+                balance: Some(500),
+                storage: Vec::new(),
+                absent_storage: Vec::new(),
+            },
+            ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
+                balance: Some(1_000_000_000 - 500),
+                storage: Vec::new(),
+                absent_storage: Vec::new(),
+            },
+        ],
+        "erc20" => {
+            let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+            let recipient = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+            vec![
+                ExpectedAccount {
+                    address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    balance: None,
+                    storage: vec![
+                        (
+                            map_key("Balances", &deployer.0),
+                            50000000u32.to_le_bytes().to_vec(),
+                        ),
+                        (
+                            map_key("Balances", &recipient.0),
+                            50000000u32.to_le_bytes().to_vec(),
+                        ),
+                    ],
+                    absent_storage: Vec::new(),
+                },
+                ExpectedAccount {
+                    address: recipient,
+                    balance: Some(1_000_000_000),
+                    storage: Vec::new(),
+                    absent_storage: Vec::new(),
+                },
+            ]
+        }
+        // The failed mint leaves the supply whole and the self-transfer moves nothing.
+        "erc20 overflow" => {
+            let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+            vec![ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                balance: None,
+                storage: vec![(
+                    map_key("Balances", &deployer.0),
+                    u32::MAX.to_le_bytes().to_vec(),
+                )],
+                absent_storage: Vec::new(),
+            }]
+        }
+        "erc20 approval cleared" => {
+            let owner = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+            let spender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+            let mut allowance_key = owner.0.to_vec();
+            allowance_key.extend_from_slice(&spender.0);
+            vec![ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                balance: None,
+                storage: vec![(
+                    map_key("Balances", &owner.0),
+                    100000000u32.to_le_bytes().to_vec(),
+                )],
+                absent_storage: vec![map_key("Allowances", &allowance_key)],
+            }]
+        }
+        "static call" => {
+            let owner = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+            let querier = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d7");
+            let spender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+            let mut allowance_key = querier.0.to_vec();
+            allowance_key.extend_from_slice(&spender.0);
+            vec![ExpectedAccount {
+                address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                balance: None,
+                storage: vec![(
+                    map_key("Balances", &owner.0),
+                    100000000u32.to_le_bytes().to_vec(),
+                )],
+                absent_storage: vec![map_key("Allowances", &allowance_key)],
+            }]
+        }
+        "ledger u128" => vec![ExpectedAccount {
+            address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4"),
+            balance: None,
+            // total (u128) then deposits (u64), little-endian with no padding.
+            storage: vec![(
+                map_key("P", b"Ledger"),
+                ledger_result((1u128 << 100) - 30, 2),
+            )],
+            absent_storage: Vec::new(),
+        }],
+        "storage batch" | "storage batch (individual writes)" => vec![ExpectedAccount {
+            address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+            balance: None,
+            storage: vec![
+                (map_key("Batch", b"a"), 1u32.to_le_bytes().to_vec()),
+                (map_key("Batch", b"b"), 2u32.to_le_bytes().to_vec()),
+                (map_key("Batch", b"c"), 3u32.to_le_bytes().to_vec()),
+            ],
+            absent_storage: Vec::new(),
+        }],
+        "hash store" => vec![ExpectedAccount {
+            address: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+            balance: None,
+            storage: vec![(map_key("Hashes", b"root"), stored_hash().to_vec())],
+            absent_storage: Vec::new(),
+        }],
+        _ => Vec::new(),
     }
 }
 
+/// Storage key the kernel uses for a `Map!` or `StorageBytes` entry: `"<domain>:<hex key>"`.
+fn map_key(domain: &str, key: &[u8]) -> String {
+    let hex: String = key.iter().map(|b| format!("{b:02x}")).collect();
+    format!("{domain}:{hex}")
+}
+
+fn event_id(name: &str) -> [u8; 32] {
+    let mut id = [0u8; 32];
+    let len = name.len().min(32);
+    id[..len].copy_from_slice(&name.as_bytes()[..len]);
+    id
+}
+
 struct HostFuncCall {
     selector: u8,
     args: Vec<u8>,
 }
 
-fn encode_router_calls(calls: &[HostFuncCall]) -> Vec<u8> {
-    let mut encoded = Vec::new();
-    for call in calls {
-        let len = call.args.len();
-        assert!(len <= 255, "argument too long for 1-byte length field");
-        encoded.push(call.selector);
-        encoded.push(len as u8);
-        encoded.extend_from_slice(&call.args);
-    }
-    encoded
+fn encode_router_calls(calls: &[HostFuncCall]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for call in calls {
+        let len = call.args.len();
+        assert!(len <= 255, "argument too long for 1-byte length field");
+        encoded.push(call.selector);
+        encoded.push(len as u8);
+        encoded.extend_from_slice(&call.args);
+    }
+    encoded
+}
+
+fn build_erc20_bundle() -> Result<TransactionBundle, String> {
+    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: deployer,
+            to: contract,
+            data: get_program_code("erc20")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x01,
+                args: {
+                    let max_supply: u32 = 100000000;
+                    let mut max_supply_bytes = max_supply.to_le_bytes().to_vec();
+                    max_supply_bytes.extend(vec![18u8]);
+                    max_supply_bytes
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x02,
+                args: {
+                    let to_addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+                    let mut args = to_addr.0.to_vec();
+                    let amount: u32 = 50000000;
+                    args.extend(amount.to_le_bytes());
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x05,
+                args: {
+                    let owner = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+                    owner.0.to_vec()
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_erc20_panic_bundle() -> Result<TransactionBundle, String> {
+    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let recipient = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let transfer_args = |amount: u32| {
+        let mut args = recipient.0.to_vec();
+        args.extend(amount.to_le_bytes());
+        args
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: deployer,
+            to: contract,
+            data: get_program_code("erc20")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x01,
+                args: {
+                    let mut args = 100000000u32.to_le_bytes().to_vec();
+                    args.push(18);
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        // The first transfer succeeds and fires an event, then the second panics with
+        // "insufficient"; both must be rolled back.
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[
+                HostFuncCall {
+                    selector: 0x02,
+                    args: transfer_args(50000000),
+                },
+                HostFuncCall {
+                    selector: 0x02,
+                    args: transfer_args(60000000),
+                },
+            ]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x05,
+                args: deployer.0.to_vec(),
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_erc20_overflow_bundle() -> Result<TransactionBundle, String> {
+    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: deployer,
+            to: contract,
+            data: get_program_code("erc20")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x01,
+                args: {
+                    let mut args = u32::MAX.to_le_bytes().to_vec();
+                    args.push(18);
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        // Minting one more token would take the total supply past u32::MAX.
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x07,
+                args: 1u32.to_le_bytes().to_vec(),
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        // A self-transfer while holding u32::MAX leaves the balance unchanged.
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x02,
+                args: {
+                    let mut args = deployer.0.to_vec();
+                    args.extend(1u32.to_le_bytes());
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x02,
+                args: {
+                    let mut args = deployer.0.to_vec();
+                    args.extend(1u32.to_le_bytes());
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x05,
+                args: deployer.0.to_vec(),
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_erc20_malformed_input_bundle() -> Result<TransactionBundle, String> {
+    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let recipient = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    // A well-formed transfer followed by a frame declaring 24 argument bytes but
+    // carrying only 10: the whole input is rejected, so the transfer never runs.
+    let mut malformed = encode_router_calls(&[HostFuncCall {
+        selector: 0x02,
+        args: {
+            let mut args = recipient.0.to_vec();
+            args.extend(1000u32.to_le_bytes());
+            args
+        },
+    }]);
+    malformed.extend([0x02, 24]);
+    malformed.extend(&recipient.0[..10]);
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: deployer,
+            to: contract,
+            data: get_program_code("erc20")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x01,
+                args: {
+                    let mut args = 100000000u32.to_le_bytes().to_vec();
+                    args.push(18);
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: malformed,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: deployer,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x05,
+                args: deployer.0.to_vec(),
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_static_call_bundle() -> Result<TransactionBundle, String> {
+    let owner = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let spender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let querier = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d7");
+    // The querier forwards everything after the target address to it as a static call.
+    let query = |selector: u8, args: Vec<u8>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: querier,
+        from: owner,
+        data: {
+            let mut data = contract.0.to_vec();
+            data.extend(encode_router_calls(&[HostFuncCall { selector, args }]));
+            data
+        },
+        value: 0,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: owner,
+            to: contract,
+            data: get_program_code("erc20")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: contract,
+            from: owner,
+            data: encode_router_calls(&[HostFuncCall {
+                selector: 0x01,
+                args: {
+                    let mut args = 100000000u32.to_le_bytes().to_vec();
+                    args.push(18);
+                    args
+                },
+            }]),
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: owner,
+            to: querier,
+            data: get_program_code("static_query")?,
+            value: 0,
+            nonce: 0,
+        },
+        // approve writes storage, which a static call must refuse.
+        query(0x03, {
+            let mut args = spender.0.to_vec();
+            args.extend(5000u32.to_le_bytes());
+            args
+        }),
+        // balance_of only reads, so it still answers.
+        query(0x05, owner.0.to_vec()),
+    ]))
+}
+
+fn build_erc20_approval_cleared_bundle() -> Result<TransactionBundle, String> {
+    let owner = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let spender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let call = |selector: u8, args: Vec<u8>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: contract,
+        from: owner,
+        data: encode_router_calls(&[HostFuncCall { selector, args }]),
+        value: 0,
+        nonce: 0,
+    };
+    let approve = |amount: u32| {
+        let mut args = spender.0.to_vec();
+        args.extend(amount.to_le_bytes());
+        call(0x03, args)
+    };
+    let has_allowance = || {
+        let mut args = owner.0.to_vec();
+        args.extend_from_slice(&spender.0);
+        call(0x06, args)
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: owner,
+            to: contract,
+            data: get_program_code("erc20")?,
+            value: 0,
+            nonce: 0,
+        },
+        call(0x01, {
+            let mut args = 100000000u32.to_le_bytes().to_vec();
+            args.push(18);
+            args
+        }),
+        approve(5000),
+        has_allowance(),
+        approve(0),
+        has_allowance(),
+    ]))
+}
+
+fn build_page_fault_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let call = |op: u8| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: addr,
+        from: addr,
+        data: vec![op],
+        value: 0,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("page_fault")?,
+            value: 0,
+            nonce: 0,
+        },
+        // Stores a mark, then faults: the mark must be rolled back.
+        call(1),
+        // Still runs after the fault and reads the mark back as 0.
+        call(0),
+    ]))
+}
+
+fn build_bad_call_args_bundle() -> Result<TransactionBundle, String> {
+    let caller = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let callee = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let deploy = |addr: Address, program: &str| -> Result<Transaction, String> {
+        Ok(Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code(program)?,
+            value: 0,
+            nonce: 0,
+        })
+    };
+    Ok(TransactionBundle::new(vec![
+        deploy(caller, "bad_call_args")?,
+        deploy(callee, "simple")?,
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: caller,
+            from: caller,
+            data: callee.0.to_vec(),
+            value: 0,
+            nonce: 0,
+        },
+        // The bundle carries on: the callee still answers a direct call.
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: callee,
+            from: caller,
+            data: vec![100, 0, 0, 0, 42, 0, 0, 0],
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_oversized_call_bundle() -> Result<TransactionBundle, String> {
+    let caller = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let callee = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let deploy = |addr: Address, program: &str| -> Result<Transaction, String> {
+        Ok(Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code(program)?,
+            value: 0,
+            nonce: 0,
+        })
+    };
+    // Target address, then 0 for call or 1 for static_call.
+    let call = |kind: u8| {
+        let mut data = callee.0.to_vec();
+        data.push(kind);
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: caller,
+            from: caller,
+            data,
+            value: 0,
+            nonce: 0,
+        }
+    };
+    Ok(TransactionBundle::new(vec![
+        deploy(caller, "oversized_call")?,
+        deploy(callee, "simple")?,
+        call(0),
+        call(1),
+        // The callee never ran and still answers a direct call.
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: callee,
+            from: caller,
+            data: vec![100, 0, 0, 0, 42, 0, 0, 0],
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_oversized_code_bundle() -> Result<TransactionBundle, String> {
+    let oversized = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d6");
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let limit = CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT;
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: oversized,
+            from: addr,
+            data: vec![0x13; limit + 1],
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("simple")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: addr,
+            from: addr,
+            data: vec![100, 0, 0, 0, 42, 0, 0, 0],
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+/// One byte over the kernel's 1024-byte `MAX_INPUT_LEN`.
+const OVERSIZED_INPUT_LEN: usize = 1025;
+
+fn build_oversized_input_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("simple")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: addr,
+            from: addr,
+            data: vec![0u8; OVERSIZED_INPUT_LEN],
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: addr,
+            from: addr,
+            data: vec![100, 0, 0, 0, 42, 0, 0, 0],
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_call_program_bundle() -> Result<TransactionBundle, String> {
+    let caller = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let callee = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: caller,
+            to: caller,
+            data: get_program_code("call_program")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: caller,
+            to: callee,
+            data: get_program_code("simple")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: caller,
+            from: caller,
+            data: {
+                let mut data = callee.0.to_vec();
+                data.extend(vec![100, 0, 0, 0, 42, 0, 0, 0]);
+                data
+            },
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_call_resume_bundle() -> Result<TransactionBundle, String> {
+    let caller = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let callee = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: caller,
+            to: caller,
+            data: get_program_code("call_resume")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: caller,
+            to: callee,
+            data: get_program_code("simple")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: caller,
+            from: caller,
+            data: {
+                let mut data = callee.0.to_vec();
+                for value in [100u32, 42, 7, 77] {
+                    data.extend(value.to_le_bytes());
+                }
+                data
+            },
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+/// Each program call uses two task slots (caller + nested callee), so this bundle
+/// only completes if finished tasks hand their slots back.
+const REPEATED_CALLS: u64 = 20;
+
+fn build_repeated_calls_bundle() -> Result<TransactionBundle, String> {
+    let caller = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let callee = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let mut txs = vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: caller,
+            to: caller,
+            data: get_program_code("call_program")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            from: caller,
+            to: callee,
+            data: get_program_code("simple")?,
+            value: 0,
+            nonce: 0,
+        },
+    ];
+    for nonce in 0..REPEATED_CALLS {
+        txs.push(Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: caller,
+            from: caller,
+            data: {
+                let mut data = callee.0.to_vec();
+                data.extend(vec![100, 0, 0, 0, 42, 0, 0, 0]);
+                data
+            },
+            value: 0,
+            nonce,
+        });
+    }
+    Ok(TransactionBundle::new(txs))
+}
+
+fn build_account_create_storage_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("storage")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: addr,
+            from: addr,
+            data: vec![],
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+const CREATE2_SALT: [u8; 32] = [0x5a; 32];
+
+fn create2_simple_address() -> Result<Address, String> {
+    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let code = get_program_code("simple")?;
+    Ok(state::create2_address(&deployer, &CREATE2_SALT, &code))
+}
+
+/// Deploy `simple`, try to deploy `fee_quote` over it, then replace it for real with
+/// a redeploy. The calls show which program answers at each point.
+fn build_account_redeploy_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let user = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let deploy = |tx_type: TransactionType, program: &str| -> Result<Transaction, String> {
+        Ok(Transaction {
+            tx_type,
+            to: addr,
+            from: addr,
+            data: get_program_code(program)?,
+            value: 0,
+            nonce: 0,
+        })
+    };
+    let call = |data: Vec<u8>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: addr,
+        from: user,
+        data,
+        value: 0,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        deploy(TransactionType::CreateAccount, "simple")?,
+        deploy(TransactionType::CreateAccount, "fee_quote")?,
+        call(vec![100, 0, 0, 0, 42, 0, 0, 0]),
+        deploy(TransactionType::Redeploy, "fee_quote")?,
+        call(10_000u64.to_le_bytes().to_vec()),
+    ]))
+}
+
+fn build_create2_bundle() -> Result<TransactionBundle, String> {
+    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let mut data = CREATE2_SALT.to_vec();
+    data.extend(get_program_code("simple")?);
+    let deploy = Transaction {
+        tx_type: TransactionType::Create2,
+        // Ignored: the kernel derives the address from (from, salt, code).
+        to: deployer,
+        from: deployer,
+        data,
+        value: 0,
+        nonce: 0,
+    };
+    // Too short to hold the salt.
+    let malformed = Transaction {
+        data: CREATE2_SALT[..10].to_vec(),
+        ..deploy.clone()
+    };
+    Ok(TransactionBundle::new(vec![
+        deploy.clone(),
+        // Same deployer, salt and code: the derived address is taken.
+        deploy,
+        malformed,
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: create2_simple_address()?,
+            from: deployer,
+            data: vec![100, 0, 0, 0, 42, 0, 0, 0],
+            value: 0,
+            nonce: 0,
+        },
+    ]))
 }
 
-fn build_erc20_bundle() -> Result<TransactionBundle, String> {
-    let deployer = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
-    let contract = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+fn build_account_create_simple_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
     Ok(TransactionBundle::new(vec![
         Transaction {
             tx_type: TransactionType::CreateAccount,
-            from: deployer,
-            to: contract,
-            data: get_program_code("erc20")?,
+            to: addr,
+            from: addr,
+            data: get_program_code("simple")?,
             value: 0,
             nonce: 0,
         },
         Transaction {
             tx_type: TransactionType::ProgramCall,
-            to: contract,
-            from: deployer,
-            data: encode_router_calls(&[HostFuncCall {
-                selector: 0x01,
-                args: {
-                    let max_supply: u32 = 100000000;
-                    let mut max_supply_bytes = max_supply.to_le_bytes().to_vec();
-                    max_supply_bytes.extend(vec![18u8]);
-                    max_supply_bytes
-                },
-            }]),
+            to: addr,
+            from: addr,
+            data: vec![100, 0, 0, 0, 42, 0, 0, 0],
             value: 0,
             nonce: 0,
         },
+    ]))
+}
+
+/// Deploys `simple` to `copies` distinct addresses, so the kernel has to keep that many
+/// copies of its code.
+pub fn build_deploy_copies_bundle(copies: u8) -> Result<TransactionBundle, String> {
+    let code = get_program_code("simple")?;
+    Ok(TransactionBundle::new(
+        (0..copies)
+            .map(|i| {
+                let addr = Address([0xe0 | i; 20]);
+                Transaction {
+                    tx_type: TransactionType::CreateAccount,
+                    to: addr,
+                    from: addr,
+                    data: code.clone(),
+                    value: 0,
+                    nonce: 0,
+                }
+            })
+            .collect(),
+    ))
+}
+
+fn build_checked_withdraw_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let withdraw = |available: u32, requested: u32| {
+        let mut data = available.to_le_bytes().to_vec();
+        data.extend(requested.to_le_bytes());
         Transaction {
             tx_type: TransactionType::ProgramCall,
-            to: contract,
-            from: deployer,
-            data: encode_router_calls(&[HostFuncCall {
-                selector: 0x02,
-                args: {
-                    let to_addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
-                    let mut args = to_addr.0.to_vec();
-                    let amount: u32 = 50000000;
-                    args.extend(amount.to_le_bytes());
-                    args
-                },
-            }]),
+            to: addr,
+            from: addr,
+            data,
+            value: 0,
+            nonce: 0,
+        }
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("checked_withdraw")?,
+            value: 0,
+            nonce: 0,
+        },
+        withdraw(5, 10),
+        withdraw(10, 4),
+    ]))
+}
+
+fn build_high_base_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let mut input = Vec::new();
+    for value in [1u32, 2, 3, 4] {
+        input.extend(value.to_le_bytes());
+    }
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("high_base")?,
             value: 0,
             nonce: 0,
         },
         Transaction {
             tx_type: TransactionType::ProgramCall,
-            to: contract,
-            from: deployer,
-            data: encode_router_calls(&[HostFuncCall {
-                selector: 0x05,
-                args: {
-                    let owner = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
-                    owner.0.to_vec()
-                },
-            }]),
+            to: addr,
+            from: addr,
+            data: input,
             value: 0,
             nonce: 0,
         },
     ]))
 }
 
-fn build_call_program_bundle() -> Result<TransactionBundle, String> {
-    let caller = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
-    let callee = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+fn stored_hash() -> [u8; 32] {
+    core::array::from_fn(|i| 0xa0 ^ i as u8)
+}
+
+fn build_hash_store_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let call = |data: Vec<u8>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: addr,
+        from: addr,
+        data,
+        value: 0,
+        nonce: 0,
+    };
+    let mut store = vec![0u8];
+    store.extend(stored_hash());
     Ok(TransactionBundle::new(vec![
         Transaction {
             tx_type: TransactionType::CreateAccount,
-            from: caller,
-            to: caller,
-            data: get_program_code("call_program")?,
+            to: addr,
+            from: addr,
+            data: get_program_code("hash_store")?,
             value: 0,
             nonce: 0,
         },
+        call(store),
+        call(vec![1]),
+        call(vec![2]),
+    ]))
+}
+
+/// `storage_batch` operation bytes: one `StorageMap::set` per key, or one flush.
+const STORAGE_BATCH_INDIVIDUAL: u8 = 0;
+const STORAGE_BATCH_BATCHED: u8 = 1;
+
+/// Deploy `storage_batch` and write its three keys with operation `op`.
+fn build_storage_batch_bundle(op: u8) -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    Ok(TransactionBundle::new(vec![
         Transaction {
             tx_type: TransactionType::CreateAccount,
-            from: caller,
-            to: callee,
-            data: get_program_code("simple")?,
+            to: addr,
+            from: addr,
+            data: get_program_code("storage_batch")?,
             value: 0,
             nonce: 0,
         },
         Transaction {
             tx_type: TransactionType::ProgramCall,
-            to: caller,
-            from: caller,
-            data: {
-                let mut data = callee.0.to_vec();
-                data.extend(vec![100, 0, 0, 0, 42, 0, 0, 0]);
-                data
-            },
+            to: addr,
+            from: addr,
+            data: vec![op],
             value: 0,
             nonce: 0,
         },
     ]))
 }
 
-fn build_account_create_storage_bundle() -> Result<TransactionBundle, String> {
+fn build_code_inspect_bundle() -> Result<TransactionBundle, String> {
+    let inspector = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let target = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
+    let user = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let inspect = |addr: Address| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: inspector,
+        from: user,
+        data: addr.0.to_vec(),
+        value: 0,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: inspector,
+            from: inspector,
+            data: get_program_code("code_inspect")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: target,
+            from: inspector,
+            data: get_program_code("simple")?,
+            value: 0,
+            nonce: 0,
+        },
+        inspect(target),
+        inspect(user),
+    ]))
+}
+
+fn build_fee_quote_bundle() -> Result<TransactionBundle, String> {
     let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let user = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
     Ok(TransactionBundle::new(vec![
         Transaction {
             tx_type: TransactionType::CreateAccount,
             to: addr,
             from: addr,
-            data: get_program_code("storage")?,
+            data: get_program_code("fee_quote")?,
             value: 0,
             nonce: 0,
         },
         Transaction {
             tx_type: TransactionType::ProgramCall,
             to: addr,
+            from: user,
+            data: 10_000u64.to_le_bytes().to_vec(),
+            value: 0,
+            nonce: 0,
+        },
+    ]))
+}
+
+fn build_tagged_input_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let user = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let call = |data: Vec<u8>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: addr,
+        from: user,
+        data,
+        value: 0,
+        nonce: 0,
+    };
+    let mut amount = vec![0x01];
+    amount.extend(500u32.to_le_bytes());
+    let mut transfer = vec![0x02];
+    transfer.extend(user.0);
+    transfer.extend(7u64.to_le_bytes());
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
             from: addr,
-            data: vec![],
+            data: get_program_code("tagged_input")?,
             value: 0,
             nonce: 0,
         },
+        call(amount),
+        call(transfer),
     ]))
 }
 
-fn build_account_create_simple_bundle() -> Result<TransactionBundle, String> {
+fn build_malformed_program_bundle() -> Result<TransactionBundle, String> {
     let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let broken = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5");
     Ok(TransactionBundle::new(vec![
         Transaction {
             tx_type: TransactionType::CreateAccount,
@@ -290,6 +1865,23 @@ fn build_account_create_simple_bundle() -> Result<TransactionBundle, String> {
             value: 0,
             nonce: 0,
         },
+        // All-zero code has no entry point inside the image.
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: broken,
+            from: addr,
+            data: vec![0u8; 64],
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: broken,
+            from: addr,
+            data: vec![1, 0, 0, 0, 2, 0, 0, 0],
+            value: 0,
+            nonce: 0,
+        },
         Transaction {
             tx_type: TransactionType::ProgramCall,
             to: addr,
@@ -361,6 +1953,24 @@ fn build_native_transfer_bundle() -> TransactionBundle {
     }])
 }
 
+fn build_native_transfer_failures_bundle() -> TransactionBundle {
+    let transfer = |from: &str, value: u64| Transaction {
+        tx_type: TransactionType::Transfer,
+        to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+        from: to_address(from),
+        data: vec![],
+        value,
+        nonce: 0,
+    };
+    TransactionBundle::new(vec![
+        transfer("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3", 10),
+        // More than the funded sender holds.
+        transfer("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3", 2_000_000_000),
+        // No account at all.
+        transfer("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d9", 1),
+    ])
+}
+
 fn build_guest_transfer_syscall_bundle() -> Result<TransactionBundle, String> {
     let program = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4");
     let sender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3");
@@ -389,6 +1999,154 @@ fn build_guest_transfer_syscall_bundle() -> Result<TransactionBundle, String> {
     ]))
 }
 
+/// The payable example's result for a call from the funded sender: the value it
+/// received, its balance (both u128) and the sender's address.
+fn payable_result(value: u128, balance: u128) -> Vec<u8> {
+    let mut data = value.to_le_bytes().to_vec();
+    data.extend(balance.to_le_bytes());
+    data.extend(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3").0);
+    data
+}
+
+fn build_payable_call_bundle() -> Result<TransactionBundle, String> {
+    let program = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4");
+    let sender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3");
+    let unfunded = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5");
+    let call = |from: Address, value: u64, expected: Option<u128>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: program,
+        from,
+        data: expected.map_or_else(Vec::new, |value| value.to_le_bytes().to_vec()),
+        value,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: program,
+            from: sender,
+            data: get_program_code("payable")?,
+            value: 0,
+            nonce: 0,
+        },
+        call(sender, 500, Some(500)),
+        call(unfunded, 10, None),
+        // The program panics on the mismatch, so the 7 goes back to the sender.
+        call(sender, 7, Some(8)),
+        // Reads the balance back: only the first call's value arrived.
+        call(sender, 0, None),
+    ]))
+}
+
+/// The ledger example's result and stored value: total (u128) then deposit count (u64).
+/// Bytes the entropy example draws per call.
+const ENTROPY_DRAW_LEN: u8 = 12;
+
+/// What the entropy example returns in transaction `tx_index`: `ENTROPY_DRAW_LEN`
+/// bytes and then a u64 of the stream seeded from `test_state_bytes`.
+fn entropy_result(tx_index: u32) -> Vec<u8> {
+    let state = state::State::decode(&test_state_bytes()).expect("test state decodes");
+    let mut stream = state::Entropy::new(&state::entropy_seed(&state), tx_index);
+    let mut data = vec![0u8; ENTROPY_DRAW_LEN as usize];
+    stream.fill(&mut data);
+    data.extend(stream.next_u64().to_le_bytes());
+    data
+}
+
+fn build_entropy_bundle() -> Result<TransactionBundle, String> {
+    let addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let draw = Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: addr,
+        from: addr,
+        data: vec![ENTROPY_DRAW_LEN],
+        value: 0,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: addr,
+            from: addr,
+            data: get_program_code("entropy")?,
+            value: 0,
+            nonce: 0,
+        },
+        draw.clone(),
+        draw,
+    ]))
+}
+
+fn ledger_result(total: u128, deposits: u64) -> Vec<u8> {
+    let mut data = total.to_le_bytes().to_vec();
+    data.extend(deposits.to_le_bytes());
+    data
+}
+
+fn build_ledger_bundle() -> Result<TransactionBundle, String> {
+    let program = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4");
+    let sender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3");
+    let call = |op: u8, amount: Option<u128>| Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: program,
+        from: sender,
+        data: {
+            let mut data = vec![op];
+            if let Some(amount) = amount {
+                data.extend(amount.to_le_bytes());
+            }
+            data
+        },
+        value: 0,
+        nonce: 0,
+    };
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: program,
+            from: sender,
+            data: get_program_code("ledger")?,
+            value: 0,
+            nonce: 0,
+        },
+        // A deposit past u64::MAX only survives if all 16 bytes are stored.
+        call(0x01, Some(1u128 << 100)),
+        call(0x01, Some(50)),
+        call(0x02, Some(80)),
+        call(0x03, None),
+    ]))
+}
+
+fn build_guest_transfer_many_bundle() -> Result<TransactionBundle, String> {
+    let program = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4");
+    let sender = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3");
+    let first = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
+    let second = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
+    let mut data = Vec::new();
+    for (to, value) in [(first, 100u64), (second, 200), (first, 50)] {
+        data.extend_from_slice(&to.0);
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    Ok(TransactionBundle::new(vec![
+        Transaction {
+            tx_type: TransactionType::CreateAccount,
+            to: program,
+            from: sender,
+            data: get_program_code("airdrop")?,
+            value: 0,
+            nonce: 0,
+        },
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: program,
+            from: sender,
+            data,
+            value: 0,
+            nonce: 1,
+        },
+    ]))
+}
+
 fn build_dex_amm_bundle() -> Result<TransactionBundle, String> {
     let erc20 = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1");
     let dex = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5");
@@ -541,6 +2299,37 @@ fn get_program_code(name: &str) -> Result<Vec<u8>, String> {
     let elf =
         parse_elf_from_bytes(&bytes).map_err(|e| format!("failed to parse elf for {name}: {e}"))?;
 
+    // Programs linked away from VA 0, or with `.data`/`.bss` the flat image would
+    // leave out, are deployed with a header carrying their base, entry point and
+    // end; everything else stays a flat image.
+    let bss = elf.get_flat_bss();
+    let has_data = elf.get_flat_data().is_some() || bss.is_some();
+    if elf.entry != 0 || has_data {
+        let (mut image, base) = elf
+            .get_load_image()
+            .ok_or_else(|| format!("no loadable segments for {name}"))?;
+        if base != 0 || has_data {
+            let mem_end = base + image.len() as u64;
+            // `mem_end` covers the zero-filled bss, so the body can stop where it starts.
+            if let Some((_, bss_start)) = bss
+                && bss_start >= base
+            {
+                image.truncate((bss_start - base) as usize);
+            }
+            // Like the kernel does for flat images, fall back to the first non-zero byte.
+            let entry = match elf.entry {
+                0 => base + image.iter().position(|&b| b != 0).unwrap_or(0) as u64,
+                entry => entry,
+            };
+            let header = ProgramImageHeader {
+                base: base as u32,
+                entry: entry as u32,
+                mem_end: mem_end as u32,
+            };
+            return Ok(header.encode(&image));
+        }
+    }
+
     let (code, code_start) = elf
         .get_flat_code()
         .ok_or_else(|| format!("no code section for {name}"))?;
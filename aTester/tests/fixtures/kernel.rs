@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kernel test bins declared in `crates/kernel/Cargo.toml`, minus the kernel itself.
+pub fn kernel_bins() -> Result<Vec<String>, String> {
+    let manifest_path = workspace_root().join("crates/kernel/Cargo.toml");
+    let contents = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("failed to read kernel Cargo.toml: {e}"))?;
+
+    let mut bins = Vec::new();
+    let mut current_name: Option<String> = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line == "[[bin]]" {
+            if let Some(name) = current_name.take()
+                && name != "kernel"
+            {
+                bins.push(name);
+            }
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("name = ") {
+            let name = name.trim().trim_matches('"').to_string();
+            current_name = Some(name);
+        }
+    }
+
+    if let Some(name) = current_name
+        && name != "kernel"
+    {
+        bins.push(name);
+    }
+
+    if bins.is_empty() {
+        return Err("no [[bin]] entries found".to_string());
+    }
+
+    Ok(bins)
+}
+
+fn workspace_root() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir
+        .parent()
+        .map(PathBuf::from)
+        .expect("missing workspace root")
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::PathBuf;
+
+use a_tests::golden::{
+    GoldenResult, check_golden, describe_data_mismatch, first_mismatch, golden_path,
+};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("atester-golden-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn sample() -> GoldenResult {
+    GoldenResult {
+        success: false,
+        error_code: 0xdead,
+        data: vec![0x80, 0xf0, 0xfa, 0x02],
+    }
+}
+
+#[test]
+fn bless_then_compare_is_a_no_op() {
+    let dir = scratch_dir("bless");
+    let path = golden_path(&dir, "erc20 panic");
+    assert_eq!(path, dir.join("erc20_panic.golden"));
+
+    check_golden(&path, &sample(), true).expect("bless writes the golden");
+    let blessed = fs::read(&path).unwrap();
+    check_golden(&path, &sample(), false).expect("fresh golden matches");
+
+    // Blessing the same result again leaves the file byte-identical.
+    check_golden(&path, &sample(), true).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), blessed);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn compare_reports_changed_or_missing_goldens() {
+    let dir = scratch_dir("compare");
+    let path = golden_path(&dir, "case");
+    assert!(check_golden(&path, &sample(), false).is_err());
+
+    check_golden(&path, &sample(), true).unwrap();
+    let changed = GoldenResult {
+        data: vec![0x80],
+        ..sample()
+    };
+    let err = check_golden(&path, &changed, false).unwrap_err();
+    assert!(err.contains("data mismatch at byte 1"), "{err}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mismatch_reports_first_differing_byte() {
+    let expected: Vec<u8> = (0..32).collect();
+    let mut actual = expected.clone();
+    actual[16] = 0xff;
+    actual[20] = 0xee;
+    assert_eq!(first_mismatch(&expected, &actual), Some(16));
+    assert_eq!(
+        describe_data_mismatch(&expected, &actual).unwrap(),
+        "data mismatch at byte 16: expected 0x10 got 0xff \
+         (expected ..0c0d0e0f[10]11121314.., got ..0c0d0e0f[ff]111213ee..)"
+    );
+
+    // A truncated payload differs where the shorter one ends.
+    assert_eq!(first_mismatch(&expected, &expected[..3]), Some(3));
+    let detail = describe_data_mismatch(&expected[..3], &expected).unwrap();
+    assert!(
+        detail.starts_with("data mismatch at byte 3: expected end of data (len 3) got 0x03"),
+        "{detail}"
+    );
+
+    assert_eq!(first_mismatch(&expected, &expected), None);
+    assert!(describe_data_mismatch(&expected, &expected).is_none());
+}
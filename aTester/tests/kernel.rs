@@ -1,12 +1,53 @@
-use std::fs;
 use std::path::{Path, PathBuf};
 
-use a_tests::{AvmRunner, RunOptions, Suite, TestCase, TestEvaluator, TestKind, TestOutcome};
+use a_tests::{
+    AvmRunner, DirectMap, RunOptions, Suite, Termination, TestCase, TestEvaluator, TestKind,
+    TestOutcome, list_enabled,
+};
+use types::boot::LogLevel;
+use types::kernel_result::KERNEL_CRASH_NO_TX;
+
+#[path = "fixtures/kernel.rs"]
+mod fixtures;
+use fixtures::kernel_bins;
+
+/// Test bins that panic on purpose, with the message their crash record must carry.
+const PANICKING_BINS: &[(&str, &str)] = &[("kernel_panic_test", "deliberate kernel panic")];
 
 struct ExitCodeEvaluator;
 
 impl TestEvaluator for ExitCodeEvaluator {
     fn evaluate(&self, case: &TestCase, result: &a_tests::RunResult) -> TestOutcome {
+        if result.termination != Termination::Halted {
+            return TestOutcome::Failed(format!(
+                "{} stopped with {:?}",
+                case.name, result.termination
+            ));
+        }
+        let expected_panic = PANICKING_BINS
+            .iter()
+            .find(|(name, _)| *name == case.name)
+            .map(|(_, message)| *message);
+        match (expected_panic, &result.kernel_crash) {
+            (Some(expected), Some(crash)) => {
+                let message = String::from_utf8_lossy(&crash.message);
+                return if message.contains(expected) && crash.tx_index == KERNEL_CRASH_NO_TX {
+                    TestOutcome::Passed
+                } else {
+                    TestOutcome::Failed(format!("{}: unexpected crash record: {crash}", case.name))
+                };
+            }
+            (Some(_), None) => {
+                return TestOutcome::Failed(format!(
+                    "{} should have left a kernel crash record",
+                    case.name
+                ));
+            }
+            (None, Some(crash)) => {
+                return TestOutcome::Failed(format!("{}: {crash}", case.name));
+            }
+            (None, None) => {}
+        }
         match read_test_results_from_output(&result.output) {
             Ok(results) => {
                 if results.status == 0 {
@@ -25,6 +66,10 @@ impl TestEvaluator for ExitCodeEvaluator {
 
 #[test]
 fn kernel_tests() {
+    // `tests/list.rs` prints the bins; listing must not build or run anything.
+    if list_enabled() {
+        return;
+    }
     build_kernel().expect("failed to build kernel test bins");
     let bins = kernel_bins().expect("failed to discover kernel bins");
     if bins.is_empty() {
@@ -43,6 +88,11 @@ fn kernel_tests() {
                 vm_memory_size: None,
                 verbose: false,
                 input: Vec::new(),
+                kernel_log_level: LogLevel::Error,
+                direct_map: DirectMap::Full,
+                gas_limit: None,
+                step_limit: None,
+                coverage: false,
             },
         })
         .collect::<Vec<_>>();
@@ -87,6 +137,11 @@ fn kernel_tests() {
     println!("\n=== kernel_tests summary ===");
     println!("Total: {total_tests}  Passed: {passed}  Failed: {failed}  Skipped: {skipped}");
     println!("Instructions executed: {instruction_count}");
+    let loads: u64 = reports.iter().map(|r| r.memory_accesses.loads).sum();
+    let stores: u64 = reports.iter().map(|r| r.memory_accesses.stores).sum();
+    let atomics: u64 = reports.iter().map(|r| r.memory_accesses.atomics).sum();
+    let fetches: u64 = reports.iter().map(|r| r.memory_accesses.fetches).sum();
+    println!("Memory accesses: loads={loads} stores={stores} atomics={atomics} fetches={fetches}");
 
     let failures: Vec<_> = reports
         .iter()
@@ -104,6 +159,22 @@ fn kernel_tests() {
     }
 }
 
+#[test]
+fn kernel_bins_are_listed_from_the_manifest() {
+    let bins = kernel_bins().expect("failed to discover kernel bins");
+    for known in [
+        "kernel_first_test",
+        "kernel_panic_test",
+        "kernel_call_layout_test",
+    ] {
+        assert!(
+            bins.iter().any(|bin| bin == known),
+            "missing kernel bin {known}"
+        );
+    }
+    assert!(!bins.iter().any(|bin| bin == "kernel"));
+}
+
 struct TestResults {
     status: u32,
     detail: u32,
@@ -118,43 +189,6 @@ fn read_test_results_from_output(output: &[u8]) -> Result<TestResults, String> {
     Ok(TestResults { status, detail })
 }
 
-fn kernel_bins() -> Result<Vec<String>, String> {
-    let manifest_path = workspace_root().join("crates/kernel/Cargo.toml");
-    let contents = fs::read_to_string(&manifest_path)
-        .map_err(|e| format!("failed to read kernel Cargo.toml: {e}"))?;
-
-    let mut bins = Vec::new();
-    let mut current_name: Option<String> = None;
-
-    for line in contents.lines() {
-        let line = line.trim();
-        if line == "[[bin]]" {
-            if let Some(name) = current_name.take()
-                && name != "kernel"
-            {
-                bins.push(name);
-            }
-            continue;
-        }
-        if let Some(name) = line.strip_prefix("name = ") {
-            let name = name.trim().trim_matches('"').to_string();
-            current_name = Some(name);
-        }
-    }
-
-    if let Some(name) = current_name
-        && name != "kernel"
-    {
-        bins.push(name);
-    }
-
-    if bins.is_empty() {
-        return Err("no [[bin]] entries found".to_string());
-    }
-
-    Ok(bins)
-}
-
 fn kernel_elf_dir() -> PathBuf {
     std::env::var("KERNEL_ELF_DIR")
         .map(PathBuf::from)
@@ -0,0 +1,26 @@
+//! `harness = false` target that prints every example case and kernel test bin as a
+//! `<suite>\t<case>` line when `ATESTER_LIST=1` is set. Without libtest there is no
+//! output capture and no progress output mixed into the list.
+
+use a_tests::{list_enabled, print_case_list};
+
+// Only the case names are needed here.
+#[allow(dead_code)]
+#[path = "fixtures/examples.rs"]
+mod examples;
+#[path = "fixtures/kernel.rs"]
+mod kernel;
+
+fn main() {
+    if !list_enabled() {
+        return;
+    }
+    print_case_list(
+        "examples_tests",
+        examples::all_example_cases().iter().map(|case| case.name),
+    );
+    print_case_list(
+        "kernel_tests",
+        kernel::kernel_bins().expect("failed to discover kernel bins"),
+    );
+}
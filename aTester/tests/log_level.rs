@@ -0,0 +1,32 @@
+mod common;
+
+use a_tests::RunOptions;
+use common::{build_kernel, run_transfer};
+use types::boot::LogLevel;
+
+#[test]
+fn kernel_log_level_filters_debug_lines() {
+    build_kernel().expect("failed to build kernel");
+
+    let quiet = console_output(LogLevel::Error);
+    assert!(
+        !quiet.contains("Kernel:"),
+        "error level printed kernel lines:\n{quiet}"
+    );
+
+    let verbose = console_output(LogLevel::Debug);
+    assert!(
+        verbose.contains("processing tx 1/1"),
+        "debug level missing per-tx trace:\n{verbose}"
+    );
+    assert!(verbose.contains("transaction bundle complete"));
+}
+
+/// Run a single successful native transfer at `level` and return the console output.
+fn console_output(level: LogLevel) -> String {
+    run_transfer(RunOptions {
+        kernel_log_level: level,
+        ..RunOptions::default()
+    })
+    .stdout
+}
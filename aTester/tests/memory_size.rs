@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use a_tests::{ArchRunner, AvmRunner, DirectMap, ElfTarget, RunOptions};
+
+#[test]
+fn undersized_memory_is_rejected_before_loading() {
+    // The size check runs before the ELF is read, so no kernel build is needed.
+    let elf = ElfTarget {
+        path: PathBuf::from("missing-kernel.elf"),
+    };
+    let options = RunOptions {
+        vm_memory_size: Some(1024 * 1024),
+        direct_map: DirectMap::Full,
+        ..RunOptions::default()
+    };
+    let err = match AvmRunner::new().run(&elf, &options) {
+        Ok(_) => panic!("1 MiB of memory should not fit the kernel"),
+        Err(err) => err,
+    };
+    // 4 MiB window + reserved frame + root table + one L2 table each for the
+    // window and the 1 MiB direct map.
+    assert_eq!(
+        err.message,
+        "vm_memory_size of 1048576 bytes is too small: the kernel needs 4210688 bytes (3162112 short)"
+    );
+}
+
+#[test]
+fn default_memory_passes_the_size_check() {
+    let elf = ElfTarget {
+        path: PathBuf::from("missing-kernel.elf"),
+    };
+    let err = match AvmRunner::new().run(&elf, &RunOptions::default()) {
+        Ok(_) => panic!("missing elf should fail to load"),
+        Err(err) => err,
+    };
+    assert!(
+        err.message.starts_with("failed to read elf"),
+        "unexpected error: {}",
+        err.message
+    );
+}
@@ -0,0 +1,132 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+mod common;
+
+use a_tests::{
+    ArchRunner, AvmRunner, ElfTarget, RunError, RunOptions, RunResult, Suite, Termination,
+    TestCase, TestEvaluator, TestKind, TestOutcome,
+};
+use common::{build_kernel, kernel_elf, transfer_options};
+use vm::cpu::PrivilegeMode;
+use vm::metering::MemoryAccessCounts;
+use vm::vm::VmState;
+
+/// Stands in for a VM run: reports `base` instructions, plus one more per earlier
+/// call when `drift` is set.
+struct StubRunner {
+    base: u64,
+    drift: bool,
+    calls: Cell<u64>,
+}
+
+impl ArchRunner for StubRunner {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    fn run(&self, _elf: &ElfTarget, _options: &RunOptions) -> Result<RunResult, RunError> {
+        let call = self.calls.get();
+        self.calls.set(call + 1);
+        Ok(RunResult {
+            exit_code: 0,
+            stdout: "done\n".to_string(),
+            stderr: String::new(),
+            output: Vec::new(),
+            instruction_count: self.base + if self.drift { call } else { 0 },
+            memory_accesses: MemoryAccessCounts::default(),
+            stack_used_bytes: 0,
+            heap_used_bytes: 0,
+            code_size_bytes: 0,
+            kernel_heap_used_bytes: 0,
+            kernel_stack_used_bytes: 0,
+            hot_back_edges: Vec::new(),
+            syscall_counts: BTreeMap::new(),
+            final_state: VmState {
+                regs: [0; 32],
+                pc: 0,
+                priv_mode: PrivilegeMode::Supervisor,
+            },
+            termination: Termination::Halted,
+            executed_pcs: BTreeSet::new(),
+            kernel_crash: None,
+            result_dump: String::new(),
+        })
+    }
+}
+
+struct PassEvaluator;
+
+impl TestEvaluator for PassEvaluator {
+    fn evaluate(&self, _case: &TestCase, _result: &RunResult) -> TestOutcome {
+        TestOutcome::Passed
+    }
+}
+
+fn suite(evaluator: &PassEvaluator) -> Suite<'_> {
+    Suite {
+        name: "repeat".to_string(),
+        cases: vec![TestCase {
+            name: "stub case".to_string(),
+            kind: TestKind::Smoke,
+            elf: PathBuf::from("stub.elf"),
+            options: RunOptions::default(),
+        }],
+        evaluator,
+    }
+}
+
+#[test]
+fn run_repeated_groups_runs_per_case() {
+    let runner = StubRunner {
+        base: 1_000,
+        drift: false,
+        calls: Cell::new(0),
+    };
+    let reports = suite(&PassEvaluator).run_repeated(&runner, 5);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].name, "stub case");
+    assert_eq!(reports[0].runs.len(), 5);
+    assert!(!reports[0].is_flaky());
+}
+
+#[test]
+fn varying_instruction_counts_are_flagged_flaky() {
+    let runner = StubRunner {
+        base: 1_000,
+        drift: true,
+        calls: Cell::new(0),
+    };
+    let reports = suite(&PassEvaluator).run_repeated(&runner, 3);
+    assert!(reports[0].is_flaky());
+    assert_eq!(reports[0].differing_runs(), vec![1, 2]);
+    assert_eq!(reports[0].runs[2].instruction_count, 1_002);
+}
+
+#[test]
+fn kernel_transfer_is_stable_across_runs() {
+    build_kernel().expect("failed to build kernel");
+
+    let evaluator = PassEvaluator;
+    let suite = Suite {
+        name: "repeat".to_string(),
+        cases: vec![TestCase {
+            name: "native transfer".to_string(),
+            kind: TestKind::Smoke,
+            elf: kernel_elf().path,
+            options: transfer_options(RunOptions::default()),
+        }],
+        evaluator: &evaluator,
+    };
+    let reports = suite.run_repeated(&AvmRunner::new(), 3);
+    let runs = &reports[0].runs;
+    assert_eq!(runs.len(), 3);
+    assert_eq!(runs[0].termination, Some(Termination::Halted));
+    assert!(runs[0].instruction_count > 0);
+    assert!(
+        !reports[0].is_flaky(),
+        "runs {:?} differ from the first",
+        reports[0].differing_runs()
+    );
+}
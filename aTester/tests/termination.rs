@@ -0,0 +1,27 @@
+mod common;
+
+use a_tests::{RunOptions, Termination};
+use common::{build_kernel, run_transfer};
+
+#[test]
+fn kernel_termination_distinguishes_out_of_gas() {
+    build_kernel().expect("failed to build kernel");
+
+    let clean = run_transfer(RunOptions::default());
+    assert_eq!(clean.termination, Termination::Halted);
+
+    // Far below what decoding a bundle takes, so the kernel is still running when
+    // the limit hits.
+    let starved = run_transfer(RunOptions {
+        gas_limit: Some(1_000),
+        ..RunOptions::default()
+    });
+    assert_eq!(starved.termination, Termination::OutOfGas);
+    assert_eq!(starved.instruction_count, 1_000);
+
+    let stepped = run_transfer(RunOptions {
+        step_limit: Some(1_000),
+        ..RunOptions::default()
+    });
+    assert_eq!(stepped.termination, Termination::StepLimit);
+}
@@ -6,7 +6,11 @@ use std::vec::Vec;
 
 use compiler::elf::parse_elf_from_bytes;
 use goblin::elf::Elf;
-use types::{SV32_DIRECT_MAP_BASE, boot::BootInfo, transaction::TransactionBundle};
+use types::{
+    SV32_DIRECT_MAP_BASE,
+    boot::{BootInfo, LogLevel},
+    transaction::TransactionBundle,
+};
 
 use state::State;
 use vm::memory::{
@@ -22,12 +26,15 @@ const KERNEL_STACK_TOP: u32 = KERNEL_WINDOW_BYTES as u32;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BootConfig {
     pub debug_console: bool,
+    /// Kernel log verbosity handed over in `BootInfo`.
+    pub log_level: LogLevel,
 }
 
 impl Default for BootConfig {
     fn default() -> Self {
         Self {
             debug_console: true,
+            log_level: LogLevel::default(),
         }
     }
 }
@@ -60,6 +67,7 @@ impl Bootloader {
         let (code, code_base) = elf.get_flat_code().expect("kernel ELF missing .text");
         let (rodata, ro_base) = elf.get_flat_rodata().unwrap_or((Vec::new(), code_base));
         let (bss, bss_base) = elf.get_flat_bss().unwrap_or((Vec::new(), code_base));
+        let (data, data_base) = elf.get_flat_data().unwrap_or((Vec::new(), code_base));
         println!(
             "kernel elf: text_base=0x{:08x} text_len=0x{:x} ro_base=0x{:08x} ro_len=0x{:x} bss_base=0x{:08x} bss_len=0x{:x}",
             code_base as u32,
@@ -71,12 +79,18 @@ impl Bootloader {
         );
 
         let mut min_base = core::cmp::min(code_base, ro_base) as usize;
+        if !data.is_empty() {
+            min_base = core::cmp::min(min_base, data_base as usize);
+        }
         if !bss.is_empty() {
             min_base = core::cmp::min(min_base, bss_base as usize);
         }
         let code_end = (code_base + code.len() as u64) as usize;
         let ro_end = (ro_base + rodata.len() as u64) as usize;
         let mut image_end = core::cmp::max(code_end, ro_end);
+        if !data.is_empty() {
+            image_end = core::cmp::max(image_end, (data_base + data.len() as u64) as usize);
+        }
         if !bss.is_empty() {
             let bss_end = bss_base
                 .checked_add(bss.len() as u64)
@@ -98,7 +112,7 @@ impl Bootloader {
             self.memory.size()
         );
 
-        // Flatten code + rodata into a single buffer and write once to set heap pointer properly.
+        // Flatten code + rodata + data into a single buffer and write once to set heap pointer properly.
         let mut image = vec![0u8; image_size];
         let code_off = (code_base as usize).saturating_sub(min_base);
         image[code_off..code_off + code.len()].copy_from_slice(&code);
@@ -106,13 +120,22 @@ impl Bootloader {
             let ro_off = (ro_base as usize).saturating_sub(min_base);
             image[ro_off..ro_off + rodata.len()].copy_from_slice(&rodata);
         }
+        if !data.is_empty() {
+            let data_off = (data_base as usize).saturating_sub(min_base);
+            image[data_off..data_off + data.len()].copy_from_slice(&data);
+        }
         if !bss.is_empty() {
             let bss_off = (bss_base as usize).saturating_sub(min_base);
             image[bss_off..bss_off + bss.len()].copy_from_slice(&bss);
         }
 
-        self.memory
-            .map_range(VirtualAddress(0), KERNEL_WINDOW_BYTES, Perms::rwx_kernel());
+        let mapped =
+            self.memory
+                .map_range(VirtualAddress(0), KERNEL_WINDOW_BYTES, Perms::rwx_kernel());
+        assert!(
+            mapped,
+            "failed to map kernel window: out of physical memory"
+        );
         self.memory
             .write_bytes(VirtualAddress(min_base as u32), &image);
         // Start the heap after the loaded image to avoid overwriting kernel text/rodata.
@@ -190,7 +213,8 @@ impl Bootloader {
             self.memory.next_free_ppn() as u32,
             0,
             KERNEL_WINDOW_BYTES as u32,
-        );
+        )
+        .with_log_level(self.config.log_level);
         let bytes = unsafe {
             slice::from_raw_parts(
                 &boot_info as *const BootInfo as *const u8,
@@ -229,8 +253,11 @@ impl Bootloader {
             .checked_add(data.len() as u32)
             .expect("heap allocation overflow");
         let start = VirtualAddress(addr);
-        self.memory.map_range(start, data.len(), Perms::rw_kernel());
-        self.memory.write_bytes(start, data);
+        let mapped = self.memory.map_range(start, data.len(), Perms::rw_kernel());
+        assert!(mapped, "heap allocation: out of physical memory");
+        if let Err(err) = self.memory.try_write_bytes(start, data) {
+            panic!("heap allocation: {err}");
+        }
         self.heap_ptr.set(end);
         start
     }
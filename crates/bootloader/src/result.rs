@@ -2,12 +2,13 @@ use core::mem;
 
 use state::State;
 use types::kernel_result::KERNEL_RESULT_ADDR;
-use types::{KernelResult, TransactionReceipt};
+use types::{BundleSummary, KernelResult, TransactionReceipt};
 use vm::memory::{Memory as MmuRef, VirtualAddress};
 
 pub struct KernelRunResult {
     pub receipts: Vec<TransactionReceipt>,
     pub state: Option<State>,
+    pub summary: BundleSummary,
 }
 
 pub(crate) fn read_kernel_result(memory: &MmuRef) -> Option<KernelRunResult> {
@@ -28,6 +29,7 @@ pub(crate) fn read_kernel_result(memory: &MmuRef) -> Option<KernelRunResult> {
     if receipts_ptr == 0 || receipts_len == 0 {
         return None;
     }
+    let summary = BundleSummary::decode(&header_bytes[mem::offset_of!(KernelResult, summary)..])?;
     let receipts_end = receipts_ptr.checked_add(receipts_len)?;
     let receipts_slice =
         memory.mem_slice(VirtualAddress(receipts_ptr), VirtualAddress(receipts_end))?;
@@ -40,5 +42,9 @@ pub(crate) fn read_kernel_result(memory: &MmuRef) -> Option<KernelRunResult> {
     } else {
         None
     };
-    Some(KernelRunResult { receipts, state })
+    Some(KernelRunResult {
+        receipts,
+        state,
+        summary,
+    })
 }
@@ -28,9 +28,10 @@ unsafe fn syscall_alloc(size: usize, align: usize) -> *mut u8 {
         core::arch::asm!(
             "li a7, 7", // syscall_storage_read
             "ecall",
-            in("a1") size,
+            inlateout("a1") size => _,
             in("a2") align,
             out("a0") result,
+            clobber_abi("C"),
         );
 
         result as *mut u8
@@ -44,8 +45,10 @@ unsafe fn syscall_dealloc(ptr: *mut u8, size: usize) {
         core::arch::asm!(
             "li a7, 8", // syscall_storage_read
             "ecall",
-            in("a1") ptr as usize,
+            inlateout("a1") ptr as usize => _,
             in("a2") size,
+            lateout("a0") _,
+            clobber_abi("C"),
             options(nostack, preserves_flags),
         );
     }
@@ -1,6 +1,8 @@
 use types::address::Address;
 use types::result::Result;
 
+use crate::syscalls::SYSCALL_MAX_INPUT_LEN;
+
 pub fn call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
     unsafe {
         let mut result_ptr: u32;
@@ -12,6 +14,9 @@ pub fn call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
             in("x13") input_data.as_ptr(), // a3
             in("x14") input_data.len(), // a4
             out("x10") result_ptr, // a0
+            // The callee runs before control returns here and the trap trampolines
+            // reuse t0-t2, so treat every caller-saved register as clobbered.
+            clobber_abi("C"),
         );
 
         if result_ptr == 0 {
@@ -21,3 +26,45 @@ pub fn call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
         Result::from_ptr(result_ptr)
     }
 }
+
+/// Like [`call`], but the callee (and anything it calls) may only read state: a
+/// storage write or transfer ends that call with a failed `STATIC_WRITE_ERROR_CODE`
+/// result, which the caller gets back here.
+pub fn static_call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
+    unsafe {
+        let mut result_ptr: u32;
+        core::arch::asm!(
+            "li a7, 18",       // syscall ID for static_call
+            "ecall",
+            in("x11") to.0.as_ptr(), // a1
+            in("x12") from.0.as_ptr(), // a2
+            in("x13") input_data.as_ptr(), // a3
+            in("x14") input_data.len(), // a4
+            out("x10") result_ptr, // a0
+            clobber_abi("C"),
+        );
+
+        if result_ptr == 0 {
+            return None;
+        }
+
+        Result::from_ptr(result_ptr)
+    }
+}
+
+/// Largest call input the kernel accepts. A longer `call`/`static_call` input fails
+/// with `INPUT_TOO_LARGE_ERROR_CODE` instead of running the callee.
+pub fn max_input_len() -> usize {
+    let len: u32;
+    unsafe {
+        core::arch::asm!(
+            "li a7, {max_input_len}",
+            "ecall",
+            lateout("a0") len,
+            lateout("a1") _,
+            max_input_len = const SYSCALL_MAX_INPUT_LEN,
+            clobber_abi("C"),
+        );
+    }
+    len as usize
+}
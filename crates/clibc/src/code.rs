@@ -0,0 +1,55 @@
+use types::address::Address;
+
+use crate::syscalls::{SYSCALL_EXT_CODE_HASH, SYSCALL_EXT_CODE_SIZE, SYSCALL_OK};
+
+/// Length of the code deployed at `addr`, or 0 if it is missing or not a contract.
+#[inline(always)]
+pub fn ext_code_size(addr: &Address) -> u32 {
+    try_ext_code_size(addr).unwrap_or(0)
+}
+
+/// Like `ext_code_size`, but returns the `SYSCALL_ERR_*` code the kernel left in `a1`
+/// (e.g. `SYSCALL_ERR_NOT_FOUND` for an address with no code) instead of 0.
+#[inline(always)]
+pub fn try_ext_code_size(addr: &Address) -> Result<u32, u32> {
+    let size: u32;
+    let error: u32;
+    unsafe {
+        core::arch::asm!(
+            "li a7, {code_size}",
+            "ecall",
+            inlateout("a1") addr.0.as_ptr() => error,
+            lateout("a0") size,
+            code_size = const SYSCALL_EXT_CODE_SIZE,
+            clobber_abi("C"),
+        );
+    }
+    match error {
+        SYSCALL_OK => Ok(size),
+        code => Err(code),
+    }
+}
+
+/// `sha256` of the code deployed at `addr` (see `state::code_hash`), or all zeros
+/// if it is missing or not a contract.
+#[inline(always)]
+pub fn ext_code_hash(addr: &Address) -> [u8; 32] {
+    let ptr: u32;
+    unsafe {
+        core::arch::asm!(
+            "li a7, {code_hash}",
+            "ecall",
+            inlateout("a1") addr.0.as_ptr() => _,
+            lateout("a0") ptr,
+            code_hash = const SYSCALL_EXT_CODE_HASH,
+            clobber_abi("C"),
+        );
+    }
+    let mut hash = [0u8; 32];
+    if ptr != 0 {
+        unsafe {
+            core::ptr::copy_nonoverlapping(ptr as *const u8, hash.as_mut_ptr(), hash.len());
+        }
+    }
+    hash
+}
@@ -82,8 +82,10 @@ macro_rules! fire_event {
             core::arch::asm!(
                 "li a7, 6",
                 "ecall",
-                in("a1") ptr,
+                inlateout("a1") ptr => _,
                 in("a2") len,
+                lateout("a0") _,
+                clobber_abi("C"),
             );
         }
         #[cfg(not(target_arch = "riscv32"))]
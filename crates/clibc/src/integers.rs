@@ -1,4 +1,4 @@
-use crate::require;
+use crate::{require, vm_panic};
 
 // program/utils.rs
 pub fn read_u32(bytes: &[u8]) -> u32 {
@@ -7,3 +7,19 @@ pub fn read_u32(bytes: &[u8]) -> u32 {
     array.copy_from_slice(&bytes[0..4]);
     u32::from_le_bytes(array)
 }
+
+/// `a + b` for token math; `vm_panic`s with "u32 addition overflow" instead of wrapping.
+pub fn checked_add_u32(a: u32, b: u32) -> u32 {
+    match a.checked_add(b) {
+        Some(sum) => sum,
+        None => vm_panic(b"u32 addition overflow"),
+    }
+}
+
+/// `a - b` for token math; `vm_panic`s with "u32 subtraction underflow" instead of wrapping.
+pub fn checked_sub_u32(a: u32, b: u32) -> u32 {
+    match a.checked_sub(b) {
+        Some(diff) => diff,
+        None => vm_panic(b"u32 subtraction underflow"),
+    }
+}
@@ -18,7 +18,18 @@ pub use integers::*;
 
 pub mod transfer;
 pub use transfer::balance;
+pub use transfer::call_value;
 pub use transfer::transfer;
+pub use transfer::{TransferError, try_transfer};
+pub use transfer::{TransferManyError, transfer_many};
+
+// Other accounts' code
+pub mod code;
+pub use code::{ext_code_hash, ext_code_size, try_ext_code_size};
+
+// Deterministic pseudo-entropy
+pub mod random;
+pub use random::{random_bytes, random_u64};
 
 // Syscall IDs
 pub mod syscalls;
@@ -26,6 +37,8 @@ pub use syscalls::*;
 
 // StorageMap
 pub mod storage_map;
+pub use storage_map::StorageBatch;
+pub use storage_map::StorageBytes;
 pub use storage_map::StorageKey;
 pub use storage_map::StorageMap;
 
@@ -48,6 +61,10 @@ pub const LOG_PREFIX: &str = "";
 pub mod parser;
 pub use parser::{DataParser, HexCodec};
 
+// Variable-length result builder
+pub mod result_builder;
+pub use result_builder::ResultBuilder;
+
 // Contract call func
 pub mod call;
 
@@ -60,6 +77,7 @@ pub mod entrypoint;
 pub mod storage;
 pub use storage::PERSISTENT_DOMAIN;
 pub use storage::Persistent; // Allow `$crate::Persistent` in macros // Allow `$crate::PERSISTENT_DOMAIN` in macros
+pub use storage::storage_bytes;
 
 // Router
 pub mod router;
@@ -123,3 +141,57 @@ pub fn require(condition: bool, msg: &[u8]) {
         vm_panic(msg);
     }
 }
+
+/// Aborts unless `a == b`, reporting both operands: `"<msg>: expected a == b: 5 != 10"`.
+pub fn require_eq<T: PartialEq + core::fmt::Display>(a: T, b: T, msg: &[u8]) {
+    if a != b {
+        comparison_failed(msg, "==", "!=", &a, &b);
+    }
+}
+
+/// Aborts unless `a != b`, reporting both operands: `"<msg>: expected a != b: 5 == 5"`.
+pub fn require_ne<T: PartialEq + core::fmt::Display>(a: T, b: T, msg: &[u8]) {
+    if a == b {
+        comparison_failed(msg, "!=", "==", &a, &b);
+    }
+}
+
+/// Aborts unless `a >= b`, reporting both operands: `"<msg>: expected a >= b: 5 < 10"`.
+pub fn require_ge<T: PartialOrd + core::fmt::Display>(a: T, b: T, msg: &[u8]) {
+    if a < b {
+        comparison_failed(msg, ">=", "<", &a, &b);
+    }
+}
+
+/// Aborts unless `a < b`, reporting both operands: `"<msg>: expected a < b: 10 >= 5"`.
+pub fn require_lt<T: PartialOrd + core::fmt::Display>(a: T, b: T, msg: &[u8]) {
+    if a >= b {
+        comparison_failed(msg, "<", ">=", &a, &b);
+    }
+}
+
+/// Formats a failed comparison into a result-sized buffer and panics with it.
+/// Output past the buffer is truncated rather than dropped.
+#[cold]
+fn comparison_failed(
+    msg: &[u8],
+    expected: &str,
+    actual: &str,
+    a: &dyn core::fmt::Display,
+    b: &dyn core::fmt::Display,
+) -> ! {
+    use core::fmt::Write;
+
+    let mut buf = [0u8; types::result::RESULT_DATA_SIZE];
+    let len = {
+        let mut writer = BufferWriter::new(&mut buf);
+        let _ = writer.write_str(core::str::from_utf8(msg).unwrap_or("require"));
+        let _ = write!(
+            &mut writer,
+            ": expected a {} b: {} {} {}",
+            expected, a, actual, b
+        );
+        writer.len()
+    };
+    vm_panic(&buf[..len]);
+}
@@ -25,16 +25,9 @@ macro_rules! logf_syscall {
 
 #[macro_export]
 macro_rules! logf {
-    ($fmt:expr) => {{
-        // Handle both string literals and byte strings
-        let fmt_bytes: &[u8] = $crate::as_bytes!($fmt);
-        let prefix_bytes = $crate::LOG_PREFIX.as_bytes();
-        let mut fmt_buf = [0u8; 256];
-        let fmt_bytes = $crate::concat_str!(fmt_buf, prefix_bytes, fmt_bytes);
-        let fmt_ptr = fmt_bytes.as_ptr();
-        let fmt_len = fmt_bytes.len();
-        $crate::logf_syscall!(fmt_ptr, fmt_len, 0 as *const u32, 0usize);
-    }};
+    ($fmt:expr) => {
+        $crate::logf_tagged!(b"", $fmt)
+    };
 
     // Special case for Debug trait formatting with %D
     ($fmt:expr, debug: $obj:expr) => {{
@@ -74,7 +67,45 @@ macro_rules! logf {
         $crate::log!($fmt, formatted);
     }};
 
-    ($fmt:expr, $($arg:expr),+ $(,)?) => {{
+    ($fmt:expr, $($arg:expr),+ $(,)?) => {
+        $crate::logf_tagged!(b"", $fmt, $($arg),+)
+    };
+}
+
+/// `logf!` with a level and category tag in front of the line, e.g.
+/// `logf_at!(Debug, "erc20", "minting: %d tokens", val)`. The host console
+/// strips the `<D|erc20> ` tag and filters the line on its level; plain
+/// `logf!` lines count as `Info`.
+#[macro_export]
+macro_rules! logf_at {
+    (Error, $category:literal, $($rest:tt)+) => {
+        $crate::logf_tagged!(core::concat!("<E|", $category, "> ").as_bytes(), $($rest)+)
+    };
+    (Info, $category:literal, $($rest:tt)+) => {
+        $crate::logf_tagged!(core::concat!("<I|", $category, "> ").as_bytes(), $($rest)+)
+    };
+    (Debug, $category:literal, $($rest:tt)+) => {
+        $crate::logf_tagged!(core::concat!("<D|", $category, "> ").as_bytes(), $($rest)+)
+    };
+}
+
+/// Shared body of `logf!`/`logf_at!`: `$tag`, then `LOG_PREFIX`, then the format.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! logf_tagged {
+    ($tag:expr, $fmt:expr) => {{
+        // Handle both string literals and byte strings
+        let fmt_bytes: &[u8] = $crate::as_bytes!($fmt);
+        let tag_bytes: &[u8] = $tag;
+        let prefix_bytes = $crate::LOG_PREFIX.as_bytes();
+        let mut fmt_buf = [0u8; 256];
+        let fmt_bytes = $crate::concat_str!(fmt_buf, tag_bytes, prefix_bytes, fmt_bytes);
+        let fmt_ptr = fmt_bytes.as_ptr();
+        let fmt_len = fmt_bytes.len();
+        $crate::logf_syscall!(fmt_ptr, fmt_len, 0 as *const u32, 0usize);
+    }};
+
+    ($tag:expr, $fmt:expr, $($arg:expr),+ $(,)?) => {{
         // Simple approach: just pass raw values as u32s
         // The host will interpret them based on format specifiers
         const MAX_ARGS: usize = 32;
@@ -90,9 +121,10 @@ macro_rules! logf {
         )+
 
         let fmt_bytes: &[u8] = $crate::as_bytes!($fmt);
+        let tag_bytes: &[u8] = $tag;
         let prefix_bytes = $crate::LOG_PREFIX.as_bytes();
         let mut fmt_buf = [0u8; 256];
-        let fmt_bytes = $crate::concat_str!(fmt_buf, prefix_bytes, fmt_bytes);
+        let fmt_bytes = $crate::concat_str!(fmt_buf, tag_bytes, prefix_bytes, fmt_bytes);
         let fmt_ptr = fmt_bytes.as_ptr();
         let fmt_len = fmt_bytes.len();
         let args_ptr = args_buf.as_ptr();
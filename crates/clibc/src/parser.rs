@@ -82,6 +82,20 @@ impl<'a> DataParser<'a> {
         self.data.len().saturating_sub(self.offset)
     }
 
+    /// Current cursor offset from the start of the input.
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Move the cursor to `pos` (at most the input length), e.g. to re-read a
+    /// discriminator after peeking ahead.
+    pub fn seek(&mut self, pos: usize) {
+        if pos > self.data.len() {
+            vm_panic(b"seek past end of input");
+        }
+        self.offset = pos;
+    }
+
     pub fn read_bytes(&mut self, len: usize) -> &'a [u8] {
         self.ensure(len);
         let start = self.offset;
@@ -95,6 +109,17 @@ impl<'a> DataParser<'a> {
         &self.data[self.offset..self.offset + len]
     }
 
+    /// Peek at the next byte without advancing the cursor.
+    pub fn peek_u8(&self) -> u8 {
+        self.peek_bytes(1)[0]
+    }
+
+    /// Peek at the next little-endian u32 without advancing the cursor.
+    pub fn peek_u32(&self) -> u32 {
+        let bytes: [u8; 4] = self.peek_bytes(4).try_into().unwrap();
+        u32::from_le_bytes(bytes)
+    }
+
     /// Read a hex-encoded byte string (2 hex chars per byte) into `out`.
     /// Returns the slice of `out` that was filled.
     pub fn read_hex_into<'b>(&mut self, out: &'b mut [u8]) -> &'b [u8] {
@@ -124,6 +149,11 @@ impl<'a> DataParser<'a> {
         u64::from_le_bytes(bytes)
     }
 
+    pub fn read_u128(&mut self) -> u128 {
+        let bytes: [u8; 16] = self.read_bytes(16).try_into().unwrap();
+        u128::from_le_bytes(bytes)
+    }
+
     pub fn read_bool(&mut self) -> bool {
         self.read_bytes(1)[0] != 0
     }
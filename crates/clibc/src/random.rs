@@ -0,0 +1,34 @@
+//! Deterministic pseudo-entropy.
+//!
+//! The kernel derives the bytes from the state the bundle started from and the
+//! transaction index, so every replay of a bundle sees the same values. Anyone
+//! can predict them: do not use them where unpredictability matters.
+use crate::syscalls::SYSCALL_RANDOM;
+use crate::vm_panic;
+
+/// Fills `buf` with the next bytes of this transaction's pseudo-entropy stream.
+#[inline(always)]
+pub fn random_bytes(buf: &mut [u8]) {
+    let written: u32;
+    unsafe {
+        core::arch::asm!(
+            "li a7, {random}",
+            "ecall",
+            inlateout("a1") buf.as_mut_ptr() => _,
+            in("a2") buf.len(),
+            lateout("a0") written,
+            random = const SYSCALL_RANDOM,
+            clobber_abi("C"),
+        );
+    }
+    if written as usize != buf.len() {
+        vm_panic(b"random: syscall failed");
+    }
+}
+
+/// Next pseudo-random `u64` of this transaction's stream.
+pub fn random_u64() -> u64 {
+    let mut bytes = [0u8; 8];
+    random_bytes(&mut bytes);
+    u64::from_le_bytes(bytes)
+}
@@ -0,0 +1,78 @@
+//! Builder for variable-length program results.
+use crate::vm_panic;
+use types::address::Address;
+use types::result::{RESULT_DATA_SIZE, Result};
+
+/// Accumulates little-endian fields into a `Result` data section.
+///
+/// Fields are appended in call order with no padding, so callers decode them
+/// with the same sequence of reads (e.g. `DataParser`). Exceeding
+/// `RESULT_DATA_SIZE` bytes aborts the program rather than truncating.
+pub struct ResultBuilder {
+    data: [u8; RESULT_DATA_SIZE],
+    len: usize,
+}
+
+impl ResultBuilder {
+    pub fn new() -> Self {
+        Self {
+            data: [0; RESULT_DATA_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        let end = self.len + bytes.len();
+        if end > RESULT_DATA_SIZE {
+            vm_panic(b"result data too large");
+        }
+        self.data[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        self
+    }
+
+    pub fn push_u8(&mut self, value: u8) -> &mut Self {
+        self.push_bytes(&[value])
+    }
+
+    pub fn push_u32(&mut self, value: u32) -> &mut Self {
+        self.push_bytes(&value.to_le_bytes())
+    }
+
+    pub fn push_u64(&mut self, value: u64) -> &mut Self {
+        self.push_bytes(&value.to_le_bytes())
+    }
+
+    pub fn push_u128(&mut self, value: u128) -> &mut Self {
+        self.push_bytes(&value.to_le_bytes())
+    }
+
+    pub fn push_address(&mut self, address: &Address) -> &mut Self {
+        self.push_bytes(&address.0)
+    }
+
+    /// Successful `Result` carrying the accumulated bytes.
+    pub fn build(&self) -> Result {
+        Result::new_with_data(true, 0, &self.data[..self.len])
+    }
+
+    /// Failed `Result` with `error_code` carrying the accumulated bytes.
+    pub fn build_error(&self, error_code: u32) -> Result {
+        Result::new_with_data(false, error_code, &self.data[..self.len])
+    }
+}
+
+impl Default for ResultBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,6 @@
 use types::O;
 use types::address::Address;
-use types::result::Result;
+use types::result::{MALFORMED_INPUT_ERROR_CODE, Result};
 
 /// Represents a function call with a selector (function ID) and arguments.
 /// This is the core data structure for routing function calls in our VM.
@@ -157,7 +157,9 @@ pub fn decode_calls<'a>(mut input: &'a [u8], buffer: &mut [FuncCall<'a>]) -> usi
 /// - max_calls: Maximum number of calls to process (safety limit)
 /// - handler: Closure that processes each individual function call
 ///
-/// RETURNS: Result of the last processed function call
+/// RETURNS: Result of the last processed function call, or a failed result with
+/// `MALFORMED_INPUT_ERROR_CODE` if a frame's header or declared argument length
+/// runs past the input. Malformed input is rejected before any call executes.
 /// Routes function calls from an encoded input buffer to the given handler closure.
 /// Passes in the `to` (contract address) and `from` (caller address) to each call.
 pub fn route<'a>(
@@ -183,14 +185,18 @@ pub fn route<'a>(
     // Phase 1: Decode calls into buffer
     while !input.is_empty() && count < buf.len() {
         if input.len() < 2 {
-            vm_panic(b"router: bad header");
+            return Result::new_with_data(false, MALFORMED_INPUT_ERROR_CODE, b"router: bad header");
         }
 
         let selector = input[0];
         let arg_len = input[1] as usize;
 
         if input.len() < 2 + arg_len {
-            vm_panic(b"router: bad arg len");
+            return Result::new_with_data(
+                false,
+                MALFORMED_INPUT_ERROR_CODE,
+                b"router: bad arg len",
+            );
         }
 
         let args = &input[2..2 + arg_len];
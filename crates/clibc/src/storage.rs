@@ -3,6 +3,31 @@ use types::{O, address::Address};
 /// Domain constant for persistent storage
 pub const PERSISTENT_DOMAIN: &str = "P";
 
+/// Storage bytes (keys + values) held by the calling program's own account.
+#[inline(always)]
+pub fn storage_bytes() -> usize {
+    #[cfg(target_arch = "riscv32")]
+    {
+        let bytes: u32;
+        unsafe {
+            core::arch::asm!(
+                "li a7, {storage_bytes}",
+                "ecall",
+                lateout("a0") bytes,
+                lateout("a1") _,
+                storage_bytes = const crate::syscalls::SYSCALL_STORAGE_BYTES,
+                clobber_abi("C"),
+            );
+        }
+        bytes as usize
+    }
+
+    #[cfg(not(target_arch = "riscv32"))]
+    {
+        0
+    }
+}
+
 /// Trait for persistent structs
 pub trait Persistent {
     fn load(address: &Address) -> O<Self>
@@ -12,7 +37,11 @@ pub trait Persistent {
     fn store(&self, address: &Address);
 }
 
-/// Macro that defines persistent structs with embedded static key
+/// Macro that defines persistent structs with embedded static key.
+///
+/// The value is stored as the struct's `repr(C)` bytes, padding included. Fields
+/// can be any plain `Copy` value: integers up to `u128`/`i128` (little-endian on
+/// the VM), `bool` and `Address`.
 #[macro_export]
 macro_rules! persist_struct {
     (
@@ -85,11 +114,12 @@ macro_rules! persist_struct {
                     core::arch::asm!(
                         "li a7, 1",  // syscall_storage_read
                         "ecall",
-                        in("a1") address.as_ref().as_ptr(), // address ptr
+                        inlateout("a1") address.as_ref().as_ptr() => _, // address ptr
                         in("a2") $crate::PERSISTENT_DOMAIN.as_ptr(), // domain ptr - use constant
                         in("a3") key_ptr, // key ptr
                         in("a4") packed_lens, // packed lens (domain | key)
                         out("a0") value_ptr,
+                        clobber_abi("C"),
                     );
 
                     if value_ptr == 0 {
@@ -148,13 +178,15 @@ macro_rules! persist_struct {
                     core::arch::asm!(
                         "li a7, 2", // syscall_storage_write
                         "ecall",
-                        in("a1") address.as_ref().as_ptr(), // address ptr
+                        inlateout("a1") address.as_ref().as_ptr() => _, // address ptr
                         in("a2") $crate::PERSISTENT_DOMAIN.as_ptr(), // domain ptr - use constant
                         in("a3") key_ptr, // key ptr
                         in("a4") packed_lens, // packed lens (domain | key)
                         in("a5") val_ptr, // value ptr
                         in("a6") val_len, // value len
-                        options(readonly, nostack, preserves_flags)
+                        lateout("a0") _,
+                        clobber_abi("C"),
+                        options(readonly, nostack, preserves_flags),
                     );
                 }
 
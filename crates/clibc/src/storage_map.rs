@@ -1,6 +1,11 @@
 use crate::{require, types::O, types::address::Address};
+use alloc::vec::Vec;
 use core::mem::{MaybeUninit, size_of};
 
+/// Bytes before each `StorageBatch` entry's data: domain len (u16), key len (u16) and
+/// value len (u32), all little-endian.
+pub const STORAGE_BATCH_ENTRY_HEADER_LEN: usize = 8;
+
 /// Trait for types that can be used as storage keys in `StorageMap`.
 pub trait StorageKey {
     /// Fills the provided buffer with the hex-encoded key.
@@ -21,90 +26,296 @@ impl StorageMap {
     where
         V: Copy + Default,
     {
-        require(key.len() <= 64, b"key too long");
-        require(domain.len() <= 64, b"domain too long");
+        let buf = match read_value(address, domain, key) {
+            Some(buf) if buf.len() == size_of::<V>() => buf,
+            _ => return O::None,
+        };
 
-        let mut full_key = [0u8; 64];
-        full_key[..key.len()].copy_from_slice(key);
+        let mut val = MaybeUninit::<V>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(buf.as_ptr(), val.as_mut_ptr() as *mut u8, buf.len());
+            O::Some(val.assume_init())
+        }
+    }
 
-        #[cfg(target_arch = "riscv32")]
+    /// Like `get`, but a read the kernel rejects returns its `SYSCALL_ERR_*` code
+    /// instead of looking like a missing key. An unset key or a value of the wrong size
+    /// is `Ok(None)`.
+    pub fn try_get<V>(address: &Address, domain: &[u8], key: &[u8]) -> Result<Option<V>, u32>
+    where
+        V: Copy + Default,
+    {
+        let buf = match try_read_value(address, domain, key)? {
+            Some(buf) if buf.len() == size_of::<V>() => buf,
+            _ => return Ok(None),
+        };
+
+        let mut val = MaybeUninit::<V>::uninit();
         unsafe {
-            let packed_lens: u32 = ((key.len() as u32) << 16) | (domain.len() as u32);
-            let mut value_ptr: u32;
-            core::arch::asm!(
-                "li a7, 1", // syscall_storage_read
-                "ecall",
-                in("a1") address.as_ref().as_ptr(), // a1 - address ptr
-                in("a2") domain.as_ptr(), // a2 - domain ptr
-                in("a3") full_key.as_ptr(), // a3 - key ptr
-                in("a4") packed_lens, // a4 - packed lens (domain | key)
-                out("a0") value_ptr, // a0
-            );
+            core::ptr::copy_nonoverlapping(buf.as_ptr(), val.as_mut_ptr() as *mut u8, buf.len());
+            Ok(Some(val.assume_init()))
+        }
+    }
 
-            if value_ptr == 0 {
-                return O::None;
-            }
+    pub fn set<V>(address: &Address, domain: &[u8], key: &[u8], val: V)
+    where
+        V: Copy,
+    {
+        let val_bytes =
+            unsafe { core::slice::from_raw_parts((&val as *const V) as *const u8, size_of::<V>()) };
+        write_value(address, domain, key, val_bytes);
+    }
 
-            let len_bytes = core::slice::from_raw_parts(value_ptr as *const u8, 4);
-            let value_len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+    /// Deletes the value under `domain`/`key`, freeing its storage entry.
+    pub fn remove(address: &Address, domain: &[u8], key: &[u8]) {
+        delete_value(address, domain, key);
+    }
 
-            if value_len != size_of::<V>() {
-                return O::None;
-            }
+    /// True if a value of any length is stored under `domain`/`key`.
+    pub fn contains(address: &Address, domain: &[u8], key: &[u8]) -> bool {
+        read_value(address, domain, key).is_some()
+    }
+}
 
-            let data_ptr = (value_ptr + 4) as *const u8;
-            let buf = core::slice::from_raw_parts(data_ptr, value_len);
+/// Fixed-size byte array values (hashes, public keys) stored under `domain`/`key`.
+///
+/// Unlike `StorageMap`, `N` is not limited to types with a `Default` impl.
+pub struct StorageBytes<const N: usize>;
 
-            let mut val = MaybeUninit::<V>::uninit();
-            core::ptr::copy_nonoverlapping(buf.as_ptr(), val.as_mut_ptr() as *mut u8, value_len);
-            O::Some(val.assume_init())
+impl<const N: usize> StorageBytes<N> {
+    /// Returns `None` if the key is unset or its stored value is not exactly `N` bytes.
+    pub fn get(address: &Address, domain: &[u8], key: &[u8]) -> O<[u8; N]> {
+        match read_value(address, domain, key) {
+            Some(buf) if buf.len() == N => {
+                let mut out = [0u8; N];
+                out.copy_from_slice(buf);
+                O::Some(out)
+            }
+            _ => O::None,
         }
+    }
 
-        #[cfg(not(target_arch = "riscv32"))]
-        {
-            let _ = address;
-            // For non-RISC-V targets, return None
-            O::None
+    pub fn set(address: &Address, domain: &[u8], key: &[u8], val: &[u8; N]) {
+        write_value(address, domain, key, val);
+    }
+}
+
+/// Reads the raw value stored under `domain`/`key`, if any.
+fn read_value(address: &Address, domain: &[u8], key: &[u8]) -> Option<&'static [u8]> {
+    try_read_value(address, domain, key).ok().flatten()
+}
+
+/// Like `read_value`, but a failed read returns the `SYSCALL_ERR_*` code the kernel
+/// left in `a1`; a missing key (`SYSCALL_ERR_NOT_FOUND`) is `Ok(None)`.
+fn try_read_value(
+    address: &Address,
+    domain: &[u8],
+    key: &[u8],
+) -> Result<Option<&'static [u8]>, u32> {
+    require(key.len() <= 64, b"key too long");
+    require(domain.len() <= 64, b"domain too long");
+
+    let mut full_key = [0u8; 64];
+    full_key[..key.len()].copy_from_slice(key);
+
+    #[cfg(target_arch = "riscv32")]
+    unsafe {
+        let packed_lens: u32 = ((key.len() as u32) << 16) | (domain.len() as u32);
+        let mut value_ptr: u32;
+        let error: u32;
+        core::arch::asm!(
+            "li a7, 1", // syscall_storage_read
+            "ecall",
+            inlateout("a1") address.as_ref().as_ptr() => error, // a1 - address ptr, then error
+            in("a2") domain.as_ptr(), // a2 - domain ptr
+            in("a3") full_key.as_ptr(), // a3 - key ptr
+            in("a4") packed_lens, // a4 - packed lens (domain | key)
+            out("a0") value_ptr, // a0
+            clobber_abi("C"),
+        );
+
+        match error {
+            crate::syscalls::SYSCALL_OK => {}
+            crate::syscalls::SYSCALL_ERR_NOT_FOUND => return Ok(None),
+            code => return Err(code),
         }
+        if value_ptr == 0 {
+            return Ok(None);
+        }
+
+        let len_bytes = core::slice::from_raw_parts(value_ptr as *const u8, 4);
+        let value_len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+
+        let data_ptr = (value_ptr + 4) as *const u8;
+        Ok(Some(core::slice::from_raw_parts(data_ptr, value_len)))
     }
 
-    pub fn set<V>(address: &Address, domain: &[u8], key: &[u8], val: V)
-    where
-        V: Copy,
+    #[cfg(not(target_arch = "riscv32"))]
     {
+        let _ = address;
+        // For non-RISC-V targets, return None
+        Ok(None)
+    }
+}
+
+/// Storage writes for the calling program, collected in guest memory and applied by a
+/// single `SYSCALL_STORAGE_SET_BATCH` call instead of one trap per write.
+///
+/// The kernel applies the whole batch or none of it. Entries are applied in order, so
+/// a later write to the same key wins.
+#[derive(Default)]
+pub struct StorageBatch {
+    entries: Vec<u8>,
+    count: u32,
+}
+
+impl StorageBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `value` under `domain`/`key`.
+    pub fn set_bytes(&mut self, domain: &[u8], key: &[u8], value: &[u8]) {
         require(key.len() <= 64, b"key too long");
         require(domain.len() <= 64, b"domain too long");
 
-        let mut full_key = [0u8; 64];
-        full_key[..key.len()].copy_from_slice(key);
+        self.entries
+            .reserve(STORAGE_BATCH_ENTRY_HEADER_LEN + domain.len() + key.len() + value.len());
+        self.entries
+            .extend_from_slice(&(domain.len() as u16).to_le_bytes());
+        self.entries
+            .extend_from_slice(&(key.len() as u16).to_le_bytes());
+        self.entries
+            .extend_from_slice(&(value.len() as u32).to_le_bytes());
+        self.entries.extend_from_slice(domain);
+        self.entries.extend_from_slice(key);
+        self.entries.extend_from_slice(value);
+        self.count += 1;
+    }
+
+    /// Queues `val` under `domain`/`key`, stored like `StorageMap::set`.
+    pub fn set<V>(&mut self, domain: &[u8], key: &[u8], val: V)
+    where
+        V: Copy,
+    {
+        let val_bytes =
+            unsafe { core::slice::from_raw_parts((&val as *const V) as *const u8, size_of::<V>()) };
+        self.set_bytes(domain, key, val_bytes);
+    }
+
+    /// Number of queued writes.
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Applies every queued write to `address`'s storage in one syscall and empties the
+    /// batch. On failure nothing is written and the `SYSCALL_ERR_*` code is returned.
+    pub fn flush(&mut self, address: &Address) -> Result<(), u32> {
+        if self.is_empty() {
+            return Ok(());
+        }
 
         #[cfg(target_arch = "riscv32")]
-        unsafe {
-            let val_bytes =
-                core::slice::from_raw_parts((&val as *const V) as *const u8, size_of::<V>());
-            let packed_lens: u32 = ((key.len() as u32) << 16) | (domain.len() as u32);
+        let error = unsafe {
+            let error: u32;
             core::arch::asm!(
-                "li a7, 2", // syscall_storage_write
+                "li a7, {storage_set_batch}",
                 "ecall",
-                in("a1") address.as_ref().as_ptr(), // a1 - address ptr
-                in("a2") domain.as_ptr(), // a2 - domain ptr
-                in("a3") full_key.as_ptr(), // a3 - key ptr
-                in("a4") packed_lens, // a4 - packed lens (domain | key)
-                in("a5") val_bytes.as_ptr(), // a5 - value ptr
-                in("a6") val_bytes.len(), // a6 - value len
-                options(readonly, nostack, preserves_flags)
+                inlateout("a1") address.as_ref().as_ptr() => error, // a1 - address ptr, then error
+                in("a2") self.entries.as_ptr(), // a2 - entries ptr
+                in("a3") self.entries.len(), // a3 - entries byte len
+                in("a4") self.count, // a4 - entry count
+                lateout("a0") _,
+                storage_set_batch = const crate::syscalls::SYSCALL_STORAGE_SET_BATCH,
+                clobber_abi("C"),
             );
-        }
+            error
+        };
 
         #[cfg(not(target_arch = "riscv32"))]
-        {
+        let error = {
             let _ = address;
-            let _ = val;
-            // For non-RISC-V targets, do nothing
+            // For non-RISC-V targets, drop the writes
+            crate::syscalls::SYSCALL_OK
+        };
+
+        self.entries.clear();
+        self.count = 0;
+        match error {
+            crate::syscalls::SYSCALL_OK => Ok(()),
+            code => Err(code),
         }
     }
 }
 
+/// Writes `bytes` as the value stored under `domain`/`key`.
+fn write_value(address: &Address, domain: &[u8], key: &[u8], bytes: &[u8]) {
+    require(key.len() <= 64, b"key too long");
+    require(domain.len() <= 64, b"domain too long");
+
+    let mut full_key = [0u8; 64];
+    full_key[..key.len()].copy_from_slice(key);
+
+    #[cfg(target_arch = "riscv32")]
+    unsafe {
+        let packed_lens: u32 = ((key.len() as u32) << 16) | (domain.len() as u32);
+        core::arch::asm!(
+            "li a7, 2", // syscall_storage_write
+            "ecall",
+            inlateout("a1") address.as_ref().as_ptr() => _, // a1 - address ptr
+            in("a2") domain.as_ptr(), // a2 - domain ptr
+            in("a3") full_key.as_ptr(), // a3 - key ptr
+            in("a4") packed_lens, // a4 - packed lens (domain | key)
+            in("a5") bytes.as_ptr(), // a5 - value ptr
+            in("a6") bytes.len(), // a6 - value len
+            lateout("a0") _,
+            clobber_abi("C"),
+            options(readonly, nostack, preserves_flags),
+        );
+    }
+
+    #[cfg(not(target_arch = "riscv32"))]
+    {
+        let _ = (address, bytes);
+        // For non-RISC-V targets, do nothing
+    }
+}
+
+/// Deletes the value stored under `domain`/`key`, if any.
+fn delete_value(address: &Address, domain: &[u8], key: &[u8]) {
+    require(key.len() <= 64, b"key too long");
+    require(domain.len() <= 64, b"domain too long");
+
+    let mut full_key = [0u8; 64];
+    full_key[..key.len()].copy_from_slice(key);
+
+    #[cfg(target_arch = "riscv32")]
+    unsafe {
+        let packed_lens: u32 = ((key.len() as u32) << 16) | (domain.len() as u32);
+        core::arch::asm!(
+            "li a7, {storage_delete}",
+            "ecall",
+            inlateout("a1") address.as_ref().as_ptr() => _, // a1 - address ptr
+            in("a2") domain.as_ptr(), // a2 - domain ptr
+            in("a3") full_key.as_ptr(), // a3 - key ptr
+            in("a4") packed_lens, // a4 - packed lens (domain | key)
+            lateout("a0") _,
+            storage_delete = const crate::syscalls::SYSCALL_STORAGE_DELETE,
+            clobber_abi("C"),
+        );
+    }
+
+    #[cfg(not(target_arch = "riscv32"))]
+    {
+        let _ = address;
+        // For non-RISC-V targets, do nothing
+    }
+}
+
 #[macro_export]
 macro_rules! Map {
     ($name:ident) => {
@@ -148,6 +359,24 @@ macro_rules! Map {
                 )
             }
 
+            /// Like `get`, but returns the kernel's `SYSCALL_ERR_*` code for a rejected read.
+            pub fn try_get<K, V>(
+                address: &$crate::types::address::Address,
+                key: K,
+            ) -> core::result::Result<Option<V>, u32>
+            where
+                K: $crate::StorageKey,
+                V: Copy + Default,
+            {
+                let mut buf = [0u8; Self::MAX_KEY_LEN];
+                let total_len = Self::build_key(key, &mut buf);
+                $crate::StorageMap::try_get::<V>(
+                    address,
+                    Self::DOMAIN_NAME.as_bytes(),
+                    &buf[..total_len],
+                )
+            }
+
             pub fn set<K, V>(address: &$crate::types::address::Address, key: K, val: V)
             where
                 K: $crate::StorageKey,
@@ -162,6 +391,34 @@ macro_rules! Map {
                     val,
                 );
             }
+
+            /// Deletes the entry for `key`; a later `get` returns `O::None`.
+            pub fn remove<K>(address: &$crate::types::address::Address, key: K)
+            where
+                K: $crate::StorageKey,
+            {
+                let mut buf = [0u8; Self::MAX_KEY_LEN];
+                let total_len = Self::build_key(key, &mut buf);
+                $crate::StorageMap::remove(
+                    address,
+                    Self::DOMAIN_NAME.as_bytes(),
+                    &buf[..total_len],
+                );
+            }
+
+            /// True if an entry exists for `key`.
+            pub fn contains<K>(address: &$crate::types::address::Address, key: K) -> bool
+            where
+                K: $crate::StorageKey,
+            {
+                let mut buf = [0u8; Self::MAX_KEY_LEN];
+                let total_len = Self::build_key(key, &mut buf);
+                $crate::StorageMap::contains(
+                    address,
+                    Self::DOMAIN_NAME.as_bytes(),
+                    &buf[..total_len],
+                )
+            }
         }
     };
 }
@@ -8,4 +8,31 @@ pub const SYSCALL_ALLOC: u32 = 7;
 pub const SYSCALL_DEALLOC: u32 = 8;
 pub const SYSCALL_TRANSFER: u32 = 9;
 pub const SYSCALL_BALANCE: u32 = 10;
+pub const SYSCALL_TRANSFER_MANY: u32 = 11;
+pub const SYSCALL_STORAGE_BYTES: u32 = 12;
+pub const SYSCALL_EXT_CODE_SIZE: u32 = 13;
+pub const SYSCALL_EXT_CODE_HASH: u32 = 14;
+pub const SYSCALL_RANDOM: u32 = 15;
+pub const SYSCALL_CALL_VALUE: u32 = 16;
+pub const SYSCALL_STORAGE_DELETE: u32 = 17;
+pub const SYSCALL_STATIC_CALL: u32 = 18;
+pub const SYSCALL_MAX_INPUT_LEN: u32 = 19;
+pub const SYSCALL_STORAGE_SET_BATCH: u32 = 20;
 pub const SYSCALL_BRK: u32 = 214; // brk(2): set program break (heap end)
+
+/// Error codes a syscall leaves in `a1` on return; `a0` carries its value. A failed
+/// read returns value 0 with a nonzero code, so a guest can tell a real 0 (or a missing
+/// key) from an error. Every syscall that returns through the trap handler sets `a1`;
+/// a `call`/`static_call` that starts its callee resumes through the task switch with
+/// the caller's saved registers instead. Wrappers must treat `a1` as clobbered.
+pub const SYSCALL_OK: u32 = 0;
+/// The account, key or code asked for does not exist.
+pub const SYSCALL_ERR_NOT_FOUND: u32 = 1;
+/// The caller may not perform this call, e.g. reading another program's storage.
+pub const SYSCALL_ERR_ACCESS_DENIED: u32 = 2;
+/// An argument pointer is unmapped or an argument is malformed.
+pub const SYSCALL_ERR_INVALID_ARGUMENT: u32 = 3;
+/// The result could not be allocated in or copied to the caller's heap.
+pub const SYSCALL_ERR_OUT_OF_MEMORY: u32 = 4;
+/// The syscall id is not implemented.
+pub const SYSCALL_ERR_UNSUPPORTED: u32 = 5;
@@ -1,25 +1,92 @@
-use types::address::Address;
+pub use state::TransferError;
+use types::address::{ADDRESS_LEN, Address};
+
+use crate::syscalls::SYSCALL_CALL_VALUE;
 
 const SYSCALL_TRANSFER: u32 = 9;
 const SYSCALL_BALANCE: u32 = 10;
+const SYSCALL_TRANSFER_MANY: u32 = 11;
+
+/// Encoded size of one `transfer_many` entry: recipient address + little-endian u64 value.
+pub const TRANSFER_ENTRY_LEN: usize = ADDRESS_LEN + 8;
 
 /// Executes a native AM token transfer via syscall. Returns true on success.
 #[inline(always)]
 pub fn transfer(to: &Address, value: u64) -> bool {
-    let mut ok: u32;
+    try_transfer(to, value).is_ok()
+}
+
+/// Executes a native AM token transfer via syscall. On failure returns the raw
+/// syscall code: `1` for a malformed request, otherwise a code that
+/// `TransferError::from_code` decodes.
+#[inline(always)]
+pub fn try_transfer(to: &Address, value: u64) -> Result<(), u32> {
+    let mut code: u32;
     unsafe {
         core::arch::asm!(
             "li a7, {transfer}",
             "ecall",
-            in("a1") 0u32,
+            inlateout("a1") 0u32 => _,
             in("a2") to.0.as_ptr(),
             in("a3") value as u32,
             in("a4") (value >> 32) as u32,
-            lateout("a0") ok,
+            lateout("a0") code,
             transfer = const SYSCALL_TRANSFER,
+            clobber_abi("C"),
+        );
+    }
+    match code {
+        0 => Ok(()),
+        code => Err(code),
+    }
+}
+
+/// Encodes one recipient for `transfer_many`.
+pub fn encode_transfer_entry(to: &Address, value: u64) -> [u8; TRANSFER_ENTRY_LEN] {
+    let mut entry = [0u8; TRANSFER_ENTRY_LEN];
+    entry[..ADDRESS_LEN].copy_from_slice(&to.0);
+    entry[ADDRESS_LEN..].copy_from_slice(&value.to_le_bytes());
+    entry
+}
+
+/// Why `transfer_many` moved nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferManyError {
+    /// The recipient at this index could not be paid.
+    Recipient(usize),
+    /// The kernel refused the batch itself: too many entries, an unreadable
+    /// entries pointer, or a caller that may not transfer.
+    Malformed,
+}
+
+/// Executes several native transfers from the caller in one syscall, all or nothing.
+/// `entries` is a concatenation of `encode_transfer_entry` records. On failure no
+/// balance changes.
+#[inline(always)]
+pub fn transfer_many(entries: &[u8]) -> Result<(), TransferManyError> {
+    let mut code: u32;
+    unsafe {
+        core::arch::asm!(
+            "li a7, {transfer_many}",
+            "ecall",
+            inlateout("a1") entries.as_ptr() => _,
+            in("a2") entries.len() / TRANSFER_ENTRY_LEN,
+            lateout("a0") code,
+            transfer_many = const SYSCALL_TRANSFER_MANY,
+            clobber_abi("C"),
         );
     }
-    ok == 0
+    decode_transfer_many(code)
+}
+
+/// Decodes the `transfer_many` syscall return: 0 on success, `index + 1` of the
+/// first failing recipient, or `u32::MAX` for a malformed batch.
+pub fn decode_transfer_many(code: u32) -> Result<(), TransferManyError> {
+    match code {
+        0 => Ok(()),
+        u32::MAX => Err(TransferManyError::Malformed),
+        code => Err(TransferManyError::Recipient(code as usize - 1)),
+    }
 }
 
 /// Returns the current balance of an address via syscall.
@@ -30,9 +97,10 @@ pub fn balance(addr: &Address) -> u128 {
         core::arch::asm!(
             "li a7, {balance}",
             "ecall",
-            in("a1") addr.0.as_ptr(),
+            inlateout("a1") addr.0.as_ptr() => _,
             lateout("a0") ptr,
             balance = const SYSCALL_BALANCE,
+            clobber_abi("C"),
         );
     }
     if ptr == 0 {
@@ -48,6 +116,32 @@ pub fn balance(addr: &Address) -> u128 {
     u128::from_le_bytes(bytes)
 }
 
+/// Native value sent with the transaction that started this program; 0 for
+/// nested calls. The kernel credits it to the program before the entry runs.
+/// The caller argument of the entrypoint is the immediate caller, which for a
+/// nested call is the calling program rather than the transaction sender.
+pub fn call_value() -> u128 {
+    let ptr: u32;
+    unsafe {
+        core::arch::asm!(
+            "li a7, {call_value}",
+            "ecall",
+            lateout("a0") ptr,
+            lateout("a1") _,
+            call_value = const SYSCALL_CALL_VALUE,
+            clobber_abi("C"),
+        );
+    }
+    if ptr == 0 {
+        return 0;
+    }
+    let mut bytes = [0u8; 16];
+    unsafe {
+        core::ptr::copy_nonoverlapping(ptr as *const u8, bytes.as_mut_ptr(), bytes.len());
+    }
+    u128::from_le_bytes(bytes)
+}
+
 /// Convenience macro to invoke a transfer from a contract.
 #[macro_export]
 macro_rules! transfer {
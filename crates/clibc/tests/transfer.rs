@@ -0,0 +1,27 @@
+use clibc::transfer::{TransferManyError, decode_transfer_many};
+
+#[test]
+fn test_decode_transfer_many_success() {
+    assert_eq!(decode_transfer_many(0), Ok(()));
+}
+
+#[test]
+fn test_decode_transfer_many_failing_recipient() {
+    assert_eq!(
+        decode_transfer_many(1),
+        Err(TransferManyError::Recipient(0))
+    );
+    assert_eq!(
+        decode_transfer_many(3),
+        Err(TransferManyError::Recipient(2))
+    );
+}
+
+#[test]
+fn test_decode_transfer_many_malformed_is_not_a_recipient() {
+    assert_eq!(
+        decode_transfer_many(u32::MAX),
+        Err(TransferManyError::Malformed)
+    );
+    assert_ne!(decode_transfer_many(u32::MAX), decode_transfer_many(1));
+}
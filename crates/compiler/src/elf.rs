@@ -1,9 +1,21 @@
+use goblin::elf::program_header::PT_LOAD;
 use goblin::elf::section_header::SHT_NOBITS;
 use goblin::elf::Elf;
 
 pub struct ElfInfo<'a> {
     pub code: &'a [u8],
     pub sections: Vec<ElfSection<'a>>,
+    /// `PT_LOAD` program headers in file order.
+    pub segments: Vec<LoadSegment<'a>>,
+    /// ELF entry point (`e_entry`); 0 when the linker script sets none.
+    pub entry: u64,
+}
+
+/// A loadable segment: `data` goes at `vaddr`, zero-filled up to `mem_size`.
+pub struct LoadSegment<'a> {
+    pub vaddr: u64,
+    pub mem_size: u64,
+    pub data: &'a [u8],
 }
 
 pub struct ElfSection<'a> {
@@ -70,6 +82,56 @@ impl<'a> ElfInfo<'a> {
         Some((flat_rodata, min_addr))
     }
 
+    /// Returns a flat buffer with all initialized `.data*`/`.sdata*` sections merged, and the
+    /// base address.
+    pub fn get_flat_data(&self) -> Option<(Vec<u8>, u64)> {
+        let data_sections: Vec<&ElfSection> = self
+            .sections
+            .iter()
+            .filter(|s| s.name.starts_with(".data") || s.name.starts_with(".sdata"))
+            .collect();
+
+        if data_sections.is_empty() {
+            return None;
+        }
+
+        let min_addr = data_sections.iter().map(|s| s.addr).min().unwrap();
+        let max_addr = data_sections.iter().map(|s| s.addr + s.size).max().unwrap();
+
+        let total_size = (max_addr - min_addr) as usize;
+        let mut flat_data = vec![0u8; total_size];
+
+        for section in data_sections {
+            let offset = (section.addr - min_addr) as usize;
+            flat_data[offset..offset + section.data.len()].copy_from_slice(section.data);
+        }
+
+        Some((flat_data, min_addr))
+    }
+
+    /// Returns a flat image of every non-empty `PT_LOAD` segment placed at its `p_vaddr`
+    /// relative to the lowest one, with `.bss`-style tails zero-filled, and that base address.
+    pub fn get_load_image(&self) -> Option<(Vec<u8>, u64)> {
+        let segments: Vec<&LoadSegment> = self.segments.iter().filter(|s| s.mem_size > 0).collect();
+
+        if segments.is_empty() {
+            return None;
+        }
+
+        let min_addr = segments.iter().map(|s| s.vaddr).min().unwrap();
+        let max_addr = segments.iter().map(|s| s.vaddr + s.mem_size).max().unwrap();
+
+        let total_size = (max_addr - min_addr) as usize;
+        let mut image = vec![0u8; total_size];
+
+        for segment in segments {
+            let offset = (segment.vaddr - min_addr) as usize;
+            image[offset..offset + segment.data.len()].copy_from_slice(segment.data);
+        }
+
+        Some((image, min_addr))
+    }
+
     /// Returns a reference to a section by its name, if it exists.
     /// Only the first section with the given name is returned, or None if not found.
     pub fn get_section_by_name(&self, name: &str) -> Option<&ElfSection<'a>> {
@@ -122,8 +184,23 @@ pub fn parse_elf_from_bytes<'a>(bytes: &'a [u8]) -> Result<ElfInfo<'a>, goblin::
         }
     }
 
+    let mut segments = Vec::new();
+    for header in elf.program_headers.iter().filter(|h| h.p_type == PT_LOAD) {
+        let offset = header.p_offset as usize;
+        let size = header.p_filesz as usize;
+        if offset + size <= bytes.len() && header.p_filesz <= header.p_memsz {
+            segments.push(LoadSegment {
+                vaddr: header.p_vaddr,
+                mem_size: header.p_memsz,
+                data: &bytes[offset..offset + size],
+            });
+        }
+    }
+
     Ok(ElfInfo {
         code: bytes,
         sections,
+        segments,
+        entry: elf.entry,
     })
 }
@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::transfer::{TRANSFER_ENTRY_LEN, TransferManyError};
+use clibc::types::address::Address;
+use clibc::types::result::Result;
+use clibc::{DataParser, entrypoint, require};
+
+/// Sends native AM from the caller to every recipient in one batched syscall.
+/// The input payload is a list of entries, each:
+/// - 20 bytes: recipient address
+/// - 8 bytes: amount (little-endian u64)
+///
+/// Returns the first recipient's balance afterwards. If any recipient fails the whole
+/// batch is rejected and the failing index is returned instead (error code 1); a batch
+/// the kernel refuses outright fails with error code 2.
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    let _ = program;
+    require(
+        !data.is_empty() && data.len() % TRANSFER_ENTRY_LEN == 0,
+        b"airdrop: need addr + amount entries",
+    );
+
+    match clibc::transfer_many(data) {
+        Ok(()) => {
+            let first = DataParser::new(data).read_address();
+            let mut result = Result::new(true, 0);
+            result.set_data(&clibc::balance!(&first).to_le_bytes());
+            result
+        }
+        Err(TransferManyError::Recipient(idx)) => {
+            let mut result = Result::new(false, 1);
+            result.set_data(&(idx as u32).to_le_bytes());
+            result
+        }
+        Err(TransferManyError::Malformed) => Result::new(false, 2),
+    }
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::call::call;
+use clibc::types::address::Address;
+use clibc::types::result::Result;
+use clibc::{entrypoint, vm_panic};
+
+/// Far outside the program window; nothing is mapped here.
+const UNMAPPED_ADDR: usize = 0x0080_0000;
+
+/// Makes a nested call whose input pointer is not mapped and returns the result
+/// the kernel hands back.
+///
+/// EDUCATIONAL PURPOSE: The kernel validates call arguments before starting the
+/// callee. A pointer it cannot read fails the call with
+/// `INVALID_CALL_ARGS_ERROR_CODE` (argument 2 = input, then the pointer) instead
+/// of running the callee on garbage or faulting.
+///
+/// INPUT FORMAT: The 20-byte address of the program to call.
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    if data.len() < 20 {
+        vm_panic(b"missing target address");
+    }
+    let mut target = [0u8; 20];
+    target.copy_from_slice(&data[..20]);
+
+    // Only the kernel reads this slice, through the caller's page tables.
+    let input = unsafe { core::slice::from_raw_parts(UNMAPPED_ADDR as *const u8, 4) };
+    match call(&program, &Address(target), input) {
+        Some(result) => result,
+        None => vm_panic(b"call returned no result"),
+    }
+}
+
+entrypoint!(program_entry);
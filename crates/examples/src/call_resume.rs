@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::call::call;
+use clibc::types::address::Address;
+use clibc::{DataParser, entrypoint, require, types::result::Result, vm_panic};
+
+// Include the auto-generated ABI client code for simple program
+include!("../bin/simple_abi.rs");
+
+/// Program that keeps executing after a nested program call returns.
+///
+/// The kernel resumes the caller at the instruction after its `call` with the
+/// callee's result pointer in `a0`, so the caller can use that value and make
+/// further calls before returning its own result.
+///
+/// The program expects:
+/// - 20 bytes: Address of the simple contract
+/// - 16 bytes: Four u32 values, compared pairwise by two calls (4 bytes each)
+///
+/// Returns the sum of the two callee results.
+fn program_entry(program: Address, caller: Address, data: &[u8]) -> Result {
+    let _ = program;
+    require(data.len() == 36, b"input data must be 36 bytes");
+
+    let mut parser = DataParser::new(data);
+    let simple_client = SimpleContract::new(parser.read_address());
+
+    let mut total: u32 = 0;
+    for _ in 0..2 {
+        let first = parser.read_u32();
+        let second = parser.read_u32();
+        let mut call_data = [0u8; 8];
+        call_data[0..4].copy_from_slice(&first.to_le_bytes());
+        call_data[4..8].copy_from_slice(&second.to_le_bytes());
+
+        let value = match simple_client.call_main(&caller, &call_data) {
+            Some(result) => match result.get_u32_data() {
+                Some(value) => value,
+                None => vm_panic(b"callee returned no value"),
+            },
+            None => vm_panic(b"program call failed"),
+        };
+        total = total.wrapping_add(value);
+    }
+
+    Result::with_u32(total)
+}
+
+// Register the function as the contract's entrypoint
+entrypoint!(program_entry);
@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::types::address::Address;
+use clibc::types::result::Result;
+use clibc::{DataParser, entrypoint, require, require_ge};
+
+/// Withdraws `requested` from an `available` balance and returns what is left.
+/// The input payload is two little-endian u32 values: available, then requested.
+///
+/// Overdrawing fails through `require_ge`, so the receipt's panic message carries
+/// both operands (e.g. "withdraw: expected a >= b: 5 < 10") instead of a fixed string.
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    let _ = program;
+    require(data.len() >= 8, b"withdraw: need available + requested");
+
+    let mut parser = DataParser::new(data);
+    let available = parser.read_u32();
+    let requested = parser.read_u32();
+    require_ge(available, requested, b"withdraw");
+
+    Result::with_u32(available - requested)
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+use clibc::types::address::Address;
+use clibc::{entrypoint, ext_code_hash, ext_code_size, require, types::result::Result};
+
+/// Reports the code hash and size of another account.
+///
+/// EDUCATIONAL PURPOSE: Before trusting a counterparty (e.g. a token the dex is
+/// about to call), a contract can check that the address really hosts the code
+/// it expects by comparing its code hash against a known value.
+///
+/// INPUT FORMAT: The 20-byte address to inspect.
+///
+/// OUTPUT FORMAT: 32-byte `sha256(code)` followed by the code length as a
+/// little-endian u32. Both are zero for missing or non-contract accounts.
+fn program_entry(_program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(data.len() == 20, b"expected a 20-byte address");
+    let mut target = [0u8; 20];
+    target.copy_from_slice(data);
+    let target = Address(target);
+
+    let mut out = [0u8; 36];
+    out[..32].copy_from_slice(&ext_code_hash(&target));
+    out[32..].copy_from_slice(&ext_code_size(&target).to_le_bytes());
+    Result::new_with_data(true, 0, &out)
+}
+
+entrypoint!(program_entry);
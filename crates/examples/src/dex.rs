@@ -4,7 +4,7 @@
 extern crate clibc;
 
 use clibc::{
-    DataParser, Map,
+    DataParser, Map, ResultBuilder,
     call::call,
     entrypoint, event, fire_event, hex_address, persist_struct, require, transfer,
     types::{address::Address, o::O, result::Result},
@@ -170,12 +170,10 @@ fn remove_liquidity(program: Address, caller: Address, mut parser: DataParser) -
     ));
 
     // AM payouts are reported in the result for visibility.
-    let mut res = Result::new(true, 0);
-    let mut buf = [0u8; 32];
-    buf[..16].copy_from_slice(&am_out.to_le_bytes());
-    buf[16..32].copy_from_slice(&token_out.to_le_bytes());
-    res.set_data(&buf);
-    res
+    ResultBuilder::new()
+        .push_u128(am_out)
+        .push_u128(token_out)
+        .build()
 }
 
 fn swap(program: Address, caller: Address, mut parser: DataParser) -> Result {
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+use clibc::types::address::Address;
+use clibc::{entrypoint, random_bytes, random_u64, require, types::result::Result};
+
+/// Largest number of bytes a single call may ask for.
+const MAX_BYTES: usize = 32;
+
+/// Returns bytes from the kernel's pseudo-entropy stream.
+///
+/// EDUCATIONAL PURPOSE: `random_bytes`/`random_u64` are deterministic. The kernel
+/// seeds each transaction's stream from the state the bundle started from and the
+/// transaction index, so every replay of a bundle returns the same bytes and anyone
+/// can predict them. Use them for tie-breaking or sampling, never for secrets.
+///
+/// INPUT FORMAT: 1 byte, the number of bytes to draw (at most 32).
+///
+/// OUTPUT FORMAT: the requested bytes, then the next `random_u64` of the same
+/// stream as a little-endian u64.
+fn program_entry(_program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(data.len() == 1, b"expected a 1-byte length");
+    let len = data[0] as usize;
+    require(len <= MAX_BYTES, b"length too large");
+
+    let mut out = [0u8; MAX_BYTES + 8];
+    random_bytes(&mut out[..len]);
+    out[len..len + 8].copy_from_slice(&random_u64().to_le_bytes());
+    Result::new_with_data(true, 0, &out[..len + 8])
+}
+
+entrypoint!(program_entry);
@@ -3,7 +3,8 @@
 
 extern crate clibc;
 use clibc::{
-    DataParser, Map, StorageKey, entrypoint, event, fire_event, logf, persist_struct, require,
+    DataParser, Map, StorageKey, checked_add_u32, checked_sub_u32, entrypoint, event, fire_event,
+    logf_at, persist_struct, require,
     router::route,
     types::{address::Address, o::O, result::Result},
     vm_panic,
@@ -13,6 +14,7 @@ use clibc::{
 persist_struct!(Metadata {
     total_supply: u32,
     decimals: u8,
+    owner: Address,
 });
 
 event!(Minted {
@@ -83,29 +85,42 @@ unsafe fn program_entry(program: Address, caller: Address, data: &[u8]) -> Resul
                 let b = balance_of(&program, owner);
                 Result::with_u32(b)
             }
+            0x06 => {
+                let mut parser = DataParser::new(call.args);
+                let owner = parser.read_address();
+                let spender = parser.read_address();
+                Result::with_u32(has_allowance(&program, owner, spender) as u32)
+            }
+            0x07 => {
+                let mut parser = DataParser::new(call.args);
+                let amount = parser.read_u32();
+                owner_mint(&program, caller, amount);
+                Result::new(true, 0)
+            }
             _ => vm_panic(b"unknown selector"),
         }
     })
 }
 
 fn init(program: &Address, caller: Address, args: &[u8]) {
-    logf!("init called");
+    logf_at!(Debug, "erc20", "init called");
     let mut meta = match Metadata::load(program) {
         O::Some(_) => vm_panic(b"already initialized"),
         O::None => Metadata {
             total_supply: 0,
             decimals: 0,
+            owner: caller,
         },
     };
 
-    logf!("initializing");
+    logf_at!(Debug, "erc20", "initializing");
 
     let mut parser = DataParser::new(args);
     let total_supply = parser.read_u32();
     let decimals = parser.read_bytes(1)[0];
 
-    logf!("total supply: %d", total_supply);
-    logf!("decimals: %d", decimals);
+    logf_at!(Debug, "erc20", "total supply: %d", total_supply);
+    logf_at!(Debug, "erc20", "decimals: %d", decimals);
 
     meta.total_supply = total_supply;
     meta.decimals = decimals;
@@ -115,14 +130,35 @@ fn init(program: &Address, caller: Address, args: &[u8]) {
     mint(program, caller, total_supply);
 }
 
+/// Mints more supply to the owner; only the account that initialized the token may
+/// call it, and a total supply past `u32::MAX` panics.
+fn owner_mint(program: &Address, caller: Address, amount: u32) {
+    let mut meta = match Metadata::load(program) {
+        O::Some(meta) => meta,
+        O::None => vm_panic(b"not initialized"),
+    };
+    require(meta.owner == caller, b"only the owner can mint");
+    meta.total_supply = checked_add_u32(meta.total_supply, amount);
+    meta.store(program);
+    mint(program, caller, amount);
+}
+
+/// Credits `val` to `caller` on top of any balance it already holds, so a later
+/// mint adds to the owner's tokens instead of replacing them.
 fn mint(program: &Address, caller: Address, val: u32) {
-    logf!("minting: %d tokens", val);
+    logf_at!(Debug, "erc20", "minting: %d tokens", val);
     fire_event!(Minted::new(caller, val));
-    Balances::set(program, caller, val);
+    let bal = match Balances::get(program, caller) {
+        O::Some(bal) => bal,
+        O::None => 0,
+    };
+    Balances::set(program, caller, checked_add_u32(bal, val));
 }
 
+// Kept out of line (like `transfer_from`) so coverage can attribute PCs to it.
+#[inline(never)]
 fn transfer(program: &Address, caller: Address, to: Address, amount: u32) {
-    logf!("erc20: transfer amount=%d", amount);
+    logf_at!(Debug, "erc20", "transfer amount=%d", amount);
     let from_bal = match Balances::get(program, caller) {
         O::Some(bal) => bal,
         O::None => 0,
@@ -131,23 +167,30 @@ fn transfer(program: &Address, caller: Address, to: Address, amount: u32) {
     if from_bal < amount {
         vm_panic(b"insufficient");
     }
+    Balances::set(program, caller, checked_sub_u32(from_bal, amount));
 
+    // Read after the debit so a self-transfer credits the debited balance and
+    // leaves it unchanged.
     let to_bal = match Balances::get(program, to) {
         O::Some(bal) => bal,
         O::None => 0,
     };
-
-    Balances::set(program, caller, from_bal - amount);
-    Balances::set(program, to, to_bal + amount);
+    Balances::set(program, to, checked_add_u32(to_bal, amount));
 
     fire_event!(Transfer::new(caller, to, amount));
 }
 
 fn approve(program: &Address, caller: Address, spender: Address, amount: u32) {
     let key = AllowanceKey::new(caller, spender);
-    Allowances::set(program, key, amount);
+    // A zero allowance is the same as none; drop the entry instead of storing 0.
+    if amount == 0 {
+        Allowances::remove(program, key);
+    } else {
+        Allowances::set(program, key, amount);
+    }
 }
 
+#[inline(never)]
 fn transfer_from(program: &Address, caller: Address, from: Address, to: Address, amount: u32) {
     let allowance = match Allowances::get(program, AllowanceKey::new(from, caller)) {
         O::Some(val) => val,
@@ -163,14 +206,14 @@ fn transfer_from(program: &Address, caller: Address, from: Address, to: Address,
         vm_panic(b"insufficient");
     }
 
+    Allowances::set(program, AllowanceKey::new(from, caller), allowance - amount);
+    Balances::set(program, from, checked_sub_u32(from_bal, amount));
+
     let to_bal = match Balances::get(program, to) {
         O::Some(bal) => bal,
         O::None => 0,
     };
-
-    Allowances::set(program, AllowanceKey::new(from, caller), allowance - amount);
-    Balances::set(program, from, from_bal - amount);
-    Balances::set(program, to, to_bal + amount);
+    Balances::set(program, to, checked_add_u32(to_bal, amount));
 
     fire_event!(Transfer::new(from, to, amount));
 }
@@ -181,5 +224,9 @@ fn balance_of(program: &Address, owner: Address) -> u32 {
         O::None => 0,
     }
 }
+
+fn has_allowance(program: &Address, owner: Address, spender: Address) -> bool {
+    Allowances::contains(program, AllowanceKey::new(owner, spender))
+}
 // ---- Entry point ----
 entrypoint!(program_entry);
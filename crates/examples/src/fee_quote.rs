@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::types::address::Address;
+use clibc::types::result::Result;
+use clibc::{DataParser, ResultBuilder, entrypoint, require};
+
+/// Quotes a 1% fee on an amount and returns a composite result.
+///
+/// EDUCATIONAL PURPOSE: Shows `ResultBuilder` packing several fields of
+/// different widths into one result instead of slicing a buffer by hand.
+///
+/// INPUT FORMAT: The amount as a little-endian u64.
+///
+/// OUTPUT FORMAT (40 bytes): the 20-byte caller, the net amount as a
+/// little-endian u128, then the fee as a little-endian u32.
+fn program_entry(_program: Address, caller: Address, data: &[u8]) -> Result {
+    require(data.len() >= 8, b"quote: need a u64 amount");
+    let amount = DataParser::new(data).read_u64();
+    let fee = amount / 100;
+    require(fee <= u32::MAX as u64, b"quote: fee overflow");
+
+    ResultBuilder::new()
+        .push_address(&caller)
+        .push_u128((amount - fee) as u128)
+        .push_u32(fee as u32)
+        .build()
+}
+
+entrypoint!(program_entry);
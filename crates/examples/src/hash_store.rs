@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+use clibc::types::address::Address;
+use clibc::{StorageBytes, entrypoint, require, types::result::Result, vm_panic};
+
+const DOMAIN: &[u8] = b"Hashes";
+const ROOT_KEY: &[u8] = b"root";
+
+const OP_STORE: u8 = 0;
+const OP_LOAD: u8 = 1;
+const OP_LOAD_SHORT: u8 = 2;
+
+/// Stores a 32-byte hash and reads it back in a later call.
+///
+/// EDUCATIONAL PURPOSE: `StorageBytes<N>` persists raw fixed-size arrays such as
+/// hashes or public keys, which don't fit `Map!`'s `Copy + Default` values once
+/// they grow past 32 bytes and have no struct to wrap them in `persist_struct!`.
+///
+/// INPUT FORMAT: The first byte selects the operation:
+/// - `0` followed by 32 bytes: store the hash under `Hashes/root`
+/// - `1`: return the stored hash as the result data
+/// - `2`: read the same key as a 20-byte array and return 1 if the load was
+///   rejected for its length, 0 otherwise
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(!data.is_empty(), b"missing operation byte");
+
+    match data[0] {
+        OP_STORE => {
+            require(data.len() == 33, b"store expects a 32-byte hash");
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&data[1..]);
+            StorageBytes::<32>::set(&program, DOMAIN, ROOT_KEY, &hash);
+            Result::new(true, 0)
+        }
+        OP_LOAD => {
+            let hash = StorageBytes::<32>::get(&program, DOMAIN, ROOT_KEY).expect("hash not set");
+            Result::new_with_data(true, 0, &hash)
+        }
+        OP_LOAD_SHORT => {
+            let rejected = StorageBytes::<20>::get(&program, DOMAIN, ROOT_KEY).is_none();
+            Result::with_u32(rejected as u32)
+        }
+        _ => vm_panic(b"unknown operation"),
+    }
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+use clibc::types::address::Address;
+use clibc::{DataParser, entrypoint, require, types::result::Result};
+
+/// Lookup table kept in `.rodata`, so the test also covers data placed after the
+/// relocated `.text`.
+static WEIGHTS: [u32; 4] = [3, 5, 7, 11];
+
+/// Program linked at a nonzero base address (see `compiler/linker_high_base.ld`).
+///
+/// EDUCATIONAL PURPOSE: Programs are normally linked at VA 0 and deployed as a flat
+/// image. This one is linked with `.text` at 0x2000 and deployed with a
+/// `ProgramImageHeader`, so the kernel copies it to its link address and jumps to
+/// the ELF entry point instead of the first non-zero byte.
+///
+/// INPUT FORMAT: Four 32-bit little-endian integers.
+///
+/// OUTPUT FORMAT: Returns the dot product of the input with `WEIGHTS` as a u32.
+fn program_entry(_program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(
+        data.len() >= 16,
+        b"Input data must be at least 16 bytes long",
+    );
+
+    let mut parser = DataParser::new(data);
+    let mut sum: u32 = 0;
+    for weight in WEIGHTS.iter() {
+        sum = sum.wrapping_add(parser.read_u32().wrapping_mul(*weight));
+    }
+    Result::with_u32(sum)
+}
+
+entrypoint!(program_entry);
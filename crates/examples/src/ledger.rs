@@ -0,0 +1,70 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::types::address::Address;
+use clibc::types::o::O;
+use clibc::types::result::Result;
+use clibc::{DataParser, ResultBuilder, entrypoint, persist_struct, vm_panic};
+
+// Running totals kept across calls. `total` is a u128 like `Account::balance`.
+persist_struct!(Ledger {
+    total: u128,
+    deposits: u64,
+});
+
+const DEPOSIT: u8 = 0x01;
+const WITHDRAW: u8 = 0x02;
+const QUERY: u8 = 0x03;
+
+/// Keeps a u128 running total in a persisted struct.
+///
+/// EDUCATIONAL PURPOSE: `persist_struct!` stores the struct's `repr(C)` bytes,
+/// and the VM is little-endian, so a u128 round-trips exactly between calls.
+///
+/// INPUT FORMAT: A selector byte, then a little-endian u128 amount for
+/// deposit (0x01) and withdraw (0x02); query (0x03) takes no amount.
+///
+/// OUTPUT FORMAT: The total (u128) and deposit count (u64), little-endian.
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    if data.is_empty() {
+        vm_panic(b"missing selector");
+    }
+    let mut parser = DataParser::new(data);
+    let op = parser.read_bytes(1)[0];
+
+    let mut ledger = match Ledger::load(&program) {
+        O::Some(ledger) => ledger,
+        O::None => Ledger {
+            total: 0,
+            deposits: 0,
+        },
+    };
+
+    match op {
+        DEPOSIT => {
+            let amount = parser.read_u128();
+            ledger.total = ledger.total.saturating_add(amount);
+            ledger.deposits += 1;
+            ledger.store(&program);
+        }
+        WITHDRAW => {
+            let amount = parser.read_u128();
+            if amount > ledger.total {
+                vm_panic(b"withdraw exceeds total");
+            }
+            ledger.total -= amount;
+            ledger.store(&program);
+        }
+        QUERY => {}
+        _ => vm_panic(b"unknown selector"),
+    }
+
+    ResultBuilder::new()
+        .push_u128(ledger.total)
+        .push_u64(ledger.deposits)
+        .build()
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::vec;
+
+use clibc::call::{call, max_input_len, static_call};
+use clibc::types::address::Address;
+use clibc::types::result::{INPUT_TOO_LARGE_ERROR_CODE, Result};
+use clibc::{DataParser, entrypoint, require, vm_panic};
+
+/// Makes a nested call with input one byte over the kernel's limit and returns the
+/// result the kernel hands back.
+///
+/// EDUCATIONAL PURPOSE: A guest can ask the kernel for its input limit with
+/// `max_input_len()`. A `call` or `static_call` whose input is longer fails with
+/// `INPUT_TOO_LARGE_ERROR_CODE`, carrying the actual and maximum lengths, instead of
+/// running the callee.
+///
+/// INPUT FORMAT:
+/// - 20 bytes: Address of the program to call
+/// - 1 byte: 0 for `call`, 1 for `static_call`
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(data.len() >= 21, b"missing target address or call kind");
+    let mut parser = DataParser::new(data);
+    let target = parser.read_address();
+    let kind = parser.read_bytes(1)[0];
+
+    let max = max_input_len();
+    let input = vec![0u8; max + 1];
+    let result = match kind {
+        0 => call(&program, &target, &input),
+        1 => static_call(&program, &target, &input),
+        _ => vm_panic(b"unknown call kind"),
+    };
+    let result = match result {
+        Some(result) => result,
+        None => vm_panic(b"call returned no result"),
+    };
+
+    // The kernel reports the same limit it hands out through max_input_len().
+    require(
+        result.error_code == INPUT_TOO_LARGE_ERROR_CODE,
+        b"expected input too large",
+    );
+    require(
+        result.data[4..8] == (max as u32).to_le_bytes(),
+        b"reported limit does not match max_input_len",
+    );
+    result
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+use clibc::types::O;
+use clibc::types::address::Address;
+use clibc::{StorageBytes, entrypoint, require, types::result::Result, vm_panic};
+
+const DOMAIN: &[u8] = b"Faults";
+const MARK_KEY: &[u8] = b"mark";
+
+const OP_READ_MARK: u8 = 0;
+const OP_FAULT: u8 = 1;
+
+/// Far outside the program window; nothing is mapped here.
+const UNMAPPED_ADDR: usize = 0x0080_0000;
+
+/// Writes storage and then dereferences an unmapped address.
+///
+/// EDUCATIONAL PURPOSE: A user program that touches memory outside its window
+/// takes a load page fault. The kernel terminates the program, rolls back the
+/// transaction, and records a fault receipt carrying `scause` and `stval`, then
+/// moves on to the next transaction in the bundle.
+///
+/// INPUT FORMAT: The first byte selects the operation:
+/// - `0`: return the stored mark (0 if never written)
+/// - `1`: store mark 1, then load from `UNMAPPED_ADDR`
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(!data.is_empty(), b"missing operation byte");
+
+    match data[0] {
+        OP_READ_MARK => {
+            let mark = match StorageBytes::<4>::get(&program, DOMAIN, MARK_KEY) {
+                O::Some(bytes) => u32::from_le_bytes(bytes),
+                O::None => 0,
+            };
+            Result::with_u32(mark)
+        }
+        OP_FAULT => {
+            StorageBytes::<4>::set(&program, DOMAIN, MARK_KEY, &1u32.to_le_bytes());
+            let value = unsafe { core::ptr::read_volatile(UNMAPPED_ADDR as *const u32) };
+            Result::with_u32(value)
+        }
+        _ => vm_panic(b"unknown operation"),
+    }
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::types::address::Address;
+use clibc::types::result::Result;
+use clibc::{DataParser, ResultBuilder, balance, call_value, entrypoint, vm_panic};
+
+/// Reports the native value it was called with and its own balance.
+///
+/// EDUCATIONAL PURPOSE: A transaction's `value` is credited to the program
+/// before its entry runs, in the same checkpoint as the call. If the call
+/// fails the value goes back to the sender; if the sender cannot pay, the
+/// program never runs.
+///
+/// INPUT FORMAT: Optionally the value the caller meant to send, as a
+/// little-endian u128; the call panics if the received value differs.
+///
+/// OUTPUT FORMAT: The received value and the program's balance (including that
+/// value) as little-endian u128s, then the immediate caller's address.
+fn program_entry(program: Address, caller: Address, data: &[u8]) -> Result {
+    let value = call_value();
+    if !data.is_empty() && DataParser::new(data).read_u128() != value {
+        vm_panic(b"call value mismatch");
+    }
+    ResultBuilder::new()
+        .push_u128(value)
+        .push_u128(balance(&program))
+        .push_address(&caller)
+        .build()
+}
+
+entrypoint!(program_entry);
@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::call::static_call;
+use clibc::types::address::Address;
+use clibc::{DataParser, entrypoint, event, fire_event, require, types::result::Result, vm_panic};
+
+event!(QueryFailed {
+    target => Address,
+    error_code => u32,
+});
+
+/// Forwards a query to another program through a static call and returns its result.
+///
+/// The callee may read its storage, but a storage write or transfer fails the static
+/// call with `STATIC_WRITE_ERROR_CODE`, so a query routed through here can never change
+/// state. This program keeps running after a failed query and fires `QueryFailed`
+/// before passing the result on.
+/// The program expects:
+/// - 20 bytes: Address of the program to query
+/// - remaining bytes: Call data forwarded as-is
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(data.len() >= 20, b"missing target address");
+    let mut parser = DataParser::new(data);
+    let target = parser.read_address();
+
+    match static_call(&program, &target, &data[20..]) {
+        Some(result) => {
+            if !result.success {
+                fire_event!(QueryFailed::new(target, result.error_code));
+            }
+            result
+        }
+        None => vm_panic(b"static call failed"),
+    }
+}
+
+entrypoint!(program_entry);
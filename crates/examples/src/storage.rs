@@ -67,7 +67,8 @@ fn program_entry(program: Address, _caller: Address, _data: &[u8]) -> Result {
         b"config timeout_ms must be 100000",
     );
 
-    Result::new(true, 0)
+    // Both structs are now persisted; report the account's storage footprint.
+    Result::with_u32(clibc::storage_bytes() as u32)
 }
 
 entrypoint!(program_entry);
@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+use clibc::types::address::Address;
+use clibc::{StorageBatch, StorageMap, entrypoint, require, types::result::Result};
+
+const DOMAIN: &[u8] = b"Batch";
+const KEYS: [&[u8]; 3] = [b"a", b"b", b"c"];
+
+const OP_INDIVIDUAL: u8 = 0;
+const OP_BATCHED: u8 = 1;
+
+/// Writes three values to storage and returns their sum as read back.
+///
+/// EDUCATIONAL PURPOSE: every `StorageMap::set` is its own trap into the kernel.
+/// `StorageBatch` queues the writes in guest memory and applies them with one
+/// syscall, all or nothing, which is cheaper for operations touching several keys.
+///
+/// INPUT FORMAT: The first byte selects how the values `1, 2, 3` are written under
+/// `Batch/a`, `Batch/b` and `Batch/c`:
+/// - `0`: one `StorageMap::set` per key
+/// - `1`: a single `StorageBatch` flush
+fn program_entry(program: Address, _caller: Address, data: &[u8]) -> Result {
+    require(!data.is_empty(), b"missing operation byte");
+
+    match data[0] {
+        OP_INDIVIDUAL => {
+            for (value, key) in (1u32..).zip(KEYS) {
+                StorageMap::set(&program, DOMAIN, key, value);
+            }
+        }
+        OP_BATCHED => {
+            let mut batch = StorageBatch::new();
+            for (value, key) in (1u32..).zip(KEYS) {
+                batch.set(DOMAIN, key, value);
+            }
+            require(batch.flush(&program).is_ok(), b"storage batch rejected");
+        }
+        _ => require(false, b"unknown operation"),
+    }
+
+    let mut sum = 0u32;
+    for key in KEYS {
+        sum += StorageMap::get::<u32>(&program, DOMAIN, key).expect("value not stored");
+    }
+    Result::with_u32(sum)
+}
+
+entrypoint!(program_entry);
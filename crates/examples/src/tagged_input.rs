@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+extern crate clibc;
+
+use clibc::types::address::Address;
+use clibc::types::result::Result;
+use clibc::{DataParser, ResultBuilder, entrypoint, require, vm_panic};
+
+const TAG_AMOUNT: u8 = 0x01;
+const TAG_TRANSFER: u8 = 0x02;
+
+/// Decodes one of two input layouts chosen by a leading tag byte.
+///
+/// EDUCATIONAL PURPOSE: Shows `DataParser::peek_u8` branching on a
+/// discriminator before committing to a layout, and `seek`/`position`
+/// rewinding and checking the cursor.
+///
+/// INPUT FORMAT: `0x01` then a nonzero u32 amount, or `0x02` then a 20-byte
+/// recipient and a u64 amount (all little-endian).
+///
+/// OUTPUT FORMAT (12 bytes): the cursor position after decoding as a u32,
+/// then the amount as a u64.
+fn program_entry(_program: Address, _caller: Address, data: &[u8]) -> Result {
+    let mut parser = DataParser::new(data);
+    let tag = parser.peek_u8();
+    require(parser.position() == 0, b"tagged: peek moved the cursor");
+
+    let amount = match tag {
+        TAG_AMOUNT => {
+            parser.seek(1);
+            require(parser.peek_u32() != 0, b"tagged: zero amount");
+            parser.read_u32() as u64
+        }
+        TAG_TRANSFER => {
+            parser.seek(1);
+            let _recipient = parser.read_address();
+            parser.read_u64()
+        }
+        _ => vm_panic(b"tagged: unknown tag"),
+    };
+    let end = parser.position();
+    require(end == data.len(), b"tagged: trailing input");
+
+    // Rewind and confirm the tag we branched on is still what the input says.
+    parser.seek(0);
+    require(parser.read_bytes(1)[0] == tag, b"tagged: tag changed");
+
+    ResultBuilder::new()
+        .push_u32(end as u32)
+        .push_u64(amount)
+        .build()
+}
+
+entrypoint!(program_entry);
@@ -1,16 +1,85 @@
-use clibc::logf;
 use clibc::parser::HexCodec;
+use clibc::{log, logf};
+use kernel::debugf;
 use kernel::global::{CODE_SIZE_LIMIT, RO_DATA_SIZE_LIMIT, STATE};
-use state::State;
+use state::{CREATE2_SALT_LEN, State, create2_address};
+use types::Result;
+use types::address::Address;
+use types::result::{
+    ADDRESS_OCCUPIED_ERROR_CODE, CODE_SIZE_EXCEEDED_ERROR_CODE, CREATE2_MALFORMED_ERROR_CODE,
+};
 use types::transaction::Transaction;
 
+use super::result::{set_receipt, set_receipt_result};
+
+/// Deploy `tx.data` at `tx.to`. Fails without touching state if the address already
+/// holds code; `redeploy` is the explicit overwrite.
 pub(crate) fn create_account(tx: &Transaction) {
-    let code_size = tx.data.len();
+    if is_occupied(&tx.to) {
+        log!("create_account: address already holds code");
+        set_receipt_result(Result::new_with_data(
+            false,
+            ADDRESS_OCCUPIED_ERROR_CODE,
+            &tx.to.0,
+        ));
+        return;
+    }
+    redeploy(tx);
+}
+
+/// Deploy `tx.data` at `tx.to`, replacing any code already there.
+pub(crate) fn redeploy(tx: &Transaction) {
+    if !deploy(&tx.to, &tx.data) {
+        set_receipt(false, CODE_SIZE_EXCEEDED_ERROR_CODE);
+    }
+}
+
+/// Salted deployment: `tx.data` is a 32-byte salt followed by the code. The contract
+/// lands at `create2_address(from, salt, code)`, which the receipt data reports.
+/// Fails without touching state if that address already holds code.
+pub(crate) fn create2(tx: &Transaction) {
+    if tx.data.len() < CREATE2_SALT_LEN {
+        log!("create2: data shorter than the salt");
+        set_receipt(false, CREATE2_MALFORMED_ERROR_CODE);
+        return;
+    }
+    let (salt, code) = tx.data.split_at(CREATE2_SALT_LEN);
+    let mut salt_buf = [0u8; CREATE2_SALT_LEN];
+    salt_buf.copy_from_slice(salt);
+    let addr = create2_address(&tx.from, &salt_buf, code);
+
+    if is_occupied(&addr) {
+        log!("create2: derived address already holds code");
+        set_receipt_result(Result::new_with_data(
+            false,
+            ADDRESS_OCCUPIED_ERROR_CODE,
+            &addr.0,
+        ));
+        return;
+    }
+
+    if !deploy(&addr, code) {
+        set_receipt(false, CODE_SIZE_EXCEEDED_ERROR_CODE);
+        return;
+    }
+    set_receipt_result(Result::new_with_data(true, 0, &addr.0));
+}
+
+fn is_occupied(addr: &Address) -> bool {
+    unsafe { STATE.get_mut().as_ref() }
+        .and_then(|state| state.get_account(addr))
+        .is_some_and(|account| account.is_contract || !account.code.is_empty())
+}
+
+/// Store `code` at `addr`. Returns false without touching state when the code is
+/// larger than the program window can hold.
+fn deploy(addr: &Address, code: &[u8]) -> bool {
+    let code_size = code.len();
     let is_contract = code_size > 0;
 
     let mut addr_buf = [0u8; 40];
-    let addr_hex = HexCodec::encode(tx.to.as_ref(), &mut addr_buf);
-    logf!(
+    let addr_hex = HexCodec::encode(addr.as_ref(), &mut addr_buf);
+    debugf!(
         "Tx creating account at address %s. Is contract: %d. Code size: %d bytes.",
         addr_hex.as_ptr() as u32,
         addr_hex.len() as u32,
@@ -20,21 +89,24 @@ pub(crate) fn create_account(tx: &Transaction) {
 
     let max = CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT;
     if code_size > max {
-        panic!(
-            "❌ Code size ({}) exceeds CODE_SIZE_LIMIT ({} bytes)",
-            code_size, max
+        logf!(
+            "create_account: code size %d exceeds limit %d",
+            code_size as u32,
+            max as u32
         );
+        return false;
     }
 
     let state = unsafe { STATE.get_mut().get_or_insert_with(State::new) };
-    let account = state.get_account_mut(&tx.to);
-    account.code = tx.data.clone();
+    let account = state.get_account_mut(addr);
+    account.code = code.to_vec();
     account.is_contract = is_contract;
-    logf!(
+    debugf!(
         "account created in kernel state: addr=%s is_contract=%d code_len=%d",
         addr_hex.as_ptr() as u32,
         addr_hex.len() as u32,
         is_contract as u32,
         code_size as u32
     );
+    true
 }
@@ -3,27 +3,31 @@ use core::mem::forget;
 extern crate alloc;
 
 use alloc::vec::Vec;
-use clibc::{log, logf};
+use clibc::logf;
+use state::State;
 use types::transaction::{Transaction, TransactionBundle, TransactionType};
 use types::{Result, TransactionReceipt};
 
-use kernel::global::{BUNDLE, CURRENT_TX, RECEIPTS};
+use kernel::global::{
+    BUNDLE, BUNDLE_START_INSTRET, CURRENT_TX, ENTROPY, ENTROPY_SEED, RECEIPTS, STATE,
+};
+use kernel::{debugf, infof};
 
 mod create_account;
 mod program_call;
 mod result;
 mod transfer;
 
-use self::create_account::create_account;
+use self::create_account::{create_account, create2, redeploy};
 use self::program_call::program_call;
-use self::result::{update_receipt_from_task, write_kernel_result};
+use self::result::{record_state_changes, update_receipt_from_task, write_kernel_result};
 use self::transfer::transfer;
 
 pub(crate) fn decode_bundle(encoded_bundle: &[u8]) -> bool {
-    log!("processing transaction bundle");
+    infof!("processing transaction bundle");
     if let Some(bundle) = TransactionBundle::decode(encoded_bundle) {
         let count = bundle.transactions.len();
-        logf!("decoded tx count=%d", count as u32);
+        debugf!("decoded tx count=%d", count as u32);
         let receipts = bundle
             .transactions
             .iter()
@@ -34,6 +38,11 @@ pub(crate) fn decode_bundle(encoded_bundle: &[u8]) -> bool {
             *BUNDLE.get_mut() = Some(bundle);
             *CURRENT_TX.get_mut() = 0;
             *RECEIPTS.get_mut() = Some(receipts);
+            // Seed guest pseudo-entropy from the state the bundle starts from.
+            *ENTROPY_SEED.get_mut() =
+                state::entropy_seed(STATE.get_mut().get_or_insert_with(State::new));
+            *ENTROPY.get_mut() = None;
+            *BUNDLE_START_INSTRET.get_mut() = read_instret();
         }
         true
     } else {
@@ -53,7 +62,7 @@ pub(crate) fn process_bundle() {
     if idx >= count {
         bundle_complete();
     }
-    logf!("processing tx %d/%d", (idx + 1) as u32, count as u32);
+    debugf!("processing tx %d/%d", (idx + 1) as u32, count as u32);
     let tx = unsafe {
         BUNDLE
             .get_mut()
@@ -61,6 +70,8 @@ pub(crate) fn process_bundle() {
             .and_then(|bundle| bundle.transactions.get(idx))
     };
     if let Some(tx) = tx {
+        // Track what this transaction changes for its receipt summary.
+        unsafe { STATE.get_mut().get_or_insert_with(State::new) }.begin_diff();
         if execute_transaction(tx) {
             resume_bundle();
         }
@@ -73,6 +84,11 @@ pub(crate) fn process_bundle() {
 pub(crate) extern "C" fn resume_bundle() -> ! {
     update_receipt_from_task();
     unsafe {
+        // Keep the finished call's changes (a panicked call has already reverted them).
+        if let Some(state) = STATE.get_mut().as_mut() {
+            state.commit();
+        }
+        record_state_changes();
         let curr = *CURRENT_TX.get_mut();
         *CURRENT_TX.get_mut() = curr.wrapping_add(1);
     }
@@ -89,18 +105,47 @@ fn execute_transaction(tx: &Transaction) -> bool {
             true
         }
         TransactionType::ProgramCall => {
+            // Only returns when the call could not be started.
             program_call(tx, resume_bundle);
-            false
+            true
         }
         TransactionType::Transfer => {
             transfer(tx);
             true
         }
+        TransactionType::Create2 => {
+            create2(tx);
+            true
+        }
+        TransactionType::Redeploy => {
+            redeploy(tx);
+            true
+        }
+    }
+}
+
+/// Instructions retired so far, read as a consistent 64-bit value on RV32.
+fn read_instret() -> u64 {
+    loop {
+        let (hi, lo, hi_again): (u32, u32, u32);
+        unsafe {
+            core::arch::asm!(
+                "csrr {0}, instreth",
+                "csrr {1}, instret",
+                "csrr {2}, instreth",
+                out(reg) hi,
+                out(reg) lo,
+                out(reg) hi_again,
+            );
+        }
+        if hi == hi_again {
+            return ((hi as u64) << 32) | lo as u64;
+        }
     }
 }
 
 fn bundle_complete() -> ! {
-    log!("transaction bundle complete");
+    infof!("transaction bundle complete");
     write_kernel_result();
     // Avoid drop-time teardown that can allocate/deallocate; we halt immediately.
     let bundle = unsafe { BUNDLE.get_mut().take() };
@@ -1,17 +1,37 @@
 use clibc::parser::HexCodec;
 use clibc::{log, logf};
-use kernel::global::TASKS;
+use kernel::global::{MAX_INPUT_LEN, STATE, TASKS, record_call};
 use kernel::user_program::with_program_image;
-use kernel::{PROGRAM_WINDOW_BYTES, kernel_run_task, prep_program_task};
+use kernel::{debugf, discard_task, kernel_run_task, prep_program_task};
+use state::State;
+use types::result::{PROGRAM_LOAD_ERROR_CODE, Result};
 use types::transaction::Transaction;
 
+use super::result::{set_receipt, set_receipt_result};
+
+/// Start the program task for `tx`; on success this jumps into it and never returns.
+///
+/// Returns only when the task could not be started, after recording a failed receipt so
+/// the caller can move on to the next transaction.
 pub(crate) fn program_call(tx: &Transaction, resume: extern "C" fn() -> !) {
+    if tx.data.len() > MAX_INPUT_LEN {
+        logf!(
+            "program_call: input too large (%d > %d)",
+            tx.data.len() as u32,
+            MAX_INPUT_LEN as u32
+        );
+        set_receipt_result(Result::input_too_large(
+            tx.data.len() as u32,
+            MAX_INPUT_LEN as u32,
+        ));
+        return;
+    }
     let mut from_buf = [0u8; 40];
     let mut to_buf = [0u8; 40];
     let from_hex = HexCodec::encode(tx.from.as_ref(), &mut from_buf);
     let to_hex = HexCodec::encode(tx.to.as_ref(), &mut to_buf);
     let task = with_program_image(&tx.to, |image| {
-        logf!(
+        debugf!(
             "Program call: from=%s to=%s input_len=%d code_len=%d",
             from_hex.as_ptr() as u32,
             from_hex.len() as u32,
@@ -20,23 +40,46 @@ pub(crate) fn program_call(tx: &Transaction, resume: extern "C" fn() -> !) {
             tx.data.len() as u32,
             image.code.len() as u32
         );
-        prep_program_task(&tx.to, &tx.from, image.code, &tx.data, image.entry_off)
+        prep_program_task(&tx.to, &tx.from, &image, &tx.data)
     });
 
-    if let Some(task) = task {
-        logf!(
+    if let Some(mut task) = task {
+        task.call_value = u128::from(tx.value);
+        debugf!(
             "Program task created: root=0x%x asid=%d window_size=%d",
             task.addr_space.root_ppn,
             task.addr_space.asid as u32,
-            PROGRAM_WINDOW_BYTES as u32
+            task.addr_space.va_len
         );
         unsafe {
-            let tasks_slot = TASKS.get_mut();
-            if !tasks_slot.push(task) {
-                log!("program task list full; skipping run");
+            // Record pre-images from here on so a guest panic can roll the call back,
+            // including the value moved to the program below.
+            let state = STATE.get_mut().get_or_insert_with(State::new);
+            state.checkpoint();
+            if tx.value > 0
+                && let Err(err) = state.try_transfer(&tx.from, &tx.to, tx.value)
+            {
+                logf!(
+                    "program_call: value transfer failed with code %d",
+                    err.code()
+                );
+                state.revert();
+                discard_task(task);
+                set_receipt(false, err.code());
                 return;
             }
-            let current = tasks_slot.len().saturating_sub(1);
+            let tasks_slot = TASKS.get_mut();
+            let current = match tasks_slot.push(task) {
+                Ok(idx) => idx,
+                Err(task) => {
+                    log!("program task list full; skipping run");
+                    discard_task(task);
+                    state.revert();
+                    set_receipt(false, PROGRAM_LOAD_ERROR_CODE);
+                    return;
+                }
+            };
+            record_call(tx.to);
             core::arch::asm!(
                 "mv ra, {resume}",
                 "j {run}",
@@ -47,6 +90,7 @@ pub(crate) fn program_call(tx: &Transaction, resume: extern "C" fn() -> !) {
             );
         }
     } else {
-        panic!("program_call: no memory manager installed; cannot create program task");
+        log!("program_call: failed to create program task");
+        set_receipt(false, PROGRAM_LOAD_ERROR_CODE);
     }
 }
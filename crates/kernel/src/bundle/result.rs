@@ -1,7 +1,13 @@
 use clibc::{log, logf};
-use kernel::global::{CURRENT_TX, KERNEL_RESULT_ADDR, LAST_COMPLETED_TASK, RECEIPTS, STATE, TASKS};
-use kernel::memory::heap;
-use types::{KernelResult, TransactionReceipt};
+use kernel::debugf;
+use kernel::global::{
+    BUNDLE_START_INSTRET, CURRENT_TX, KERNEL_RESULT_ADDR, LAST_COMPLETED_TASK, RECEIPTS, STATE,
+    TASKS,
+};
+use kernel::memory::{heap, stack};
+use types::{BundleSummary, KernelResult, Result, StateChangeSummary, TransactionReceipt};
+
+use super::read_instret;
 
 pub(crate) fn update_receipt_from_task() {
     let (tx_idx, task_idx) = unsafe {
@@ -12,13 +18,16 @@ pub(crate) fn update_receipt_from_task() {
     let task_idx = match task_idx {
         Some(idx) => idx,
         None => {
-            log!("resume_bundle: no completed task to update receipt");
+            debugf!("resume_bundle: no completed task to update receipt");
             return;
         }
     };
     let result = unsafe {
         let tasks = TASKS.get_mut();
-        tasks.get(task_idx).and_then(|task| task.last_result)
+        let result = tasks.get(task_idx).and_then(|task| task.last_result);
+        // The task is done once its result is captured; free the slot for later transactions.
+        tasks.release(task_idx);
+        result
     };
     let result = match result {
         Some(res) => res,
@@ -40,14 +49,59 @@ pub(crate) fn update_receipt_from_task() {
     }
 }
 
+/// Overwrite the current transaction's receipt result (used by kernel-side failures).
+pub(crate) fn set_receipt(success: bool, error_code: u32) {
+    set_receipt_result(Result::new(success, error_code));
+}
+
+/// Overwrite the current transaction's receipt result, including its data.
+pub(crate) fn set_receipt_result(result: Result) {
+    let tx_idx = unsafe { *CURRENT_TX.get_mut() };
+    unsafe {
+        if let Some(receipts) = RECEIPTS.get_mut().as_mut()
+            && let Some(receipt) = receipts.get_mut(tx_idx)
+        {
+            receipt.result = result;
+        }
+    }
+}
+
+/// Close the current transaction's state diff and record its size on the receipt.
+pub(crate) fn record_state_changes() {
+    let tx_idx = unsafe { *CURRENT_TX.get_mut() };
+    let diff = match unsafe { STATE.get_mut().as_mut() }.and_then(|state| state.finish_diff()) {
+        Some(diff) => diff,
+        None => return,
+    };
+    debugf!(
+        "tx %d changed %d accounts, %d storage keys",
+        tx_idx as u32,
+        diff.accounts_changed.len() as u32,
+        diff.storage_changed.len() as u32
+    );
+    unsafe {
+        if let Some(receipts) = RECEIPTS.get_mut().as_mut()
+            && let Some(receipt) = receipts.get_mut(tx_idx)
+        {
+            receipt.state_changes = StateChangeSummary {
+                accounts_changed: diff.accounts_changed.len() as u32,
+                storage_changed: diff.storage_changed.len() as u32,
+            };
+        }
+    }
+}
+
 pub(crate) fn write_kernel_result() {
+    let total_gas = read_instret().saturating_sub(unsafe { *BUNDLE_START_INSTRET.get_mut() });
     let encoded = unsafe {
-        RECEIPTS
-            .get_mut()
-            .as_ref()
-            .map(|receipts| TransactionReceipt::encode_list(receipts))
+        RECEIPTS.get_mut().as_ref().map(|receipts| {
+            (
+                TransactionReceipt::encode_list(receipts),
+                BundleSummary::from_receipts(receipts, total_gas),
+            )
+        })
     };
-    let encoded = match encoded {
+    let (encoded, summary) = match encoded {
         Some(data) => data,
         None => {
             log!("kernel_result: receipts missing");
@@ -84,9 +138,24 @@ pub(crate) fn write_kernel_result() {
         receipts_len: len,
         state_ptr,
         state_len,
+        heap_used: heap::used() as u32,
+        stack_used: stack::high_water(),
+        summary,
     };
     unsafe {
         core::ptr::write_volatile(KERNEL_RESULT_ADDR as *mut KernelResult, header);
     }
-    logf!("kernel_result: receipts_ptr=0x%x receipts_len=%d", ptr, len);
+    debugf!(
+        "kernel_result: receipts_ptr=0x%x receipts_len=%d heap_used=%d stack_used=%d",
+        ptr,
+        len,
+        header.heap_used,
+        header.stack_used
+    );
+    debugf!(
+        "kernel_result: %d txs, %d succeeded, %d failed",
+        summary.total,
+        summary.succeeded,
+        summary.failed
+    );
 }
@@ -4,10 +4,12 @@ use alloc::vec::Vec;
 use core::cell::UnsafeCell;
 use core::mem::MaybeUninit;
 use core::ptr;
-use state::State;
+use state::{Entropy, State};
 use types::TransactionReceipt;
+use types::boot::LogLevel;
+use types::result::Result as VmResult;
 use types::transaction::TransactionBundle;
-use types::{ADDRESS_LEN, SV32_PAGE_SIZE};
+use types::{ADDRESS_LEN, Address, SV32_PAGE_SIZE};
 
 use crate::Task;
 use crate::memory::heap::BumpAllocator;
@@ -42,23 +44,73 @@ unsafe impl<T> Sync for Global<T> {}
 // ============================================
 /// Maximum input buffer length accepted by program calls.
 pub const MAX_INPUT_LEN: usize = 1024;
-/// Upper bound for program text + data bytes in a user image.
-pub const CODE_SIZE_LIMIT: usize = 0x30000;
-/// Reserved space for read-only data in the user window.
-pub const RO_DATA_SIZE_LIMIT: usize = 0x2000;
+// Code size limits live in `types` so host tooling can size fixtures against them.
+pub use types::program_image::{CODE_SIZE_LIMIT, RO_DATA_SIZE_LIMIT};
 /// User VA base for program mappings.
 pub const PROGRAM_VA_BASE: u32 = 0x0;
 /// User stack size (bytes).
 pub const STACK_BYTES: usize = 0x4000; // 16 KiB user stack
 /// User heap size (bytes).
 pub const HEAP_BYTES: usize = 0x8000; // 32 KiB user heap
-/// Total mapped window for a program: code/rodata, stack, and heap.
-pub const PROGRAM_WINDOW_BYTES: usize = align_up(
-    CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT + STACK_BYTES + HEAP_BYTES,
-    SV32_PAGE_SIZE,
-);
-/// Start of the user heap within the program window.
-pub const HEAP_START_ADDR: usize = CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT + 0x100;
+/// Largest mapped window for a program: code/rodata, stack, and heap. Fixed VAs
+/// above the window (call args, trampoline) are placed past this size.
+pub const PROGRAM_WINDOW_BYTES: usize = WindowClass::Large.window_bytes();
+/// Start of the user heap within the largest program window.
+pub const HEAP_START_ADDR: usize = WindowClass::Large.heap_start();
+
+/// Program window presets. Each reserves a different amount of room for the image
+/// (code, rodata, data and bss) below the same `STACK_BYTES` stack and `HEAP_BYTES`
+/// heap, so a small contract maps and zeroes far fewer pages per call. The image
+/// size is the only thing known about a contract, so stack and heap do not shrink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowClass {
+    /// Images up to 32 KiB.
+    Small,
+    /// Images up to 96 KiB.
+    Medium,
+    /// Images up to `CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT`.
+    Large,
+}
+
+impl WindowClass {
+    /// Bytes reserved for the image at the bottom of the window.
+    pub const fn image_bytes(self) -> usize {
+        match self {
+            WindowClass::Small => 0x8000,
+            WindowClass::Medium => 0x18000,
+            WindowClass::Large => CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT,
+        }
+    }
+
+    /// Total mapped window: image, stack and heap, page aligned.
+    pub const fn window_bytes(self) -> usize {
+        align_up(
+            self.image_bytes() + STACK_BYTES + HEAP_BYTES,
+            SV32_PAGE_SIZE,
+        )
+    }
+
+    /// Start of the user heap, just past the image region.
+    pub const fn heap_start(self) -> usize {
+        self.image_bytes() + 0x100
+    }
+
+    /// Smallest class whose image region holds an image ending at `image_end`
+    /// (base + body, or the header's `mem_end` when bss runs past the body), or
+    /// `None` when even `Large` is too small. Image regions are
+    /// page multiples, so the image's last page fits too.
+    pub const fn for_image_end(image_end: usize) -> Option<Self> {
+        if image_end <= WindowClass::Small.image_bytes() {
+            Some(WindowClass::Small)
+        } else if image_end <= WindowClass::Medium.image_bytes() {
+            Some(WindowClass::Medium)
+        } else if image_end <= WindowClass::Large.image_bytes() {
+            Some(WindowClass::Large)
+        } else {
+            None
+        }
+    }
+}
 /// Maximum size of a program result payload.
 pub const MAX_RESULT_SIZE: usize = types::result::RESULT_SIZE;
 /// Default program entry address within the user window.
@@ -75,6 +127,54 @@ pub(crate) const TO_PTR_ADDR: u32 = CALL_ARGS_PAGE_BASE + 0x100;
 pub(crate) const FROM_PTR_ADDR: u32 = TO_PTR_ADDR + ADDRESS_LEN as u32;
 /// User VA base for the input buffer in the call-args page.
 pub(crate) const INPUT_BASE_ADDR: u32 = FROM_PTR_ADDR + ADDRESS_LEN as u32;
+/// User VA ranges `[start, end)` a program call occupies: the program window, then the
+/// `to`, `from` and input regions of the call-args page.
+pub const CALL_LAYOUT: [(u32, u32); 4] = [
+    (
+        PROGRAM_VA_BASE,
+        PROGRAM_VA_BASE + PROGRAM_WINDOW_BYTES as u32,
+    ),
+    (TO_PTR_ADDR, TO_PTR_ADDR + ADDRESS_LEN as u32),
+    (FROM_PTR_ADDR, FROM_PTR_ADDR + ADDRESS_LEN as u32),
+    (INPUT_BASE_ADDR, INPUT_BASE_ADDR + MAX_INPUT_LEN as u32),
+];
+/// Whether `CALL_LAYOUT` is usable; `prep_program_task` refuses to run otherwise.
+pub const CALL_LAYOUT_VALID: bool = call_layout_valid(&CALL_LAYOUT, CALL_ARGS_PAGE_BASE);
+
+/// True if no two non-empty `[start, end)` ranges overlap.
+pub const fn ranges_disjoint(ranges: &[(u32, u32)]) -> bool {
+    let mut i = 0;
+    while i < ranges.len() {
+        let mut j = i + 1;
+        while j < ranges.len() {
+            let (a, b) = (ranges[i], ranges[j]);
+            if a.0 < b.1 && b.0 < a.1 {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// True if `layout` (program window first, then the call-args regions) is disjoint
+/// and every call-args region lies inside the single page mapped at `args_page_base`.
+pub const fn call_layout_valid(layout: &[(u32, u32)], args_page_base: u32) -> bool {
+    if !ranges_disjoint(layout) {
+        return false;
+    }
+    let page_end = args_page_base as u64 + SV32_PAGE_SIZE as u64;
+    let mut i = 1;
+    while i < layout.len() {
+        let (start, end) = layout[i];
+        if start > end || start < args_page_base || end as u64 > page_end {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
 
 // ============================================
 // Task Scheduling and Bookkeeping
@@ -92,15 +192,43 @@ pub static CURRENT_TX: Global<usize> = Global::new(0);
 pub static LAST_COMPLETED_TASK: Global<Option<usize>> = Global::new(None);
 /// Active receipts buffer being filled while processing a bundle.
 pub static RECEIPTS: Global<Option<Vec<TransactionReceipt>>> = Global::new(None);
+
+/// Append `program` to the current transaction's receipt call trace.
+pub fn record_call(program: Address) {
+    let tx_idx = unsafe { *CURRENT_TX.get_mut() };
+    if let Some(receipt) = unsafe { RECEIPTS.get_mut().as_mut() }.and_then(|r| r.get_mut(tx_idx)) {
+        receipt.call_trace.push(program);
+    }
+}
 /// Currently decoded bundle, if any.
 pub static BUNDLE: Global<Option<TransactionBundle>> = Global::new(None);
+/// Instructions retired when the current bundle was decoded.
+pub static BUNDLE_START_INSTRET: Global<u64> = Global::new(0);
+/// `state::entropy_seed` of the state the current bundle started from.
+pub static ENTROPY_SEED: Global<[u8; 32]> = Global::new([0; 32]);
+/// Pseudo-entropy stream of the current transaction, tagged with its index so the
+/// next transaction starts a fresh stream.
+pub static ENTROPY: Global<Option<(usize, Entropy)>> = Global::new(None);
+/// Kernel log verbosity; set from `BootInfo::log_level` during boot.
+pub static LOG_LEVEL: Global<LogLevel> = Global::new(LogLevel::Error);
+/// Failure result raised by `sys_panic` for the running task. The trap handler takes it
+/// and unwinds the transaction back to the kernel task.
+pub static PENDING_PANIC: Global<Option<VmResult>> = Global::new(None);
+/// Failure result for the running call alone, raised by `reject_static_write`. The trap
+/// handler takes it and returns it to the task's caller, which keeps running.
+pub static PENDING_CALL_FAILURE: Global<Option<VmResult>> = Global::new(None);
 
 // ============================================
 // Task List Storage
 // ============================================
 /// Fixed-size task list backing store for scheduler bookkeeping.
+///
+/// Slots below `len` are initialized. A completed task's slot is marked free via
+/// `release` and handed out again by the next `push`, so long chains of completed
+/// calls do not exhaust `MAX_TASKS`.
 pub struct TaskList {
     len: usize,
+    free: [bool; MAX_TASKS],
     slots: MaybeUninit<[Task; MAX_TASKS]>,
 }
 
@@ -108,6 +236,7 @@ impl TaskList {
     pub const fn new() -> Self {
         Self {
             len: 0,
+            free: [false; MAX_TASKS],
             slots: MaybeUninit::uninit(),
         }
     }
@@ -120,9 +249,22 @@ impl TaskList {
         self.len == 0
     }
 
-    pub fn push(&mut self, task: Task) -> bool {
+    /// Store `task` in a free slot (reusing released slots first) and return its index.
+    /// When every slot is taken the task is handed back, so the caller can
+    /// `discard_task` it and return its ASID.
+    #[allow(clippy::result_large_err)]
+    pub fn push(&mut self, task: Task) -> Result<usize, Task> {
+        if let Some(idx) = (0..self.len).find(|&idx| self.free[idx]) {
+            unsafe {
+                let slot = (self.slots.as_mut_ptr() as *mut Task).add(idx);
+                ptr::drop_in_place(slot);
+                slot.write(task);
+            }
+            self.free[idx] = false;
+            return Ok(idx);
+        }
         if self.len >= MAX_TASKS {
-            return false;
+            return Err(task);
         }
         let idx = self.len;
         unsafe {
@@ -130,11 +272,25 @@ impl TaskList {
             base.add(idx).write(task);
         }
         self.len += 1;
+        Ok(idx)
+    }
+
+    /// Mark a completed task's slot as reusable and return its ASID to `ASIDS`.
+    /// The kernel slot is never released.
+    pub fn release(&mut self, idx: usize) -> bool {
+        if idx == KERNEL_TASK_SLOT || idx >= self.len || self.free[idx] {
+            return false;
+        }
+        if let Some(task) = self.get(idx) {
+            let asid = task.addr_space.asid;
+            unsafe { ASIDS.get_mut().free(asid) };
+        }
+        self.free[idx] = true;
         true
     }
 
     pub fn get(&self, idx: usize) -> Option<&Task> {
-        if idx < self.len {
+        if idx < self.len && !self.free[idx] {
             Some(unsafe { &*(self.slots.as_ptr() as *const Task).add(idx) })
         } else {
             None
@@ -142,7 +298,7 @@ impl TaskList {
     }
 
     pub fn get_mut(&mut self, idx: usize) -> Option<&mut Task> {
-        if idx < self.len {
+        if idx < self.len && !self.free[idx] {
             Some(unsafe { &mut *(self.slots.as_mut_ptr() as *mut Task).add(idx) })
         } else {
             None
@@ -169,6 +325,7 @@ impl TaskList {
             let base = self.slots.as_mut_ptr() as *mut Task;
             base.add(idx).write(task);
         }
+        self.free[idx] = false;
         true
     }
 
@@ -181,6 +338,57 @@ impl TaskList {
     }
 }
 
+/// ASID allocator for program address spaces.
+///
+/// ASIDs of released tasks are reissued before new values, so the set of ASIDs in use
+/// never grows past the peak number of live tasks and the `u16` counter does not wrap
+/// into ASIDs still owned by mapped roots. ASID 0 belongs to the kernel and is never
+/// handed out.
+pub struct AsidAllocator {
+    next: u16,
+    free: [u16; MAX_TASKS],
+    free_len: usize,
+}
+
+impl AsidAllocator {
+    pub const fn new() -> Self {
+        Self {
+            next: 1,
+            free: [0; MAX_TASKS],
+            free_len: 0,
+        }
+    }
+
+    /// Hand out an ASID, preferring released ones. The flag is true for a recycled
+    /// ASID, whose cached translations must be flushed before reuse.
+    pub fn alloc(&mut self) -> (u16, bool) {
+        if self.free_len > 0 {
+            self.free_len -= 1;
+            return (self.free[self.free_len], true);
+        }
+        let asid = if self.next == 0 { 1 } else { self.next };
+        self.next = asid.wrapping_add(1);
+        (asid, false)
+    }
+
+    /// Return `asid` for reuse. Ignores the kernel ASID and duplicates.
+    pub fn free(&mut self, asid: u16) {
+        if asid == 0 || self.free[..self.free_len].contains(&asid) {
+            return;
+        }
+        if self.free_len < MAX_TASKS {
+            self.free[self.free_len] = asid;
+            self.free_len += 1;
+        }
+    }
+}
+
+impl Default for AsidAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Default for TaskList {
     fn default() -> Self {
         Self::new()
@@ -205,14 +413,21 @@ impl Drop for TaskList {
 pub static TASKS: Global<TaskList> = Global::new(TaskList::new());
 /// Global chain state snapshot, if loaded.
 pub static STATE: Global<Option<State>> = Global::new(None);
-/// Next ASID to assign when launching a program.
-pub static NEXT_ASID: Global<u16> = Global::new(1);
+/// ASIDs assigned when launching programs, recycled as task slots are released.
+pub static ASIDS: Global<AsidAllocator> = Global::new(AsidAllocator::new());
+/// Physical address of the shared trampoline page, set when the first program task
+/// builds it; later tasks only map this frame into their root.
+pub static TRAMPOLINE_PHYS: Global<Option<u32>> = Global::new(None);
 /// Root physical page number for the kernel address space.
 pub static ROOT_PPN: Global<u32> = Global::new(0);
+/// Bytes of physical memory reachable through the direct map (0 when it is absent).
+pub static DIRECT_MAP_LEN: Global<usize> = Global::new(0);
 /// Page allocator backing store.
 pub static PAGE_ALLOC: Global<Option<PageAllocator>> = Global::new(None);
 /// Kernel heap allocator instance.
 pub(crate) static KERNEL_HEAP: Global<BumpAllocator> = Global::new(BumpAllocator::empty());
+/// Kernel stack top recorded when the stack is painted for watermarking.
+pub(crate) static KERNEL_STACK_TOP: Global<u32> = Global::new(0);
 
 const fn align_up(val: usize, align: usize) -> usize {
     (val + (align - 1)) & !(align - 1)
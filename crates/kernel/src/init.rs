@@ -1,11 +1,11 @@
 use core::slice;
 
-use clibc::{log, logf};
+use clibc::log;
 use state::State;
 
 use kernel::global::STATE;
-use kernel::memory::{heap, page_allocator};
-use kernel::{BootInfo, trap};
+use kernel::memory::{heap, page_allocator, stack};
+use kernel::{BootInfo, debugf, infof, trap};
 
 /// Initialize kernel state from the bootloader handoff and optional state blob.
 pub fn init_kernel(state_ptr: *const u8, state_len: usize, boot_info_ptr: *const BootInfo) {
@@ -14,11 +14,12 @@ pub fn init_kernel(state_ptr: *const u8, state_len: usize, boot_info_ptr: *const
         page_allocator::init(info);
         heap::init(info.heap_ptr, info.va_base, info.va_len);
         trap::init_trap_vector(info.kstack_top);
+        stack::paint(info.kstack_top);
         init_state(state_ptr, state_len);
     } else {
         panic!("init_kernel: missing boot info");
     }
-    log!("kernel initialized");
+    infof!("kernel initialized");
 }
 
 fn init_state(state_ptr: *const u8, state_len: usize) {
@@ -31,7 +32,7 @@ fn init_state(state_ptr: *const u8, state_len: usize) {
                 Some(State::new())
             });
             if state_slot.is_some() {
-                logf!("state initialized (len=%d)", state_len as u32);
+                debugf!("state initialized (len=%d)", state_len as u32);
             }
         } else {
             *state_slot = Some(State::new());
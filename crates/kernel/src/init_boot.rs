@@ -1,10 +1,15 @@
-use clibc::{log, logf};
+use clibc::log;
 
 use kernel::global::{CURRENT_TASK, KERNEL_TASK_SLOT, TASKS};
-use kernel::{BootInfo, Task};
+use kernel::log::{LogLevel, set_log_level};
+use kernel::{BootInfo, Task, debugf};
 
 pub(crate) fn init_boot_info(boot_info: Option<&BootInfo>) -> Option<&BootInfo> {
-    logf!(
+    // Apply the requested verbosity before anything below logs.
+    if let Some(info) = boot_info {
+        set_log_level(LogLevel::from_u32(info.log_level));
+    }
+    debugf!(
         "init_boot_info: boot_info_ptr=0x%x",
         boot_info
             .map(|info| info as *const BootInfo as usize as u32)
@@ -19,7 +24,7 @@ pub(crate) fn init_boot_info(boot_info: Option<&BootInfo>) -> Option<&BootInfo>
             }
             *CURRENT_TASK.get_mut() = KERNEL_TASK_SLOT;
         }
-        logf!(
+        debugf!(
             "boot_info: root_ppn=0x%x kstack_top=0x%x heap_ptr=0x%x mem_size=%d",
             info.root_ppn,
             info.kstack_top,
@@ -4,10 +4,12 @@
 pub use types::boot::BootInfo;
 
 pub mod global;
+pub mod log;
 pub mod task;
 pub use task::{AddressSpace, Task, TrapFrame};
 pub use task::{
-    PROGRAM_VA_BASE, PROGRAM_WINDOW_BYTES, kernel_run_task, prep_program_task, run_task,
+    PROGRAM_VA_BASE, PROGRAM_WINDOW_BYTES, discard_task, kernel_run_task, prep_program_task,
+    run_task,
 };
 pub mod memory;
 pub mod syscall;
@@ -32,6 +34,27 @@ fn panic(info: &core::panic::PanicInfo) -> ! {
     } else {
         clibc::logf!("kernel panic: %s", buf.as_ptr() as u32, len as u32);
     }
+    write_crash_record(&buf[..len]);
     unsafe { core::arch::asm!("ebreak") };
     loop {}
 }
+
+/// Leave a `KernelCrash` record in the result region so the host can report the
+/// panic message and transaction instead of just missing receipts.
+fn write_crash_record(message: &[u8]) {
+    use types::kernel_result::{KERNEL_CRASH_NO_TX, KernelCrash};
+
+    let tx_index = unsafe {
+        if global::RECEIPTS.get_mut().is_some() {
+            *global::CURRENT_TX.get_mut() as u32
+        } else {
+            KERNEL_CRASH_NO_TX
+        }
+    };
+    let mut record = [0u8; KernelCrash::MAX_LEN];
+    let written = KernelCrash::encode_into(tx_index, message, &mut record);
+    let dst = global::KERNEL_RESULT_ADDR as *mut u8;
+    for (offset, byte) in record[..written].iter().enumerate() {
+        unsafe { dst.add(offset).write_volatile(*byte) };
+    }
+}
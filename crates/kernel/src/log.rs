@@ -0,0 +1,37 @@
+//! Level-gated kernel logging.
+//!
+//! Plain `log!`/`logf!` are reserved for failures and always print. Routine
+//! tracing goes through `infof!`/`debugf!`, which take the same arguments as
+//! `logf!` but only print when `LOG_LEVEL` (set from `BootInfo`) allows it.
+
+pub use types::boot::LogLevel;
+
+use crate::global::LOG_LEVEL;
+
+pub fn set_log_level(level: LogLevel) {
+    unsafe { *LOG_LEVEL.get_mut() = level };
+}
+
+pub fn log_enabled(level: LogLevel) -> bool {
+    level <= unsafe { *LOG_LEVEL.get_mut() }
+}
+
+/// `logf!` that only prints at `LogLevel::Info` or above.
+#[macro_export]
+macro_rules! infof {
+    ($($arg:tt)*) => {
+        if $crate::log::log_enabled($crate::log::LogLevel::Info) {
+            ::clibc::logf!($($arg)*);
+        }
+    };
+}
+
+/// `logf!` that only prints at `LogLevel::Debug`.
+#[macro_export]
+macro_rules! debugf {
+    ($($arg:tt)*) => {
+        if $crate::log::log_enabled($crate::log::LogLevel::Debug) {
+            ::clibc::logf!($($arg)*);
+        }
+    };
+}
@@ -5,7 +5,7 @@ extern crate alloc;
 
 use clibc::log;
 use core::slice;
-use kernel::BootInfo;
+use kernel::{BootInfo, infof};
 
 mod bundle;
 mod init;
@@ -30,7 +30,7 @@ pub unsafe extern "C" fn _start(
     state_len: usize,
     boot_info_ptr: *const BootInfo,
 ) {
-    log!("kernel boot");
+    infof!("kernel boot");
 
     init_kernel(state_ptr, state_len, boot_info_ptr);
 
@@ -41,7 +41,7 @@ pub unsafe extern "C" fn _start(
         log!("bundle decode failed");
     }
 
-    log!("finished bundle execution");
+    infof!("finished bundle execution");
     halt();
 }
 
@@ -2,16 +2,22 @@ use core::alloc::{GlobalAlloc, Layout};
 
 #[derive(Clone, Copy)]
 pub(crate) struct BumpAllocator {
+    start: usize,
     next: usize,
     end: usize,
 }
 
 impl BumpAllocator {
     pub(crate) const fn empty() -> Self {
-        Self { next: 0, end: 0 }
+        Self {
+            start: 0,
+            next: 0,
+            end: 0,
+        }
     }
 
     fn init(&mut self, start: usize, end: usize) {
+        self.start = start;
         self.next = start;
         self.end = end;
     }
@@ -47,6 +53,12 @@ pub fn alloc(size: usize, align: usize) -> Option<*mut u8> {
     unsafe { crate::global::KERNEL_HEAP.get_mut().alloc(size, align) }
 }
 
+/// Bytes handed out since `init`, including alignment padding.
+pub fn used() -> usize {
+    let heap = unsafe { crate::global::KERNEL_HEAP.get_mut() };
+    heap.next - heap.start
+}
+
 /// Deallocate a kernel buffer. Bump allocator does not reclaim memory yet.
 pub fn dealloc(_ptr: *mut u8, _size: usize, _align: usize) {}
 
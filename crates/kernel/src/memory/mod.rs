@@ -1,2 +1,3 @@
 pub mod heap;
 pub mod page_allocator;
+pub mod stack;
@@ -1,7 +1,9 @@
 use core::{cmp, marker::PhantomData, ptr};
 
+use clibc::logf;
+
 use crate::BootInfo;
-use crate::global::{PAGE_ALLOC, ROOT_PPN};
+use crate::global::{DIRECT_MAP_LEN, PAGE_ALLOC, ROOT_PPN};
 use types::{
     SV32_DIRECT_MAP_BASE, SV32_PAGE_SIZE, SV32_PTE_R, SV32_PTE_U, SV32_PTE_V, SV32_PTE_W,
     SV32_PTE_X, SV32_VPN_MASK, Sv32PagePerms, Sv32PageTable, map_allocating, map_to_physical,
@@ -43,7 +45,8 @@ impl PageAllocator {
         let base = (ppn as usize)
             .checked_mul(PAGE_SIZE)
             .expect("page offset overflow");
-        let virt = direct_map_addr(base).expect("direct map overflow while zeroing page");
+        let virt =
+            direct_map_range(base, PAGE_SIZE).expect("zeroing a page outside the direct map");
         unsafe {
             ptr::write_bytes(virt as *mut u8, 0, PAGE_SIZE);
         }
@@ -81,10 +84,27 @@ pub fn set_current_root(root_ppn: u32) {
     }
 }
 
+/// Switch the helpers' current root and drop any cached translations for `old_asid`.
+///
+/// Call this whenever satp moves away from an address space so later walks cannot
+/// observe stale mappings once that ASID is reused.
+pub fn switch_root(root_ppn: u32, old_asid: u16) {
+    set_current_root(root_ppn);
+    sfence_vma(old_asid);
+}
+
+/// Flush cached address translations tagged with `asid`.
+pub fn sfence_vma(asid: u16) {
+    unsafe {
+        core::arch::asm!("sfence.vma zero, {0}", in(reg) asid as u32);
+    }
+}
+
 /// Initialize the kernel MMU allocator state from bootloader handoff.
 pub fn init(boot_info: &BootInfo) {
     unsafe {
         *ROOT_PPN.get_mut() = boot_info.root_ppn;
+        *DIRECT_MAP_LEN.get_mut() = boot_info.direct_map_len as usize;
         let limit_ppn = (boot_info.memory_size as usize / PAGE_SIZE) as u32;
         *PAGE_ALLOC.get_mut() = Some(PageAllocator::new(boot_info.next_free_ppn, limit_ppn));
     }
@@ -96,6 +116,10 @@ pub fn alloc_root() -> Option<u32> {
     match alloc {
         Some(alloc) => {
             let root = alloc.alloc()?;
+            if direct_map_range(root as usize * PAGE_SIZE, PAGE_SIZE).is_none() {
+                logf!("alloc_root: frame 0x%x is outside the direct map", root);
+                return None;
+            }
             PageAllocator::zero_page(root);
             Some(root)
         }
@@ -283,7 +307,7 @@ fn leaf_pte(root_ppn: u32, va: u32) -> Option<u32> {
 /// Peek a 32-bit value at a VA in a given root using the direct-map window.
 pub fn peek_word(root_ppn: u32, va: u32) -> Option<u32> {
     let phys = translate(root_ppn, va)?;
-    let va_ptr = direct_map_addr(phys)?;
+    let va_ptr = direct_map_range(phys, 4)?;
     Some(unsafe { (va_ptr as *const u32).read_volatile() })
 }
 
@@ -302,7 +326,7 @@ pub fn copy(root_ppn: u32, va_start: u32, data: &[u8]) -> bool {
         };
         let page_off = (va as usize) & (PAGE_SIZE - 1);
         let to_copy = cmp::min(remaining, PAGE_SIZE - page_off);
-        let dst = match direct_map_addr(phys) {
+        let dst = match direct_map_range(phys, to_copy) {
             Some(v) => v,
             None => return false,
         };
@@ -337,11 +361,11 @@ pub fn copy_user(root_ppn: u32, va_start: u32, data: &[u8]) -> bool {
             Some(p) => p,
             None => return false,
         };
-        if direct_map_addr(phys).is_none() {
-            return false;
-        }
         let page_off = (va as usize) & (PAGE_SIZE - 1);
         let to_copy = cmp::min(remaining, PAGE_SIZE - page_off);
+        if direct_map_range(phys, to_copy).is_none() {
+            return false;
+        }
         remaining -= to_copy;
         va = va.wrapping_add(to_copy as u32);
     }
@@ -356,7 +380,7 @@ pub fn copy_user(root_ppn: u32, va_start: u32, data: &[u8]) -> bool {
         };
         let page_off = (va as usize) & (PAGE_SIZE - 1);
         let to_copy = cmp::min(remaining, PAGE_SIZE - page_off);
-        let dst = match direct_map_addr(phys) {
+        let dst = match direct_map_range(phys, to_copy) {
             Some(v) => v,
             None => return false,
         };
@@ -391,12 +415,12 @@ impl<'a> Sv32PageTable for KernelMapper<'a> {
     }
 
     fn read_pte(&self, phys_addr: usize) -> Option<u32> {
-        let va = direct_map_addr(phys_addr)?;
+        let va = direct_map_range(phys_addr, 4)?;
         Some(unsafe { (va as *const u32).read_volatile() })
     }
 
     fn write_pte(&self, phys_addr: usize, val: u32) {
-        if let Some(va) = direct_map_addr(phys_addr) {
+        if let Some(va) = direct_map_range(phys_addr, 4) {
             unsafe { (va as *mut u32).write_volatile(val) };
         }
     }
@@ -411,17 +435,36 @@ impl<'a> Sv32PageTable for KernelMapper<'a> {
     }
 }
 
-fn direct_map_addr(phys: usize) -> Option<usize> {
+/// Kernel VA of physical `[phys, phys + len)` through the direct map, or None when any
+/// of it lies past the physical range the bootloader mapped there (all of it when the
+/// runner set up no direct map).
+pub fn direct_map_range(phys: usize, len: usize) -> Option<usize> {
+    let end = phys.checked_add(len)?;
+    if end > direct_map_len() {
+        return None;
+    }
     DIRECT_MAP_BASE.checked_add(phys)
 }
 
+/// Bytes of physical memory mapped at the direct-map base, from `BootInfo::direct_map_len`.
+pub fn direct_map_len() -> usize {
+    unsafe { *DIRECT_MAP_LEN.get_mut() }
+}
+
+/// Override the direct-map length, e.g. to simulate a boot without a direct map.
+pub fn set_direct_map_len(len: usize) {
+    unsafe {
+        *DIRECT_MAP_LEN.get_mut() = len;
+    }
+}
+
 fn read_pte(phys_addr: usize) -> Option<u32> {
-    let va = direct_map_addr(phys_addr)?;
+    let va = direct_map_range(phys_addr, 4)?;
     Some(unsafe { (va as *const u32).read_volatile() })
 }
 
 fn write_pte(phys_addr: usize, val: u32) {
-    if let Some(va) = direct_map_addr(phys_addr) {
+    if let Some(va) = direct_map_range(phys_addr, 4) {
         unsafe { (va as *mut u32).write_volatile(val) };
     }
 }
@@ -0,0 +1,46 @@
+//! Kernel stack high-water mark via sentinel painting.
+//!
+//! At boot the unused part of the kernel stack is filled with `STACK_SENTINEL`.
+//! Anything the kernel later pushes overwrites the pattern, so the lowest
+//! overwritten word bounds how deep the stack ever grew.
+
+use core::arch::asm;
+
+use crate::global::KERNEL_STACK_TOP;
+
+/// Bytes below the stack top that are painted and scanned.
+pub const STACK_WATCH_BYTES: u32 = 0x10000;
+/// Pattern written into unused stack words.
+const STACK_SENTINEL: u32 = 0x57AC_57AC;
+/// Space left unpainted below the live `sp` so `paint` never clobbers its own frame.
+const PAINT_MARGIN: u32 = 256;
+
+/// Fill the unused kernel stack below the current `sp` with the sentinel.
+pub fn paint(kstack_top: u32) {
+    unsafe {
+        *KERNEL_STACK_TOP.get_mut() = kstack_top;
+    }
+    let sp: u32;
+    unsafe { asm!("mv {0}, sp", out(reg) sp) };
+    let bottom = kstack_top.saturating_sub(STACK_WATCH_BYTES);
+    let limit = sp.saturating_sub(PAINT_MARGIN) & !3;
+    let mut addr = bottom;
+    while addr < limit {
+        unsafe { core::ptr::write_volatile(addr as *mut u32, STACK_SENTINEL) };
+        addr += 4;
+    }
+}
+
+/// Deepest stack use since `paint`, in bytes below the stack top. Returns 0 if the
+/// stack was never painted.
+pub fn high_water() -> u32 {
+    let top = unsafe { *KERNEL_STACK_TOP.get_mut() };
+    if top == 0 {
+        return 0;
+    }
+    let mut addr = top.saturating_sub(STACK_WATCH_BYTES);
+    while addr < top && unsafe { core::ptr::read_volatile(addr as *const u32) } == STACK_SENTINEL {
+        addr += 4;
+    }
+    top - addr
+}
@@ -0,0 +1,111 @@
+#![no_std]
+#![no_main]
+
+// Direct-map tests: with the direct map absent or narrowed, physical accesses through it
+// fail instead of reading or writing whatever lies at the window address.
+use clibc::log;
+use kernel::BootInfo;
+use kernel::memory::page_allocator;
+use types::SV32_PAGE_SIZE;
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+static PROBE: u32 = 0xfeed_beef;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel direct map test boot");
+    let info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_full_direct_map(&info) {
+        fail::fail(code);
+    }
+    if let Err(code) = test_disabled_direct_map_fails_cleanly() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_narrowed_direct_map() {
+        fail::fail(code);
+    }
+
+    log!("kernel direct map test done");
+    utils::pass();
+}
+
+fn probe_va() -> u32 {
+    &PROBE as *const u32 as u32
+}
+
+fn test_full_direct_map(info: &BootInfo) -> Result<(), u32> {
+    // Description: the default boot maps all of physical memory and reads through it work.
+    log!("test: full direct map covers physical memory");
+    if page_allocator::direct_map_len() != info.memory_size as usize {
+        return Err(1);
+    }
+    let root = page_allocator::current_root();
+    if page_allocator::peek_word(root, probe_va()) != Some(PROBE) {
+        return Err(2);
+    }
+    Ok(())
+}
+
+fn test_disabled_direct_map_fails_cleanly() -> Result<(), u32> {
+    // Description: without a direct map, translated reads and writes report failure.
+    log!("test: disabled direct map rejects physical access");
+    let saved = page_allocator::direct_map_len();
+    let root = page_allocator::current_root();
+    page_allocator::set_direct_map_len(0);
+
+    let result = (|| {
+        log!("subtest: peek returns None");
+        if page_allocator::peek_word(root, probe_va()).is_some() {
+            return Err(10);
+        }
+        log!("subtest: copy returns false");
+        let mut scratch = [0u8; 4];
+        if page_allocator::copy(root, scratch.as_mut_ptr() as u32, &[1, 2, 3, 4]) {
+            return Err(11);
+        }
+        log!("subtest: root allocation is refused");
+        if page_allocator::alloc_root().is_some() {
+            return Err(12);
+        }
+        Ok(())
+    })();
+
+    page_allocator::set_direct_map_len(saved);
+    result
+}
+
+fn test_narrowed_direct_map() -> Result<(), u32> {
+    // Description: only ranges wholly inside a narrowed direct map resolve.
+    log!("test: narrowed direct map bounds physical ranges");
+    let saved = page_allocator::direct_map_len();
+    page_allocator::set_direct_map_len(2 * SV32_PAGE_SIZE);
+
+    let inside = page_allocator::direct_map_range(SV32_PAGE_SIZE, SV32_PAGE_SIZE).is_some();
+    let straddling = page_allocator::direct_map_range(2 * SV32_PAGE_SIZE - 2, 4).is_some();
+    let past_end = page_allocator::direct_map_range(2 * SV32_PAGE_SIZE, 1).is_some();
+
+    page_allocator::set_direct_map_len(saved);
+    if !inside {
+        return Err(20);
+    }
+    if straddling || past_end {
+        return Err(21);
+    }
+    Ok(())
+}
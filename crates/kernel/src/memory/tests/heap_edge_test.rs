@@ -3,8 +3,9 @@
 
 // Heap edge tests: invalid layouts and monotonic bump behavior.
 use clibc::log;
-use kernel::BootInfo;
 use kernel::memory::heap;
+use kernel::syscall::alloc::alloc_in_task;
+use kernel::{BootInfo, Task};
 
 #[path = "../../tests/fail.rs"]
 mod fail;
@@ -22,7 +23,7 @@ pub unsafe extern "C" fn _start(
     boot_info_ptr: *const BootInfo,
 ) {
     log!("kernel heap edge test boot");
-    let _info = utils::init_test_kernel(boot_info_ptr);
+    let info = utils::init_test_kernel(boot_info_ptr);
 
     clibc::logf!("kernel test input len: %d", input_len as u32);
     let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
@@ -33,6 +34,9 @@ pub unsafe extern "C" fn _start(
     if let Err(code) = test_monotonic_bump_and_data() {
         fail::fail(code);
     }
+    if let Err(code) = test_task_heap_window(&info) {
+        fail::fail(code);
+    }
 
     log!("kernel heap edge test done");
     utils::pass();
@@ -93,3 +97,51 @@ fn test_monotonic_bump_and_data() -> Result<(), u32> {
     }
     Ok(())
 }
+
+fn test_task_heap_window(info: &BootInfo) -> Result<(), u32> {
+    // Description: task allocations must stay inside [heap_ptr, heap_end).
+    log!("test: task heap allocations are bounded by heap_end");
+    const WINDOW: usize = 128;
+    let backing = heap::alloc(WINDOW, 8).unwrap_or_default();
+    if backing.is_null() {
+        return Err(30);
+    }
+    let base = backing as u32;
+    let mut task = Task::kernel(info.root_ppn, base, info.va_base, info.va_len);
+    task.heap_end = base + WINDOW as u32;
+
+    log!("subtest: invalid task layouts are rejected");
+    if alloc_in_task(&mut task, 0, 4).is_some() || alloc_in_task(&mut task, 16, 3).is_some() {
+        return Err(31);
+    }
+    if alloc_in_task(&mut task, u32::MAX, 4).is_some() || task.heap_ptr != base {
+        return Err(32);
+    }
+
+    log!("subtest: allocating past heap_end returns none");
+    let a = alloc_in_task(&mut task, 48, 8).ok_or(33u32)?;
+    let b = alloc_in_task(&mut task, 48, 8).ok_or(34u32)?;
+    unsafe {
+        (a as *mut u8).write_bytes(0x5a, 48);
+        (b as *mut u8).write_bytes(0xa5, 48);
+    }
+    let used = task.heap_ptr;
+    if alloc_in_task(&mut task, 64, 8).is_some() {
+        return Err(35);
+    }
+    if task.heap_ptr != used {
+        return Err(36);
+    }
+
+    log!("subtest: prior allocations remain valid after exhaustion");
+    let a_bytes = unsafe { core::slice::from_raw_parts(a as *const u8, 48) };
+    let b_bytes = unsafe { core::slice::from_raw_parts(b as *const u8, 48) };
+    if a_bytes.iter().any(|&v| v != 0x5a) || b_bytes.iter().any(|&v| v != 0xa5) {
+        return Err(37);
+    }
+    let rest = task.heap_end - task.heap_ptr;
+    if alloc_in_task(&mut task, rest, 1) != Some(used) || task.heap_ptr != task.heap_end {
+        return Err(38);
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Root-switch tests: a mapping installed under one root must not resolve after switching
+// (with sfence.vma) to a different root, and must resolve again after switching back.
+use clibc::log;
+use kernel::BootInfo;
+use kernel::memory::page_allocator::{self, PagePerms};
+
+const PAGE_SIZE: usize = 0x1000;
+const ASID_A: u16 = 7;
+const ASID_B: u16 = 8;
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel sfence test boot");
+    let info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_switch_root_hides_old_mappings(info) {
+        fail::fail(code);
+    }
+
+    log!("kernel sfence test done");
+    utils::pass();
+}
+
+fn test_switch_root_hides_old_mappings(info: BootInfo) -> Result<(), u32> {
+    // Description: map a VA under root A, switch to an empty root B and confirm the VA no
+    // longer resolves through the current root; switching back restores the mapping.
+    log!("test: switch_root hides mappings from the previous root");
+    let kernel_root = page_allocator::current_root();
+    let root_a = page_allocator::alloc_root().ok_or(1u32)?;
+    let root_b = page_allocator::alloc_root().ok_or(2u32)?;
+
+    let va = info.va_base.saturating_add(PAGE_SIZE as u32 * 4);
+    let perms = PagePerms::new(true, true, false, true);
+    if !page_allocator::map_range_for_root(root_a, va, PAGE_SIZE, perms) {
+        return Err(3);
+    }
+
+    log!("subtest: VA resolves under root A");
+    page_allocator::switch_root(root_a, 0);
+    if page_allocator::translate(page_allocator::current_root(), va).is_none() {
+        return Err(4);
+    }
+
+    log!("subtest: VA does not resolve after switching to root B");
+    page_allocator::switch_root(root_b, ASID_A);
+    if page_allocator::current_root() != root_b {
+        return Err(5);
+    }
+    if page_allocator::translate(page_allocator::current_root(), va).is_some() {
+        return Err(6);
+    }
+
+    log!("subtest: VA resolves again after switching back to root A");
+    page_allocator::switch_root(root_a, ASID_B);
+    if page_allocator::translate(page_allocator::current_root(), va).is_none() {
+        return Err(7);
+    }
+
+    page_allocator::switch_root(kernel_root, ASID_A);
+    Ok(())
+}
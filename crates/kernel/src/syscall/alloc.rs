@@ -3,7 +3,11 @@ use clibc::{log, logf};
 use crate::Task;
 use crate::global::{CURRENT_TASK, KERNEL_TASK_SLOT, TASKS};
 
-pub(crate) fn alloc_in_task(task: &mut Task, size: u32, align: u32) -> Option<u32> {
+/// Bump-allocate `size` bytes from `task`'s heap, bounded by `task.heap_end`.
+///
+/// Returns `None` (leaving the heap pointer untouched) on invalid layouts,
+/// arithmetic overflow, or when the request does not fit in the heap.
+pub fn alloc_in_task(task: &mut Task, size: u32, align: u32) -> Option<u32> {
     if size == 0 {
         log!("sys_alloc: invalid size 0");
         return None;
@@ -41,6 +45,15 @@ pub(crate) fn alloc_in_task(task: &mut Task, size: u32, align: u32) -> Option<u3
         );
         return None;
     }
+    if end > task.heap_end {
+        logf!(
+            "sys_alloc: out of heap size=%d heap_ptr=0x%x heap_end=0x%x",
+            size,
+            task.heap_ptr,
+            task.heap_end
+        );
+        return None;
+    }
     task.heap_ptr = end;
     Some(start)
 }
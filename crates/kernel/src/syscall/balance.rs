@@ -1,20 +1,29 @@
+extern crate alloc;
+
 use clibc::{log, logf};
 use types::{ADDRESS_LEN, Address};
 
+use alloc::vec::Vec;
 use state::State;
 
 use crate::global::FROM_PTR_ADDR;
-use crate::global::{CURRENT_TASK, KERNEL_TASK_SLOT, STATE};
+use crate::global::{CURRENT_TASK, KERNEL_TASK_SLOT, STATE, TASKS};
 use crate::memory::page_allocator as mmu;
 use crate::syscall::alloc::sys_alloc;
+use crate::syscall::panic::reject_static_write;
 use crate::syscall::storage::{current_task_root_ppn, read_user_bytes};
 
+/// Native transfer from the caller. Returns 0 on success, 1 for a malformed
+/// request, or a `TransferError::code` when the state rejects the transfer.
 pub(crate) fn sys_transfer(args: [u32; 6]) -> u32 {
     let current = unsafe { *CURRENT_TASK.get_mut() };
     if current == KERNEL_TASK_SLOT {
         log!("sys_transfer: kernel task not allowed");
         return 1;
     }
+    if reject_static_write("sys_transfer") {
+        return 1;
+    }
 
     let root_ppn = match current_task_root_ppn() {
         Some(root) => root,
@@ -45,8 +54,79 @@ pub(crate) fn sys_transfer(args: [u32; 6]) -> u32 {
     let to = Address(to_buf);
 
     let state = unsafe { STATE.get_mut().get_or_insert_with(State::new) };
-    let ok = state.transfer(&from, &to, value);
-    if ok { 0 } else { 1 }
+    match state.try_transfer(&from, &to, value) {
+        Ok(()) => 0,
+        Err(err) => {
+            logf!("sys_transfer: rejected with code %d", err.code());
+            err.code()
+        }
+    }
+}
+
+/// Largest batch accepted by `sys_transfer_many`.
+const MAX_TRANSFER_ENTRIES: usize = 64;
+/// Encoded entry size: recipient address + little-endian u64 value.
+const TRANSFER_ENTRY_LEN: usize = ADDRESS_LEN + 8;
+
+/// Batched native transfer from the caller. `args[0]` points at `args[1]` entries.
+/// Returns 0 on success, `index + 1` of the first failing recipient (nothing applied),
+/// or `u32::MAX` for a malformed batch.
+pub(crate) fn sys_transfer_many(args: [u32; 6]) -> u32 {
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    if current == KERNEL_TASK_SLOT {
+        log!("sys_transfer_many: kernel task not allowed");
+        return u32::MAX;
+    }
+    if reject_static_write("sys_transfer_many") {
+        return u32::MAX;
+    }
+
+    let root_ppn = match current_task_root_ppn() {
+        Some(root) => root,
+        None => return u32::MAX,
+    };
+
+    let entries_ptr = args[0];
+    let count = args[1] as usize;
+    if count > MAX_TRANSFER_ENTRIES {
+        logf!("sys_transfer_many: too many entries (%d)", count as u32);
+        return u32::MAX;
+    }
+
+    let from_bytes = match read_user_bytes(root_ppn, FROM_PTR_ADDR, ADDRESS_LEN) {
+        Some(bytes) if bytes.len() == ADDRESS_LEN => bytes,
+        _ => return u32::MAX,
+    };
+    let entries = match read_user_bytes(root_ppn, entries_ptr, count * TRANSFER_ENTRY_LEN) {
+        Some(bytes) if bytes.len() == count * TRANSFER_ENTRY_LEN => bytes,
+        _ => {
+            logf!("sys_transfer_many: invalid entries ptr 0x%x", entries_ptr);
+            return u32::MAX;
+        }
+    };
+
+    let mut from_buf = [0u8; ADDRESS_LEN];
+    from_buf.copy_from_slice(&from_bytes);
+    let from = Address(from_buf);
+    let recipients = entries
+        .chunks_exact(TRANSFER_ENTRY_LEN)
+        .map(|entry| {
+            let mut to = [0u8; ADDRESS_LEN];
+            to.copy_from_slice(&entry[..ADDRESS_LEN]);
+            let mut value = [0u8; 8];
+            value.copy_from_slice(&entry[ADDRESS_LEN..]);
+            (Address(to), u64::from_le_bytes(value))
+        })
+        .collect::<Vec<_>>();
+
+    let state = unsafe { STATE.get_mut().get_or_insert_with(State::new) };
+    match state.transfer_many(&from, &recipients) {
+        Ok(()) => 0,
+        Err(idx) => {
+            logf!("sys_transfer_many: recipient %d failed", idx as u32);
+            idx as u32 + 1
+        }
+    }
 }
 
 pub(crate) fn sys_balance(args: [u32; 6]) -> u32 {
@@ -90,3 +170,28 @@ pub(crate) fn sys_balance(args: [u32; 6]) -> u32 {
     }
     addr
 }
+
+/// Pointer to the 16-byte little-endian u128 value forwarded to the current task,
+/// allocated in the caller's heap. Returns 0 (read as no value) on failure.
+pub(crate) fn sys_call_value(_args: [u32; 6]) -> u32 {
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    if current == KERNEL_TASK_SLOT {
+        log!("sys_call_value: kernel task not allowed");
+        return 0;
+    }
+    let (root_ppn, value) = match unsafe { TASKS.get_mut() }.get(current) {
+        Some(task) => (task.addr_space.root_ppn, task.call_value),
+        None => return 0,
+    };
+
+    let addr = sys_alloc([16, 8, 0, 0, 0, 0]);
+    if addr == 0 {
+        log!("sys_call_value: allocation failed");
+        return 0;
+    }
+    if !mmu::copy(root_ppn, addr, &value.to_le_bytes()) {
+        logf!("sys_call_value: failed to write to 0x%x", addr);
+        return 0;
+    }
+    addr
+}
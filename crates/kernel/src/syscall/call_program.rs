@@ -1,24 +1,68 @@
 use clibc::logf;
-use types::{ADDRESS_LEN, Address};
+use types::{ADDRESS_LEN, Address, Result};
 
-use crate::global::{CURRENT_TASK, MAX_INPUT_LEN, TASKS};
+use crate::global::{CURRENT_TASK, MAX_INPUT_LEN, TASKS, record_call};
 use crate::syscall::SyscallContext;
+use crate::syscall::panic::current_task_read_only;
 use crate::syscall::storage::{caller_address_matches, current_task_root_ppn, read_user_bytes};
-use crate::task::prep_program_task;
+use crate::task::{check_call_args, check_call_layout, discard_task, prep_program_task};
+use crate::trap::write_result_to_caller;
 use crate::user_program::with_program_image;
 
 const REG_COUNT: usize = 32;
 const REG_PC: usize = 32;
 
 pub(crate) fn sys_call_program(args: [u32; 6], ctx: &mut SyscallContext<'_>) -> u32 {
+    start_call(args, ctx, false)
+}
+
+/// `sys_call_program` with the callee limited to reads; see `reject_static_write`.
+pub(crate) fn sys_static_call(args: [u32; 6], ctx: &mut SyscallContext<'_>) -> u32 {
+    start_call(args, ctx, true)
+}
+
+/// Report `MAX_INPUT_LEN` so guests can size call input up front.
+pub(crate) fn sys_max_input_len(_args: [u32; 6]) -> u32 {
+    MAX_INPUT_LEN as u32
+}
+
+/// Hand the caller a failed `INPUT_TOO_LARGE_ERROR_CODE` result naming the actual and
+/// maximum lengths, rather than the bare 0 of a call that could not start.
+fn input_too_large(input_len: usize) -> u32 {
+    fail_call(&Result::input_too_large(
+        input_len as u32,
+        MAX_INPUT_LEN as u32,
+    ))
+}
+
+/// Hand the caller a failed `INVALID_CALL_ARGS_ERROR_CODE` result for argument `arg`
+/// (0 = to, 1 = from, 2 = input) at `ptr`.
+fn invalid_call_args(arg: u32, ptr: u32) -> u32 {
+    fail_call(&Result::invalid_call_args(arg, ptr))
+}
+
+/// Write `result` to the caller as the outcome of a call that never started.
+fn fail_call(result: &Result) -> u32 {
+    let caller_idx = unsafe { *CURRENT_TASK.get_mut() };
+    match unsafe { TASKS.get_mut() }.get_mut(caller_idx) {
+        Some(caller) => write_result_to_caller(caller, result).unwrap_or(0),
+        None => 0,
+    }
+}
+
+fn start_call(args: [u32; 6], ctx: &mut SyscallContext<'_>, read_only: bool) -> u32 {
     let to_ptr = args[0];
     let from_ptr = args[1];
     let input_ptr = args[2];
     let input_len = args[3] as usize;
 
     if input_len > MAX_INPUT_LEN {
-        logf!("sys_call_program: input too large");
-        return 0;
+        logf!(
+            "sys_call_program: input too large (%d > %d)",
+            input_len as u32,
+            MAX_INPUT_LEN as u32
+        );
+        return input_too_large(input_len);
     }
 
     let root_ppn = match current_task_root_ppn() {
@@ -28,15 +72,15 @@ pub(crate) fn sys_call_program(args: [u32; 6], ctx: &mut SyscallContext<'_>) ->
 
     let to_bytes = match read_user_bytes(root_ppn, to_ptr, ADDRESS_LEN) {
         Some(bytes) => bytes,
-        None => return 0,
+        None => return invalid_call_args(0, to_ptr),
     };
     let from_bytes = match read_user_bytes(root_ppn, from_ptr, ADDRESS_LEN) {
         Some(bytes) => bytes,
-        None => return 0,
+        None => return invalid_call_args(1, from_ptr),
     };
     let input = match read_user_bytes(root_ppn, input_ptr, input_len) {
         Some(bytes) => bytes,
-        None => return 0,
+        None => return invalid_call_args(2, input_ptr),
     };
 
     if to_bytes.len() != ADDRESS_LEN || from_bytes.len() != ADDRESS_LEN {
@@ -56,21 +100,36 @@ pub(crate) fn sys_call_program(args: [u32; 6], ctx: &mut SyscallContext<'_>) ->
         return 0;
     }
 
-    let task = match with_program_image(&to, |image| {
-        prep_program_task(&to, &from, image.code, &input, image.entry_off)
-    }) {
-        Some(task) => task,
-        None => return 0,
-    };
+    // Reject an argument layout the callee could not read before preparing its task.
+    if let Some((arg, ptr)) = check_call_layout(input.len()) {
+        return invalid_call_args(arg, ptr);
+    }
+
+    let mut task =
+        match with_program_image(&to, |image| prep_program_task(&to, &from, &image, &input)) {
+            Some(task) => task,
+            None => return 0,
+        };
+    // A static call stays read-only all the way down its call chain.
+    task.read_only = read_only || current_task_read_only();
+    // The callee reads a0..a3 as to/from/input/len; never start it on registers
+    // that do not cover the call-args page.
+    if let Some((arg, ptr)) = check_call_args(&task, input.len()) {
+        discard_task(task);
+        return invalid_call_args(arg, ptr);
+    }
 
     let task_idx = unsafe {
-        let tasks = TASKS.get_mut();
-        if !tasks.push(task) {
-            logf!("sys_call_program: task list full");
-            return 0;
+        match TASKS.get_mut().push(task) {
+            Ok(idx) => idx,
+            Err(task) => {
+                logf!("sys_call_program: task list full");
+                discard_task(task);
+                return 0;
+            }
         }
-        tasks.len().saturating_sub(1)
     };
+    record_call(to);
 
     let caller_idx = unsafe { *CURRENT_TASK.get_mut() };
     unsafe {
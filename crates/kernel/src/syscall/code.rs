@@ -0,0 +1,61 @@
+use clibc::{log, logf};
+use types::{ADDRESS_LEN, Address};
+
+use crate::global::{CURRENT_TASK, KERNEL_TASK_SLOT, STATE};
+use crate::memory::page_allocator as mmu;
+use crate::syscall::SyscallError;
+use crate::syscall::alloc::sys_alloc;
+use crate::syscall::storage::{current_task_root_ppn, read_user_bytes};
+
+/// Code length of the account at `args[0]`. A missing or non-contract account is
+/// `NotFound`, so an empty 0 is never ambiguous.
+pub(crate) fn sys_ext_code_size(args: [u32; 6]) -> Result<u32, SyscallError> {
+    let (_, address) = read_target(args[0], "sys_ext_code_size")?;
+    with_contract_code(&address, |code| code.len() as u32).ok_or(SyscallError::NotFound)
+}
+
+/// Pointer to a 32-byte `state::code_hash` of the account at `args[0]`, allocated in
+/// the caller's heap. A missing or non-contract account is `NotFound` (read as a zero
+/// hash by the clibc wrapper).
+pub(crate) fn sys_ext_code_hash(args: [u32; 6]) -> Result<u32, SyscallError> {
+    let (root_ppn, address) = read_target(args[0], "sys_ext_code_hash")?;
+    let hash = with_contract_code(&address, state::code_hash).ok_or(SyscallError::NotFound)?;
+
+    let addr = sys_alloc([hash.len() as u32, 8, 0, 0, 0, 0]);
+    if addr == 0 {
+        log!("sys_ext_code_hash: allocation failed");
+        return Err(SyscallError::OutOfMemory);
+    }
+    if !mmu::copy(root_ppn, addr, &hash) {
+        logf!("sys_ext_code_hash: failed to write to 0x%x", addr);
+        return Err(SyscallError::OutOfMemory);
+    }
+    Ok(addr)
+}
+
+/// Reads the queried address from the calling task.
+fn read_target(addr_ptr: u32, name: &str) -> Result<(u32, Address), SyscallError> {
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    if current == KERNEL_TASK_SLOT {
+        logf!("%s: kernel task not allowed", display: name);
+        return Err(SyscallError::AccessDenied);
+    }
+    let root_ppn = current_task_root_ppn().ok_or(SyscallError::AccessDenied)?;
+    let bytes =
+        read_user_bytes(root_ppn, addr_ptr, ADDRESS_LEN).ok_or(SyscallError::InvalidArgument)?;
+    if bytes.len() != ADDRESS_LEN {
+        logf!("%s: invalid address length", display: name);
+        return Err(SyscallError::InvalidArgument);
+    }
+    let mut addr_buf = [0u8; ADDRESS_LEN];
+    addr_buf.copy_from_slice(&bytes);
+    Ok((root_ppn, Address(addr_buf)))
+}
+
+fn with_contract_code<R>(address: &Address, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+    unsafe { STATE.get_mut() }
+        .as_ref()
+        .and_then(|state| state.get_account(address))
+        .filter(|account| account.is_contract)
+        .map(|account| f(&account.code))
+}
@@ -1,7 +1,8 @@
 use clibc::logf;
+use types::LogEntry;
 
 use crate::global::{CURRENT_TX, RECEIPTS};
-use crate::syscall::storage::{current_task_root_ppn, read_user_bytes};
+use crate::syscall::storage::{current_program_address, current_task_root_ppn, read_user_bytes};
 
 pub(crate) fn sys_fire_event(args: [u32; 6]) -> u32 {
     let ptr = args[0];
@@ -12,10 +13,17 @@ pub(crate) fn sys_fire_event(args: [u32; 6]) -> u32 {
         None => return 0,
     };
 
-    let event_bytes = match read_user_bytes(root_ppn, ptr, len) {
+    let data = match read_user_bytes(root_ppn, ptr, len) {
         Some(bytes) => bytes,
         None => return 0,
     };
+    let emitter = match current_program_address(root_ppn) {
+        Some(addr) => addr,
+        None => {
+            logf!("sys_fire_event: unable to resolve emitter address");
+            return 0;
+        }
+    };
 
     let current_idx = unsafe { *CURRENT_TX.get_mut() };
     let receipts = unsafe { RECEIPTS.get_mut() };
@@ -24,7 +32,7 @@ pub(crate) fn sys_fire_event(args: [u32; 6]) -> u32 {
         .and_then(|receipts| receipts.get_mut(current_idx))
     {
         Some(receipt) => {
-            receipt.add_event(event_bytes);
+            receipt.add_log(LogEntry { emitter, data });
         }
         None => {
             logf!(
@@ -2,24 +2,35 @@
 //! are now dispatched from the kernel trap handler. Implementations will
 //! land here; for now they panic to make missing pieces explicit.
 use clibc::syscalls::{
-    SYSCALL_ALLOC, SYSCALL_BALANCE, SYSCALL_BRK, SYSCALL_CALL_PROGRAM, SYSCALL_DEALLOC,
-    SYSCALL_FIRE_EVENT, SYSCALL_PANIC, SYSCALL_STORAGE_GET, SYSCALL_STORAGE_SET, SYSCALL_TRANSFER,
+    SYSCALL_ALLOC, SYSCALL_BALANCE, SYSCALL_BRK, SYSCALL_CALL_PROGRAM, SYSCALL_CALL_VALUE,
+    SYSCALL_DEALLOC, SYSCALL_ERR_ACCESS_DENIED, SYSCALL_ERR_INVALID_ARGUMENT,
+    SYSCALL_ERR_NOT_FOUND, SYSCALL_ERR_OUT_OF_MEMORY, SYSCALL_ERR_UNSUPPORTED,
+    SYSCALL_EXT_CODE_HASH, SYSCALL_EXT_CODE_SIZE, SYSCALL_FIRE_EVENT, SYSCALL_MAX_INPUT_LEN,
+    SYSCALL_OK, SYSCALL_PANIC, SYSCALL_RANDOM, SYSCALL_STATIC_CALL, SYSCALL_STORAGE_BYTES,
+    SYSCALL_STORAGE_DELETE, SYSCALL_STORAGE_GET, SYSCALL_STORAGE_SET, SYSCALL_TRANSFER,
+    SYSCALL_TRANSFER_MANY,
 };
 use clibc::{log, logf};
 
 pub mod alloc;
 pub mod balance;
 pub mod call_program;
+pub mod code;
 pub mod fire_event;
 pub mod panic;
+pub mod random;
 pub mod storage;
 
 use alloc::{sys_alloc, sys_dealloc};
-use balance::{sys_balance, sys_transfer};
-use call_program::sys_call_program;
+use balance::{sys_balance, sys_call_value, sys_transfer, sys_transfer_many};
+use call_program::{sys_call_program, sys_max_input_len, sys_static_call};
+use code::{sys_ext_code_hash, sys_ext_code_size};
 use fire_event::sys_fire_event;
 use panic::sys_panic;
-use storage::{sys_storage_get, sys_storage_set};
+use random::sys_random;
+use storage::{
+    sys_storage_bytes, sys_storage_delete, sys_storage_get, sys_storage_set, sys_storage_set_batch,
+};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CallerMode {
@@ -32,26 +43,98 @@ pub struct SyscallContext<'a> {
     pub caller_mode: CallerMode,
 }
 
+/// Why a syscall failed. The trap handler returns the code in `a1` and 0 in `a0`; see
+/// the `SYSCALL_ERR_*` constants for what each means to a guest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SyscallError {
+    NotFound,
+    AccessDenied,
+    InvalidArgument,
+    OutOfMemory,
+    Unsupported,
+}
+
+impl SyscallError {
+    pub fn code(self) -> u32 {
+        match self {
+            SyscallError::NotFound => SYSCALL_ERR_NOT_FOUND,
+            SyscallError::AccessDenied => SYSCALL_ERR_ACCESS_DENIED,
+            SyscallError::InvalidArgument => SYSCALL_ERR_INVALID_ARGUMENT,
+            SyscallError::OutOfMemory => SYSCALL_ERR_OUT_OF_MEMORY,
+            SyscallError::Unsupported => SYSCALL_ERR_UNSUPPORTED,
+        }
+    }
+}
+
+/// What a syscall hands back to its caller: `value` goes to `a0` and `error` to `a1`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SyscallReturn {
+    pub value: u32,
+    pub error: u32,
+}
+
+impl From<u32> for SyscallReturn {
+    fn from(value: u32) -> Self {
+        Self {
+            value,
+            error: SYSCALL_OK,
+        }
+    }
+}
+
+impl From<Result<u32, SyscallError>> for SyscallReturn {
+    fn from(result: Result<u32, SyscallError>) -> Self {
+        match result {
+            Ok(value) => value.into(),
+            Err(err) => Self {
+                value: 0,
+                error: err.code(),
+            },
+        }
+    }
+}
+
 pub trait SyscallHandler: core::fmt::Debug {
-    fn handle_syscall(&mut self, call_id: u32, args: [u32; 6], ctx: &mut SyscallContext<'_>)
-    -> u32;
+    fn handle_syscall(
+        &mut self,
+        call_id: u32,
+        args: [u32; 6],
+        ctx: &mut SyscallContext<'_>,
+    ) -> SyscallReturn;
 }
 
-pub fn dispatch_syscall(call_id: u32, args: [u32; 6], ctx: &mut SyscallContext<'_>) -> u32 {
+/// Runs syscall `call_id`. Syscalls that can tell a failure from a 0 value (the state
+/// reads and batched storage writes) report it through `SyscallReturn::error`; the rest
+/// always report `SYSCALL_OK`.
+pub fn dispatch_syscall(
+    call_id: u32,
+    args: [u32; 6],
+    ctx: &mut SyscallContext<'_>,
+) -> SyscallReturn {
     match call_id {
-        SYSCALL_STORAGE_GET => sys_storage_get(args),
-        SYSCALL_STORAGE_SET => sys_storage_set(args),
-        SYSCALL_PANIC => sys_panic(args),
-        SYSCALL_CALL_PROGRAM => sys_call_program(args, ctx),
-        SYSCALL_FIRE_EVENT => sys_fire_event(args),
-        SYSCALL_ALLOC => sys_alloc(args),
-        SYSCALL_DEALLOC => sys_dealloc(args),
-        SYSCALL_TRANSFER => sys_transfer(args),
-        SYSCALL_BALANCE => sys_balance(args),
-        SYSCALL_BRK => sys_brk(args),
+        SYSCALL_STORAGE_GET => sys_storage_get(args).into(),
+        SYSCALL_STORAGE_SET => sys_storage_set(args).into(),
+        SYSCALL_STORAGE_SET_BATCH => sys_storage_set_batch(args).into(),
+        SYSCALL_STORAGE_BYTES => sys_storage_bytes(args).into(),
+        SYSCALL_STORAGE_DELETE => sys_storage_delete(args).into(),
+        SYSCALL_PANIC => sys_panic(args).into(),
+        SYSCALL_CALL_PROGRAM => sys_call_program(args, ctx).into(),
+        SYSCALL_STATIC_CALL => sys_static_call(args, ctx).into(),
+        SYSCALL_MAX_INPUT_LEN => sys_max_input_len(args).into(),
+        SYSCALL_FIRE_EVENT => sys_fire_event(args).into(),
+        SYSCALL_ALLOC => sys_alloc(args).into(),
+        SYSCALL_DEALLOC => sys_dealloc(args).into(),
+        SYSCALL_TRANSFER => sys_transfer(args).into(),
+        SYSCALL_TRANSFER_MANY => sys_transfer_many(args).into(),
+        SYSCALL_BALANCE => sys_balance(args).into(),
+        SYSCALL_CALL_VALUE => sys_call_value(args).into(),
+        SYSCALL_EXT_CODE_SIZE => sys_ext_code_size(args).into(),
+        SYSCALL_EXT_CODE_HASH => sys_ext_code_hash(args).into(),
+        SYSCALL_RANDOM => sys_random(args).into(),
+        SYSCALL_BRK => sys_brk(args).into(),
         _ => {
             logf!("unknown syscall id %d", call_id);
-            0
+            Err(SyscallError::Unsupported).into()
         }
     }
 }
@@ -1,16 +1,26 @@
 use clibc::{log, logf};
-use types::{SV32_DIRECT_MAP_BASE, SV32_PAGE_SIZE};
+use types::SV32_PAGE_SIZE;
+use types::result::{
+    PANIC_ERROR_CODE, RESULT_DATA_SIZE, Result as VmResult, STATIC_WRITE_ERROR_CODE,
+};
 
-use crate::global::{CURRENT_TASK, TASKS};
+use crate::global::{
+    CURRENT_TASK, CURRENT_TX, KERNEL_TASK_SLOT, PENDING_CALL_FAILURE, PENDING_PANIC, RECEIPTS,
+    STATE, TASKS,
+};
 use crate::memory::page_allocator as mmu;
 
+/// Abort the running transaction with the guest's panic message.
+///
+/// State changes and logs from the transaction are rolled back, and a failure result
+/// (`PANIC_ERROR_CODE` plus the message bytes) is left in `PENDING_PANIC` for the trap
+/// handler, which unwinds back to the kernel task so the bundle can continue.
 pub(crate) fn sys_panic_with_message(msg_ptr: u32, msg_len: u32) -> u32 {
-    if msg_ptr == 0 || msg_len == 0 {
-        log!("sys_panic: empty message");
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    if current == KERNEL_TASK_SLOT {
+        log!("sys_panic: kernel task panicked");
         halt();
     }
-
-    let current = unsafe { *CURRENT_TASK.get_mut() };
     let tasks = unsafe { TASKS.get_mut() };
     let task = match tasks.get(current) {
         Some(task) => task,
@@ -21,8 +31,12 @@ pub(crate) fn sys_panic_with_message(msg_ptr: u32, msg_len: u32) -> u32 {
     };
     let root_ppn = task.addr_space.root_ppn;
 
-    let mut buf = [0u8; 256];
-    let mut remaining = core::cmp::min(msg_len as usize, buf.len());
+    let mut buf = [0u8; RESULT_DATA_SIZE];
+    let mut remaining = if msg_ptr == 0 {
+        0
+    } else {
+        core::cmp::min(msg_len as usize, buf.len())
+    };
     let mut dst_off = 0usize;
     let mut va = msg_ptr;
     while remaining > 0 {
@@ -30,12 +44,21 @@ pub(crate) fn sys_panic_with_message(msg_ptr: u32, msg_len: u32) -> u32 {
             Some(p) => p,
             None => {
                 logf!("sys_panic: invalid msg ptr 0x%x", va);
-                halt();
+                break;
             }
         };
         let page_off = (va as usize) & (SV32_PAGE_SIZE - 1);
         let to_copy = core::cmp::min(remaining, SV32_PAGE_SIZE - page_off);
-        let src = SV32_DIRECT_MAP_BASE as usize + phys;
+        let src = match mmu::direct_map_range(phys, to_copy) {
+            Some(src) => src,
+            None => {
+                logf!(
+                    "sys_panic: phys 0x%x is outside the direct map",
+                    phys as u32
+                );
+                break;
+            }
+        };
         unsafe {
             core::ptr::copy_nonoverlapping(
                 src as *const u8,
@@ -54,7 +77,53 @@ pub(crate) fn sys_panic_with_message(msg_ptr: u32, msg_len: u32) -> u32 {
     } else {
         log!("guest panic");
     }
-    halt();
+    rollback_transaction();
+    unsafe {
+        *PENDING_PANIC.get_mut() = Some(VmResult::new_with_data(false, PANIC_ERROR_CODE, msg));
+    }
+    0
+}
+
+/// Whether the current task runs under a static call.
+pub(crate) fn current_task_read_only() -> bool {
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    unsafe { TASKS.get_mut() }
+        .get(current)
+        .is_some_and(|task| task.read_only)
+}
+
+/// Called by state-writing syscalls before they act. Under a static call this ends the
+/// running task with a `STATIC_WRITE_ERROR_CODE` result, left in `PENDING_CALL_FAILURE`
+/// for the trap handler to hand to its caller, and returns true so the syscall skips the
+/// write. A read-only task has written nothing, so there is nothing to roll back.
+pub(crate) fn reject_static_write(name: &str) -> bool {
+    if !current_task_read_only() {
+        return false;
+    }
+    logf!("%s: state write inside a static call", display: name);
+    unsafe {
+        *PENDING_CALL_FAILURE.get_mut() = Some(VmResult::new(false, STATIC_WRITE_ERROR_CODE));
+    }
+    true
+}
+
+/// Undo the state writes and drop the logs of the transaction being executed.
+pub(crate) fn rollback_transaction() {
+    unsafe {
+        if let Some(state) = STATE.get_mut().as_mut()
+            && !state.revert()
+        {
+            log!("sys_panic: no state checkpoint to revert");
+        }
+        let tx_idx = *CURRENT_TX.get_mut();
+        if let Some(receipt) = RECEIPTS
+            .get_mut()
+            .as_mut()
+            .and_then(|receipts| receipts.get_mut(tx_idx))
+        {
+            receipt.logs.clear();
+        }
+    }
 }
 
 pub(crate) fn sys_panic(args: [u32; 6]) -> u32 {
@@ -0,0 +1,48 @@
+use clibc::{log, logf};
+use state::Entropy;
+
+use crate::global::{CURRENT_TASK, CURRENT_TX, ENTROPY, ENTROPY_SEED, KERNEL_TASK_SLOT};
+use crate::memory::page_allocator as mmu;
+use crate::syscall::storage::current_task_root_ppn;
+
+/// Bytes generated per copy into the caller's buffer.
+const CHUNK_LEN: usize = 64;
+
+/// Fills `args[1]` bytes at `args[0]` with the current transaction's pseudo-entropy
+/// stream (see `state::entropy`). Deterministic per bundle, not secure.
+/// Returns the number of bytes written, or 0 if the buffer is not user-writable.
+pub(crate) fn sys_random(args: [u32; 6]) -> u32 {
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    if current == KERNEL_TASK_SLOT {
+        log!("sys_random: kernel task not allowed");
+        return 0;
+    }
+    let root_ppn = match current_task_root_ppn() {
+        Some(root) => root,
+        None => return 0,
+    };
+    let (buf_ptr, len) = (args[0], args[1] as usize);
+
+    let tx_idx = unsafe { *CURRENT_TX.get_mut() };
+    let slot = unsafe { ENTROPY.get_mut() };
+    if !matches!(slot, Some((idx, _)) if *idx == tx_idx) {
+        let seed = unsafe { ENTROPY_SEED.get_mut() };
+        *slot = Some((tx_idx, Entropy::new(seed, tx_idx as u32)));
+    }
+    let Some((_, stream)) = slot else {
+        return 0;
+    };
+
+    let mut chunk = [0u8; CHUNK_LEN];
+    let mut written = 0usize;
+    while written < len {
+        let n = (len - written).min(CHUNK_LEN);
+        stream.fill(&mut chunk[..n]);
+        if !mmu::copy_user(root_ppn, buf_ptr.wrapping_add(written as u32), &chunk[..n]) {
+            logf!("sys_random: failed to write to 0x%x", buf_ptr);
+            return 0;
+        }
+        written += n;
+    }
+    written as u32
+}
@@ -3,89 +3,43 @@ extern crate alloc;
 use alloc::{format, string::String, vec, vec::Vec};
 use core::cmp;
 
+use clibc::storage_map::STORAGE_BATCH_ENTRY_HEADER_LEN;
 use clibc::{log, logf};
-use types::{ADDRESS_LEN, Address, SV32_DIRECT_MAP_BASE, SV32_PAGE_SIZE};
+use types::{ADDRESS_LEN, Address, SV32_PAGE_SIZE};
 
 use crate::global::TO_PTR_ADDR;
 use crate::global::{CURRENT_TASK, KERNEL_TASK_SLOT, STATE, TASKS};
 use crate::memory::page_allocator as mmu;
+use crate::syscall::SyscallError;
 use crate::syscall::alloc::sys_alloc;
+use crate::syscall::panic::reject_static_write;
 use state::State;
 
-pub(crate) fn sys_storage_get(args: [u32; 6]) -> u32 {
-    let address_ptr = args[0];
-    let domain_ptr = args[1];
-    let key_ptr = args[2];
-    let lens_packed = args[3] as usize;
-    let domain_len = lens_packed & 0xffff;
-    let key_len = lens_packed >> 16;
-
-    let root_ppn = match current_task_root_ppn() {
-        Some(root) => root,
-        None => return 0,
-    };
-
-    let address_bytes = match read_user_bytes(root_ppn, address_ptr, ADDRESS_LEN) {
-        Some(bytes) => bytes,
-        None => return 0,
-    };
-    let mut addr_buf = [0u8; ADDRESS_LEN];
-    if address_bytes.len() != ADDRESS_LEN {
-        log!("sys_storage_get: invalid address length");
-        return 0;
-    }
-    addr_buf.copy_from_slice(&address_bytes);
-    let address = Address(addr_buf);
-    if !caller_address_matches(root_ppn, &address) {
-        log!("sys_storage_get: address mismatch with caller");
-        return 0;
-    }
-
-    let domain_bytes = match read_user_bytes(root_ppn, domain_ptr, domain_len) {
-        Some(bytes) => bytes,
-        None => return 0,
-    };
-    let domain = match core::str::from_utf8(&domain_bytes) {
-        Ok(s) => s,
-        Err(_) => {
-            log!("sys_storage_get: invalid domain utf8");
-            return 0;
-        }
-    };
-
-    let key_bytes = match read_user_bytes(root_ppn, key_ptr, key_len) {
-        Some(bytes) => bytes,
-        None => return 0,
-    };
-    let key_hex = hex_encode(&key_bytes);
-    let composite_key = format!("{}:{}", domain, key_hex);
+/// Pointer to the `[len: u32][value]` stored under the composite key, allocated in the
+/// caller's heap. A missing key or account is `NotFound`; another program's account is
+/// `AccessDenied`.
+pub(crate) fn sys_storage_get(args: [u32; 6]) -> Result<u32, SyscallError> {
+    let root_ppn = current_task_root_ppn().ok_or(SyscallError::AccessDenied)?;
+    let (address, composite_key) = read_storage_key(root_ppn, args, "sys_storage_get")?;
 
     let value = unsafe { STATE.get_mut() }
         .as_ref()
         .and_then(|state| state.get_account(&address))
-        .and_then(|account| account.storage.get(&composite_key).cloned());
-
-    let value = match value {
-        Some(value) => value,
-        None => return 0,
-    };
+        .and_then(|account| account.storage.get(&composite_key).cloned())
+        .ok_or(SyscallError::NotFound)?;
 
     let total_len = match value.len().checked_add(4) {
-        Some(len) => len,
-        None => {
-            log!("sys_storage_get: value too large");
-            return 0;
+        Some(len) if len <= u32::MAX as usize => len,
+        _ => {
+            log!("sys_storage_get: value exceeds u32 size");
+            return Err(SyscallError::OutOfMemory);
         }
     };
-    if total_len > u32::MAX as usize {
-        log!("sys_storage_get: value exceeds u32 size");
-        return 0;
-    }
 
     let addr = sys_alloc([total_len as u32, 8, 0, 0, 0, 0]);
     if addr == 0 {
         log!("sys_storage_get: allocation failed");
-        return 0;
+        return Err(SyscallError::OutOfMemory);
     }
 
     let mut buf = Vec::with_capacity(total_len);
@@ -94,74 +48,209 @@ pub(crate) fn sys_storage_get(args: [u32; 6]) -> u32 {
 
     if !mmu::copy(root_ppn, addr, &buf) {
         logf!("sys_storage_get: failed to write to 0x%x", addr);
-        return 0;
+        return Err(SyscallError::OutOfMemory);
     }
 
-    addr
+    Ok(addr)
 }
 
 pub(crate) fn sys_storage_set(args: [u32; 6]) -> u32 {
-    let address_ptr = args[0];
-    let domain_ptr = args[1];
-    let key_ptr = args[2];
-    let lens_packed = args[3] as usize;
     let val_ptr = args[4];
     let val_len = args[5] as usize;
-
-    let domain_len = lens_packed & 0xffff;
-    let key_len = lens_packed >> 16;
+    if reject_static_write("sys_storage_set") {
+        return 0;
+    }
 
     let root_ppn = match current_task_root_ppn() {
         Some(root) => root,
         None => return 0,
     };
+    let (address, composite_key) = match read_storage_key(root_ppn, args, "sys_storage_set") {
+        Ok(target) => target,
+        Err(_) => return 0,
+    };
 
-    let address_bytes = match read_user_bytes(root_ppn, address_ptr, ADDRESS_LEN) {
+    let value = match read_user_bytes(root_ppn, val_ptr, val_len) {
         Some(bytes) => bytes,
         None => return 0,
     };
-    if address_bytes.len() != ADDRESS_LEN {
-        log!("sys_storage_set: invalid address length");
+
+    let state = unsafe { STATE.get_mut().get_or_insert_with(State::new) };
+    state
+        .get_account_mut(&address)
+        .storage
+        .insert(composite_key, value);
+    0
+}
+
+/// Removes the value stored under the composite key, so it no longer counts towards
+/// the account's storage. Returns 1 if a value was removed, 0 otherwise.
+pub(crate) fn sys_storage_delete(args: [u32; 6]) -> u32 {
+    if reject_static_write("sys_storage_delete") {
         return 0;
     }
-    let mut addr_buf = [0u8; ADDRESS_LEN];
-    addr_buf.copy_from_slice(&address_bytes);
-    let address = Address(addr_buf);
-    if !caller_address_matches(root_ppn, &address) {
-        log!("sys_storage_set: address mismatch with caller");
+    let root_ppn = match current_task_root_ppn() {
+        Some(root) => root,
+        None => return 0,
+    };
+    let (address, composite_key) = match read_storage_key(root_ppn, args, "sys_storage_delete") {
+        Ok(target) => target,
+        Err(_) => return 0,
+    };
+
+    let state = unsafe { STATE.get_mut().get_or_insert_with(State::new) };
+    // Only touch accounts that hold the key so a miss neither creates an account
+    // nor records a pre-image.
+    let present = state
+        .get_account(&address)
+        .is_some_and(|account| account.storage.contains_key(&composite_key));
+    if !present {
         return 0;
     }
+    state
+        .get_account_mut(&address)
+        .storage
+        .remove(&composite_key);
+    1
+}
 
-    let domain_bytes = match read_user_bytes(root_ppn, domain_ptr, domain_len) {
-        Some(bytes) => bytes,
-        None => return 0,
-    };
+/// Reads the storage syscall target shared by get/set/delete: the account at
+/// `args[0]` (which must be the calling program) and the `domain:hex(key)`
+/// composite key from `args[1]`, `args[2]` and the packed lengths in `args[3]`.
+fn read_storage_key(
+    root_ppn: u32,
+    args: [u32; 6],
+    name: &str,
+) -> Result<(Address, String), SyscallError> {
+    let address_ptr = args[0];
+    let domain_ptr = args[1];
+    let key_ptr = args[2];
+    let lens_packed = args[3] as usize;
+    let domain_len = lens_packed & 0xffff;
+    let key_len = lens_packed >> 16;
+
+    let address = read_caller_address(root_ppn, address_ptr, name)?;
+
+    let domain_bytes =
+        read_user_bytes(root_ppn, domain_ptr, domain_len).ok_or(SyscallError::InvalidArgument)?;
     let domain = match core::str::from_utf8(&domain_bytes) {
         Ok(s) => s,
         Err(_) => {
-            log!("sys_storage_set: invalid domain utf8");
-            return 0;
+            logf!("%s: invalid domain utf8", display: name);
+            return Err(SyscallError::InvalidArgument);
         }
     };
 
-    let key_bytes = match read_user_bytes(root_ppn, key_ptr, key_len) {
-        Some(bytes) => bytes,
-        None => return 0,
-    };
+    let key_bytes =
+        read_user_bytes(root_ppn, key_ptr, key_len).ok_or(SyscallError::InvalidArgument)?;
     let key_hex = hex_encode(&key_bytes);
+    Ok((address, format!("{}:{}", domain, key_hex)))
+}
 
-    let value = match read_user_bytes(root_ppn, val_ptr, val_len) {
-        Some(bytes) => bytes,
-        None => return 0,
-    };
+/// Reads the account address at `ptr`, which must be the calling program's own.
+fn read_caller_address(root_ppn: u32, ptr: u32, name: &str) -> Result<Address, SyscallError> {
+    let address_bytes =
+        read_user_bytes(root_ppn, ptr, ADDRESS_LEN).ok_or(SyscallError::InvalidArgument)?;
+    if address_bytes.len() != ADDRESS_LEN {
+        logf!("%s: invalid address length", display: name);
+        return Err(SyscallError::InvalidArgument);
+    }
+    let mut addr_buf = [0u8; ADDRESS_LEN];
+    addr_buf.copy_from_slice(&address_bytes);
+    let address = Address(addr_buf);
+    if !caller_address_matches(root_ppn, &address) {
+        logf!("%s: address mismatch with caller", display: name);
+        return Err(SyscallError::AccessDenied);
+    }
+    Ok(address)
+}
+
+/// Largest `StorageBatch` the kernel copies in from a guest.
+const MAX_STORAGE_BATCH_BYTES: usize = 64 * 1024;
+
+/// Applies a `StorageBatch` of writes to the account at `args[0]` (which must be the
+/// calling program). `args[1]` points at `args[2]` bytes holding `args[3]` entries of
+/// `[domain_len: u16][key_len: u16][value_len: u32][domain][key][value]`.
+///
+/// The whole batch is decoded before anything is written, so a malformed entry leaves
+/// storage untouched. Returns the number of entries written.
+pub(crate) fn sys_storage_set_batch(args: [u32; 6]) -> Result<u32, SyscallError> {
+    let entries_ptr = args[1];
+    let entries_len = args[2] as usize;
+    let count = args[3] as usize;
+    if reject_static_write("sys_storage_set_batch") {
+        return Err(SyscallError::AccessDenied);
+    }
+    if entries_len > MAX_STORAGE_BATCH_BYTES {
+        logf!(
+            "sys_storage_set_batch: batch too large (%d bytes)",
+            entries_len as u32
+        );
+        return Err(SyscallError::InvalidArgument);
+    }
+
+    let root_ppn = current_task_root_ppn().ok_or(SyscallError::AccessDenied)?;
+    let address = read_caller_address(root_ppn, args[0], "sys_storage_set_batch")?;
+    let entries =
+        read_user_bytes(root_ppn, entries_ptr, entries_len).ok_or(SyscallError::InvalidArgument)?;
+    let writes = decode_storage_batch(&entries, count).ok_or_else(|| {
+        log!("sys_storage_set_batch: malformed batch");
+        SyscallError::InvalidArgument
+    })?;
 
-    let composite_key = format!("{}:{}", domain, key_hex);
     let state = unsafe { STATE.get_mut().get_or_insert_with(State::new) };
-    state
-        .get_account_mut(&address)
-        .storage
-        .insert(composite_key, value);
-    0
+    let storage = &mut state.get_account_mut(&address).storage;
+    for (key, value) in writes {
+        storage.insert(key, value);
+    }
+    Ok(count as u32)
+}
+
+/// Decodes exactly `count` batch entries spanning all of `bytes` into
+/// `(composite key, value)` writes.
+fn decode_storage_batch(bytes: &[u8], count: usize) -> Option<Vec<(String, Vec<u8>)>> {
+    let mut writes = Vec::new();
+    let mut rest = bytes;
+    for _ in 0..count {
+        let header = rest.get(..STORAGE_BATCH_ENTRY_HEADER_LEN)?;
+        let domain_len = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let key_len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let value_len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        rest = &rest[STORAGE_BATCH_ENTRY_HEADER_LEN..];
+
+        let key_end = domain_len.checked_add(key_len)?;
+        let value_end = key_end.checked_add(value_len)?;
+        let entry = rest.get(..value_end)?;
+        let domain = core::str::from_utf8(&entry[..domain_len]).ok()?;
+        let key_hex = hex_encode(&entry[domain_len..key_end]);
+        writes.push((format!("{}:{}", domain, key_hex), entry[key_end..].to_vec()));
+        rest = &rest[value_end..];
+    }
+    rest.is_empty().then_some(writes)
+}
+
+/// Storage footprint (key + value bytes) of the calling program's own account,
+/// saturated to `u32::MAX`. Returns 0 for the kernel task or an account without storage.
+pub(crate) fn sys_storage_bytes(_args: [u32; 6]) -> u32 {
+    let current = unsafe { *CURRENT_TASK.get_mut() };
+    if current == KERNEL_TASK_SLOT {
+        log!("sys_storage_bytes: kernel task not allowed");
+        return 0;
+    }
+    let root_ppn = match current_task_root_ppn() {
+        Some(root) => root,
+        None => return 0,
+    };
+    let address = match current_program_address(root_ppn) {
+        Some(address) => address,
+        None => return 0,
+    };
+    let bytes = unsafe { STATE.get_mut() }
+        .as_ref()
+        .and_then(|state| state.get_account(&address))
+        .map(|account| account.storage_bytes())
+        .unwrap_or(0);
+    u32::try_from(bytes).unwrap_or(u32::MAX)
 }
 
 pub(crate) fn current_task_root_ppn() -> Option<u32> {
@@ -194,7 +283,16 @@ pub(crate) fn read_user_bytes(root_ppn: u32, ptr: u32, len: usize) -> Option<Vec
         };
         let page_off = (va as usize) & (SV32_PAGE_SIZE - 1);
         let to_copy = cmp::min(remaining, SV32_PAGE_SIZE - page_off);
-        let src = SV32_DIRECT_MAP_BASE as usize + phys;
+        let src = match mmu::direct_map_range(phys, to_copy) {
+            Some(src) => src,
+            None => {
+                logf!(
+                    "sys_storage: phys 0x%x is outside the direct map",
+                    phys as u32
+                );
+                return None;
+            }
+        };
         unsafe {
             core::ptr::copy_nonoverlapping(
                 src as *const u8,
@@ -214,16 +312,18 @@ pub(crate) fn caller_address_matches(root_ppn: u32, address: &Address) -> bool {
     if current == KERNEL_TASK_SLOT {
         return true;
     }
-    let caller_bytes = match read_user_bytes(root_ppn, TO_PTR_ADDR, ADDRESS_LEN) {
-        Some(bytes) => bytes,
-        None => return false,
-    };
-    if caller_bytes.len() != ADDRESS_LEN {
-        return false;
+    current_program_address(root_ppn).is_some_and(|current| current == *address)
+}
+
+/// Address of the program running in the current task, read from its call-args page.
+pub(crate) fn current_program_address(root_ppn: u32) -> Option<Address> {
+    let bytes = read_user_bytes(root_ppn, TO_PTR_ADDR, ADDRESS_LEN)?;
+    if bytes.len() != ADDRESS_LEN {
+        return None;
     }
-    let mut caller_buf = [0u8; ADDRESS_LEN];
-    caller_buf.copy_from_slice(&caller_bytes);
-    Address(caller_buf) == *address
+    let mut buf = [0u8; ADDRESS_LEN];
+    buf.copy_from_slice(&bytes);
+    Some(Address(buf))
 }
 
 fn hex_encode(bytes: &[u8]) -> String {
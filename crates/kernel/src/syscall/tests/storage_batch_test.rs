@@ -0,0 +1,165 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Storage batch tests: one SYSCALL_STORAGE_SET_BATCH writes every entry, and a malformed
+// batch writes none of them.
+use alloc::format;
+use alloc::vec::Vec;
+use clibc::log;
+use clibc::syscalls::{SYSCALL_ERR_INVALID_ARGUMENT, SYSCALL_OK, SYSCALL_STORAGE_SET_BATCH};
+use kernel::BootInfo;
+use kernel::global::STATE;
+use kernel::syscall::{CallerMode, SyscallContext, SyscallReturn, dispatch_syscall};
+use types::Address;
+
+const DOMAIN: &[u8] = b"Batch";
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel storage batch test boot");
+    let _info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_batch_writes_every_key() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_malformed_batch_writes_nothing() {
+        fail::fail(code);
+    }
+
+    log!("kernel storage batch test done");
+    utils::pass();
+}
+
+/// A `StorageBatch` encoded in a kernel heap buffer, as a guest would pass it.
+struct Batch {
+    address: Vec<u8>,
+    entries: Vec<u8>,
+    count: u32,
+}
+
+impl Batch {
+    fn new(address: Address) -> Self {
+        Self {
+            address: address.0.to_vec(),
+            entries: Vec::new(),
+            count: 0,
+        }
+    }
+
+    fn set(mut self, key: &[u8], value: &[u8]) -> Self {
+        self.entries
+            .extend_from_slice(&(DOMAIN.len() as u16).to_le_bytes());
+        self.entries
+            .extend_from_slice(&(key.len() as u16).to_le_bytes());
+        self.entries
+            .extend_from_slice(&(value.len() as u32).to_le_bytes());
+        self.entries.extend_from_slice(DOMAIN);
+        self.entries.extend_from_slice(key);
+        self.entries.extend_from_slice(value);
+        self.count += 1;
+        self
+    }
+
+    fn flush(&self, count: u32) -> SyscallReturn {
+        let args = [
+            self.address.as_ptr() as u32,
+            self.entries.as_ptr() as u32,
+            self.entries.len() as u32,
+            count,
+            0,
+            0,
+        ];
+        let mut regs = [0u32; 32];
+        let mut ctx = SyscallContext {
+            regs: &mut regs,
+            caller_mode: CallerMode::Supervisor,
+        };
+        dispatch_syscall(SYSCALL_STORAGE_SET_BATCH, args, &mut ctx)
+    }
+}
+
+/// Value stored under `Batch:<hex(key)>` for `address`, if any.
+fn stored(address: &Address, key_hex: &str) -> Option<Vec<u8>> {
+    let composite = format!("Batch:{}", key_hex);
+    unsafe { STATE.get_mut() }
+        .as_ref()
+        .and_then(|state| state.get_account(address))
+        .and_then(|account| account.storage.get(&composite).cloned())
+}
+
+fn test_batch_writes_every_key() -> Result<(), u32> {
+    // Description: three keys in one batch are all persisted by a single syscall.
+    log!("test: one batch writes every key");
+    let address = Address([0x11; 20]);
+    let batch = Batch::new(address)
+        .set(b"a", &[1])
+        .set(b"b", &[2, 2])
+        .set(b"c", &[3, 3, 3]);
+
+    log!("subtest: the batch reports every entry written");
+    let ret = batch.flush(batch.count);
+    if ret.value != 3 || ret.error != SYSCALL_OK {
+        return Err(1);
+    }
+
+    log!("subtest: all three keys are persisted");
+    if stored(&address, "61").as_deref() != Some(&[1][..])
+        || stored(&address, "62").as_deref() != Some(&[2, 2][..])
+        || stored(&address, "63").as_deref() != Some(&[3, 3, 3][..])
+    {
+        return Err(2);
+    }
+
+    log!("subtest: a later entry for the same key wins");
+    let batch = Batch::new(address).set(b"a", &[4]).set(b"a", &[5]);
+    if batch.flush(batch.count).error != SYSCALL_OK {
+        return Err(3);
+    }
+    if stored(&address, "61").as_deref() != Some(&[5][..]) {
+        return Err(4);
+    }
+    Ok(())
+}
+
+fn test_malformed_batch_writes_nothing() -> Result<(), u32> {
+    // Description: a batch whose count or lengths do not match its bytes is rejected
+    // before any entry is applied.
+    log!("test: malformed batch writes nothing");
+    let address = Address([0x22; 20]);
+    let batch = Batch::new(address).set(b"a", &[1]).set(b"b", &[2]);
+
+    log!("subtest: count larger than the entries");
+    let ret = batch.flush(batch.count + 1);
+    if ret.value != 0 || ret.error != SYSCALL_ERR_INVALID_ARGUMENT {
+        return Err(10);
+    }
+
+    log!("subtest: trailing bytes after the last entry");
+    let ret = batch.flush(batch.count - 1);
+    if ret.value != 0 || ret.error != SYSCALL_ERR_INVALID_ARGUMENT {
+        return Err(11);
+    }
+
+    if stored(&address, "61").is_some() || stored(&address, "62").is_some() {
+        return Err(12);
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Storage delete tests: a deleted key reads back as missing and deleting an absent key
+// leaves state untouched.
+use alloc::vec::Vec;
+use clibc::log;
+use clibc::syscalls::{SYSCALL_STORAGE_DELETE, SYSCALL_STORAGE_GET, SYSCALL_STORAGE_SET};
+use kernel::BootInfo;
+use kernel::global::STATE;
+use kernel::syscall::{CallerMode, SyscallContext, dispatch_syscall};
+use types::Address;
+
+const DOMAIN: &[u8] = b"Balances";
+const COMPOSITE_KEY: &str = "Balances:6b6579";
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel storage delete test boot");
+    let _info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_delete_removes_key() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_delete_missing_key_is_noop() {
+        fail::fail(code);
+    }
+
+    log!("kernel storage delete test done");
+    utils::pass();
+}
+
+/// Syscall arguments laid out in kernel heap buffers, as a guest would pass them.
+struct StorageArgs {
+    address: Vec<u8>,
+    domain: Vec<u8>,
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl StorageArgs {
+    fn new(address: Address, key: &[u8], value: &[u8]) -> Self {
+        Self {
+            address: address.0.to_vec(),
+            domain: DOMAIN.to_vec(),
+            key: key.to_vec(),
+            value: value.to_vec(),
+        }
+    }
+
+    fn args(&self) -> [u32; 6] {
+        let packed_lens = ((self.key.len() as u32) << 16) | self.domain.len() as u32;
+        [
+            self.address.as_ptr() as u32,
+            self.domain.as_ptr() as u32,
+            self.key.as_ptr() as u32,
+            packed_lens,
+            self.value.as_ptr() as u32,
+            self.value.len() as u32,
+        ]
+    }
+}
+
+fn syscall(call_id: u32, args: [u32; 6]) -> u32 {
+    let mut regs = [0u32; 32];
+    let mut ctx = SyscallContext {
+        regs: &mut regs,
+        caller_mode: CallerMode::Supervisor,
+    };
+    dispatch_syscall(call_id, args, &mut ctx).value
+}
+
+fn stored(address: &Address) -> bool {
+    unsafe { STATE.get_mut() }
+        .as_ref()
+        .and_then(|state| state.get_account(address))
+        .is_some_and(|account| account.storage.contains_key(COMPOSITE_KEY))
+}
+
+fn test_delete_removes_key() -> Result<(), u32> {
+    // Description: set a key, delete it, and confirm storage_get no longer finds it.
+    log!("test: deleting a stored key removes it");
+    let address = Address([0x11; 20]);
+    let target = StorageArgs::new(address, b"key", &[1, 2, 3, 4]);
+
+    log!("subtest: set stores the value");
+    syscall(SYSCALL_STORAGE_SET, target.args());
+    if !stored(&address) {
+        return Err(1);
+    }
+
+    log!("subtest: delete reports the removal");
+    if syscall(SYSCALL_STORAGE_DELETE, target.args()) != 1 {
+        return Err(2);
+    }
+    if stored(&address) {
+        return Err(3);
+    }
+
+    log!("subtest: storage_get returns 0 after delete");
+    if syscall(SYSCALL_STORAGE_GET, target.args()) != 0 {
+        return Err(4);
+    }
+    Ok(())
+}
+
+fn test_delete_missing_key_is_noop() -> Result<(), u32> {
+    // Description: deleting an absent key returns 0 and neither creates the account
+    // nor disturbs the other keys it holds.
+    log!("test: deleting a missing key is a no-op");
+    let address = Address([0x22; 20]);
+
+    log!("subtest: unknown account is not created");
+    let missing = StorageArgs::new(address, b"key", &[]);
+    if syscall(SYSCALL_STORAGE_DELETE, missing.args()) != 0 {
+        return Err(10);
+    }
+    let created = unsafe { STATE.get_mut() }
+        .as_ref()
+        .is_some_and(|state| state.get_account(&address).is_some());
+    if created {
+        return Err(11);
+    }
+
+    log!("subtest: other keys survive a missing-key delete");
+    let kept = StorageArgs::new(address, b"key", &[9]);
+    syscall(SYSCALL_STORAGE_SET, kept.args());
+    let other = StorageArgs::new(address, b"other", &[]);
+    if syscall(SYSCALL_STORAGE_DELETE, other.args()) != 0 {
+        return Err(12);
+    }
+    if !stored(&address) {
+        return Err(13);
+    }
+    Ok(())
+}
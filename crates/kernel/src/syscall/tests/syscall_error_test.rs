@@ -0,0 +1,197 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Syscall error register tests: a failed read returns 0 in `a0` and a code in `a1` that
+// tells a missing key apart from a denied access, and the trap handler copies both into
+// the trapped frame.
+use alloc::vec::Vec;
+use clibc::log;
+use clibc::syscalls::{
+    SYSCALL_ERR_ACCESS_DENIED, SYSCALL_ERR_NOT_FOUND, SYSCALL_ERR_UNSUPPORTED,
+    SYSCALL_MAX_INPUT_LEN, SYSCALL_OK, SYSCALL_STORAGE_GET, SYSCALL_STORAGE_SET,
+};
+use kernel::global::{CURRENT_TASK, KERNEL_TASK_SLOT, TASKS};
+use kernel::memory::page_allocator;
+use kernel::syscall::{CallerMode, SyscallContext, SyscallReturn, dispatch_syscall};
+use kernel::trap::handle_trap;
+use kernel::user_program::ProgramImage;
+use kernel::{BootInfo, prep_program_task};
+use types::Address;
+
+// ebreak
+const CODE: [u8; 4] = [0x73, 0x00, 0x10, 0x00];
+const DOMAIN: &[u8] = b"Balances";
+const PROGRAM: Address = Address([0x11; 20]);
+const OTHER: Address = Address([0x33; 20]);
+
+// Trap frame layout handed to `handle_trap`: x0..x31, then pc.
+const TRAP_FRAME_WORDS: usize = 33;
+const REG_A0: usize = 10;
+const REG_A1: usize = 11;
+const REG_A7: usize = 17;
+const REG_PC: usize = 32;
+const ECALL_PC: u32 = 0x1000;
+const SCAUSE_ECALL_FROM_U: u32 = 8;
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel syscall error test boot");
+    let _info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_storage_get_errors() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_unknown_syscall_is_unsupported() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_trap_writes_error_register() {
+        fail::fail(code);
+    }
+
+    log!("kernel syscall error test done");
+    utils::pass();
+}
+
+fn syscall(call_id: u32, args: [u32; 6]) -> SyscallReturn {
+    let mut regs = [0u32; 32];
+    let mut ctx = SyscallContext {
+        regs: &mut regs,
+        caller_mode: CallerMode::User,
+    };
+    dispatch_syscall(call_id, args, &mut ctx)
+}
+
+/// Copies `address`, `DOMAIN` and `key` to `va` in the task rooted at `root_ppn` and
+/// returns the storage syscall arguments pointing at them.
+fn write_args(root_ppn: u32, va: u32, address: &Address, key: &[u8]) -> Option<[u32; 6]> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&address.0);
+    buf.extend_from_slice(DOMAIN);
+    buf.extend_from_slice(key);
+    if !page_allocator::copy(root_ppn, va, &buf) {
+        return None;
+    }
+    let domain_ptr = va + address.0.len() as u32;
+    let key_ptr = domain_ptr + DOMAIN.len() as u32;
+    let packed_lens = ((key.len() as u32) << 16) | DOMAIN.len() as u32;
+    Some([va, domain_ptr, key_ptr, packed_lens, 0, 0])
+}
+
+fn test_storage_get_errors() -> Result<(), u32> {
+    // Description: run storage_get as a program task; a missing key and another
+    // program's account both return value 0 but with different error codes.
+    log!("test: storage_get reports why it returned 0");
+    let image = ProgramImage {
+        code: &CODE,
+        base: 0,
+        entry_off: 0,
+        mem_end: CODE.len() as u32,
+    };
+    let task = prep_program_task(&PROGRAM, &Address([0x22; 20]), &image, &[]).ok_or(1u32)?;
+    let root_ppn = task.addr_space.root_ppn;
+    let args_va = task.heap_end - 0x100;
+    let slot = unsafe { TASKS.get_mut() }.push(task).map_err(|_| 2u32)?;
+    unsafe { *CURRENT_TASK.get_mut() = slot };
+
+    let result = storage_get_cases(root_ppn, args_va);
+
+    unsafe { *CURRENT_TASK.get_mut() = KERNEL_TASK_SLOT };
+    unsafe { TASKS.get_mut() }.release(slot);
+    result
+}
+
+fn storage_get_cases(root_ppn: u32, args_va: u32) -> Result<(), u32> {
+    log!("subtest: a missing key is not found");
+    let own = write_args(root_ppn, args_va, &PROGRAM, b"missing").ok_or(10u32)?;
+    let ret = syscall(SYSCALL_STORAGE_GET, own);
+    if ret.value != 0 || ret.error != SYSCALL_ERR_NOT_FOUND {
+        return Err(11);
+    }
+
+    log!("subtest: another program's account is access denied");
+    let other = write_args(root_ppn, args_va, &OTHER, b"missing").ok_or(12u32)?;
+    let ret = syscall(SYSCALL_STORAGE_GET, other);
+    if ret.value != 0 || ret.error != SYSCALL_ERR_ACCESS_DENIED {
+        return Err(13);
+    }
+
+    log!("subtest: a stored key returns a value and no error");
+    let mut stored = write_args(root_ppn, args_va, &PROGRAM, b"key").ok_or(15u32)?;
+    let value = [7u8; 4];
+    let value_va = args_va + 0x80;
+    if !page_allocator::copy(root_ppn, value_va, &value) {
+        return Err(16);
+    }
+    stored[4] = value_va;
+    stored[5] = value.len() as u32;
+    syscall(SYSCALL_STORAGE_SET, stored);
+    let ret = syscall(SYSCALL_STORAGE_GET, stored);
+    if ret.value == 0 || ret.error != SYSCALL_OK {
+        return Err(17);
+    }
+    Ok(())
+}
+
+fn test_unknown_syscall_is_unsupported() -> Result<(), u32> {
+    // Description: an unknown syscall id is reported instead of a silent 0.
+    log!("test: unknown syscall id is unsupported");
+    let ret = syscall(0xdead, [0; 6]);
+    if ret.value != 0 || ret.error != SYSCALL_ERR_UNSUPPORTED {
+        return Err(20);
+    }
+    Ok(())
+}
+
+/// Runs `call_id` through `handle_trap` as if a user task had trapped on `ecall` with
+/// `a1` holding `a1`, and returns the frame it resumes with.
+fn trap_ecall(call_id: u32, a1: u32) -> [u32; TRAP_FRAME_WORDS] {
+    let mut frame = [0u32; TRAP_FRAME_WORDS];
+    frame[REG_A1] = a1;
+    frame[REG_A7] = call_id;
+    frame[REG_PC] = ECALL_PC;
+    unsafe {
+        core::arch::asm!("csrw scause, {0}", in(reg) SCAUSE_ECALL_FROM_U);
+        let _ = handle_trap(frame.as_mut_ptr());
+        core::arch::asm!("csrw scause, zero");
+    }
+    frame
+}
+
+fn test_trap_writes_error_register() -> Result<(), u32> {
+    // Description: the trap handler, not only dispatch, reports the code: it leaves it in
+    // the frame's `a1`, overwriting whatever the guest had there, and skips the ecall.
+    log!("test: handle_trap writes the error code to a1");
+    log!("subtest: an unknown syscall id leaves SYSCALL_ERR_UNSUPPORTED");
+    let frame = trap_ecall(0xdead, 0xdead_beef);
+    if frame[REG_A0] != 0 || frame[REG_A1] != SYSCALL_ERR_UNSUPPORTED {
+        return Err(30);
+    }
+    if frame[REG_PC] != ECALL_PC + 4 {
+        return Err(31);
+    }
+
+    log!("subtest: a successful syscall clears a1 to SYSCALL_OK");
+    let frame = trap_ecall(SYSCALL_MAX_INPUT_LEN, 0xdead_beef);
+    if frame[REG_A0] == 0 || frame[REG_A1] != SYSCALL_OK {
+        return Err(32);
+    }
+    Ok(())
+}
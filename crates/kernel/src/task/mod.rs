@@ -5,14 +5,16 @@
 // Goals:
 // - Create a fresh address space for each program call (new root PPN + ASID).
 // - Map a fixed, contiguous user window starting at VA 0x0 that holds:
-//     * Code/rodata (program bytes copied starting at VA 0x0; entry at `entry_off`)
+//     * Code/rodata (program bytes copied at the image base, VA 0x0 for flat images)
 //     * A user stack (STACK_BYTES)
 //     * A user heap (HEAP_BYTES) with call args in a dedicated page at INPUT_BASE_ADDR
 // - Copy call arguments (to/from addresses + input buffer) into that call-args page.
 // - Prepare a trapframe with PC/SP/args and transfer control to user code.
 //
 // Key pieces:
-// - PROGRAM_WINDOW_BYTES covers code + rodata + stack + heap: a single map call per program.
+// - The window covers code + rodata + stack + heap. Its size comes from the smallest
+//   `WindowClass` whose image region fits the image; PROGRAM_WINDOW_BYTES is the
+//   largest (`WindowClass::Large`) and fixes the VAs placed above the window.
 // - TRAMPOLINE_VA is one page immediately after the user window, mapped into both
 //   the kernel root and the new user root. It contains:
 //     * an entry trampoline that switches satp and sret's into user mode
@@ -20,13 +22,15 @@
 //       to the real trap_entry
 //   This keeps trap entry valid even when the current root is the user page table.
 //
-// prep_program_task(to, from, code, input, entry_off):
+// prep_program_task(to, from, image, input):
 // 1) Allocate ASID and a fresh root PPN; map the user window + call-args page.
-// 2) Copy program code starting at VA 0 (so section offsets are preserved), copy args (to/from/input).
-// 3) Map the trampoline page into the user root and mirror the same physical page
-//    into the current kernel root; write trampoline code into it.
+// 2) Copy program code at `image.base` (0 for flat images; headered images linked at a
+//    nonzero base carry it, see `types::program_image`), copy args (to/from/input).
+// 3) Map the trampoline page into the user root. The first call also maps it into the
+//    kernel root and writes the trampoline code; the frame is cached in
+//    `TRAMPOLINE_PHYS` and shared by every later task.
 // 4) Build a Task with AddressSpace {root_ppn, asid} and set trapframe:
-//       pc = PROGRAM_VA_BASE + entry_off
+//       pc = PROGRAM_VA_BASE + image.base + image.entry_off
 //       sp = top of user stack within the window
 //       a0..a3 = to/from/input_base/input_len
 //    Caller can push the task into TASKS for bookkeeping.
@@ -40,15 +44,27 @@
 // - Set sepc to the user PC and clear sstatus.SPP so sret enters user mode.
 // - Set stvec to the trap trampoline VA.
 // - jr TRAMPOLINE_VA. The trampoline executes under the old root, writes satp
-//   to the new root, and executes sret into user code. There is no return
-//   path yet; this is a one-way handoff.
+//   to the new root, flushes translations with sfence.vma, and executes sret
+//   into user code.
+//
+// Returning to the caller:
+// - sys_call_program saves the caller's registers into its TASKS slot with
+//   pc = ecall + 4 before running the callee.
+// - When the callee hits its final ebreak, the trap handler reads its result,
+//   copies it into the caller's address space, restores the caller's trapframe
+//   with a0 = result pointer, switches back to the caller root and srets into it.
 //
 // Notes:
 // - The window and trampoline VAs are low for simplicity; nothing here relocates.
-// - We currently do not touch sstatus/mstatus or perform sfence.vma; add those
-//   when modeling fuller privilege transitions.
+// - Every `csrw satp` in the trampolines is followed by `sfence.vma zero, zero`,
+//   and the kernel flushes a finished task's ASID when it switches back to the
+//   caller root (see `page_allocator::switch_root`). The VM has no TLB today, but
+//   this keeps the invalidation contract in place if translation caching is added.
+// - ASIDs of released task slots are reissued before fresh ones (`AsidAllocator`);
+//   `alloc_asid` flushes a reissued ASID so it cannot resolve the old owner's mappings.
 
-use crate::global::NEXT_ASID;
+use crate::global::ASIDS;
+use crate::memory::page_allocator as mmu;
 
 pub mod prep;
 pub mod run;
@@ -56,7 +72,7 @@ pub mod run;
 pub mod task;
 mod trampoline;
 
-pub use prep::prep_program_task;
+pub use prep::{check_call_args, check_call_layout, prep_program_task};
 pub use run::{kernel_run_task, run_task};
 pub use task::{AddressSpace, Task, TrapFrame};
 
@@ -83,16 +99,25 @@ const REG_A3: usize = 13;
 // loads t0 = target satp before entering this stub so we can change roots
 // and return to user mode at sepc without returning to unmapped kernel text.
 // t0: target satp value.
-const TRAMPOLINE_CODE: [u32; 2] = [
+const TRAMPOLINE_CODE: [u32; 3] = [
     0x1802_9073, // csrw satp, t0
+    SFENCE_VMA_ALL,
     0x1020_0073, // sret
 ];
+// sfence.vma zero, zero: flush translations for every address space.
+const SFENCE_VMA_ALL: u32 = 0x1200_0073;
+
+/// Drop a prepared task that will never run, returning its ASID to `ASIDS`. Its page
+/// frames stay allocated, as they do for a released slot.
+pub fn discard_task(task: Task) {
+    unsafe { ASIDS.get_mut().free(task.addr_space.asid) };
+}
 
 pub(super) fn alloc_asid() -> u16 {
-    unsafe {
-        let counter = NEXT_ASID.get_mut();
-        let asid = if *counter == 0 { 1 } else { *counter };
-        *counter = asid.wrapping_add(1);
-        asid
+    let (asid, recycled) = unsafe { ASIDS.get_mut().alloc() };
+    if recycled {
+        // Drop translations the previous owner may have left tagged with this ASID.
+        mmu::sfence_vma(asid);
     }
+    asid
 }
@@ -1,37 +1,72 @@
 use crate::global::{
-    CALL_ARGS_PAGE_BASE, CURRENT_TASK, FROM_PTR_ADDR, HEAP_START_ADDR, INPUT_BASE_ADDR,
-    MAX_INPUT_LEN, TO_PTR_ADDR,
+    CALL_ARGS_PAGE_BASE, CALL_LAYOUT_VALID, CURRENT_TASK, FROM_PTR_ADDR, INPUT_BASE_ADDR,
+    MAX_INPUT_LEN, TO_PTR_ADDR, WindowClass,
 };
 use crate::memory::page_allocator as mmu;
-use crate::{AddressSpace, Task};
+use crate::user_program::ProgramImage;
+use crate::{AddressSpace, Task, debugf};
 use clibc::{log, logf};
-use types::SV32_PAGE_SIZE;
 use types::address::Address;
+use types::{ADDRESS_LEN, SV32_PAGE_SIZE};
 
 use super::{
-    PROGRAM_VA_BASE, PROGRAM_WINDOW_BYTES, REG_A0, REG_A1, REG_A2, REG_A3, REG_SP, STACK_BYTES,
-    alloc_asid, trampoline::map_trampoline_page,
+    HEAP_BYTES, PROGRAM_VA_BASE, REG_A0, REG_A1, REG_A2, REG_A3, REG_SP, STACK_BYTES, alloc_asid,
+    trampoline::map_trampoline_page,
 };
 
 /// Create a new task for a program and map its virtual address window via syscalls.
 ///
 /// This sets up:
-/// - Maps a fixed VA window [PROGRAM_VA_BASE, PROGRAM_VA_BASE + PROGRAM_WINDOW_BYTES).
+/// - Maps a VA window [PROGRAM_VA_BASE, PROGRAM_VA_BASE + class.window_bytes()), where the
+///   `WindowClass` is the smallest one whose image region holds the image up to `mem_end`.
+/// - Copies the image body to `PROGRAM_VA_BASE + image.base`, so segments linked at a
+///   nonzero base land at their link addresses.
 /// - Returns a Task with the new address space.
 ///
-/// The caller is responsible for copying program bytes into the mapped window
-/// and initializing the user trapframe (PC/SP/args) before running.
+/// Returns `None` without mapping anything when the image is malformed (entry offset
+/// outside the code, or base + code that does not fit the window), the input is too large,
+/// or the call-args layout constants overlap.
 pub fn prep_program_task(
     to: &Address,
     from: &Address,
-    code: &[u8],
+    image: &ProgramImage<'_>,
     input: &[u8],
-    entry_off: u32,
 ) -> Option<Task> {
+    let (code, entry_off) = (image.code, image.entry_off);
+    // Catches layout constants drifting into each other (e.g. a larger MAX_INPUT_LEN).
+    if !CALL_LAYOUT_VALID {
+        log!("launch_program: call-args layout overlaps or exceeds its page");
+        return None;
+    }
     if input.len() > MAX_INPUT_LEN {
         log!("launch_program: input too large");
         return None;
     }
+    // Reject malformed images before allocating an address space for them.
+    if entry_off as usize >= code.len() {
+        logf!(
+            "launch_program: invalid entry offset 0x%x (code_len=%d)",
+            entry_off,
+            code.len() as u32
+        );
+        return None;
+    }
+    // The class must hold the whole image, not just the body: `.data` and `.bss`
+    // follow `.rodata` and would otherwise run into the heap.
+    let fit = (image.base as usize)
+        .checked_add(code.len())
+        .and_then(|end| {
+            let mem_end = end.max(image.mem_end as usize);
+            WindowClass::for_image_end(mem_end).map(|class| (end, class))
+        });
+    let Some((image_end, class)) = fit else {
+        logf!(
+            "launch_program: code window exceeds program window (base=0x%x code_len=%d)",
+            image.base,
+            code.len() as u32
+        );
+        return None;
+    };
 
     let asid = alloc_asid();
     let root_ppn = match mmu::alloc_root() {
@@ -42,8 +77,9 @@ pub fn prep_program_task(
         }
     };
 
-    let window_end = PROGRAM_VA_BASE.wrapping_add(PROGRAM_WINDOW_BYTES as u32);
-    logf!(
+    let window_bytes = class.window_bytes();
+    let window_end = PROGRAM_VA_BASE.wrapping_add(window_bytes as u32);
+    debugf!(
         "launch_program: asid=%d root=0x%x map=[0x%x,0x%x)",
         asid as u32,
         root_ppn,
@@ -57,14 +93,12 @@ pub fn prep_program_task(
             root_ppn
         );
     }
-    map_program_window(root_ppn, code.len());
+    map_program_window(root_ppn, image_end, window_bytes);
 
-    // Copy the full program image starting at VA 0 so section offsets (e.g. .text at 0x400)
-    // land where the ELF expected them. Entry offset is provided by the caller.
-    if entry_off as usize >= code.len() {
-        panic!("launch_program: invalid entry offset");
-    }
-    if !mmu::copy(root_ppn, PROGRAM_VA_BASE, code) {
+    // Copy the image at its link base (VA 0 for flat images) so section offsets
+    // (e.g. .text at 0x400) land where the ELF expected them.
+    let load_va = PROGRAM_VA_BASE.wrapping_add(image.base);
+    if !mmu::copy(root_ppn, load_va, code) {
         logf!(
             "launch_program: failed to copy code into root=0x%x",
             root_ppn
@@ -94,10 +128,10 @@ pub fn prep_program_task(
     }
 
     // Sanity check where the code landed in the user root.
-    let entry_va = PROGRAM_VA_BASE.wrapping_add(entry_off);
+    let entry_va = load_va.wrapping_add(entry_off);
     let user_phys = mmu::translate(root_ppn, entry_va).unwrap_or(usize::MAX);
     let user_word = mmu::peek_word(root_ppn, entry_va).unwrap_or(0);
-    logf!(
+    debugf!(
         "prep_program_task: code VA=0x%x user_phys=0x%x user_word=0x%x code_start=0x%x",
         entry_va,
         user_phys as u32,
@@ -106,21 +140,23 @@ pub fn prep_program_task(
     );
     map_trampoline_page(root_ppn);
 
+    let heap_start = class.heap_start();
     let mut task = Task::new(
-        AddressSpace::new(root_ppn, asid, PROGRAM_VA_BASE, PROGRAM_WINDOW_BYTES as u32),
-        HEAP_START_ADDR as u32,
+        AddressSpace::new(root_ppn, asid, PROGRAM_VA_BASE, window_bytes as u32),
+        heap_start as u32,
+        (heap_start + HEAP_BYTES) as u32,
     );
     let caller = unsafe { *CURRENT_TASK.get_mut() };
     task.caller_task_id = Some(caller);
     // Set up initial trapframe.
-    let stack_top = PROGRAM_VA_BASE.wrapping_add(PROGRAM_WINDOW_BYTES as u32);
+    let stack_top = window_end;
     task.tf.pc = entry_va;
     task.tf.regs[REG_SP] = stack_top;
     task.tf.regs[REG_A0] = TO_PTR_ADDR;
     task.tf.regs[REG_A1] = FROM_PTR_ADDR;
     task.tf.regs[REG_A2] = INPUT_BASE_ADDR;
     task.tf.regs[REG_A3] = input.len() as u32;
-    logf!(
+    debugf!(
         "prep_program_task: trapframe pc=0x%x sp=0x%x a0=0x%x a1=0x%x a2=0x%x a3=%d",
         task.tf.pc,
         task.tf.regs[REG_SP],
@@ -131,16 +167,79 @@ pub fn prep_program_task(
     );
     // Also log the expected user stack window for sanity.
     let stack_base = stack_top.saturating_sub(STACK_BYTES as u32);
-    logf!(
+    debugf!(
         "prep_program_task: stack window=[0x%x,0x%x) heap_base=0x%x",
         stack_base,
         stack_top,
-        HEAP_START_ADDR as u32
+        heap_start as u32
     );
 
     Some(task)
 }
 
+/// The layout half of `check_call_args`, checked before a task is prepared: the fixed
+/// argument pointers must put both addresses and `input_len` bytes of input inside the
+/// call-args page.
+///
+/// Returns the first argument that does not (0 = to, 1 = from, 2 = input) and its
+/// pointer.
+pub fn check_call_layout(input_len: usize) -> Option<(u32, u32)> {
+    let args = [
+        (TO_PTR_ADDR, ADDRESS_LEN),
+        (FROM_PTR_ADDR, ADDRESS_LEN),
+        (INPUT_BASE_ADDR, input_len),
+    ];
+    for (arg, (ptr, len)) in args.into_iter().enumerate() {
+        if !in_call_args_page(ptr, len) {
+            logf!(
+                "check_call_layout: call argument %d at 0x%x does not fit the call-args page",
+                arg as u32,
+                ptr
+            );
+            return Some((arg as u32, ptr));
+        }
+    }
+    None
+}
+
+/// Checks that `task`'s argument registers follow the call convention before it runs:
+/// a0/a1 each cover an address, a2/a3 cover `input_len` bytes of input, and every
+/// range sits inside the call-args page and is mapped in the task's root.
+///
+/// Returns the first argument that does not (0 = to, 1 = from, 2 = input) and its
+/// pointer.
+pub fn check_call_args(task: &Task, input_len: usize) -> Option<(u32, u32)> {
+    let regs = &task.tf.regs;
+    if regs[REG_A3] as usize != input_len {
+        return Some((2, regs[REG_A2]));
+    }
+    let args = [
+        (regs[REG_A0], ADDRESS_LEN),
+        (regs[REG_A1], ADDRESS_LEN),
+        (regs[REG_A2], input_len),
+    ];
+    for (arg, (ptr, len)) in args.into_iter().enumerate() {
+        if !in_call_args_page(ptr, len) || mmu::translate(task.addr_space.root_ppn, ptr).is_none() {
+            logf!(
+                "prep_program_task: call argument %d at 0x%x is outside the call-args page",
+                arg as u32,
+                ptr
+            );
+            return Some((arg as u32, ptr));
+        }
+    }
+    None
+}
+
+/// Whether `[ptr, ptr + len)` lies inside the call-args page.
+fn in_call_args_page(ptr: u32, len: usize) -> bool {
+    let page_end = CALL_ARGS_PAGE_BASE as usize + SV32_PAGE_SIZE;
+    ptr >= CALL_ARGS_PAGE_BASE
+        && (ptr as usize)
+            .checked_add(len)
+            .is_some_and(|end| end <= page_end)
+}
+
 fn align_up(value: usize, align: usize) -> usize {
     if align == 0 {
         return value;
@@ -150,11 +249,10 @@ fn align_up(value: usize, align: usize) -> usize {
 
 /// Map the program window so code pages are RX and data/stack/heap are RW.
 /// The first page stays RWX because the program writes its result at 0x100.
-fn map_program_window(root_ppn: u32, code_len: usize) {
+/// `code_len` is the image end (base + body) and must already fit within the
+/// `window_bytes` window.
+fn map_program_window(root_ppn: u32, code_len: usize, window_bytes: usize) {
     let code_len = align_up(code_len, SV32_PAGE_SIZE);
-    if code_len > PROGRAM_WINDOW_BYTES {
-        panic!("launch_program: code window exceeds program window");
-    }
     let first_page_len = core::cmp::min(code_len, SV32_PAGE_SIZE);
     let first_page_perms = mmu::PagePerms::user_rwx();
     // Page 0 hosts the result header at 0x100, so keep it writable.
@@ -177,7 +275,7 @@ fn map_program_window(root_ppn: u32, code_len: usize) {
         }
     }
     let data_start = PROGRAM_VA_BASE.wrapping_add(code_len as u32);
-    let data_len = PROGRAM_WINDOW_BYTES.saturating_sub(code_len);
+    let data_len = window_bytes.saturating_sub(code_len);
     let data_perms = mmu::PagePerms::new(true, true, false, true);
     // Data/stack/heap region is RW, non-exec.
     if !mmu::map_range_for_root(root_ppn, data_start, data_len, data_perms) {
@@ -1,3 +1,4 @@
+use crate::debugf;
 use crate::global::{CURRENT_TASK, KERNEL_TASK_SLOT, TASKS};
 use crate::memory::page_allocator as mmu;
 use clibc::logf;
@@ -11,8 +12,10 @@ const TRAP_FRAME_WORDS: usize = REG_COUNT + 1; // regs + pc
 const TRAP_FRAME_BYTES: i32 = (TRAP_FRAME_WORDS * 4) as i32;
 const REG_RA: usize = 1;
 
-/// One-way context switch into a user task:
-/// - Loads the task's satp/regs/pc and jumps to user code (no return path yet)
+/// Context switch into a user task:
+/// - Loads the task's satp/regs/pc and jumps to user code. This call does not
+///   return; control comes back through the breakpoint trap, which resumes the
+///   task's caller (see `trap::handle_trap`).
 pub fn run_task(task_idx: usize) {
     let (target_root, asid, pc, sp, a0, a1, a2, a3) = unsafe {
         let tasks = TASKS.get_mut();
@@ -38,7 +41,7 @@ pub fn run_task(task_idx: usize) {
         *CURRENT_TASK.get_mut() = task_idx;
     }
     let kernel_root = mmu::current_root();
-    logf!(
+    debugf!(
         "run_task: switching satp 0x%x -> 0x%x asid=%d pc=0x%x sp=0x%x",
         kernel_root,
         target_root,
@@ -51,7 +54,7 @@ pub fn run_task(task_idx: usize) {
             && let Some(caller_idx) = task.caller_task_id
             && let Some(caller_task) = TASKS.get_mut().get(caller_idx)
         {
-            logf!(
+            debugf!(
                 "run_task: return ra=0x%x sp=0x%x for caller %d",
                 caller_task.tf.regs[REG_RA],
                 caller_task.tf.regs[REG_SP],
@@ -54,26 +54,41 @@ pub struct Task {
     pub addr_space: AddressSpace,
     /// Next heap pointer for this task (virtual address).
     pub heap_ptr: u32,
+    /// Exclusive upper bound of the task heap (virtual address).
+    pub heap_end: u32,
     /// Task slot that initiated this task, if any.
     pub caller_task_id: Option<usize>,
     /// Last decoded program result for this task, if any.
     pub last_result: Option<VmResult>,
+    /// Native value forwarded to this task by the transaction that started it; 0 for
+    /// nested calls. Reported to the guest as a u128, like balances.
+    pub call_value: u128,
+    /// Set for static calls and everything they call: state-writing syscalls abort
+    /// the transaction instead of running.
+    pub read_only: bool,
 }
 
 impl Task {
-    pub fn new(addr_space: AddressSpace, heap_ptr: u32) -> Self {
+    pub fn new(addr_space: AddressSpace, heap_ptr: u32, heap_end: u32) -> Self {
         Self {
             tf: TrapFrame::default(),
             addr_space,
             heap_ptr,
+            heap_end,
             caller_task_id: None,
             last_result: None,
+            call_value: 0,
+            read_only: false,
         }
     }
 
     /// Create the initial kernel task. This represents the supervisor itself:
     /// - `root_ppn` is the kernel page-table root PPN that will be loaded into satp.
     pub fn kernel(root_ppn: u32, heap_ptr: u32, va_base: u32, va_len: u32) -> Self {
-        Task::new(AddressSpace::new(root_ppn, 0, va_base, va_len), heap_ptr)
+        Task::new(
+            AddressSpace::new(root_ppn, 0, va_base, va_len),
+            heap_ptr,
+            va_base.saturating_add(va_len),
+        )
     }
 }
@@ -0,0 +1,117 @@
+#![no_std]
+#![no_main]
+
+// ASID allocator tests: released ASIDs are reissued before new values, the kernel
+// ASID is never handed out, and a task refused by a full task list gives its ASID back.
+use clibc::log;
+use kernel::global::{ASIDS, AsidAllocator, MAX_TASKS, TaskList};
+use kernel::{AddressSpace, BootInfo, Task, discard_task};
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel asid test boot");
+    let _info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_freed_asids_are_reused_first() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_kernel_asid_is_never_issued() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_full_task_list_hands_the_task_back() {
+        fail::fail(code);
+    }
+
+    log!("kernel asid test done");
+    utils::pass();
+}
+
+fn test_freed_asids_are_reused_first() -> Result<(), u32> {
+    // Description: freed ASIDs come back (flagged as recycled) before the counter
+    // issues a new, higher value.
+    log!("test: freed ASIDs are reissued before new ones");
+    let mut asids = AsidAllocator::new();
+
+    log!("subtest: fresh ASIDs count up from 1");
+    if asids.alloc() != (1, false) || asids.alloc() != (2, false) || asids.alloc() != (3, false) {
+        return Err(1);
+    }
+
+    log!("subtest: freed ASIDs are reissued as recycled");
+    asids.free(2);
+    asids.free(1);
+    asids.free(1);
+    if asids.alloc() != (1, true) || asids.alloc() != (2, true) {
+        return Err(2);
+    }
+
+    log!("subtest: the counter resumes once the freelist is empty");
+    if asids.alloc() != (4, false) {
+        return Err(3);
+    }
+    Ok(())
+}
+
+fn test_kernel_asid_is_never_issued() -> Result<(), u32> {
+    // Description: freeing ASID 0 is ignored, so it never comes back from alloc.
+    log!("test: the kernel ASID is never issued");
+    let mut asids = AsidAllocator::new();
+    asids.free(0);
+    if asids.alloc() != (1, false) {
+        return Err(10);
+    }
+    Ok(())
+}
+
+/// A task that owns a freshly allocated ASID and nothing else.
+fn task_with_new_asid() -> Task {
+    let (asid, _) = unsafe { ASIDS.get_mut() }.alloc();
+    Task::new(AddressSpace::new(0, asid, 0, 0), 0, 0)
+}
+
+fn test_full_task_list_hands_the_task_back() -> Result<(), u32> {
+    // Description: pushing onto a full task list returns the task, and discarding it
+    // frees its ASID, so overflowing many more times than there are ASIDs to spare
+    // keeps reissuing the same one.
+    log!("test: a full task list hands the task back");
+    let mut tasks = TaskList::new();
+    for _ in 0..MAX_TASKS {
+        if tasks.push(task_with_new_asid()).is_err() {
+            return Err(20);
+        }
+    }
+
+    log!("subtest: repeated overflows reuse one ASID");
+    let mut reused = None;
+    for _ in 0..4 * MAX_TASKS {
+        let task = match tasks.push(task_with_new_asid()) {
+            Ok(_) => return Err(21),
+            Err(task) => task,
+        };
+        let asid = task.addr_space.asid;
+        if *reused.get_or_insert(asid) != asid {
+            return Err(22);
+        }
+        discard_task(task);
+    }
+    if unsafe { ASIDS.get_mut() }.alloc().0 != reused.ok_or(23u32)? {
+        return Err(24);
+    }
+    Ok(())
+}
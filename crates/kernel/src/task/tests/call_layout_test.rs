@@ -0,0 +1,96 @@
+#![no_std]
+#![no_main]
+
+// Call-args layout tests: the to/from/input regions never overlap each other or the
+// program window, and all of them fit in the single mapped call-args page.
+use clibc::log;
+use kernel::BootInfo;
+use kernel::global::{CALL_LAYOUT, CALL_LAYOUT_VALID, call_layout_valid, ranges_disjoint};
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel call layout test boot");
+    let _info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_configured_layout_is_disjoint() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_bad_layouts_are_rejected() {
+        fail::fail(code);
+    }
+
+    log!("kernel call layout test done");
+    utils::pass();
+}
+
+fn test_configured_layout_is_disjoint() -> Result<(), u32> {
+    // Description: the layout built from the kernel constants passes validation.
+    log!("test: configured call-args layout is disjoint");
+    if !ranges_disjoint(&CALL_LAYOUT) {
+        return Err(1);
+    }
+    if !CALL_LAYOUT_VALID {
+        return Err(2);
+    }
+
+    log!("subtest: call-args regions start after the program window");
+    let window_end = CALL_LAYOUT[0].1;
+    if CALL_LAYOUT[1..]
+        .iter()
+        .any(|&(start, _)| start < window_end)
+    {
+        return Err(3);
+    }
+    Ok(())
+}
+
+fn test_bad_layouts_are_rejected() -> Result<(), u32> {
+    // Description: overlapping regions, or regions spilling past the page, fail.
+    log!("test: misconfigured layouts are rejected");
+    let page = 0x1000;
+
+    log!("subtest: input overlapping from");
+    let overlap = [
+        (0, page),
+        (page + 0x100, page + 0x114),
+        (page + 0x114, page + 0x128),
+        (page + 0x120, page + 0x200),
+    ];
+    if ranges_disjoint(&overlap) || call_layout_valid(&overlap, page) {
+        return Err(10);
+    }
+
+    log!("subtest: input spilling past the call-args page");
+    let spill = [
+        (0, page),
+        (page + 0x100, page + 0x114),
+        (page + 0x114, page + 0x128),
+        (page + 0x128, page + 0x1128),
+    ];
+    if !ranges_disjoint(&spill) || call_layout_valid(&spill, page) {
+        return Err(11);
+    }
+
+    log!("subtest: call-args page inside the program window");
+    let inside = [(0, 2 * page), (page + 0x100, page + 0x114)];
+    if call_layout_valid(&inside, page) {
+        return Err(12);
+    }
+    Ok(())
+}
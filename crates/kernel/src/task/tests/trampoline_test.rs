@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Trampoline reuse test: sequential task preps share one trampoline frame, which is
+// also the one mapped in the kernel root.
+use clibc::log;
+use kernel::global::TRAMPOLINE_PHYS;
+use kernel::memory::page_allocator;
+use kernel::task::TRAMPOLINE_VA;
+use kernel::user_program::ProgramImage;
+use kernel::{BootInfo, prep_program_task};
+use types::Address;
+
+// ebreak
+const CODE: [u8; 4] = [0x73, 0x00, 0x10, 0x00];
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel trampoline test boot");
+    let info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_task_preps_share_trampoline(info) {
+        fail::fail(code);
+    }
+
+    log!("kernel trampoline test done");
+    utils::pass();
+}
+
+fn test_task_preps_share_trampoline(info: BootInfo) -> Result<(), u32> {
+    // Description: prep two tasks and confirm both roots map TRAMPOLINE_VA to the
+    // cached frame, which is the frame the kernel root maps as well.
+    log!("test: task preps reuse the trampoline frame");
+    let image = ProgramImage {
+        code: &CODE,
+        base: 0,
+        entry_off: 0,
+        mem_end: CODE.len() as u32,
+    };
+    let to = Address([0x11; 20]);
+    let from = Address([0x22; 20]);
+
+    log!("subtest: first prep builds and caches the trampoline");
+    if unsafe { *TRAMPOLINE_PHYS.get_mut() }.is_some() {
+        return Err(1);
+    }
+    let first = prep_program_task(&to, &from, &image, &[]).ok_or(2u32)?;
+    let cached = unsafe { *TRAMPOLINE_PHYS.get_mut() }.ok_or(3u32)? as usize;
+    if page_allocator::translate(first.addr_space.root_ppn, TRAMPOLINE_VA) != Some(cached) {
+        return Err(4);
+    }
+    if page_allocator::translate(info.root_ppn, TRAMPOLINE_VA) != Some(cached) {
+        return Err(5);
+    }
+
+    log!("subtest: second prep maps the same frame");
+    let second = prep_program_task(&to, &from, &image, &[]).ok_or(6u32)?;
+    if second.addr_space.root_ppn == first.addr_space.root_ppn {
+        return Err(7);
+    }
+    if unsafe { *TRAMPOLINE_PHYS.get_mut() } != Some(cached as u32) {
+        return Err(8);
+    }
+    if page_allocator::translate(second.addr_space.root_ppn, TRAMPOLINE_VA) != Some(cached) {
+        return Err(9);
+    }
+    if page_allocator::translate(info.root_ppn, TRAMPOLINE_VA) != Some(cached) {
+        return Err(10);
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Program window class tests: a small image gets a small mapped window while an image
+// past the medium limit still gets the full `PROGRAM_WINDOW_BYTES` window.
+use alloc::vec;
+use clibc::log;
+use kernel::global::{HEAP_BYTES, WindowClass};
+use kernel::memory::page_allocator;
+use kernel::user_program::ProgramImage;
+use kernel::{BootInfo, PROGRAM_WINDOW_BYTES, Task, prep_program_task};
+use types::Address;
+
+// ebreak
+const CODE: [u8; 4] = [0x73, 0x00, 0x10, 0x00];
+const REG_SP: usize = 2;
+
+#[path = "../../tests/fail.rs"]
+mod fail;
+#[path = "../../tests/results.rs"]
+mod results;
+#[path = "../../tests/utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    input_ptr: *const u8,
+    input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    log!("kernel window class test boot");
+    let _info = utils::init_test_kernel(boot_info_ptr);
+
+    clibc::logf!("kernel test input len: %d", input_len as u32);
+    let _input = unsafe { core::slice::from_raw_parts(input_ptr, input_len) };
+
+    if let Err(code) = test_class_selection() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_small_image_maps_a_small_window() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_large_image_maps_the_full_window() {
+        fail::fail(code);
+    }
+    if let Err(code) = test_bss_past_the_small_region_picks_medium() {
+        fail::fail(code);
+    }
+
+    log!("kernel window class test done");
+    utils::pass();
+}
+
+fn test_class_selection() -> Result<(), u32> {
+    // Description: the smallest class whose image region holds the image is chosen.
+    log!("test: window class follows the image end");
+    let small = WindowClass::Small.image_bytes();
+    let medium = WindowClass::Medium.image_bytes();
+    let large = WindowClass::Large.image_bytes();
+    let cases = [
+        (0, Some(WindowClass::Small)),
+        (small, Some(WindowClass::Small)),
+        (small + 1, Some(WindowClass::Medium)),
+        (medium, Some(WindowClass::Medium)),
+        (medium + 1, Some(WindowClass::Large)),
+        (large, Some(WindowClass::Large)),
+        (large + 1, None),
+    ];
+    for (end, class) in cases {
+        if WindowClass::for_image_end(end) != class {
+            return Err(1);
+        }
+    }
+
+    log!("subtest: the large class is the configured program window");
+    if WindowClass::Large.window_bytes() != PROGRAM_WINDOW_BYTES
+        || WindowClass::Small.window_bytes() >= WindowClass::Medium.window_bytes()
+    {
+        return Err(2);
+    }
+    Ok(())
+}
+
+/// Checks `task`'s window, stack and heap against `class` and that the mapping ends
+/// exactly at the window end.
+fn check_window(task: &Task, class: WindowClass, code: u32) -> Result<(), u32> {
+    let window_end = class.window_bytes() as u32;
+    if task.addr_space.va_len != window_end || task.tf.regs[REG_SP] != window_end {
+        return Err(code);
+    }
+    let heap_start = class.heap_start() as u32;
+    if task.heap_ptr != heap_start || task.heap_end != heap_start + HEAP_BYTES as u32 {
+        return Err(code + 1);
+    }
+    let root = task.addr_space.root_ppn;
+    if page_allocator::translate(root, window_end - 4).is_none() {
+        return Err(code + 2);
+    }
+    if window_end < PROGRAM_WINDOW_BYTES as u32
+        && page_allocator::translate(root, window_end).is_some()
+    {
+        return Err(code + 3);
+    }
+    Ok(())
+}
+
+fn test_small_image_maps_a_small_window() -> Result<(), u32> {
+    // Description: a one-instruction program maps only the small window.
+    log!("test: small image maps a small window");
+    let image = ProgramImage {
+        code: &CODE,
+        base: 0,
+        entry_off: 0,
+        mem_end: CODE.len() as u32,
+    };
+    let task =
+        prep_program_task(&Address([0x11; 20]), &Address([0x22; 20]), &image, &[]).ok_or(10u32)?;
+    check_window(&task, WindowClass::Small, 11)
+}
+
+fn test_large_image_maps_the_full_window() -> Result<(), u32> {
+    // Description: an image past the medium limit still gets the full window.
+    log!("test: large image maps the full window");
+    let mut code = vec![0x13u8; WindowClass::Medium.image_bytes() + 0x1000];
+    code[..CODE.len()].copy_from_slice(&CODE);
+    let image = ProgramImage {
+        code: &code,
+        base: 0,
+        entry_off: 0,
+        mem_end: code.len() as u32,
+    };
+    let task =
+        prep_program_task(&Address([0x11; 20]), &Address([0x22; 20]), &image, &[]).ok_or(20u32)?;
+    check_window(&task, WindowClass::Large, 21)
+}
+
+fn test_bss_past_the_small_region_picks_medium() -> Result<(), u32> {
+    // Description: a tiny body whose bss runs past the small image region gets the
+    // medium window, so the bss does not overlap the small class's heap.
+    log!("test: bss past the small region maps a medium window");
+    let image = ProgramImage {
+        code: &CODE,
+        base: 0,
+        entry_off: 0,
+        mem_end: (WindowClass::Small.image_bytes() + 0x1000) as u32,
+    };
+    let task =
+        prep_program_task(&Address([0x11; 20]), &Address([0x22; 20]), &image, &[]).ok_or(30u32)?;
+    check_window(&task, WindowClass::Medium, 31)?;
+    if (task.heap_ptr as usize) < image.mem_end as usize {
+        return Err(35);
+    }
+    Ok(())
+}
@@ -1,12 +1,12 @@
-use crate::global::{KERNEL_TASK_SLOT, TASKS};
+use crate::global::{KERNEL_TASK_SLOT, TASKS, TRAMPOLINE_PHYS};
 use crate::memory::page_allocator as mmu;
 
-use super::{PAGE_SIZE, TRAMPOLINE_CODE, TRAMPOLINE_VA, TRAP_TRAMPOLINE_OFFSET};
+use super::{PAGE_SIZE, SFENCE_VMA_ALL, TRAMPOLINE_CODE, TRAMPOLINE_VA, TRAP_TRAMPOLINE_OFFSET};
 
 const REG_T0: u32 = 5;
 const REG_T1: u32 = 6;
 const REG_T2: u32 = 7;
-const TRAP_TRAMPOLINE_WORDS: usize = 7; // csrr + 2x(hi/lo) + csrw + jalr
+const TRAP_TRAMPOLINE_WORDS: usize = 8; // csrr + 2x(hi/lo) + csrw + sfence + jalr
 
 fn split_imm(val: u32) -> (u32, i32) {
     // Build a LUI/ADDI pair for a full 32-bit immediate.
@@ -45,6 +45,7 @@ fn build_trap_trampoline(kernel_satp: u32, trap_entry: u32) -> [u32; TRAP_TRAMPO
         encode_lui(REG_T1, satp_hi), // lui t1, %hi(kernel_satp): load upper bits.
         encode_addi(REG_T1, REG_T1, satp_lo), // addi t1, t1, %lo(kernel_satp): finish satp.
         0x1803_1073,                // csrw satp, t1: switch to kernel page table.
+        SFENCE_VMA_ALL,             // sfence.vma: drop translations cached for the user root.
         encode_lui(REG_T2, entry_hi), // lui t2, %hi(trap_entry): load trap handler addr.
         encode_addi(REG_T2, REG_T2, entry_lo), // addi t2, t2, %lo(trap_entry).
         encode_jalr(0, REG_T2, 0),  // jalr x0, t2, 0: jump to trap handler.
@@ -52,10 +53,34 @@ fn build_trap_trampoline(kernel_satp: u32, trap_entry: u32) -> [u32; TRAP_TRAMPO
 }
 
 pub(super) fn map_trampoline_page(root_ppn: u32) {
+    // The trampoline page is identical for every task, so it is built and mapped into
+    // the kernel root once; each user root then maps the same physical frame.
+    let user_tramp_perms = mmu::PagePerms::new(true, false, true, true);
+    let tramp_phys = match unsafe { *TRAMPOLINE_PHYS.get_mut() } {
+        Some(phys) => phys,
+        None => {
+            let phys = build_trampoline_page();
+            unsafe { *TRAMPOLINE_PHYS.get_mut() = Some(phys) };
+            phys
+        }
+    };
+    if !mmu::map_physical_range_for_root(
+        root_ppn,
+        TRAMPOLINE_VA,
+        tramp_phys,
+        PAGE_SIZE,
+        user_tramp_perms,
+    ) {
+        panic!("prep_program_task: failed to map trampoline page in user root");
+    }
+}
+
+/// Map the trampoline page into the kernel root, write both trampolines into it and
+/// return its physical address.
+fn build_trampoline_page() -> u32 {
     // Install a small trampoline page mapped in both roots so we can switch
     // satp safely before jumping into the user program.
     let kernel_tramp_perms = mmu::PagePerms::kernel_rwx();
-    let user_tramp_perms = mmu::PagePerms::new(true, false, true, true);
     let kernel_root = unsafe {
         TASKS
             .get_mut()
@@ -81,19 +106,10 @@ pub(super) fn map_trampoline_page(root_ppn: u32) {
     if !mmu::copy(kernel_root, TRAMPOLINE_VA, &tramp_bytes) {
         panic!("prep_program_task: failed to populate trampoline code");
     }
-    let tramp_phys = match mmu::translate(kernel_root, TRAMPOLINE_VA) {
+    match mmu::translate(kernel_root, TRAMPOLINE_VA) {
         Some(p) => p as u32,
         None => {
             panic!("prep_program_task: trampoline VA not mapped in kernel root");
         }
-    };
-    if !mmu::map_physical_range_for_root(
-        root_ppn,
-        TRAMPOLINE_VA,
-        tramp_phys,
-        PAGE_SIZE,
-        user_tramp_perms,
-    ) {
-        panic!("prep_program_task: failed to map trampoline page in user root");
     }
 }
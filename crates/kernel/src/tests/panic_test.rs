@@ -0,0 +1,25 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+// Panic on purpose: the panic handler must leave a crash record with this message
+// in the result region (see `PANICKING_BINS` in aTester/tests/kernel.rs).
+use kernel::BootInfo;
+
+#[path = "results.rs"]
+mod results;
+#[path = "utils.rs"]
+mod utils;
+
+/// # Safety
+/// The pointers must be valid for the provided lengths.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _start(
+    _input_ptr: *const u8,
+    _input_len: usize,
+    boot_info_ptr: *const BootInfo,
+) {
+    let _info = utils::init_test_kernel(boot_info_ptr);
+    panic!("deliberate kernel panic");
+}
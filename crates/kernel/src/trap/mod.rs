@@ -1,16 +1,18 @@
 use clibc::{log, logf};
 use core::arch::asm;
-use types::result::{RESULT_DATA_SIZE, Result as VmResult};
+use types::result::{FAULT_ERROR_CODE, RESULT_DATA_SIZE, Result as VmResult};
 
-use crate::Task;
 use crate::global::{
-    CURRENT_TASK, KERNEL_TASK_SLOT, LAST_COMPLETED_TASK, MAX_RESULT_SIZE, RESULT_ADDR, TASKS,
+    CURRENT_TASK, KERNEL_TASK_SLOT, LAST_COMPLETED_TASK, MAX_RESULT_SIZE, PENDING_CALL_FAILURE,
+    PENDING_PANIC, RESULT_ADDR, TASKS,
 };
 use crate::memory::page_allocator as mmu;
 use crate::syscall;
 use crate::syscall::alloc::alloc_in_task;
+use crate::syscall::panic::rollback_transaction;
 use crate::syscall::storage::read_user_bytes;
 use crate::task::TRAMPOLINE_VA;
+use crate::{Task, debugf};
 
 mod restore_trap_frame;
 mod save_trap_frame;
@@ -47,7 +49,7 @@ pub struct TrapReturn {
 pub fn init_trap_vector(kstack_top: u32) {
     // Seed sscratch with the kernel stack top so trap entry can swap sp with
     // sscratch and immediately land on a known-good kernel stack.
-    logf!("init_trap_vector: kstack_top=0x%x", kstack_top);
+    debugf!("init_trap_vector: kstack_top=0x%x", kstack_top);
     unsafe {
         asm!("csrw sscratch, {0}", in(reg) kstack_top);
         asm!("csrw stvec, {0}", in(reg) trap_entry as *const () as usize);
@@ -162,90 +164,32 @@ pub unsafe extern "C" fn handle_trap(saved: *mut u32) -> TrapReturn {
                 let mut ctx = syscall::SyscallContext { regs, caller_mode };
                 syscall::dispatch_syscall(call_id, args, &mut ctx)
             };
-            regs[REG_A0] = ret; // a0 return value
-            regs[REG_PC] = regs[REG_PC].wrapping_add(4); // Advance past ecall
-            return_kind = 0;
-            return_sp = regs[REG_SP];
-        }
-        SCAUSE_BREAKPOINT => {
-            // Default to returning to the kernel task unless the current task has a caller.
-            let mut caller_idx = KERNEL_TASK_SLOT;
-            let mut result_for_caller: Option<VmResult> = None;
-            unsafe {
-                let current = *CURRENT_TASK.get_mut();
-                let tasks = TASKS.get_mut();
-                // If this is a user task, save its current trapframe so it can be resumed later.
-                if current != KERNEL_TASK_SLOT
-                    && let Some(task) = tasks.get_mut(current)
-                {
-                    if let Some(result) = read_task_result(task) {
-                        task.last_result = Some(result);
-                        log_task_result(&result);
-                        result_for_caller = Some(result);
-                    } else {
-                        log!("program result: failed to read result bytes");
-                    }
-                    for (idx, value) in regs.iter().take(REG_COUNT).enumerate() {
-                        task.tf.regs[idx] = *value;
-                    }
-                    task.tf.pc = regs[REG_PC];
-                    // Use the recorded caller task as the return target.
-                    caller_idx = task.caller_task_id.unwrap_or(KERNEL_TASK_SLOT);
-                    if caller_idx == KERNEL_TASK_SLOT {
-                        // Only record tasks that return to the kernel so bundle resume can
-                        // associate the completed task with the current transaction receipt.
-                        *LAST_COMPLETED_TASK.get_mut() = Some(current);
-                    }
-                }
-                // Restore the caller task's trapframe and address-space root.
-                if let Some(caller_task) = tasks.get_mut(caller_idx) {
-                    if caller_idx != KERNEL_TASK_SLOT {
-                        let result_ptr = match result_for_caller {
-                            Some(result) => {
-                                write_result_to_caller(caller_task, &result).unwrap_or(0)
-                            }
-                            None => 0,
-                        };
-                        caller_task.tf.regs[REG_A0] = result_ptr;
-                    }
-                    for (idx, value) in caller_task.tf.regs.iter().take(REG_COUNT).enumerate() {
-                        regs[idx] = *value;
-                    }
-                    // Resume at the caller's return address.
-                    regs[REG_PC] = if caller_idx == KERNEL_TASK_SLOT {
-                        caller_task.tf.regs[REG_RA]
-                    } else {
-                        caller_task.tf.pc
-                    };
-                    mmu::set_current_root(caller_task.addr_space.root_ppn);
-                    return_sp = caller_task.tf.regs[REG_SP];
-                    logf!(
-                        "breakpoint return: caller=%d pc=0x%x ra=0x%x sp=0x%x",
-                        caller_idx as u32,
-                        caller_task.tf.pc,
-                        caller_task.tf.regs[REG_RA],
-                        caller_task.tf.regs[REG_SP]
-                    );
-                } else {
-                    panic!("breakpoint trap: caller task missing");
-                }
-                // Mark the caller as the current task after the handoff.
-                *CURRENT_TASK.get_mut() = caller_idx;
-            }
-            let mut sstatus = read_sstatus();
-            // Set SPP so sret returns to the correct privilege level.
-            if caller_idx == KERNEL_TASK_SLOT {
-                // Return to supervisor when the caller is the kernel task.
-                sstatus |= SSTATUS_SPP;
-                return_kind = 1;
+            if let Some(result) = unsafe { PENDING_PANIC.get_mut().take() } {
+                // The task panicked: abandon its call chain and resume the kernel task.
+                return_sp = unwind_to_kernel(regs, result);
+                return_kind = set_return_mode(KERNEL_TASK_SLOT);
+            } else if let Some(result) = unsafe { PENDING_CALL_FAILURE.get_mut().take() } {
+                // Only this call fails: hand the result to its caller, which keeps running.
+                let (sp, caller_idx) = return_to_caller(regs, Some(result));
+                return_sp = sp;
+                return_kind = set_return_mode(caller_idx);
             } else {
-                // Clear SPP to return to user mode for user callers.
-                sstatus &= !SSTATUS_SPP;
+                regs[REG_A0] = ret.value; // a0 return value
+                regs[REG_A1] = ret.error; // a1 error code (SYSCALL_OK on success)
+                regs[REG_PC] = regs[REG_PC].wrapping_add(4); // Advance past ecall
                 return_kind = 0;
+                return_sp = regs[REG_SP];
             }
-            unsafe {
-                asm!("csrw sstatus, {0}", in(reg) sstatus);
-            }
+        }
+        SCAUSE_BREAKPOINT => {
+            let (sp, caller_idx) = return_to_caller(regs, None);
+            return_sp = sp;
+            return_kind = set_return_mode(caller_idx);
+        }
+        _ if return_kind == 0 => {
+            // A user task faulted: fail its transaction and resume the kernel task.
+            return_sp = fault_to_kernel(regs, scause, stval);
+            return_kind = set_return_mode(KERNEL_TASK_SLOT);
         }
         _ => log!("unhandled trap"),
     }
@@ -255,6 +199,156 @@ pub unsafe extern "C" fn handle_trap(saved: *mut u32) -> TrapReturn {
     }
 }
 
+/// Set SPP so `sret` returns to the caller's privilege level. Returns the trap return kind
+/// (1 for the kernel task, 0 for user callers).
+fn set_return_mode(caller_idx: usize) -> u32 {
+    let mut sstatus = read_sstatus();
+    let return_kind = if caller_idx == KERNEL_TASK_SLOT {
+        // Return to supervisor when the caller is the kernel task.
+        sstatus |= SSTATUS_SPP;
+        1
+    } else {
+        // Clear SPP to return to user mode for user callers.
+        sstatus &= !SSTATUS_SPP;
+        0
+    };
+    unsafe {
+        asm!("csrw sstatus, {0}", in(reg) sstatus);
+    }
+    return_kind
+}
+
+/// Finish the current task and load its caller's trapframe into `regs`: the kernel task,
+/// or the calling task with the result pointer in `a0`. The result is the one the task
+/// wrote at `RESULT_ADDR`, unless `failure` replaces it. Returns the stack pointer to
+/// resume on and the caller's slot.
+fn return_to_caller(regs: &mut [u32], failure: Option<VmResult>) -> (u32, usize) {
+    // Default to returning to the kernel task unless the current task has a caller.
+    let mut caller_idx = KERNEL_TASK_SLOT;
+    let mut result_for_caller: Option<VmResult> = None;
+    let mut finished_asid = 0u16;
+    unsafe {
+        let current = *CURRENT_TASK.get_mut();
+        let tasks = TASKS.get_mut();
+        // If this is a user task, save its current trapframe so it can be resumed later.
+        if current != KERNEL_TASK_SLOT
+            && let Some(task) = tasks.get_mut(current)
+        {
+            if let Some(result) = failure.or_else(|| read_task_result(task)) {
+                task.last_result = Some(result);
+                log_task_result(&result);
+                result_for_caller = Some(result);
+            } else {
+                log!("program result: failed to read result bytes");
+            }
+            for (idx, value) in regs.iter().take(REG_COUNT).enumerate() {
+                task.tf.regs[idx] = *value;
+            }
+            task.tf.pc = regs[REG_PC];
+            finished_asid = task.addr_space.asid;
+            // Use the recorded caller task as the return target.
+            caller_idx = task.caller_task_id.unwrap_or(KERNEL_TASK_SLOT);
+            if caller_idx == KERNEL_TASK_SLOT {
+                // Only record tasks that return to the kernel so bundle resume can
+                // associate the completed task with the current transaction receipt.
+                *LAST_COMPLETED_TASK.get_mut() = Some(current);
+            }
+        }
+        // Restore the caller task's trapframe and address-space root.
+        let return_sp = if let Some(caller_task) = tasks.get_mut(caller_idx) {
+            if caller_idx != KERNEL_TASK_SLOT {
+                let result_ptr = match result_for_caller {
+                    Some(result) => write_result_to_caller(caller_task, &result).unwrap_or(0),
+                    None => 0,
+                };
+                caller_task.tf.regs[REG_A0] = result_ptr;
+            }
+            for (idx, value) in caller_task.tf.regs.iter().take(REG_COUNT).enumerate() {
+                regs[idx] = *value;
+            }
+            // Resume at the caller's return address.
+            regs[REG_PC] = if caller_idx == KERNEL_TASK_SLOT {
+                caller_task.tf.regs[REG_RA]
+            } else {
+                caller_task.tf.pc
+            };
+            mmu::switch_root(caller_task.addr_space.root_ppn, finished_asid);
+            debugf!(
+                "breakpoint return: caller=%d pc=0x%x ra=0x%x sp=0x%x",
+                caller_idx as u32,
+                caller_task.tf.pc,
+                caller_task.tf.regs[REG_RA],
+                caller_task.tf.regs[REG_SP]
+            );
+            caller_task.tf.regs[REG_SP]
+        } else {
+            panic!("breakpoint trap: caller task missing");
+        };
+        // Mark the caller as the current task after the handoff.
+        *CURRENT_TASK.get_mut() = caller_idx;
+        // A nested callee is finished once its result is copied to the caller.
+        // Tasks returning to the kernel keep their slot until the bundle loop
+        // has read `last_result` (see `update_receipt_from_task`).
+        if caller_idx != KERNEL_TASK_SLOT {
+            tasks.release(current);
+        }
+        (return_sp, caller_idx)
+    }
+}
+
+/// Abandon a panicked or faulted transaction: release every nested task in the call chain, record
+/// `result` on the task the kernel launched, and load the kernel task's trapframe into
+/// `regs`. Returns the kernel stack pointer to resume on.
+fn unwind_to_kernel(regs: &mut [u32], result: VmResult) -> u32 {
+    unsafe {
+        let tasks = TASKS.get_mut();
+        let mut idx = *CURRENT_TASK.get_mut();
+        let finished_asid = tasks.get(idx).map(|task| task.addr_space.asid).unwrap_or(0);
+        while let Some(task) = tasks.get_mut(idx) {
+            let caller_idx = task.caller_task_id.unwrap_or(KERNEL_TASK_SLOT);
+            if caller_idx == KERNEL_TASK_SLOT {
+                task.last_result = Some(result);
+                log_task_result(&result);
+                *LAST_COMPLETED_TASK.get_mut() = Some(idx);
+                break;
+            }
+            tasks.release(idx);
+            idx = caller_idx;
+        }
+        let kernel_task = match tasks.get(KERNEL_TASK_SLOT) {
+            Some(task) => task,
+            None => panic!("panic unwind: kernel task missing"),
+        };
+        for (idx, value) in kernel_task.tf.regs.iter().take(REG_COUNT).enumerate() {
+            regs[idx] = *value;
+        }
+        regs[REG_PC] = kernel_task.tf.regs[REG_RA];
+        mmu::switch_root(kernel_task.addr_space.root_ppn, finished_asid);
+        *CURRENT_TASK.get_mut() = KERNEL_TASK_SLOT;
+        debugf!("panic unwind: resuming kernel at pc=0x%x", regs[REG_PC]);
+        kernel_task.tf.regs[REG_SP]
+    }
+}
+
+/// Terminate the faulting user task: roll back its transaction and unwind to the kernel
+/// with a `FAULT_ERROR_CODE` result carrying `scause` and `stval`.
+fn fault_to_kernel(regs: &mut [u32], scause: usize, stval: usize) -> u32 {
+    logf!(
+        "user fault: scause=0x%x stval=0x%x sepc=0x%x",
+        scause as u32,
+        stval as u32,
+        regs[REG_PC]
+    );
+    rollback_transaction();
+    let mut data = [0u8; 8];
+    data[..4].copy_from_slice(&(scause as u32).to_le_bytes());
+    data[4..].copy_from_slice(&(stval as u32).to_le_bytes());
+    unwind_to_kernel(
+        regs,
+        VmResult::new_with_data(false, FAULT_ERROR_CODE, &data),
+    )
+}
+
 #[unsafe(no_mangle)]
 /// Restore the kernel address-space root for traps arriving from user mode.
 extern "C" fn ensure_kernel_root_for_trap() {
@@ -313,24 +407,31 @@ fn read_task_result(task: &Task) -> Option<VmResult> {
 
 fn log_task_result(result: &VmResult) {
     let data_len = (result.data_len as usize).min(RESULT_DATA_SIZE);
-    logf!(
+    debugf!(
         "program result: success=%d error=%d data_len=%d",
         result.success as u32,
         result.error_code,
         data_len as u32
     );
     if data_len > 0 {
-        log!("program result data: %b", &result.data[..data_len]);
+        let data = &result.data[..data_len];
+        debugf!(
+            "program result data: %b",
+            data.as_ptr() as u32,
+            data.len() as u32
+        );
     }
 }
 
-fn write_result_to_caller(caller_task: &mut Task, result: &VmResult) -> Option<u32> {
+/// Copy `result` into a fresh allocation in the caller's heap and return its user VA,
+/// the pointer a nested call hands back in `a0`.
+pub(crate) fn write_result_to_caller(caller_task: &mut Task, result: &VmResult) -> Option<u32> {
     let addr = alloc_in_task(caller_task, MAX_RESULT_SIZE as u32, 4)?;
     let mut buf = [0u8; MAX_RESULT_SIZE];
     buf[0] = result.success as u8;
     buf[1..5].copy_from_slice(&result.error_code.to_le_bytes());
-    buf[5..9].copy_from_slice(&result.data_len.to_le_bytes());
     let data_len = (result.data_len as usize).min(RESULT_DATA_SIZE);
+    buf[5..9].copy_from_slice(&(data_len as u32).to_le_bytes());
     if data_len > 0 {
         buf[9..9 + data_len].copy_from_slice(&result.data[..data_len]);
     }
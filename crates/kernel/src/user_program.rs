@@ -3,13 +3,22 @@ extern crate alloc;
 use alloc::format;
 use clibc::logf;
 use state::State;
+use types::ProgramImageHeader;
 use types::address::Address;
 
+use crate::debugf;
 use crate::global::{CODE_SIZE_LIMIT, RO_DATA_SIZE_LIMIT, STATE};
 
 pub struct ProgramImage<'a> {
+    /// Image body, loaded starting at user VA `base`.
     pub code: &'a [u8],
+    /// Link base of `code`: 0 for flat images, the header base for headered ones.
+    pub base: u32,
+    /// Entry point as an offset into `code`.
     pub entry_off: u32,
+    /// User VA one past the image's last byte, including `.bss` left out of `code`;
+    /// `base + code.len()` for flat images. The window class is picked from this.
+    pub mem_end: u32,
 }
 
 // Load a program image from STATE, validate it, and pass a borrowed view to a caller.
@@ -44,37 +53,80 @@ pub fn with_program_image<R>(
         return None;
     }
 
-    // Find the first non-zero byte to infer the entry offset and log code stats.
-    let first_nz = account
-        .code
-        .iter()
-        .position(|&b| b != 0)
-        .unwrap_or(account.code.len());
-    let nz_count = account.code.iter().filter(|&&b| b != 0).count();
-    logf!(
+    // Headered images carry their link base, entry point and end (which covers any
+    // `.bss`). Flat images load at VA 0, start at their first non-zero byte and end
+    // with their last one.
+    let (code, base, entry_off, mem_end) = match ProgramImageHeader::decode(&account.code) {
+        Some((header, body)) => match header.entry.checked_sub(header.base) {
+            Some(_)
+                if (header.mem_end as usize)
+                    < (header.base as usize).saturating_add(body.len()) =>
+            {
+                logf!(
+                    "%s",
+                    display: format!(
+                        "Program call failed: image end 0x{:x} is below its body end (base 0x{:x}, len {})",
+                        header.mem_end,
+                        header.base,
+                        body.len()
+                    )
+                );
+                return None;
+            }
+            Some(entry_off) => (body, header.base, entry_off, header.mem_end as usize),
+            None => {
+                logf!(
+                    "%s",
+                    display: format!(
+                        "Program call failed: entry 0x{:x} is below image base 0x{:x}",
+                        header.entry, header.base
+                    )
+                );
+                return None;
+            }
+        },
+        None => {
+            let first_nz = account
+                .code
+                .iter()
+                .position(|&b| b != 0)
+                .unwrap_or(account.code.len());
+            let code = account.code.as_slice();
+            (code, 0, first_nz as u32, code.len())
+        }
+    };
+    let nz_count = code.iter().filter(|&&b| b != 0).count();
+    debugf!(
         "%s",
         display: format!(
-            "Program code stats: len={} first_nz={} nz_count={}",
-            account.code.len(),
-            first_nz,
+            "Program code stats: len={} base=0x{:x} entry_off={} mem_end=0x{:x} nz_count={}",
+            code.len(),
+            base,
+            entry_off,
+            mem_end,
             nz_count
         )
     );
 
-    // Enforce the code size limit to prevent oversized binaries.
-    let code_len = account.code.len();
+    // Enforce the code size limit on the loaded extent (bss included) to prevent
+    // oversized binaries.
     let max = CODE_SIZE_LIMIT + RO_DATA_SIZE_LIMIT;
-    if code_len > max {
-        panic!(
-            "❌ Program call rejected: code size ({}) exceeds limit ({})",
-            code_len, max
+    if mem_end > max {
+        logf!(
+            "%s",
+            display: format!(
+                "Program call rejected: code size ({}) exceeds limit ({})",
+                mem_end, max
+            )
         );
+        return None;
     }
 
-    // Provide the borrowed code slice and entry offset to the caller.
-    let entry_off = first_nz as u32;
+    // Provide the borrowed code slice, its base and entry offset to the caller.
     f(ProgramImage {
-        code: &account.code,
+        code,
+        base,
         entry_off,
+        mem_end: mem_end as u32,
     })
 }
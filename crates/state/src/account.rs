@@ -11,3 +11,27 @@ pub struct Account {
 
     pub storage: BTreeMap<String, Vec<u8>>,
 }
+
+impl Account {
+    /// Bytes held in storage: the sum of every key and value length.
+    pub fn storage_bytes(&self) -> usize {
+        self.storage.iter().fold(0usize, |acc, (k, v)| {
+            acc.saturating_add(k.len()).saturating_add(v.len())
+        })
+    }
+
+    /// Adds `amount` to the balance; errors without changing it on overflow.
+    #[allow(clippy::result_unit_err)]
+    pub fn credit(&mut self, amount: u128) -> Result<(), ()> {
+        self.balance = self.balance.checked_add(amount).ok_or(())?;
+        Ok(())
+    }
+
+    /// Subtracts `amount` from the balance; errors without changing it when the
+    /// balance is lower than `amount`.
+    #[allow(clippy::result_unit_err)]
+    pub fn debit(&mut self, amount: u128) -> Result<(), ()> {
+        self.balance = self.balance.checked_sub(amount).ok_or(())?;
+        Ok(())
+    }
+}
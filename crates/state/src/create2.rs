@@ -0,0 +1,34 @@
+//! Deterministic contract addresses for salted (CREATE2-style) deployments.
+//!
+//! The address depends only on the deployer, a caller-chosen salt and the code,
+//! so anyone can compute where a contract will live before it is deployed:
+//!
+//! ```text
+//! address = sha256(0xff || deployer || salt || sha256(code))[12..32]
+//! ```
+
+use sha2::{Digest, Sha256};
+use types::address::Address;
+
+/// Salt length for salted deployments.
+pub const CREATE2_SALT_LEN: usize = 32;
+
+/// Hash identifying a contract's code: `sha256(code)`.
+pub fn code_hash(code: &[u8]) -> [u8; 32] {
+    Sha256::digest(code).into()
+}
+
+/// Derive the address a salted deployment of `code` by `deployer` lands at.
+pub fn create2_address(deployer: &Address, salt: &[u8; CREATE2_SALT_LEN], code: &[u8]) -> Address {
+    let code_hash = code_hash(code);
+    let mut hasher = Sha256::new();
+    hasher.update([0xff]);
+    hasher.update(deployer.0);
+    hasher.update(salt);
+    hasher.update(code_hash);
+    let digest = hasher.finalize();
+
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(&digest[12..]);
+    Address(addr)
+}
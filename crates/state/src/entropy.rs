@@ -0,0 +1,61 @@
+//! Deterministic pseudo-entropy for guest programs.
+//!
+//! Every node replaying a bundle must produce the same state, so guests cannot
+//! be given real randomness. Instead each transaction gets a stream derived from
+//! the pre-bundle state root and its index in the bundle:
+//!
+//! ```text
+//! seed    = sha256(state.encode())            // once per bundle
+//! stream  = splitmix64(sha256(seed || tx_index)[..8])
+//! ```
+//!
+//! The output is reproducible and anyone can predict it from public data. It is
+//! NOT suitable for anything that must be unpredictable (lotteries with real
+//! stakes, key generation, nonces).
+
+use sha2::{Digest, Sha256};
+
+use crate::State;
+
+/// Bundle-wide seed: `sha256` of the encoded state the bundle starts from.
+pub fn entropy_seed(state: &State) -> [u8; 32] {
+    Sha256::digest(state.encode()).into()
+}
+
+/// Per-transaction splitmix64 stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entropy {
+    state: u64,
+}
+
+impl Entropy {
+    /// Stream for transaction `tx_index` of a bundle seeded with `seed`.
+    pub fn new(seed: &[u8; 32], tx_index: u32) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(tx_index.to_le_bytes());
+        let digest = hasher.finalize();
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&digest[..8]);
+        Self {
+            state: u64::from_le_bytes(word),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Fills `out` with the next bytes of the stream. A trailing partial word is
+    /// consumed whole, so the stream position only depends on the call lengths.
+    pub fn fill(&mut self, out: &mut [u8]) {
+        for chunk in out.chunks_mut(8) {
+            let word = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Property-testing helpers: seeded arbitrary states and a structural
+//! invariant check to run on them (or on anything `decode` returns).
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use types::address::Address;
+
+use crate::{Account, Entropy, State};
+
+/// Storage domains `State::arbitrary` draws keys from.
+const DOMAINS: [&str; 4] = ["Balances", "Allowances", "Metadata", "Ledger"];
+
+impl State {
+    /// A populated state derived only from `seed`: up to 8 accounts with random
+    /// balances, nonces, code and `domain:hex` storage. The same seed always
+    /// yields the same state, and the result passes `check_invariants`.
+    pub fn arbitrary(seed: u64) -> Self {
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        let mut rng = Entropy::new(&seed_bytes, 0);
+        let mut below = |n: u64| rng.next_u64() % n;
+
+        let mut state = State::new();
+        for _ in 0..below(9) {
+            let mut addr = [0u8; 20];
+            for byte in addr.iter_mut() {
+                *byte = below(256) as u8;
+            }
+            // Keep each balance under 2^124 so even 8 of them cannot overflow a u128 sum.
+            let balance = ((below(u64::MAX) as u128) << 64 | below(u64::MAX) as u128) >> 4;
+            let is_contract = below(2) == 1;
+            let code = if is_contract {
+                (0..1 + below(64)).map(|_| below(256) as u8).collect()
+            } else {
+                Vec::new()
+            };
+            let mut storage = BTreeMap::new();
+            for _ in 0..below(6) {
+                let domain = DOMAINS[below(DOMAINS.len() as u64) as usize];
+                let key: Vec<u8> = (0..below(33)).map(|_| below(256) as u8).collect();
+                let value = (0..below(33)).map(|_| below(256) as u8).collect();
+                storage.insert(format!("{}:{}", domain, hex(&key)), value);
+            }
+            state.accounts.insert(
+                Address(addr),
+                Account {
+                    nonce: below(u64::MAX),
+                    balance,
+                    code,
+                    is_contract,
+                    storage,
+                },
+            );
+        }
+        state
+    }
+
+    /// Structural checks every state the kernel produces should pass:
+    /// - every storage key is a `domain:hex` composite key, as the storage
+    ///   syscalls build them, with a non-empty domain and lowercase hex;
+    /// - code, storage keys and values fit the encoding's u32 length prefixes;
+    /// - the sum of all balances fits a u128, so no transfer can overflow.
+    ///
+    /// RETURNS: a description of the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut supply = 0u128;
+        for (addr, account) in self.iter_accounts() {
+            supply = supply
+                .checked_add(account.balance)
+                .ok_or_else(|| format!("{}: total balance overflows u128", addr))?;
+            if account.code.len() > u32::MAX as usize {
+                return Err(format!("{}: code too long to encode", addr));
+            }
+            for (key, value) in &account.storage {
+                check_composite_key(key)
+                    .map_err(|why| format!("{}: key {:?} {}", addr, key, why))?;
+                if key.len() > u32::MAX as usize || value.len() > u32::MAX as usize {
+                    return Err(format!("{}: key {:?} too long to encode", addr, key));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_composite_key(key: &str) -> Result<(), &'static str> {
+    let (domain, key_hex) = key.rsplit_once(':').ok_or("has no domain separator")?;
+    if domain.is_empty() {
+        return Err("has an empty domain");
+    }
+    if key_hex.len() % 2 != 0 {
+        return Err("has an odd-length hex key");
+    }
+    if !key_hex
+        .bytes()
+        .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return Err("has a key that is not lowercase hex");
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,124 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use types::address::Address;
+
+use crate::{code_hash, Account};
+
+/// Account contents recorded before the first modification while a journal is open.
+///
+/// Code is recorded by its `code_hash` rather than copied, which is enough for
+/// `diff` to report a redeploy. `revert` does not restore code: it is only written
+/// by deployments, which never run inside a checkpoint. An account that did not
+/// exist when the journal opened is recorded as `Missing`.
+#[derive(Clone, Debug)]
+pub(crate) enum Preimage {
+    Missing,
+    Existing {
+        nonce: u64,
+        balance: u128,
+        is_contract: bool,
+        code_hash: [u8; 32],
+        storage: BTreeMap<String, Vec<u8>>,
+    },
+}
+
+/// Pre-images of every account touched since the journal was opened, keyed by address.
+///
+/// `State` keeps one for `checkpoint`/`revert` and, independently, one for
+/// `begin_diff`/`finish_diff`; both are fed by `get_account_mut`.
+#[derive(Clone, Debug, Default)]
+pub struct StateJournal {
+    pub(crate) preimages: BTreeMap<Address, Preimage>,
+}
+
+/// Accounts and storage keys whose contents differ from when a journal was opened.
+///
+/// An account is listed when its nonce, balance, contract flag or code changed, or
+/// when it was created. Storage keys are listed when written with a new value, added
+/// or removed. Touching an account without changing it leaves both lists empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub accounts_changed: Vec<Address>,
+    pub storage_changed: Vec<(Address, String)>,
+}
+
+impl StateJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `addr` has no pre-image yet, so the next `record` would save one.
+    pub(crate) fn wants(&self, addr: &Address) -> bool {
+        !self.preimages.contains_key(addr)
+    }
+
+    /// Records `account`, whose code hashes to `code_hash`, as the pre-image of
+    /// `addr` unless one is already saved.
+    pub(crate) fn record(
+        &mut self,
+        addr: &Address,
+        account: Option<&Account>,
+        code_hash: [u8; 32],
+    ) {
+        if !self.wants(addr) {
+            return;
+        }
+        let preimage = match account {
+            Some(account) => Preimage::Existing {
+                nonce: account.nonce,
+                balance: account.balance,
+                is_contract: account.is_contract,
+                code_hash,
+                storage: account.storage.clone(),
+            },
+            None => Preimage::Missing,
+        };
+        self.preimages.insert(*addr, preimage);
+    }
+
+    /// Compares every recorded pre-image with the current accounts.
+    pub fn diff(&self, accounts: &BTreeMap<Address, Account>) -> StateDiff {
+        static EMPTY: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        let mut diff = StateDiff::default();
+        for (addr, preimage) in &self.preimages {
+            let current = accounts.get(addr);
+            let (account_changed, before) = match (preimage, current) {
+                (Preimage::Missing, None) => continue,
+                (Preimage::Missing, Some(_)) => (true, &EMPTY),
+                (
+                    Preimage::Existing {
+                        nonce,
+                        balance,
+                        is_contract,
+                        code_hash: before_code,
+                        storage,
+                    },
+                    Some(account),
+                ) => (
+                    *nonce != account.nonce
+                        || *balance != account.balance
+                        || *is_contract != account.is_contract
+                        || *before_code != code_hash(&account.code),
+                    storage,
+                ),
+                (Preimage::Existing { storage, .. }, None) => (true, storage),
+            };
+            if account_changed {
+                diff.accounts_changed.push(*addr);
+            }
+            let after = current.map_or(&EMPTY, |account| &account.storage);
+            for (key, value) in after {
+                if before.get(key) != Some(value) {
+                    diff.storage_changed.push((*addr, key.clone()));
+                }
+            }
+            for key in before.keys() {
+                if !after.contains_key(key) {
+                    diff.storage_changed.push((*addr, key.clone()));
+                }
+            }
+        }
+        diff
+    }
+}
@@ -0,0 +1,286 @@
+//! JSON import/export for `State`.
+//!
+//! The schema is meant for fixtures and golden files, not for the guest path
+//! (which keeps using the binary `encode`/`decode`):
+//!
+//! ```text
+//! {
+//!   "accounts": {
+//!     "<address hex>": {
+//!       "nonce": 1,
+//!       "balance": "1000",
+//!       "is_contract": true,
+//!       "code": "<hex>",
+//!       "storage": { "<key bytes hex>": "<value hex>" }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Balances are decimal strings because u128 does not fit a JSON number.
+//! Only the subset of JSON needed by this schema is parsed: objects, strings
+//! without escapes other than `\"` and `\\`, unsigned integers and booleans.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use types::address::Address;
+
+use crate::{Account, State};
+
+impl State {
+    /// Render the state as pretty-printed JSON (see module docs for the schema).
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\n  \"accounts\": {");
+        for (i, (addr, acc)) in self.iter_accounts().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "\n    \"{}\": {{", hex_encode(&addr.0));
+            let _ = write!(out, "\n      \"nonce\": {},", acc.nonce);
+            let _ = write!(out, "\n      \"balance\": \"{}\",", acc.balance);
+            let _ = write!(out, "\n      \"is_contract\": {},", acc.is_contract);
+            let _ = write!(out, "\n      \"code\": \"{}\",", hex_encode(&acc.code));
+            out.push_str("\n      \"storage\": {");
+            for (j, (k, v)) in acc.storage.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                let _ = write!(
+                    out,
+                    "\n        \"{}\": \"{}\"",
+                    hex_encode(k.as_bytes()),
+                    hex_encode(v)
+                );
+            }
+            if !acc.storage.is_empty() {
+                out.push_str("\n      ");
+            }
+            out.push_str("}\n    }");
+        }
+        if self.account_count() > 0 {
+            out.push_str("\n  ");
+        }
+        out.push_str("}\n}\n");
+        out
+    }
+
+    /// Parse state produced by `to_json` (or a hand-written file in the same schema).
+    /// Returns None on malformed input.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let mut parser = Parser {
+            bytes: json.as_bytes(),
+            pos: 0,
+        };
+        let root = parser.parse_value()?;
+        parser.skip_ws();
+        if parser.pos != parser.bytes.len() {
+            return None;
+        }
+
+        let mut accounts = BTreeMap::new();
+        for (addr_hex, acc_value) in root.field("accounts")?.as_object()? {
+            let addr_bytes = hex_decode(addr_hex)?;
+            if addr_bytes.len() != 20 {
+                return None;
+            }
+            let mut addr = [0u8; 20];
+            addr.copy_from_slice(&addr_bytes);
+
+            let mut storage = BTreeMap::new();
+            for (key_hex, val) in acc_value.field("storage")?.as_object()? {
+                let key = String::from_utf8(hex_decode(key_hex)?).ok()?;
+                storage.insert(key, hex_decode(val.as_str()?)?);
+            }
+
+            let account = Account {
+                nonce: acc_value.field("nonce")?.as_number()?,
+                balance: acc_value.field("balance")?.as_str()?.parse().ok()?,
+                code: hex_decode(acc_value.field("code")?.as_str()?)?,
+                is_contract: acc_value.field("is_contract")?.as_bool()?,
+                storage,
+            };
+            if accounts.insert(Address(addr), account).is_some() {
+                return None;
+            }
+        }
+        let mut state = Self::new();
+        state.accounts = accounts;
+        Some(state)
+    }
+}
+
+enum Value {
+    Object(Vec<(String, Value)>),
+    Str(String),
+    Number(u64),
+    Bool(bool),
+}
+
+impl Value {
+    fn field(&self, name: &str) -> Option<&Value> {
+        self.as_object()?
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    fn as_object(&self) -> Option<&Vec<(String, Value)>> {
+        match self {
+            Value::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while let Some(b) = self.bytes.get(self.pos) {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.peek()? != byte {
+            return None;
+        }
+        self.pos += 1;
+        Some(())
+    }
+
+    fn eat_literal(&mut self, literal: &[u8]) -> bool {
+        if self.bytes[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<Value> {
+        match self.peek()? {
+            b'{' => self.parse_object(),
+            b'"' => self.parse_string().map(Value::Str),
+            b'0'..=b'9' => self.parse_number(),
+            _ if self.eat_literal(b"true") => Some(Value::Bool(true)),
+            _ if self.eat_literal(b"false") => Some(Value::Bool(false)),
+            _ => None,
+        }
+    }
+
+    fn parse_object(&mut self) -> Option<Value> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Some(Value::Object(fields));
+        }
+        loop {
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b'}' => {
+                    self.pos += 1;
+                    return Some(Value::Object(fields));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            let b = *self.bytes.get(self.pos)?;
+            self.pos += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self.bytes.get(self.pos)?;
+                    self.pos += 1;
+                    match escaped {
+                        b'"' | b'\\' => out.push(escaped),
+                        _ => return None,
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+        String::from_utf8(out).ok()
+    }
+
+    fn parse_number(&mut self) -> Option<Value> {
+        let start = self.pos;
+        while matches!(self.bytes.get(self.pos), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        let digits = core::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+        digits.parse().ok().map(Value::Number)
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        out.push(HEX[(b >> 4) as usize] as char);
+        out.push(HEX[(b & 0x0f) as usize] as char);
+    }
+    out
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    let bytes = hex.as_bytes();
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            Some(((hi << 4) | lo) as u8)
+        })
+        .collect()
+}
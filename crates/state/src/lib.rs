@@ -3,9 +3,18 @@
 extern crate alloc;
 
 pub mod account;
+pub mod create2;
+pub mod entropy;
+pub mod fuzz;
+pub mod journal;
+pub mod json;
+pub mod patch;
 pub mod state;
 pub mod types;
 
 pub use account::*;
+pub use create2::*;
+pub use entropy::{entropy_seed, Entropy};
+pub use journal::{StateDiff, StateJournal};
 pub use state::*;
 pub use types::*;
@@ -0,0 +1,211 @@
+//! Binary state patches: the accounts and storage keys that differ between two
+//! states, for shipping updates without a full `encode`.
+//!
+//! ```text
+//! patch   := count:u32 entry*
+//! entry   := address:[u8; 20] tag:u8 body
+//!   tag 0 := (account removed, no body)
+//!   tag 1 := balance:u128 nonce:u64 is_contract:u8 code storage
+//! code    := 0u8 (unchanged) | 1u8 len:u32 bytes
+//! storage := count:u32 (key_len:u32 key op)*
+//! op      := 0u8 (key removed) | 1u8 len:u32 value
+//! ```
+//!
+//! Integers are little-endian, as in `State::encode`. Entries are sorted by
+//! address and storage ops by key, so equal diffs encode to equal bytes.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use types::address::Address;
+
+use crate::{Account, State};
+
+const TAG_REMOVED: u8 = 0;
+const TAG_UPSERT: u8 = 1;
+const UNCHANGED: u8 = 0;
+const CHANGED: u8 = 1;
+
+impl State {
+    /// Encode what changed from `base` to `self` (see module docs for the format).
+    /// Applying the result to `base` with `apply_diff` reproduces `self`.
+    pub fn diff_encode(&self, base: &State) -> Vec<u8> {
+        static EMPTY: Account = Account {
+            nonce: 0,
+            balance: 0,
+            code: Vec::new(),
+            is_contract: false,
+            storage: BTreeMap::new(),
+        };
+        let mut entries = 0u32;
+        let mut out = Vec::new();
+        out.extend_from_slice(&0u32.to_le_bytes());
+
+        for addr in base.accounts.keys() {
+            if !self.accounts.contains_key(addr) {
+                out.extend_from_slice(&addr.0);
+                out.push(TAG_REMOVED);
+                entries += 1;
+            }
+        }
+
+        for (addr, after) in &self.accounts {
+            let before = base.accounts.get(addr);
+            let before_or_empty = before.unwrap_or(&EMPTY);
+            let storage_ops: BTreeMap<&String, Option<&Vec<u8>>> = after
+                .storage
+                .iter()
+                .filter(|(key, value)| before_or_empty.storage.get(*key) != Some(*value))
+                .map(|(key, value)| (key, Some(value)))
+                .chain(
+                    before_or_empty
+                        .storage
+                        .keys()
+                        .filter(|key| !after.storage.contains_key(*key))
+                        .map(|key| (key, None)),
+                )
+                .collect();
+            let code_changed = after.code != before_or_empty.code;
+            let unchanged = before.is_some()
+                && !code_changed
+                && storage_ops.is_empty()
+                && after.nonce == before_or_empty.nonce
+                && after.balance == before_or_empty.balance
+                && after.is_contract == before_or_empty.is_contract;
+            if unchanged {
+                continue;
+            }
+
+            out.extend_from_slice(&addr.0);
+            out.push(TAG_UPSERT);
+            out.extend_from_slice(&after.balance.to_le_bytes());
+            out.extend_from_slice(&after.nonce.to_le_bytes());
+            out.push(after.is_contract as u8);
+            if code_changed {
+                out.push(CHANGED);
+                push_bytes(&mut out, &after.code);
+            } else {
+                out.push(UNCHANGED);
+            }
+            out.extend_from_slice(&(storage_ops.len() as u32).to_le_bytes());
+            for (key, value) in storage_ops {
+                push_bytes(&mut out, key.as_bytes());
+                match value {
+                    Some(value) => {
+                        out.push(CHANGED);
+                        push_bytes(&mut out, value);
+                    }
+                    None => out.push(UNCHANGED),
+                }
+            }
+            entries += 1;
+        }
+
+        out[..4].copy_from_slice(&entries.to_le_bytes());
+        out
+    }
+
+    /// Apply a patch produced by `diff_encode`. Returns `None` without changing
+    /// anything when `diff` is malformed or has trailing bytes.
+    ///
+    /// Changes are written straight to the accounts, bypassing any open checkpoint
+    /// or diff journal.
+    pub fn apply_diff(&mut self, diff: &[u8]) -> Option<()> {
+        let mut reader = Reader {
+            bytes: diff,
+            pos: 0,
+        };
+        let count = reader.u32()?;
+        let mut patched = Vec::new();
+        for _ in 0..count {
+            let addr = Address(reader.take(20)?.try_into().ok()?);
+            match reader.u8()? {
+                TAG_REMOVED => patched.push((addr, None)),
+                TAG_UPSERT => {
+                    let mut account = self.accounts.get(&addr).cloned().unwrap_or(Account {
+                        nonce: 0,
+                        balance: 0,
+                        code: Vec::new(),
+                        is_contract: false,
+                        storage: BTreeMap::new(),
+                    });
+                    account.balance = u128::from_le_bytes(reader.take(16)?.try_into().ok()?);
+                    account.nonce = u64::from_le_bytes(reader.take(8)?.try_into().ok()?);
+                    account.is_contract = reader.flag()?;
+                    if reader.flag()? {
+                        account.code = reader.len_prefixed()?.to_vec();
+                    }
+                    for _ in 0..reader.u32()? {
+                        let key = core::str::from_utf8(reader.len_prefixed()?)
+                            .ok()?
+                            .to_string();
+                        if reader.flag()? {
+                            account.storage.insert(key, reader.len_prefixed()?.to_vec());
+                        } else {
+                            account.storage.remove(&key);
+                        }
+                    }
+                    patched.push((addr, Some(account)));
+                }
+                _ => return None,
+            }
+        }
+        if reader.pos != diff.len() {
+            return None;
+        }
+
+        for (addr, account) in patched {
+            match account {
+                Some(account) => {
+                    self.accounts.insert(addr, account);
+                }
+                None => {
+                    self.accounts.remove(&addr);
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let slice = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        match self.u8()? {
+            UNCHANGED => Some(false),
+            CHANGED => Some(true),
+            _ => None,
+        }
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn len_prefixed(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
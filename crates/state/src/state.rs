@@ -1,9 +1,43 @@
-use crate::Account;
+use crate::journal::{Preimage, StateDiff, StateJournal};
+use crate::{code_hash, Account};
 use alloc::collections::BTreeMap;
 use alloc::string::ToString;
 use alloc::vec::Vec;
 use types::address::Address;
 
+/// Why a native balance transfer was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferError {
+    /// The sender balance is lower than the transferred value.
+    InsufficientFunds,
+    /// Crediting the recipient would overflow its balance.
+    RecipientOverflow,
+    /// The sender has no account.
+    SenderMissing,
+}
+
+impl TransferError {
+    /// Syscall return code for this error. `0` is success and `1` is reserved
+    /// for malformed requests, so state errors start at `2`.
+    pub const fn code(self) -> u32 {
+        match self {
+            TransferError::InsufficientFunds => 2,
+            TransferError::RecipientOverflow => 3,
+            TransferError::SenderMissing => 4,
+        }
+    }
+
+    /// Inverse of [`TransferError::code`].
+    pub const fn from_code(code: u32) -> Option<Self> {
+        match code {
+            2 => Some(TransferError::InsufficientFunds),
+            3 => Some(TransferError::RecipientOverflow),
+            4 => Some(TransferError::SenderMissing),
+            _ => None,
+        }
+    }
+}
+
 /// Represents the global state of the blockchain virtual machine.
 ///
 /// EDUCATIONAL PURPOSE: This struct manages all accounts in the blockchain,
@@ -46,6 +80,14 @@ pub struct State {
     /// entire blockchain state. Each entry contains an account with its
     /// balance, code, storage, and other metadata.
     pub accounts: BTreeMap<Address, Account>,
+
+    /// Pre-images of accounts modified since `checkpoint`.
+    /// `None` while no checkpoint is open.
+    journal: Option<StateJournal>,
+
+    /// Pre-images of accounts modified since `begin_diff`, kept across
+    /// checkpoints. `None` while no diff is being recorded.
+    diff_journal: Option<StateJournal>,
 }
 
 impl State {
@@ -60,6 +102,8 @@ impl State {
     pub fn new() -> Self {
         Self {
             accounts: BTreeMap::new(),
+            journal: None,
+            diff_journal: None,
         }
     }
 
@@ -85,6 +129,24 @@ impl State {
         self.accounts.get(addr).map(|acc| acc.balance).unwrap_or(0)
     }
 
+    /// Iterates accounts in ascending address order (byte-wise), independent of
+    /// how they were inserted. Encoding, JSON export and diffs rely on this order.
+    pub fn iter_accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.accounts.iter()
+    }
+
+    /// Number of accounts in the state.
+    pub fn account_count(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Storage bytes across all accounts (see `Account::storage_bytes`).
+    pub fn total_storage_bytes(&self) -> usize {
+        self.iter_accounts().fold(0usize, |acc, (_, account)| {
+            acc.saturating_add(account.storage_bytes())
+        })
+    }
+
     /// Retrieves an account by address (mutable reference), creating it if it doesn't exist.
     ///
     /// EDUCATIONAL PURPOSE: This demonstrates account creation on-demand.
@@ -118,6 +180,7 @@ impl State {
     ///
     /// RETURNS: Mutable reference to the account (guaranteed to exist)
     pub fn get_account_mut(&mut self, addr: &Address) -> &mut Account {
+        self.record_preimage(addr);
         self.accounts.entry(*addr).or_insert_with(|| Account {
             nonce: 0,                 // No transactions yet
             balance: 0,               // No initial balance
@@ -127,34 +190,168 @@ impl State {
         })
     }
 
-    /// Transfers native balance between accounts. Returns false on insufficient funds or overflow.
-    pub fn transfer(&mut self, from: &Address, to: &Address, value: u64) -> bool {
-        let amount = value as u128;
-        let from_balance = match self.get_account(from) {
-            Some(account) => account.balance,
+    /// Starts recording account pre-images so the changes that follow can be
+    /// undone with `revert` or kept with `commit`.
+    ///
+    /// ROLLBACK: Every mutation goes through `get_account_mut`, which saves an
+    /// account the first time it is touched after the checkpoint. Reverting
+    /// only restores those accounts instead of copying the whole state up front.
+    pub fn checkpoint(&mut self) {
+        self.journal = Some(StateJournal::new());
+    }
+
+    /// Keeps all changes made since `checkpoint` and stops recording.
+    pub fn commit(&mut self) {
+        self.journal = None;
+    }
+
+    /// Restores every account touched since `checkpoint` and stops recording.
+    /// Returns false when no checkpoint was open.
+    pub fn revert(&mut self) -> bool {
+        let journal = match self.journal.take() {
+            Some(journal) => journal,
             None => return false,
         };
-        if from_balance < amount {
-            return false;
+        for (addr, preimage) in journal.preimages {
+            match preimage {
+                Preimage::Missing => {
+                    self.accounts.remove(&addr);
+                }
+                Preimage::Existing {
+                    nonce,
+                    balance,
+                    is_contract,
+                    storage,
+                    ..
+                } => {
+                    if let Some(account) = self.accounts.get_mut(&addr) {
+                        account.nonce = nonce;
+                        account.balance = balance;
+                        account.is_contract = is_contract;
+                        account.storage = storage;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Starts recording which accounts and storage keys change, independently of
+    /// any checkpoint, until `finish_diff`.
+    ///
+    /// Changes undone by `revert` in the meantime drop out of the diff, since it
+    /// compares the recorded pre-images with the accounts as they end up.
+    pub fn begin_diff(&mut self) {
+        self.diff_journal = Some(StateJournal::new());
+    }
+
+    /// Stops recording and returns what changed since `begin_diff`.
+    /// Returns `None` when no diff was being recorded.
+    pub fn finish_diff(&mut self) -> Option<StateDiff> {
+        self.diff_journal
+            .take()
+            .map(|journal| journal.diff(&self.accounts))
+    }
+
+    fn record_preimage(&mut self, addr: &Address) {
+        let wanted =
+            |journal: &Option<StateJournal>| journal.as_ref().is_some_and(|j| j.wants(addr));
+        if !wanted(&self.journal) && !wanted(&self.diff_journal) {
+            return;
+        }
+        // Hash the code once for both journals.
+        let account = self.accounts.get(addr);
+        let hash = account.map_or([0u8; 32], |account| code_hash(&account.code));
+        if let Some(journal) = self.journal.as_mut() {
+            journal.record(addr, account, hash);
+        }
+        if let Some(journal) = self.diff_journal.as_mut() {
+            journal.record(addr, account, hash);
+        }
+    }
+
+    /// Transfers native balance between accounts. Returns false on any failure;
+    /// use `try_transfer` to learn why.
+    pub fn transfer(&mut self, from: &Address, to: &Address, value: u64) -> bool {
+        self.try_transfer(from, to, value).is_ok()
+    }
+
+    /// Transfers native balance between accounts, reporting why it was rejected.
+    /// Nothing is applied on error.
+    pub fn try_transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        value: u64,
+    ) -> Result<(), TransferError> {
+        let amount = value as u128;
+        match self.get_account(from) {
+            Some(account) if account.balance >= amount => {}
+            Some(_) => return Err(TransferError::InsufficientFunds),
+            None => return Err(TransferError::SenderMissing),
         }
         if from == to {
-            return true;
+            return Ok(());
         }
-        let to_balance = self.balance_of(to);
-        let new_to_balance = match to_balance.checked_add(amount) {
-            Some(balance) => balance,
-            None => return false,
+        if self.balance_of(to).checked_add(amount).is_none() {
+            return Err(TransferError::RecipientOverflow);
+        }
+
+        // Both sides were checked above, so neither step can fail after the other applied.
+        self.get_account_mut(from)
+            .debit(amount)
+            .map_err(|()| TransferError::InsufficientFunds)?;
+        self.get_account_mut(to)
+            .credit(amount)
+            .map_err(|()| TransferError::RecipientOverflow)
+    }
+
+    /// Transfers native balance from one sender to many recipients, all or nothing.
+    ///
+    /// The running total is checked against the sender balance once and every
+    /// recipient is looked up once (repeated recipients are merged), so an airdrop of
+    /// `n` transfers avoids the repeated lookups of calling `transfer` `n` times.
+    ///
+    /// RETURNS: `Err(idx)` with the index of the first recipient that would overdraw
+    /// the sender or overflow its own balance; nothing is applied in that case.
+    pub fn transfer_many(
+        &mut self,
+        from: &Address,
+        recipients: &[(Address, u64)],
+    ) -> Result<(), usize> {
+        if recipients.is_empty() {
+            return Ok(());
+        }
+        let from_balance = match self.get_account(from) {
+            Some(account) => account.balance,
+            None => return Err(0),
         };
 
-        {
-            let from_account = self.get_account_mut(from);
-            from_account.balance = from_balance - amount;
+        let mut debited = 0u128;
+        let mut credited: BTreeMap<Address, u128> = BTreeMap::new();
+        for (idx, (to, value)) in recipients.iter().enumerate() {
+            let amount = *value as u128;
+            let total = debited
+                .checked_add(amount)
+                .filter(|total| *total <= from_balance)
+                .ok_or(idx)?;
+            if to == from {
+                // Self-transfers only need the funds to be present, like `transfer`.
+                continue;
+            }
+            debited = total;
+            let balance = match credited.get(to) {
+                Some(balance) => *balance,
+                None => self.balance_of(to),
+            };
+            credited.insert(*to, balance.checked_add(amount).ok_or(idx)?);
         }
-        {
-            let to_account = self.get_account_mut(to);
-            to_account.balance = new_to_balance;
+
+        self.get_account_mut(from).balance = from_balance - debited;
+        for (to, balance) in credited {
+            self.get_account_mut(&to).balance = balance;
         }
-        true
+        Ok(())
     }
 
     /// Checks if an address corresponds to a contract account.
@@ -178,16 +375,13 @@ impl State {
 
     /// Encode state into a byte buffer for guest consumption.
     pub fn encode(&self) -> alloc::vec::Vec<u8> {
-        let len = self.encoded_len();
-        let mut out = alloc::vec![0u8; len];
-        let _ = self.encode_into(&mut out);
-        out
+        encode_exact(self.encoded_len(), |out| self.write_encoding(out))
     }
 
     /// Returns the byte length of the encoded state.
     pub fn encoded_len(&self) -> usize {
         let mut total = 4usize; // account count
-        for (addr, acc) in &self.accounts {
+        for (addr, acc) in self.iter_accounts() {
             let mut acc_len = 0usize;
             acc_len = acc_len.saturating_add(addr.0.len());
             acc_len = acc_len.saturating_add(16); // balance
@@ -208,7 +402,19 @@ impl State {
     }
 
     /// Encode state into a provided buffer. Returns bytes written on success.
+    ///
+    /// Returns `None` without touching `out` when it is shorter than `encoded_len`,
+    /// so a failed call never leaves a partial encoding behind.
     pub fn encode_into(&self, out: &mut [u8]) -> Option<usize> {
+        if out.len() < self.encoded_len() {
+            return None;
+        }
+        self.write_encoding(out)
+    }
+
+    /// Writes the encoding into `out`, stopping with `None` at the first field that
+    /// does not fit.
+    fn write_encoding(&self, out: &mut [u8]) -> Option<usize> {
         let mut cursor = 0usize;
         let write = |buf: &mut [u8], cursor: &mut usize, bytes: &[u8]| -> Option<()> {
             if *cursor + bytes.len() > buf.len() {
@@ -219,10 +425,10 @@ impl State {
             Some(())
         };
 
-        let count = self.accounts.len() as u32;
+        let count = self.account_count() as u32;
         write(out, &mut cursor, &count.to_le_bytes())?;
 
-        for (addr, acc) in &self.accounts {
+        for (addr, acc) in self.iter_accounts() {
             write(out, &mut cursor, &addr.0)?;
             write(out, &mut cursor, &acc.balance.to_le_bytes())?;
             write(out, &mut cursor, &acc.nonce.to_le_bytes())?;
@@ -330,7 +536,11 @@ impl State {
             );
         }
 
-        Some(Self { accounts })
+        Some(Self {
+            accounts,
+            journal: None,
+            diff_journal: None,
+        })
     }
 }
 
@@ -339,3 +549,21 @@ impl Default for State {
         Self::new()
     }
 }
+
+/// Allocates exactly `len` bytes and fills them with `write`, which returns the bytes
+/// it wrote. `State::encode` sizes the buffer with `encoded_len` and writes it with a
+/// separate writer; a drift between the two would hand out a zero-padded or
+/// truncated encoding, so debug builds check the writer filled the buffer exactly.
+pub fn encode_exact(
+    len: usize,
+    write: impl FnOnce(&mut [u8]) -> Option<usize>,
+) -> alloc::vec::Vec<u8> {
+    let mut out = alloc::vec![0u8; len];
+    let written = write(&mut out);
+    debug_assert_eq!(
+        written,
+        Some(len),
+        "encoding length disagrees with the length it was sized for"
+    );
+    out
+}
@@ -0,0 +1,38 @@
+use state::State;
+use types::address::Address;
+
+const ALICE: Address = Address([0x11; 20]);
+
+#[test]
+fn credit_and_debit_adjust_balance() {
+    let mut state = State::new();
+    let account = state.get_account_mut(&ALICE);
+    assert_eq!(account.credit(100), Ok(()));
+    assert_eq!(account.debit(40), Ok(()));
+    assert_eq!(account.balance, 60);
+    assert_eq!(account.debit(60), Ok(()));
+    assert_eq!(account.balance, 0);
+}
+
+#[test]
+fn credit_overflow_leaves_balance_untouched() {
+    let mut state = State::new();
+    let account = state.get_account_mut(&ALICE);
+    account.balance = u128::MAX;
+    assert_eq!(account.credit(1), Err(()));
+    assert_eq!(account.balance, u128::MAX);
+    assert_eq!(account.credit(0), Ok(()));
+    assert_eq!(account.balance, u128::MAX);
+}
+
+#[test]
+fn debit_underflow_leaves_balance_untouched() {
+    let mut state = State::new();
+    let account = state.get_account_mut(&ALICE);
+    account.balance = 5;
+    assert_eq!(account.debit(6), Err(()));
+    assert_eq!(account.balance, 5);
+    account.balance = 0;
+    assert_eq!(account.debit(1), Err(()));
+    assert_eq!(account.balance, 0);
+}
@@ -0,0 +1,49 @@
+use state::State;
+use types::address::Address;
+
+const ALICE: Address = Address([0x11; 20]);
+const BOB: Address = Address([0x22; 20]);
+const CAROL: Address = Address([0x33; 20]);
+
+fn funded_state() -> State {
+    let mut state = State::new();
+    let alice = state.get_account_mut(&ALICE);
+    alice.balance = 100;
+    alice.storage.insert(String::from("key"), vec![1, 2, 3]);
+    state.get_account_mut(&BOB).balance = 5;
+    state
+}
+
+#[test]
+fn revert_restores_touched_accounts_and_drops_new_ones() {
+    let mut state = funded_state();
+    state.checkpoint();
+
+    assert!(state.transfer(&ALICE, &BOB, 40));
+    state
+        .get_account_mut(&ALICE)
+        .storage
+        .insert(String::from("key"), vec![9]);
+    state.get_account_mut(&CAROL).balance = 7;
+
+    assert!(state.revert());
+    assert_eq!(state.balance_of(&ALICE), 100);
+    assert_eq!(state.balance_of(&BOB), 5);
+    assert_eq!(
+        state.get_account(&ALICE).unwrap().storage.get("key"),
+        Some(&vec![1, 2, 3])
+    );
+    assert!(state.get_account(&CAROL).is_none());
+}
+
+#[test]
+fn commit_keeps_changes_and_closes_the_checkpoint() {
+    let mut state = funded_state();
+    state.checkpoint();
+    assert!(state.transfer(&ALICE, &BOB, 40));
+    state.commit();
+
+    assert!(!state.revert());
+    assert_eq!(state.balance_of(&ALICE), 60);
+    assert_eq!(state.balance_of(&BOB), 45);
+}
@@ -0,0 +1,29 @@
+use state::{create2_address, State};
+use types::address::Address;
+
+const DEPLOYER: Address = Address([0x11; 20]);
+const OTHER: Address = Address([0x22; 20]);
+
+#[test]
+fn same_inputs_derive_the_same_address() {
+    let salt = [7u8; 32];
+    let code = [0x13, 0x00, 0x00, 0x00];
+    let first = create2_address(&DEPLOYER, &salt, &code);
+    assert_eq!(first, create2_address(&DEPLOYER, &salt, &code));
+
+    let mut state = State::new();
+    state.get_account_mut(&first).code = code.to_vec();
+    assert!(state
+        .get_account(&create2_address(&DEPLOYER, &salt, &code))
+        .is_some());
+}
+
+#[test]
+fn each_input_changes_the_address() {
+    let salt = [7u8; 32];
+    let code = [0x13, 0x00, 0x00, 0x00];
+    let base = create2_address(&DEPLOYER, &salt, &code);
+    assert_ne!(base, create2_address(&OTHER, &salt, &code));
+    assert_ne!(base, create2_address(&DEPLOYER, &[8u8; 32], &code));
+    assert_ne!(base, create2_address(&DEPLOYER, &salt, &[0x13, 0, 0, 1]));
+}
@@ -0,0 +1,47 @@
+use state::{entropy_seed, Entropy, State};
+use types::address::Address;
+
+fn bytes(seed: &[u8; 32], tx_index: u32, len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; len];
+    Entropy::new(seed, tx_index).fill(&mut out);
+    out
+}
+
+fn funded_state() -> State {
+    let mut state = State::new();
+    state.get_account_mut(&Address([0x11; 20])).balance = 1000;
+    state
+}
+
+#[test]
+fn same_bundle_state_and_index_repeat() {
+    let seed = entropy_seed(&funded_state());
+    assert_eq!(seed, entropy_seed(&funded_state()));
+    assert_eq!(bytes(&seed, 3, 37), bytes(&seed, 3, 37));
+}
+
+#[test]
+fn index_and_state_change_the_stream() {
+    let seed = entropy_seed(&funded_state());
+    assert_ne!(bytes(&seed, 0, 32), bytes(&seed, 1, 32));
+    assert_ne!(seed, entropy_seed(&State::new()));
+    assert_ne!(
+        bytes(&seed, 0, 32),
+        bytes(&entropy_seed(&State::new()), 0, 32)
+    );
+}
+
+#[test]
+fn successive_fills_continue_the_stream() {
+    let seed = entropy_seed(&State::new());
+    let mut stream = Entropy::new(&seed, 0);
+    let mut first = [0u8; 16];
+    let mut second = [0u8; 16];
+    stream.fill(&mut first);
+    stream.fill(&mut second);
+    assert_ne!(first, second);
+
+    let whole = bytes(&seed, 0, 32);
+    assert_eq!(&whole[..16], &first);
+    assert_eq!(&whole[16..], &second);
+}
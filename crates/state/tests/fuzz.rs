@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use state::State;
+use types::address::Address;
+
+#[test]
+fn arbitrary_states_round_trip_and_keep_their_invariants() {
+    let mut populated = 0;
+    for seed in 0..256u64 {
+        let state = State::arbitrary(seed);
+        state
+            .check_invariants()
+            .unwrap_or_else(|why| panic!("seed {}: generated state: {}", seed, why));
+        populated += (state.account_count() > 0) as usize;
+
+        let encoded = state.encode();
+        let decoded = State::decode(&encoded).expect("decode generated state");
+        decoded
+            .check_invariants()
+            .unwrap_or_else(|why| panic!("seed {}: decoded state: {}", seed, why));
+        assert_eq!(decoded.encode(), encoded, "seed {}", seed);
+        assert_eq!(decoded.account_count(), state.account_count());
+    }
+    assert!(populated > 200, "only {} populated states", populated);
+}
+
+#[test]
+fn arbitrary_is_deterministic_per_seed() {
+    assert_eq!(State::arbitrary(7).encode(), State::arbitrary(7).encode());
+    assert_ne!(State::arbitrary(7).encode(), State::arbitrary(8).encode());
+}
+
+#[test]
+fn invariant_violations_are_reported() {
+    let addr = Address([0x11; 20]);
+    for (key, why) in [
+        ("plain", "no domain separator"),
+        (":00", "empty domain"),
+        ("Balances:abc", "odd-length"),
+        ("Balances:AB", "not lowercase hex"),
+    ] {
+        let mut state = State::new();
+        state
+            .get_account_mut(&addr)
+            .storage
+            .insert(key.into(), Vec::new());
+        let err = state.check_invariants().unwrap_err();
+        assert!(err.contains(why), "{}: {}", key, err);
+    }
+
+    let mut state = State::new();
+    state.get_account_mut(&addr).balance = u128::MAX;
+    state.get_account_mut(&Address([0x22; 20])).balance = 1;
+    assert!(state.check_invariants().unwrap_err().contains("overflows"));
+
+    let mut ok = State::new();
+    ok.get_account_mut(&addr).storage = BTreeMap::from([("Balances:".into(), vec![1])]);
+    assert_eq!(ok.check_invariants(), Ok(()));
+}
@@ -0,0 +1,38 @@
+use state::State;
+use types::address::Address;
+
+fn addr(first: u8, last: u8) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[0] = first;
+    bytes[19] = last;
+    Address(bytes)
+}
+
+#[test]
+fn iteration_is_ascending_by_address_bytes() {
+    let expected = [
+        addr(0x00, 0xff),
+        addr(0x01, 0x00),
+        addr(0x01, 0x02),
+        addr(0xfe, 0x00),
+    ];
+    let mut state = State::new();
+    for (i, a) in [expected[3], expected[1], expected[0], expected[2]]
+        .iter()
+        .enumerate()
+    {
+        state.get_account_mut(a).balance = i as u128;
+    }
+
+    let order: Vec<Address> = state.iter_accounts().map(|(a, _)| *a).collect();
+    assert_eq!(order, expected);
+    assert_eq!(state.account_count(), 4);
+    assert_eq!(state.iter_accounts().next().unwrap().1.balance, 2);
+}
+
+#[test]
+fn empty_state_has_no_accounts() {
+    let state = State::new();
+    assert_eq!(state.iter_accounts().count(), 0);
+    assert_eq!(state.account_count(), 0);
+}
@@ -0,0 +1,85 @@
+use state::{State, StateDiff};
+use types::address::Address;
+
+const ALICE: Address = Address([0x11; 20]);
+const BOB: Address = Address([0x22; 20]);
+const CONTRACT: Address = Address([0x33; 20]);
+
+fn funded_state() -> State {
+    let mut state = State::new();
+    state.get_account_mut(&ALICE).balance = 100;
+    state.get_account_mut(&BOB).balance = 5;
+    let contract = state.get_account_mut(&CONTRACT);
+    contract.is_contract = true;
+    contract.storage.insert(String::from("P:count"), vec![1]);
+    contract.storage.insert(String::from("P:owner"), vec![0x11]);
+    state
+}
+
+#[test]
+fn diff_lists_exactly_the_touched_balances_and_keys() {
+    let mut state = funded_state();
+    state.begin_diff();
+
+    assert!(state.transfer(&ALICE, &BOB, 40));
+    let contract = state.get_account_mut(&CONTRACT);
+    contract.storage.insert(String::from("P:count"), vec![2]);
+    // Rewriting a key with its current value is not a change.
+    contract.storage.insert(String::from("P:owner"), vec![0x11]);
+    contract.storage.insert(String::from("P:new"), vec![7]);
+
+    let diff = state.finish_diff().expect("diff was open");
+    assert_eq!(
+        diff,
+        StateDiff {
+            accounts_changed: vec![ALICE, BOB],
+            storage_changed: vec![
+                (CONTRACT, String::from("P:count")),
+                (CONTRACT, String::from("P:new")),
+            ],
+        }
+    );
+    assert!(state.finish_diff().is_none());
+}
+
+#[test]
+fn reverted_changes_drop_out_of_the_diff() {
+    let mut state = funded_state();
+    state.begin_diff();
+
+    assert!(state.transfer(&ALICE, &BOB, 10));
+    state.checkpoint();
+    state.get_account_mut(&CONTRACT).storage.remove("P:count");
+    state.get_account_mut(&Address([0x44; 20])).balance = 1;
+    assert!(state.revert());
+
+    let diff = state.finish_diff().expect("diff was open");
+    assert_eq!(diff.accounts_changed, vec![ALICE, BOB]);
+    assert!(diff.storage_changed.is_empty());
+}
+
+#[test]
+fn redeploying_code_lists_the_account() {
+    let mut state = funded_state();
+    state.get_account_mut(&CONTRACT).code = vec![0x13, 0x00, 0x00, 0x00];
+    state.begin_diff();
+
+    // Same length and flags, different bytes: only the code hash tells them apart.
+    state.get_account_mut(&CONTRACT).code = vec![0x73, 0x00, 0x10, 0x00];
+
+    let diff = state.finish_diff().expect("diff was open");
+    assert_eq!(diff.accounts_changed, vec![CONTRACT]);
+    assert!(diff.storage_changed.is_empty());
+}
+
+#[test]
+fn rewriting_the_same_code_is_not_a_change() {
+    let mut state = funded_state();
+    state.get_account_mut(&CONTRACT).code = vec![0x13, 0x00, 0x00, 0x00];
+    state.begin_diff();
+
+    state.get_account_mut(&CONTRACT).code = vec![0x13, 0x00, 0x00, 0x00];
+
+    let diff = state.finish_diff().expect("diff was open");
+    assert_eq!(diff, StateDiff::default());
+}
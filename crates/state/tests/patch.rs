@@ -0,0 +1,77 @@
+use state::State;
+use types::address::Address;
+
+const KEPT: Address = Address([0x01; 20]);
+const CHANGED: Address = Address([0x02; 20]);
+const REMOVED: Address = Address([0x03; 20]);
+const CREATED: Address = Address([0x04; 20]);
+
+fn base_state() -> State {
+    let mut state = State::new();
+    state.get_account_mut(&KEPT).balance = 10;
+    let changed = state.get_account_mut(&CHANGED);
+    changed.balance = 20;
+    changed.code = vec![0xaa; 8];
+    changed.is_contract = true;
+    changed.storage.insert("stay".into(), vec![1]);
+    changed.storage.insert("edit".into(), vec![2]);
+    changed.storage.insert("drop".into(), vec![3]);
+    state.get_account_mut(&REMOVED).balance = 30;
+    state
+}
+
+#[test]
+fn applying_a_diff_to_its_base_reproduces_the_target() {
+    let base = base_state();
+    let mut modified = base_state();
+    let changed = modified.get_account_mut(&CHANGED);
+    changed.nonce = 1;
+    changed.storage.insert("edit".into(), vec![9, 9]);
+    changed.storage.remove("drop");
+    changed.storage.insert("new".into(), vec![]);
+    modified.accounts.remove(&REMOVED);
+    let created = modified.get_account_mut(&CREATED);
+    created.code = vec![0xbb; 4];
+    created.is_contract = true;
+
+    let diff = modified.diff_encode(&base);
+    let mut patched = base_state();
+    patched.apply_diff(&diff).expect("apply diff");
+    assert_eq!(patched.encode(), modified.encode());
+
+    // Only CHANGED, REMOVED and CREATED are listed; KEPT is left out.
+    assert_eq!(diff[..4], 3u32.to_le_bytes());
+    assert!(diff.len() < modified.encode().len());
+}
+
+#[test]
+fn identical_states_diff_to_an_empty_patch() {
+    let base = base_state();
+    let diff = base_state().diff_encode(&base);
+    assert_eq!(diff, 0u32.to_le_bytes());
+
+    let mut patched = base_state();
+    patched.apply_diff(&diff).expect("apply empty diff");
+    assert_eq!(patched.encode(), base.encode());
+}
+
+#[test]
+fn malformed_diffs_leave_the_state_untouched() {
+    let base = base_state();
+    let mut modified = base_state();
+    modified.get_account_mut(&KEPT).balance = 11;
+    modified.accounts.remove(&REMOVED);
+    let diff = modified.diff_encode(&base);
+
+    let mut state = base_state();
+    for len in 0..diff.len() {
+        assert_eq!(state.apply_diff(&diff[..len]), None, "truncated to {len}");
+    }
+    let mut trailing = diff.clone();
+    trailing.push(0);
+    assert_eq!(state.apply_diff(&trailing), None);
+    let mut bad_tag = diff.clone();
+    bad_tag[24] = 7;
+    assert_eq!(state.apply_diff(&bad_tag), None);
+    assert_eq!(state.encode(), base.encode());
+}
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 use std::string::String;
 use std::vec::Vec;
 
-use state::{Account, State};
+use state::{encode_exact, Account, State};
 use types::address::Address;
 
 fn assert_account_eq(expected: &Account, actual: &Account) {
@@ -72,3 +72,141 @@ fn decode_zero_count_header_returns_empty_state() {
     let decoded = State::decode(&bytes).expect("decode zero-count header");
     assert!(decoded.accounts.is_empty());
 }
+
+#[test]
+fn json_round_trip_preserves_accounts() {
+    let mut state = State::new();
+    let mut storage = BTreeMap::new();
+    storage.insert(String::from("P:6b6579"), vec![0xde, 0xad, 0xbe, 0xef]);
+    storage.insert(String::from("empty"), Vec::new());
+    let contract = Account {
+        nonce: 7,
+        balance: u128::MAX,
+        code: vec![0x13, 0x00, 0x00, 0x00],
+        is_contract: true,
+        storage,
+    };
+    let user = Account {
+        nonce: 0,
+        balance: 0,
+        code: Vec::new(),
+        is_contract: false,
+        storage: BTreeMap::new(),
+    };
+    state.accounts.insert(Address([0xaa; 20]), contract.clone());
+    state.accounts.insert(Address([0x01; 20]), user.clone());
+
+    let json = state.to_json();
+    let decoded = State::from_json(&json).expect("parse exported json");
+    assert_eq!(decoded.accounts.len(), 2);
+    assert_account_eq(&contract, &decoded.accounts[&Address([0xaa; 20])]);
+    assert_account_eq(&user, &decoded.accounts[&Address([0x01; 20])]);
+    assert_eq!(decoded.to_json(), json);
+}
+
+#[test]
+fn json_fixture_matches_built_state() {
+    let fixture = r#"
+    {
+      "accounts": {
+        "0102030405060708090a0b0c0d0e0f1011121314": {
+          "nonce": 42,
+          "balance": "123456789",
+          "is_contract": true,
+          "code": "aabbcc",
+          "storage": { "6b6579": "deadbeef" }
+        },
+        "1111111111111111111111111111111111111111": {
+          "nonce": 0, "balance": "5", "is_contract": false, "code": "", "storage": {}
+        }
+      }
+    }"#;
+
+    let mut expected = State::new();
+    let mut storage = BTreeMap::new();
+    storage.insert(String::from("key"), vec![0xde, 0xad, 0xbe, 0xef]);
+    let addr = Address([
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        0x10, 0x11, 0x12, 0x13, 0x14,
+    ]);
+    let account = expected.get_account_mut(&addr);
+    account.nonce = 42;
+    account.balance = 123_456_789;
+    account.code = vec![0xaa, 0xbb, 0xcc];
+    account.is_contract = true;
+    account.storage = storage;
+    expected.get_account_mut(&Address([0x11; 20])).balance = 5;
+
+    let parsed = State::from_json(fixture).expect("parse fixture");
+    assert_eq!(parsed.encode(), expected.encode());
+}
+
+#[test]
+fn from_json_rejects_malformed_input() {
+    assert!(State::from_json("").is_none());
+    assert!(State::from_json("{\"accounts\": {}} trailing").is_none());
+    assert!(State::from_json("{\"accounts\": {\"zz\": {}}}").is_none());
+    let bad_balance = r#"{"accounts": {"1111111111111111111111111111111111111111":
+        {"nonce": 0, "balance": "-1", "is_contract": false, "code": "", "storage": {}}}}"#;
+    assert!(State::from_json(bad_balance).is_none());
+}
+
+fn populated_state() -> State {
+    let mut state = State::new();
+    let mut storage = BTreeMap::new();
+    storage.insert(String::from("key"), vec![0xde, 0xad]);
+    state.accounts.insert(
+        Address([0x22; 20]),
+        Account {
+            nonce: 3,
+            balance: 4,
+            code: vec![0x01, 0x02, 0x03],
+            is_contract: true,
+            storage,
+        },
+    );
+    state.get_account_mut(&Address([0x33; 20])).balance = 9;
+    state
+}
+
+#[test]
+fn encode_matches_encoded_len_and_encode_into() {
+    let state = populated_state();
+    let encoded = state.encode();
+    assert_eq!(encoded.len(), state.encoded_len());
+
+    // A larger buffer reports only the bytes actually written.
+    let mut buf = vec![0u8; encoded.len() + 8];
+    assert_eq!(state.encode_into(&mut buf), Some(encoded.len()));
+    assert_eq!(&buf[..encoded.len()], encoded.as_slice());
+}
+
+#[test]
+fn encode_into_short_buffer_writes_nothing() {
+    let state = populated_state();
+    for len in 0..state.encoded_len() {
+        let mut buf = vec![0xa5u8; len];
+        assert_eq!(state.encode_into(&mut buf), None, "buffer of {} bytes", len);
+        assert!(
+            buf.iter().all(|&b| b == 0xa5),
+            "partial encoding left in a {}-byte buffer",
+            len
+        );
+    }
+}
+
+#[test]
+fn encode_exact_accepts_a_writer_that_fills_its_length() {
+    let state = populated_state();
+    let encoded = encode_exact(state.encoded_len(), |out| state.encode_into(out));
+    assert_eq!(encoded, state.encode());
+}
+
+#[test]
+#[should_panic(expected = "encoding length disagrees")]
+fn encode_exact_catches_a_length_that_disagrees_with_the_writer() {
+    // An account whose length counts one byte its writer never writes, as if a field
+    // was added to `encoded_len` but not to the writer.
+    let state = populated_state();
+    encode_exact(state.encoded_len() + 1, |out| state.encode_into(out));
+}
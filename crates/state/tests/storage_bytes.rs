@@ -0,0 +1,32 @@
+use state::State;
+use types::address::Address;
+
+const ALICE: Address = Address([0x11; 20]);
+const BOB: Address = Address([0x22; 20]);
+
+#[test]
+fn storage_bytes_sums_keys_and_values() {
+    let mut state = State::new();
+    let alice = state.get_account_mut(&ALICE);
+    alice.storage.insert(String::from("P:aa"), vec![1, 2, 3]);
+    alice.storage.insert(String::from("P:bbbb"), vec![0; 16]);
+    alice.storage.insert(String::from("k"), Vec::new());
+    state
+        .get_account_mut(&BOB)
+        .storage
+        .insert(String::from("x"), vec![9; 5]);
+
+    assert_eq!(
+        state.get_account(&ALICE).unwrap().storage_bytes(),
+        7 + 22 + 1
+    );
+    assert_eq!(state.get_account(&BOB).unwrap().storage_bytes(), 6);
+    assert_eq!(state.total_storage_bytes(), 36);
+}
+
+#[test]
+fn empty_state_has_no_storage() {
+    let mut state = State::new();
+    state.get_account_mut(&ALICE).balance = 10;
+    assert_eq!(state.total_storage_bytes(), 0);
+}
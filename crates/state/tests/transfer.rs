@@ -0,0 +1,69 @@
+use state::{State, TransferError};
+use types::address::Address;
+
+const ALICE: Address = Address([0x11; 20]);
+const BOB: Address = Address([0x22; 20]);
+const CAROL: Address = Address([0x33; 20]);
+
+fn state_with(balances: &[(Address, u128)]) -> State {
+    let mut state = State::new();
+    for (addr, balance) in balances {
+        state.get_account_mut(addr).balance = *balance;
+    }
+    state
+}
+
+#[test]
+fn successful_transfer_moves_balance() {
+    let mut state = state_with(&[(ALICE, 100)]);
+    assert_eq!(state.try_transfer(&ALICE, &BOB, 40), Ok(()));
+    assert_eq!(state.balance_of(&ALICE), 60);
+    assert_eq!(state.balance_of(&BOB), 40);
+}
+
+#[test]
+fn insufficient_funds_is_reported() {
+    let mut state = state_with(&[(ALICE, 10)]);
+    assert_eq!(
+        state.try_transfer(&ALICE, &BOB, 11),
+        Err(TransferError::InsufficientFunds)
+    );
+    assert!(!state.transfer(&ALICE, &BOB, 11));
+    assert_eq!(state.balance_of(&ALICE), 10);
+    assert_eq!(state.balance_of(&BOB), 0);
+}
+
+#[test]
+fn recipient_overflow_is_reported() {
+    let mut state = state_with(&[(ALICE, 10), (BOB, u128::MAX - 5)]);
+    assert_eq!(
+        state.try_transfer(&ALICE, &BOB, 6),
+        Err(TransferError::RecipientOverflow)
+    );
+    assert_eq!(state.balance_of(&ALICE), 10);
+    assert_eq!(state.balance_of(&BOB), u128::MAX - 5);
+}
+
+#[test]
+fn missing_sender_is_reported() {
+    let mut state = State::new();
+    assert_eq!(
+        state.try_transfer(&CAROL, &BOB, 0),
+        Err(TransferError::SenderMissing)
+    );
+    assert!(state.get_account(&BOB).is_none());
+}
+
+#[test]
+fn error_codes_round_trip() {
+    for err in [
+        TransferError::InsufficientFunds,
+        TransferError::RecipientOverflow,
+        TransferError::SenderMissing,
+    ] {
+        assert!(err.code() > 1);
+        assert_eq!(TransferError::from_code(err.code()), Some(err));
+    }
+    assert_eq!(TransferError::from_code(0), None);
+    assert_eq!(TransferError::from_code(1), None);
+}
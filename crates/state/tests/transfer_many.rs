@@ -0,0 +1,56 @@
+use state::State;
+use types::address::Address;
+
+const SENDER: Address = Address([0x01; 20]);
+const ALICE: Address = Address([0x11; 20]);
+const BOB: Address = Address([0x22; 20]);
+
+fn state_with(balances: &[(Address, u128)]) -> State {
+    let mut state = State::new();
+    for (addr, balance) in balances {
+        state.get_account_mut(addr).balance = *balance;
+    }
+    state
+}
+
+#[test]
+fn credits_every_recipient_and_debits_the_total() {
+    let mut state = state_with(&[(SENDER, 100)]);
+    assert_eq!(
+        state.transfer_many(&SENDER, &[(ALICE, 10), (BOB, 20), (ALICE, 5)]),
+        Ok(())
+    );
+    assert_eq!(state.balance_of(&SENDER), 65);
+    assert_eq!(state.balance_of(&ALICE), 15);
+    assert_eq!(state.balance_of(&BOB), 20);
+}
+
+#[test]
+fn overflowing_recipient_applies_nothing() {
+    let mut state = state_with(&[(SENDER, 100), (BOB, u128::MAX - 5)]);
+    assert_eq!(
+        state.transfer_many(&SENDER, &[(ALICE, 10), (BOB, 6), (ALICE, 1)]),
+        Err(1)
+    );
+    assert_eq!(state.balance_of(&SENDER), 100);
+    assert_eq!(state.balance_of(&ALICE), 0);
+    assert_eq!(state.balance_of(&BOB), u128::MAX - 5);
+}
+
+#[test]
+fn reports_first_recipient_past_the_sender_balance() {
+    let mut state = state_with(&[(SENDER, 25)]);
+    assert_eq!(
+        state.transfer_many(&SENDER, &[(ALICE, 10), (BOB, 10), (ALICE, 10)]),
+        Err(2)
+    );
+    assert_eq!(state.balance_of(&SENDER), 25);
+    assert!(state.get_account(&ALICE).is_none());
+}
+
+#[test]
+fn missing_sender_fails_at_the_first_recipient() {
+    let mut state = State::new();
+    assert_eq!(state.transfer_many(&SENDER, &[(ALICE, 0)]), Err(0));
+    assert_eq!(state.transfer_many(&SENDER, &[]), Ok(()));
+}
@@ -3,6 +3,30 @@
 //! These types live in `types` so both sides agree on layout without
 //! introducing circular dependencies.
 
+/// Kernel log verbosity selected at boot. Each level includes the ones above it.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Failures only; the default for normal runs.
+    #[default]
+    Error = 0,
+    /// Bundle lifecycle milestones (boot, bundle start/finish).
+    Info = 1,
+    /// Per-transaction and per-task tracing (task prep, satp switches, results).
+    Debug = 2,
+}
+
+impl LogLevel {
+    /// Decode a raw `BootInfo::log_level`; unknown values saturate to `Debug`.
+    pub const fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::Error,
+            1 => Self::Info,
+            _ => Self::Debug,
+        }
+    }
+}
+
 /// Minimal boot information passed from the bootloader to the kernel.
 ///
 /// Fields are kept simple and `#[repr(C)]` so the bootloader can write this
@@ -24,6 +48,10 @@ pub struct BootInfo {
     pub va_base: u32,
     /// Size in bytes of the mapped VA window.
     pub va_len: u32,
+    /// Kernel log verbosity, a raw [`LogLevel`].
+    pub log_level: u32,
+    /// Bytes of physical memory mapped at `SV32_DIRECT_MAP_BASE`; 0 means no direct map.
+    pub direct_map_len: u32,
 }
 
 impl BootInfo {
@@ -44,6 +72,20 @@ impl BootInfo {
             next_free_ppn,
             va_base,
             va_len,
+            log_level: LogLevel::Error as u32,
+            direct_map_len: memory_size,
         }
     }
+
+    /// Same boot info with the kernel log verbosity set to `level`.
+    pub const fn with_log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = level as u32;
+        self
+    }
+
+    /// Same boot info with only the first `len` bytes of physical memory direct-mapped.
+    pub const fn with_direct_map_len(mut self, len: u32) -> Self {
+        self.direct_map_len = len;
+        self
+    }
 }
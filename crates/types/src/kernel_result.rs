@@ -1,5 +1,10 @@
 //! Kernel-to-bootloader handoff header for serialized receipts.
 
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::receipt::TransactionReceipt;
+
 /// Pointer + length describing kernel-owned output buffers.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
@@ -8,7 +13,146 @@ pub struct KernelResult {
     pub receipts_len: u32,
     pub state_ptr: u32,
     pub state_len: u32,
+    /// Kernel heap bytes allocated by the end of the run.
+    pub heap_used: u32,
+    /// Deepest kernel stack use observed, in bytes below the stack top.
+    pub stack_used: u32,
+    /// Aggregate outcome of the bundle the receipts belong to.
+    pub summary: BundleSummary,
+}
+
+/// Bundle-wide transaction counts and cost, so callers can check aggregate
+/// outcomes without decoding every receipt.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BundleSummary {
+    pub total: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    /// Instructions retired while the bundle ran, the VM's measure of gas.
+    pub total_gas: u64,
+}
+
+impl BundleSummary {
+    /// Tally receipt outcomes; `total_gas` is measured by the caller.
+    pub fn from_receipts(receipts: &[TransactionReceipt], total_gas: u64) -> Self {
+        let succeeded = receipts.iter().filter(|r| r.result.success).count() as u32;
+        let total = receipts.len() as u32;
+        BundleSummary {
+            total,
+            succeeded,
+            failed: total - succeeded,
+            total_gas,
+        }
+    }
+
+    /// Decode a summary from its in-memory (`repr(C)`, little-endian) layout.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let u32_at = |offset: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(
+                bytes.get(offset..offset + 4)?.try_into().ok()?,
+            ))
+        };
+        let gas = core::mem::offset_of!(BundleSummary, total_gas);
+        Some(BundleSummary {
+            total: u32_at(core::mem::offset_of!(BundleSummary, total))?,
+            succeeded: u32_at(core::mem::offset_of!(BundleSummary, succeeded))?,
+            failed: u32_at(core::mem::offset_of!(BundleSummary, failed))?,
+            total_gas: u64::from_le_bytes(bytes.get(gas..gas + 8)?.try_into().ok()?),
+        })
+    }
 }
 
 /// Kernel VA where the handoff header is written.
 pub const KERNEL_RESULT_ADDR: u32 = 0x100;
+
+/// First word of a crash record. It takes the place of `KernelResult::receipts_ptr`,
+/// which always points inside the kernel window and so can never equal it.
+pub const KERNEL_CRASH_MAGIC: u32 = u32::from_le_bytes(*b"KPNC");
+/// Longest panic message kept in a crash record; longer messages are truncated.
+pub const KERNEL_CRASH_MESSAGE_MAX: usize = 256;
+/// `KernelCrash::tx_index` when the kernel panicked outside any transaction.
+pub const KERNEL_CRASH_NO_TX: u32 = u32::MAX;
+
+/// What the kernel panic handler leaves at `KERNEL_RESULT_ADDR` instead of a
+/// `KernelResult` header: `magic:u32 tx_index:u32 message_len:u32 message NUL`,
+/// little-endian. The NUL lets the host read the message as a C string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KernelCrash {
+    /// Bundle transaction being executed, or `KERNEL_CRASH_NO_TX`.
+    pub tx_index: u32,
+    /// The panic message, as formatted by the handler.
+    pub message: Vec<u8>,
+}
+
+impl KernelCrash {
+    /// Bytes of the record header before the message.
+    pub const HEADER_LEN: usize = 12;
+    /// Largest record `encode_into` writes: header, message and its NUL.
+    pub const MAX_LEN: usize = Self::HEADER_LEN + KERNEL_CRASH_MESSAGE_MAX + 1;
+
+    /// Write the record for `message` into `out` without allocating, truncating the
+    /// message to `KERNEL_CRASH_MESSAGE_MAX` and to what fits (never inside a UTF-8
+    /// sequence). Returns the bytes written, or 0 when `out` cannot hold the header
+    /// and the NUL.
+    pub fn encode_into(tx_index: u32, message: &[u8], out: &mut [u8]) -> usize {
+        if out.len() < Self::HEADER_LEN + 1 {
+            return 0;
+        }
+        let mut len = message
+            .len()
+            .min(KERNEL_CRASH_MESSAGE_MAX)
+            .min(out.len() - Self::HEADER_LEN - 1);
+        while len > 0 && len < message.len() && message[len] & 0xc0 == 0x80 {
+            len -= 1;
+        }
+        out[0..4].copy_from_slice(&KERNEL_CRASH_MAGIC.to_le_bytes());
+        out[4..8].copy_from_slice(&tx_index.to_le_bytes());
+        out[8..12].copy_from_slice(&(len as u32).to_le_bytes());
+        out[Self::HEADER_LEN..Self::HEADER_LEN + len].copy_from_slice(&message[..len]);
+        out[Self::HEADER_LEN + len] = 0;
+        Self::HEADER_LEN + len + 1
+    }
+
+    /// Decode just the record header as `(tx_index, message_len)`; `None` when
+    /// `bytes` does not start with a crash record.
+    pub fn decode_header(bytes: &[u8]) -> Option<(u32, usize)> {
+        let u32_at = |offset: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(
+                bytes.get(offset..offset + 4)?.try_into().ok()?,
+            ))
+        };
+        if u32_at(0)? != KERNEL_CRASH_MAGIC {
+            return None;
+        }
+        let len = u32_at(8)? as usize;
+        if len > KERNEL_CRASH_MESSAGE_MAX {
+            return None;
+        }
+        Some((u32_at(4)?, len))
+    }
+
+    /// Decode a crash record from the start of the result region; `None` when the
+    /// region holds anything else (such as a normal `KernelResult`).
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let (tx_index, len) = Self::decode_header(bytes)?;
+        Some(KernelCrash {
+            tx_index,
+            message: bytes
+                .get(Self::HEADER_LEN..Self::HEADER_LEN + len)?
+                .to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for KernelCrash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = core::str::from_utf8(&self.message).unwrap_or("<non-utf8 message>");
+        write!(f, "kernel panicked: {message}")?;
+        if self.tx_index == KERNEL_CRASH_NO_TX {
+            write!(f, " outside any transaction")
+        } else {
+            write!(f, " during tx {}", self.tx_index)
+        }
+    }
+}
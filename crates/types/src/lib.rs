@@ -18,10 +18,10 @@ pub mod transaction;
 pub use transaction::*;
 
 pub mod receipt;
-pub use receipt::TransactionReceipt;
+pub use receipt::{LogEntry, StateChangeSummary, TransactionReceipt};
 
 pub mod kernel_result;
-pub use kernel_result::KernelResult;
+pub use kernel_result::{BundleSummary, KernelCrash, KernelResult};
 
 pub mod boot;
 pub use boot::BootInfo;
@@ -29,6 +29,9 @@ pub use boot::BootInfo;
 pub mod mmu;
 pub use mmu::*;
 
+pub mod program_image;
+pub use program_image::ProgramImageHeader;
+
 // used for serialization
 pub trait SerializeField {
     /// Appends `self` into `buf` at `*offset`, advancing the offset.
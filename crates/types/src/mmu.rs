@@ -5,6 +5,8 @@ use core::mem;
 
 /// Sv32 page size in bytes (4 KiB).
 pub const SV32_PAGE_SIZE: usize = 4096;
+/// Sv32 megapage size in bytes (4 MiB): the span of one L1 leaf PTE.
+pub const SV32_MEGAPAGE_SIZE: usize = 4 * 1024 * 1024;
 /// Number of bits in a VPN index.
 pub const SV32_VPN_MASK: u32 = 0x3ff;
 
@@ -130,6 +132,95 @@ pub fn map_to_physical<T: Sv32PageTable>(
     )
 }
 
+/// Map the 4 MiB megapage at `va` to `phys` with a single L1 leaf PTE.
+///
+/// Both addresses must be megapage aligned. Returns false, leaving the table
+/// unchanged, when they are not or when the L1 entry is already in use (by an
+/// L2 table or another megapage).
+pub fn map_megapage<T: Sv32PageTable>(
+    pt: &T,
+    root_ppn: u32,
+    va: u32,
+    phys: u32,
+    perms: Sv32PagePerms,
+) -> bool {
+    let aligned = |addr: u32| (addr as usize).is_multiple_of(SV32_MEGAPAGE_SIZE);
+    if !aligned(va) || !aligned(phys) {
+        return false;
+    }
+    let vpn1 = (va >> 22) & SV32_VPN_MASK;
+    let root_base = match (root_ppn as usize).checked_mul(pt.page_size()) {
+        Some(base) => base,
+        None => return false,
+    };
+    let l1_entry_addr = root_base + vpn1 as usize * mem::size_of::<u32>();
+    match pt.read_pte(l1_entry_addr) {
+        Some(pte) if pte & SV32_PTE_V == 0 => {}
+        _ => return false,
+    }
+    let ppn = phys / pt.page_size() as u32;
+    pt.write_pte(l1_entry_addr, (ppn << 10) | perms.to_pte_flags());
+    true
+}
+
+/// Rewrite the permission bits of an already-mapped virtual range in place.
+///
+/// Leaf PPNs are preserved, so the physical backing is untouched. Every page
+/// in the range is checked before any PTE is written; returns false (with no
+/// changes) if a page is unmapped, sits under a superpage, or the range overflows.
+pub fn protect_range<T: Sv32PageTable>(
+    pt: &T,
+    root_ppn: u32,
+    va_start: u32,
+    len: usize,
+    perms: Sv32PagePerms,
+) -> bool {
+    if len == 0 {
+        return true;
+    }
+
+    let page_size = pt.page_size();
+    let start = align_down(va_start as usize, page_size);
+    let end = match (va_start as usize).checked_add(len) {
+        Some(v) => align_up(v, page_size),
+        None => return false,
+    };
+
+    let all_mapped = (start..end).step_by(page_size).all(|va| {
+        leaf_entry_addr(pt, root_ppn, va as u32)
+            .and_then(|entry| pt.read_pte(entry))
+            .is_some_and(|pte| pte & SV32_PTE_V != 0)
+    });
+    if !all_mapped {
+        return false;
+    }
+
+    for va in (start..end).step_by(page_size) {
+        if let Some(entry) = leaf_entry_addr(pt, root_ppn, va as u32) {
+            let ppn = pt.read_pte(entry).unwrap_or(0) >> 10;
+            pt.write_pte(entry, (ppn << 10) | perms.to_pte_flags());
+        }
+    }
+    true
+}
+
+/// Physical address of the L2 PTE for `va`, if its L1 entry points at a table.
+fn leaf_entry_addr<T: Sv32PageTable>(pt: &T, root_ppn: u32, va: u32) -> Option<usize> {
+    let page_size = pt.page_size();
+    let vpn1 = (va >> 22) & SV32_VPN_MASK;
+    let vpn0 = (va >> 12) & SV32_VPN_MASK;
+
+    let root_base = (root_ppn as usize).checked_mul(page_size)?;
+    let l1_pte = pt.read_pte(root_base + vpn1 as usize * mem::size_of::<u32>())?;
+    if l1_pte & SV32_PTE_V == 0 || l1_pte & (SV32_PTE_R | SV32_PTE_W | SV32_PTE_X) != 0 {
+        return None;
+    }
+    let l2_base = usize::try_from(l1_pte >> 10)
+        .ok()
+        .and_then(|ppn| ppn.checked_mul(page_size))?;
+    Some(l2_base + vpn0 as usize * mem::size_of::<u32>())
+}
+
 #[derive(Clone, Copy)]
 enum LeafStrategy {
     Allocate,
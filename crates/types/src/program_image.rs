@@ -0,0 +1,63 @@
+//! Header for program images linked at a nonzero base address.
+//!
+//! Account code is normally a flat image copied to user VA 0, with the entry
+//! point at its first non-zero byte. An image whose loadable segments start
+//! elsewhere is prefixed with this header instead, so the kernel can place it
+//! at its link address without zero-padding the bottom of the window.
+//!
+//! A flat image is assumed to end with its last byte. Programs with `.data` or
+//! `.bss` are deployed with a header too, whose `mem_end` covers the zero-filled
+//! tail the body leaves out, so the kernel sizes the window for the whole image.
+
+use alloc::vec::Vec;
+
+/// Upper bound for program text + data bytes in a user image.
+pub const CODE_SIZE_LIMIT: usize = 0x30000;
+/// Reserved space for read-only data in the user window.
+pub const RO_DATA_SIZE_LIMIT: usize = 0x2000;
+
+/// Leading bytes that mark a headered image.
+pub const PROGRAM_IMAGE_MAGIC: [u8; 4] = *b"AVMI";
+
+/// Encoded header size: magic, base, entry, mem_end.
+pub const PROGRAM_IMAGE_HEADER_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgramImageHeader {
+    /// User VA where the first byte of the image body is loaded.
+    pub base: u32,
+    /// User VA of the entry point; must fall inside the body.
+    pub entry: u32,
+    /// User VA one past the last byte the program uses, including `.bss` that is
+    /// not part of the body; at least `base` + body length.
+    pub mem_end: u32,
+}
+
+impl ProgramImageHeader {
+    /// Prefix `body` with this header.
+    pub fn encode(&self, body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PROGRAM_IMAGE_HEADER_LEN + body.len());
+        out.extend_from_slice(&PROGRAM_IMAGE_MAGIC);
+        out.extend_from_slice(&self.base.to_le_bytes());
+        out.extend_from_slice(&self.entry.to_le_bytes());
+        out.extend_from_slice(&self.mem_end.to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// Split a headered image into its header and body. Returns `None` for flat images.
+    pub fn decode(code: &[u8]) -> Option<(Self, &[u8])> {
+        if code.len() < PROGRAM_IMAGE_HEADER_LEN || code[..4] != PROGRAM_IMAGE_MAGIC {
+            return None;
+        }
+        let base = u32::from_le_bytes(code[4..8].try_into().ok()?);
+        let entry = u32::from_le_bytes(code[8..12].try_into().ok()?);
+        let mem_end = u32::from_le_bytes(code[12..16].try_into().ok()?);
+        let header = Self {
+            base,
+            entry,
+            mem_end,
+        };
+        Some((header, &code[PROGRAM_IMAGE_HEADER_LEN..]))
+    }
+}
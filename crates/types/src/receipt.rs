@@ -4,9 +4,28 @@ use alloc::vec::Vec;
 use core::convert::TryInto;
 use core::fmt;
 
+use crate::address::Address;
 use crate::result::Result;
 use crate::transaction::Transaction;
 
+/// A single event fired by a program during execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Address of the program that fired the event.
+    pub emitter: Address,
+
+    /// Raw event payload (32-byte id followed by the serialized fields).
+    pub data: Vec<u8>,
+}
+
+/// How many accounts and storage keys a transaction left changed
+/// (see `state::StateDiff`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateChangeSummary {
+    pub accounts_changed: u32,
+    pub storage_changed: u32,
+}
+
 /// Represents the result of a transaction execution.
 #[derive(Debug, Clone)]
 pub struct TransactionReceipt {
@@ -17,7 +36,14 @@ pub struct TransactionReceipt {
     pub result: Result,
 
     /// List of log entries generated during execution.
-    pub events: Vec<Vec<u8>>,
+    pub logs: Vec<LogEntry>,
+
+    /// Accounts and storage keys the transaction changed.
+    pub state_changes: StateChangeSummary,
+
+    /// Programs entered during execution, in call order (top-level call first,
+    /// then each nested call as it starts).
+    pub call_trace: Vec<Address>,
 }
 
 impl TransactionReceipt {
@@ -26,19 +52,21 @@ impl TransactionReceipt {
         TransactionReceipt {
             tx,
             result,
-            events: Vec::new(),
+            logs: Vec::new(),
+            state_changes: StateChangeSummary::default(),
+            call_trace: Vec::new(),
         }
     }
 
-    /// Adds an event to the receipt.
-    pub fn add_event(&mut self, event: Vec<u8>) -> &TransactionReceipt {
-        self.events.push(event);
+    /// Adds a log entry to the receipt.
+    pub fn add_log(&mut self, log: LogEntry) -> &TransactionReceipt {
+        self.logs.push(log);
         self
     }
 
-    /// Optionally add multiple events at once.
-    pub fn set_events(mut self, events: Vec<Vec<u8>>) -> Self {
-        self.events = events;
+    /// Optionally add multiple log entries at once.
+    pub fn set_logs(mut self, logs: Vec<LogEntry>) -> Self {
+        self.logs = logs;
         self
     }
 
@@ -59,10 +87,19 @@ impl TransactionReceipt {
         let data_len = self.result.data_len as usize;
         out.extend_from_slice(&self.result.data[..data_len.min(self.result.data.len())]);
 
-        out.extend_from_slice(&(self.events.len() as u32).to_le_bytes());
-        for event in &self.events {
-            out.extend_from_slice(&(event.len() as u32).to_le_bytes());
-            out.extend_from_slice(event);
+        out.extend_from_slice(&(self.logs.len() as u32).to_le_bytes());
+        for log in &self.logs {
+            out.extend_from_slice(&log.emitter.0);
+            out.extend_from_slice(&(log.data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&log.data);
+        }
+
+        out.extend_from_slice(&self.state_changes.accounts_changed.to_le_bytes());
+        out.extend_from_slice(&self.state_changes.storage_changed.to_le_bytes());
+
+        out.extend_from_slice(&(self.call_trace.len() as u32).to_le_bytes());
+        for program in &self.call_trace {
+            out.extend_from_slice(&program.0);
         }
 
         out
@@ -114,24 +151,51 @@ impl TransactionReceipt {
             result.data_len = crate::result::RESULT_DATA_SIZE as u32;
         }
 
-        let event_count = u32::from_le_bytes(read(4)?.try_into().ok()?) as usize;
-        let mut events = Vec::with_capacity(event_count);
-        for _ in 0..event_count {
+        let log_count = u32::from_le_bytes(read(4)?.try_into().ok()?) as usize;
+        let mut logs = Vec::new();
+        for _ in 0..log_count {
+            let mut emitter = [0u8; 20];
+            emitter.copy_from_slice(read(20)?);
             let len = u32::from_le_bytes(read(4)?.try_into().ok()?) as usize;
-            let bytes = read(len)?.to_vec();
-            events.push(bytes);
+            let data = read(len)?.to_vec();
+            logs.push(LogEntry {
+                emitter: Address(emitter),
+                data,
+            });
+        }
+
+        let state_changes = StateChangeSummary {
+            accounts_changed: u32::from_le_bytes(read(4)?.try_into().ok()?),
+            storage_changed: u32::from_le_bytes(read(4)?.try_into().ok()?),
+        };
+
+        let call_count = u32::from_le_bytes(read(4)?.try_into().ok()?) as usize;
+        let mut call_trace = Vec::new();
+        for _ in 0..call_count {
+            let mut program = [0u8; 20];
+            program.copy_from_slice(read(20)?);
+            call_trace.push(Address(program));
         }
 
         let tx = Transaction {
             tx_type,
-            to: crate::address::Address(to),
-            from: crate::address::Address(from),
+            to: Address(to),
+            from: Address(from),
             data,
             value,
             nonce,
         };
 
-        Some((TransactionReceipt { tx, result, events }, cursor))
+        Some((
+            TransactionReceipt {
+                tx,
+                result,
+                logs,
+                state_changes,
+                call_trace,
+            },
+            cursor,
+        ))
     }
 
     /// Encode a receipts list with a count prefix and per-receipt length.
@@ -178,11 +242,22 @@ impl fmt::Display for TransactionReceipt {
         writeln!(f, "From: {:?}", self.tx.from)?;
         writeln!(f, "To: {:?}", self.tx.to)?;
         writeln!(f, "Result: {:?}", self.result)?;
-        writeln!(f, "Events:")?;
+        writeln!(
+            f,
+            "State changes: {} accounts, {} storage keys",
+            self.state_changes.accounts_changed, self.state_changes.storage_changed
+        )?;
+        if !self.call_trace.is_empty() {
+            writeln!(f, "Calls:")?;
+            for (i, program) in self.call_trace.iter().enumerate() {
+                writeln!(f, "  [{i}] {program}")?;
+            }
+        }
+        writeln!(f, "Logs:")?;
 
-        for (i, event) in self.events.iter().enumerate() {
-            write!(f, "  [{i}] ")?;
-            for (j, byte) in event.iter().enumerate() {
+        for (i, log) in self.logs.iter().enumerate() {
+            write!(f, "  [{i}] {} ", log.emitter)?;
+            for (j, byte) in log.data.iter().enumerate() {
                 if j > 0 {
                     write!(f, " ")?;
                 }
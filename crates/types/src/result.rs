@@ -4,6 +4,48 @@ pub const RESULT_DATA_SIZE: usize = 256;
 /// Total size of the Result struct in bytes
 pub const RESULT_SIZE: usize = 1 + 4 + 4 + RESULT_DATA_SIZE; // success + error_code + data_len + data
 
+/// Error code the kernel records when a program aborts via `vm_panic`.
+/// The result data carries the panic message.
+pub const PANIC_ERROR_CODE: u32 = 0xDEAD;
+
+/// Error code the kernel records when a program call cannot start: the target is
+/// missing or not a contract, or its image is malformed or too large for the window.
+pub const PROGRAM_LOAD_ERROR_CODE: u32 = 0x10AD;
+
+/// Error code for a deployment (`CreateAccount` or salted) to an address that already
+/// holds code. The result data carries the address; use `Redeploy` to replace the code.
+pub const ADDRESS_OCCUPIED_ERROR_CODE: u32 = 0xC0DE;
+
+/// Error code for a deployment whose code exceeds the code + rodata size limit.
+pub const CODE_SIZE_EXCEEDED_ERROR_CODE: u32 = 0xB16;
+
+/// Error code for a salted (`Create2`) deployment whose data is shorter than the
+/// 32-byte salt. Nothing is deployed.
+pub const CREATE2_MALFORMED_ERROR_CODE: u32 = 0xC2E2;
+
+/// Error code `route` returns for a router frame whose header or declared argument
+/// length runs past the end of the input. No call in the frame is executed, and the
+/// result data carries a short reason.
+pub const MALFORMED_INPUT_ERROR_CODE: u32 = 0xBAD1;
+
+/// Error code the kernel records when a program takes an unhandled trap (page fault,
+/// illegal instruction). The result data carries `scause` then `stval`, little-endian.
+pub const FAULT_ERROR_CODE: u32 = 0xFA17;
+
+/// Error code the kernel records when a program running under a static call tries to
+/// write storage or move value. Only that call fails; its caller gets the result.
+pub const STATIC_WRITE_ERROR_CODE: u32 = 0x57A7;
+
+/// Error code the kernel records when call input exceeds its limit (`MAX_INPUT_LEN`).
+/// The result data carries the actual length then the limit, as little-endian u32s.
+pub const INPUT_TOO_LARGE_ERROR_CODE: u32 = 0x1B16;
+
+/// Error code the kernel hands a caller whose nested call arguments are unusable: a
+/// `to`/`from`/input pointer that is not mapped, or callee argument registers that do
+/// not cover the call-args page. The result data carries the argument (0 = to,
+/// 1 = from, 2 = input) then the offending pointer, as little-endian u32s.
+pub const INVALID_CALL_ARGS_ERROR_CODE: u32 = 0xA265;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(C, packed)]
 pub struct Result {
@@ -38,6 +80,23 @@ impl Result {
         self.data[..len].copy_from_slice(&data[..len]);
     }
 
+    /// Failed result for `len` bytes of input against a `max`-byte limit; see
+    /// `INPUT_TOO_LARGE_ERROR_CODE`.
+    pub fn input_too_large(len: u32, max: u32) -> Self {
+        let mut data = [0u8; 8];
+        data[..4].copy_from_slice(&len.to_le_bytes());
+        data[4..].copy_from_slice(&max.to_le_bytes());
+        Self::new_with_data(false, INPUT_TOO_LARGE_ERROR_CODE, &data)
+    }
+
+    /// Failed result for call argument `arg` at `ptr`; see `INVALID_CALL_ARGS_ERROR_CODE`.
+    pub fn invalid_call_args(arg: u32, ptr: u32) -> Self {
+        let mut data = [0u8; 8];
+        data[..4].copy_from_slice(&arg.to_le_bytes());
+        data[4..].copy_from_slice(&ptr.to_le_bytes());
+        Self::new_with_data(false, INVALID_CALL_ARGS_ERROR_CODE, &data)
+    }
+
     /// Creates a Result with success=true and the u32 value stored in data
     pub fn with_u32(value: u32) -> Self {
         let mut result = Self::new(true, 0);
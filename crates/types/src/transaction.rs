@@ -11,6 +11,12 @@ pub enum TransactionType {
     CreateAccount = 1,
     /// Type 2 - Contract call (calling into existing code)
     ProgramCall = 2,
+    /// Type 3 - Salted contract deployment; `data` is a 32-byte salt followed by the code.
+    /// The address is derived from (from, salt, code) and `to` is ignored.
+    Create2 = 3,
+    /// Type 4 - Replace the code at `to`, whether or not it already holds a contract.
+    /// `CreateAccount` refuses an address that already holds code.
+    Redeploy = 4,
 }
 
 impl TransactionType {
@@ -19,6 +25,8 @@ impl TransactionType {
             0 => Some(TransactionType::Transfer),
             1 => Some(TransactionType::CreateAccount),
             2 => Some(TransactionType::ProgramCall),
+            3 => Some(TransactionType::Create2),
+            4 => Some(TransactionType::Redeploy),
             _ => None,
         }
     }
@@ -2,13 +2,59 @@ use crate::cpu::PrivilegeMode;
 use crate::memory::{Memory, VirtualAddress};
 use crate::metering::{MemoryAccessKind, MeterResult, Metering};
 use core::fmt::Write;
+use core::iter::Peekable;
+use core::str::Chars;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::string::String;
 use std::vec::Vec;
+use types::boot::LogLevel;
 
 pub const CONSOLE_WRITE_ID: u32 = 1000;
 
+/// Structured receiver for console output. When installed on the CPU it gets
+/// every completed line together with the privilege mode of the caller, in
+/// place of the raw `verbose_writer`/stdout path.
+pub trait ConsoleSink {
+    /// `level` and `category` come from a `logf_at!` tag (see [`parse_level_tag`]);
+    /// the tag itself is stripped from `line`.
+    fn on_line(
+        &mut self,
+        line: &str,
+        level: LogLevel,
+        category: Option<&str>,
+        caller_mode: PrivilegeMode,
+    );
+
+    /// Most verbose level passed to `on_line`; noisier lines are dropped.
+    fn max_level(&self) -> LogLevel {
+        LogLevel::Debug
+    }
+}
+
+/// Split the `<L|category> ` tag `logf_at!` puts in front of a line, where `L`
+/// is `E`, `I` or `D`. Untagged lines are `Info` with no category.
+pub fn parse_level_tag(line: &str) -> (LogLevel, Option<&str>, &str) {
+    let untagged = (LogLevel::Info, None, line);
+    let Some(rest) = line.strip_prefix('<') else {
+        return untagged;
+    };
+    let level = match rest.as_bytes().first() {
+        Some(b'E') => LogLevel::Error,
+        Some(b'I') => LogLevel::Info,
+        Some(b'D') => LogLevel::Debug,
+        _ => return untagged,
+    };
+    match rest[1..].strip_prefix('|').and_then(|r| r.split_once("> ")) {
+        Some((category, text)) => (level, Some(category), text),
+        None => untagged,
+    }
+}
+
+/// Upper bound on a requested field width so a guest cannot make the host
+/// allocate arbitrarily large padding.
+const MAX_FORMAT_WIDTH: usize = 64;
+
 enum Arg {
     U32(u32),
     F32(f32),
@@ -17,12 +63,23 @@ enum Arg {
     Bytes(Vec<u8>),
 }
 
+/// Optional C-style modifiers between `%` and the conversion char, e.g. the
+/// `08` in `%08x` or the `-10` in `%-10s`.
+#[derive(Clone, Copy, Default)]
+struct FormatSpec {
+    left: bool,
+    zero: bool,
+    width: Option<usize>,
+}
+
 pub fn console_write(
     args: [u32; 6],
     caller_mode: PrivilegeMode,
     memory: Memory,
     metering: &mut dyn Metering,
     verbose_writer: &Option<Rc<RefCell<dyn Write>>>,
+    console_sink: &Option<Rc<RefCell<dyn ConsoleSink>>>,
+    console_level: LogLevel,
 ) -> u32 {
     let [fmt_ptr, fmt_len, arg_ptr, arg_len, ..] = args;
     let payload_len = fmt_len.saturating_add(arg_len) as usize;
@@ -73,7 +130,8 @@ pub fn console_write(
         if c != '%' {
             continue;
         }
-        let spec: char = chars.next().unwrap_or('%');
+        let (_, spec) = parse_spec(&mut chars);
+        let spec: char = spec.unwrap_or('%');
         let mut next = || raw_iter.next().unwrap_or(0);
         match spec {
             'd' | 'u' | 'x' => args.push(Arg::U32(next())),
@@ -148,25 +206,29 @@ pub fn console_write(
     let mut fmt_chars = fmt.chars().peekable();
     while let Some(c) = fmt_chars.next() {
         if c == '%' {
-            match fmt_chars.next() {
+            let (fs, conv) = parse_spec(&mut fmt_chars);
+            match conv {
                 Some('d') | Some('u') => match args_iter.next() {
-                    Some(Arg::U32(v)) => output.push_str(&format!("{}", *v as i32)),
+                    Some(Arg::U32(v)) => pad(&mut output, &format!("{}", *v as i32), fs, true),
                     _ => output.push_str("<err>"),
                 },
                 Some('x') => match args_iter.next() {
-                    Some(Arg::U32(v)) => output.push_str(&format!("{v:08x}")),
+                    Some(Arg::U32(v)) => match fs.width {
+                        Some(_) => pad(&mut output, &format!("{v:x}"), fs, true),
+                        None => output.push_str(&format!("{v:08x}")),
+                    },
                     _ => output.push_str("<err>"),
                 },
                 Some('f') => match args_iter.next() {
-                    Some(Arg::F32(f)) => output.push_str(&format!("{f}")),
+                    Some(Arg::F32(f)) => pad(&mut output, &format!("{f}"), fs, true),
                     _ => output.push_str("<err>"),
                 },
                 Some('c') => match args_iter.next() {
-                    Some(Arg::Char(c)) => output.push(*c),
+                    Some(Arg::Char(c)) => pad(&mut output, c.encode_utf8(&mut [0u8; 4]), fs, false),
                     _ => output.push_str("<err>"),
                 },
                 Some('s') => match args_iter.next() {
-                    Some(Arg::Str(s)) => output.push_str(s),
+                    Some(Arg::Str(s)) => pad(&mut output, s, fs, false),
                     _ => output.push_str("<err>"),
                 },
                 Some('b') => match args_iter.next() {
@@ -216,18 +278,75 @@ pub fn console_write(
             output.push(c);
         }
     }
-    let _ = caller_mode;
+    let (level, category, text) = parse_level_tag(&output);
+    // The sink chooses its own level; the writer/stdout path uses `console_level`.
+    let max_level = match console_sink {
+        Some(sink) => sink.borrow().max_level(),
+        None => console_level,
+    };
+    if level > max_level {
+        return 0;
+    }
+    if let Some(sink) = console_sink {
+        sink.borrow_mut()
+            .on_line(text, level, category, caller_mode);
+        return 0;
+    }
     match verbose_writer {
         Some(writer) => {
-            let _ = writeln!(writer.borrow_mut(), "{output}");
+            let _ = writeln!(writer.borrow_mut(), "{text}");
         }
         None => {
-            println!("{output}");
+            println!("{text}");
         }
     }
     0
 }
 
+/// Parse optional `-`/`0` flags and a decimal width, returning them together
+/// with the conversion char that follows.
+fn parse_spec(chars: &mut Peekable<Chars<'_>>) -> (FormatSpec, Option<char>) {
+    let mut spec = FormatSpec::default();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '-' => spec.left = true,
+            '0' => spec.zero = true,
+            _ => break,
+        }
+        chars.next();
+    }
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        let width = spec.width.unwrap_or(0) * 10 + digit as usize;
+        spec.width = Some(width.min(MAX_FORMAT_WIDTH));
+        chars.next();
+    }
+    (spec, chars.next())
+}
+
+/// Append `body` to `out`, padded to the spec's width. Zero padding only
+/// applies to numeric conversions and is inserted after a leading minus sign.
+fn pad(out: &mut String, body: &str, spec: FormatSpec, numeric: bool) {
+    let len = body.chars().count();
+    let fill = spec.width.unwrap_or(0).saturating_sub(len);
+    if spec.left {
+        out.push_str(body);
+        out.extend(core::iter::repeat_n(' ', fill));
+    } else if spec.zero && numeric {
+        let digits = match body.strip_prefix('-') {
+            Some(digits) => {
+                out.push('-');
+                digits
+            }
+            None => body,
+        };
+        out.extend(core::iter::repeat_n('0', fill));
+        out.push_str(digits);
+    } else {
+        out.extend(core::iter::repeat_n(' ', fill));
+        out.push_str(body);
+    }
+}
+
 fn va_range(ptr: usize, len: usize) -> (VirtualAddress, VirtualAddress) {
     let start = VirtualAddress(ptr as u32);
     let end = start.wrapping_add(len as u32);
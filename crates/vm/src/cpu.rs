@@ -1,16 +1,20 @@
-use crate::decoder::{decode_compressed, decode_full};
+use crate::console::ConsoleSink;
+use crate::decoder::decode_instruction;
+use crate::hook::{Hook, HookAction};
 use crate::instruction::Instruction;
-use crate::memory::{Memory, VirtualAddress};
+use crate::memory::{Memory, MemoryFault, VirtualAddress};
 use crate::metering::{MemoryAccessKind, MeterResult, Metering, NoopMeter};
 use core::cell::RefCell;
 use core::fmt::Write;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::rc::Rc;
+use types::boot::LogLevel;
 #[path = "exe.rs"]
 mod exec;
 
 pub const CSR_SATP: u16 = 0x180;
 pub const CSR_SSTATUS: u16 = 0x100;
+pub const CSR_MSTATUS: u16 = 0x300;
 pub const CSR_STVEC: u16 = 0x105;
 pub const CSR_SEPC: u16 = 0x141;
 pub const CSR_SCAUSE: u16 = 0x142;
@@ -19,11 +23,29 @@ pub const CSR_MEPC: u16 = 0x341;
 pub const CSR_MTVEC: u16 = 0x305;
 pub const CSR_MCAUSE: u16 = 0x342;
 pub const CSR_MTVAL: u16 = 0x343;
+pub const CSR_MCYCLE: u16 = 0xB00;
+pub const CSR_MINSTRET: u16 = 0xB02;
+pub const CSR_MCYCLEH: u16 = 0xB80;
+pub const CSR_MINSTRETH: u16 = 0xB82;
+pub const CSR_CYCLE: u16 = 0xC00;
+pub const CSR_INSTRET: u16 = 0xC02;
+pub const CSR_CYCLEH: u16 = 0xC80;
+pub const CSR_INSTRETH: u16 = 0xC82;
 const SCAUSE_ECALL_FROM_U: u32 = 8;
 const SCAUSE_ECALL_FROM_S: u32 = 9;
 const SCAUSE_ECALL_FROM_M: u32 = 11;
+const SCAUSE_ILLEGAL_INSTRUCTION: u32 = 2;
 const SCAUSE_BREAKPOINT: u32 = 3;
+const SCAUSE_INSTRUCTION_PAGE_FAULT: u32 = 12;
+const SCAUSE_LOAD_PAGE_FAULT: u32 = 13;
+const SCAUSE_STORE_PAGE_FAULT: u32 = 15;
+const SSTATUS_SIE: u32 = 1 << 1;
+const SSTATUS_SPIE: u32 = 1 << 5;
 const SSTATUS_SPP: u32 = 1 << 8;
+const MSTATUS_MIE: u32 = 1 << 3;
+const MSTATUS_MPIE: u32 = 1 << 7;
+const MSTATUS_MPP_SHIFT: u32 = 11;
+const MSTATUS_MPP: u32 = 0b11 << MSTATUS_MPP_SHIFT;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PrivilegeMode {
@@ -32,12 +54,44 @@ pub enum PrivilegeMode {
     Machine,
 }
 
+/// PCs of executed instructions, kept per privilege level because kernel and
+/// guest code can live at the same virtual addresses.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Coverage {
+    /// PCs executed in user mode (guest programs).
+    pub user: BTreeSet<u32>,
+    /// PCs executed in supervisor or machine mode.
+    pub privileged: BTreeSet<u32>,
+}
+
+impl Coverage {
+    fn record(&mut self, mode: PrivilegeMode, pc: u32) {
+        match mode {
+            PrivilegeMode::User => self.user.insert(pc),
+            PrivilegeMode::Supervisor | PrivilegeMode::Machine => self.privileged.insert(pc),
+        };
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TrapMode {
     Supervisor,
     Machine,
 }
 
+/// How `ecall` is serviced.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum EcallBehavior {
+    /// Console writes are handled inline; every other ecall traps to the kernel's vector.
+    #[default]
+    Syscall,
+    /// Bare-metal exit convention: `a7 == 93` halts with `a0` as the exit code
+    /// (recorded in `CPU::exit_code`). Other ecalls behave as `Syscall`.
+    ExitOnA7_93,
+    /// Every ecall, console writes included, traps to the vector.
+    Trap,
+}
+
 /// Represents the Central Processing Unit (CPU) of our RISC-V virtual machine.
 ///
 /// EDUCATIONAL PURPOSE: This struct models the core components of a real CPU:
@@ -97,14 +151,46 @@ pub struct CPU {
     /// If None, uses println! to console
     pub verbose_writer: Option<Rc<RefCell<dyn Write>>>,
 
+    /// Optional structured receiver for console lines.
+    /// If set, console output goes here instead of `verbose_writer`.
+    pub console_sink: Option<Rc<RefCell<dyn ConsoleSink>>>,
+
+    /// Most verbose console level written to `verbose_writer`/stdout. A
+    /// `console_sink` filters with its own `max_level` instead.
+    pub console_level: LogLevel,
+
     /// Pluggable metering implementation (gas, resource accounting, etc.)
     pub metering: Box<dyn Metering>,
 
+    /// Optional instruction hook that may inspect/mutate the CPU before each instruction.
+    pub hook: Option<Box<dyn Hook>>,
+
     /// Minimal CSR storage for CSR instructions
     pub csrs: HashMap<u16, u32>,
 
+    /// Instructions executed, backing the `cycle`/`mcycle` CSRs.
+    /// EDUCATIONAL: Every instruction takes one "cycle" in this VM.
+    pub cycle: u64,
+
+    /// Instructions retired (executed without faulting), backing `instret`/`minstret`.
+    pub instret: u64,
+
     /// Current privilege mode (minimal U/S support).
     pub priv_mode: PrivilegeMode,
+
+    /// How `ecall` is serviced; the kernel path uses the default `Syscall`.
+    pub ecall_behavior: EcallBehavior,
+
+    /// Exit code captured from an `ExitOnA7_93` exit ecall.
+    pub exit_code: Option<u32>,
+
+    /// Set when the last step halted on `wfi`. Nothing in this single-threaded VM raises
+    /// interrupts, so waiting would never end; the PC stays on the `wfi`, and running
+    /// again halts on it again. Cleared at the start of every step.
+    pub waiting_for_interrupt: bool,
+
+    /// Executed PCs, recorded by `step` only while set (see `enable_coverage`).
+    pub coverage: Option<Coverage>,
 }
 
 impl std::fmt::Debug for CPU {
@@ -118,7 +204,13 @@ impl std::fmt::Debug for CPU {
                 "verbose_writer",
                 &self.verbose_writer.as_ref().map(|_| "Some(<writer>)"),
             )
+            .field(
+                "console_sink",
+                &self.console_sink.as_ref().map(|_| "Some(<sink>)"),
+            )
+            .field("console_level", &self.console_level)
             .field("metering", &"<dyn Metering>")
+            .field("hook", &self.hook.as_ref().map(|_| "Some(<hook>)"))
             .finish()
     }
 }
@@ -145,9 +237,18 @@ impl CPU {
             verbose: false,
             reservation_addr: None,
             verbose_writer: None,
+            console_sink: None,
+            console_level: LogLevel::Debug,
             metering,
+            hook: None,
             csrs: HashMap::new(),
+            cycle: 0,
+            instret: 0,
             priv_mode: PrivilegeMode::Supervisor,
+            ecall_behavior: EcallBehavior::default(),
+            exit_code: None,
+            waiting_for_interrupt: false,
+            coverage: None,
         }
     }
 
@@ -156,11 +257,27 @@ impl CPU {
         self.verbose_writer = Some(writer);
     }
 
+    /// Sets a structured sink for console output
+    pub fn set_console_sink(&mut self, sink: Rc<RefCell<dyn ConsoleSink>>) {
+        self.console_sink = Some(sink);
+    }
+
     /// Swap in a new metering implementation.
     pub fn set_metering(&mut self, metering: Box<dyn Metering>) {
         self.metering = metering;
     }
 
+    /// Start recording executed PCs into `coverage`. Off by default so normal runs
+    /// skip the per-instruction set insert.
+    pub fn enable_coverage(&mut self) {
+        self.coverage.get_or_insert_with(Coverage::default);
+    }
+
+    /// Install an instruction hook, replacing any existing one.
+    pub fn set_hook(&mut self, hook: Box<dyn Hook>) {
+        self.hook = Some(hook);
+    }
+
     /// Helper method to log output
     /// Only logs if verbose is true and self.verbose is enabled
     fn log(&self, message: &str, verbose: bool) {
@@ -179,6 +296,16 @@ impl CPU {
         }
     }
 
+    /// Number of instructions executed so far (the `cycle` CSR).
+    pub fn cycle_count(&self) -> u64 {
+        self.cycle
+    }
+
+    /// Number of instructions retired so far (the `instret` CSR).
+    pub fn instret_count(&self) -> u64 {
+        self.instret
+    }
+
     fn can_continue(result: MeterResult) -> bool {
         matches!(result, MeterResult::Continue)
     }
@@ -192,7 +319,11 @@ impl CPU {
             0xF14 => *self.csrs.get(&csr).unwrap_or(&0), // mhartid
             0xF11..=0xF13 => *self.csrs.get(&csr).unwrap_or(&0), // mvendorid/marchid/mimpid
             0x301 => *self.csrs.get(&csr).unwrap_or(&0), // misa
-            0x300 => *self.csrs.get(&csr).unwrap_or(&0), // mstatus
+            CSR_MSTATUS => *self.csrs.get(&csr).unwrap_or(&0),
+            CSR_CYCLE | CSR_MCYCLE => self.cycle as u32,
+            CSR_CYCLEH | CSR_MCYCLEH => (self.cycle >> 32) as u32,
+            CSR_INSTRET | CSR_MINSTRET => self.instret as u32,
+            CSR_INSTRETH | CSR_MINSTRETH => (self.instret >> 32) as u32,
             CSR_SSTATUS => *self.csrs.get(&csr).unwrap_or(&0),
             _ => *self.csrs.get(&csr).unwrap_or(&0),
         })
@@ -202,7 +333,17 @@ impl CPU {
         if !Self::can_continue(self.metering.on_pc_update(self.pc, self.pc)) {
             return false;
         }
-        self.csrs.insert(csr, value);
+        // Counters live outside the CSR map; only the machine-mode copies are writable.
+        match csr {
+            CSR_MCYCLE => self.cycle = (self.cycle & !0xffff_ffff) | value as u64,
+            CSR_MCYCLEH => self.cycle = (self.cycle & 0xffff_ffff) | (value as u64) << 32,
+            CSR_MINSTRET => self.instret = (self.instret & !0xffff_ffff) | value as u64,
+            CSR_MINSTRETH => self.instret = (self.instret & 0xffff_ffff) | (value as u64) << 32,
+            CSR_CYCLE | CSR_CYCLEH | CSR_INSTRET | CSR_INSTRETH => {}
+            _ => {
+                self.csrs.insert(csr, value);
+            }
+        }
         true
     }
 
@@ -211,6 +352,7 @@ impl CPU {
         self.write_csr(CSR_SATP, value)
     }
 
+    /// Supervisor trap entry: record the trapped mode in SPP and stack SIE into SPIE.
     fn set_sstatus_spp(&mut self, prev: PrivilegeMode) {
         let mut sstatus = self.read_csr(CSR_SSTATUS).unwrap_or(0);
         match prev {
@@ -218,9 +360,17 @@ impl CPU {
             PrivilegeMode::Supervisor => sstatus |= SSTATUS_SPP,
             PrivilegeMode::Machine => sstatus |= SSTATUS_SPP,
         }
+        if sstatus & SSTATUS_SIE != 0 {
+            sstatus |= SSTATUS_SPIE;
+        } else {
+            sstatus &= !SSTATUS_SPIE;
+        }
+        sstatus &= !SSTATUS_SIE;
         let _ = self.write_csr(CSR_SSTATUS, sstatus);
     }
 
+    /// `sret` side of `set_sstatus_spp`: return the mode to resume in, restore SIE
+    /// from SPIE, then set SPIE and clear SPP (back to user) as the spec requires.
     fn take_sstatus_spp(&mut self) -> PrivilegeMode {
         let mut sstatus = self.read_csr(CSR_SSTATUS).unwrap_or(0);
         let prev = if sstatus & SSTATUS_SPP != 0 {
@@ -228,11 +378,55 @@ impl CPU {
         } else {
             PrivilegeMode::User
         };
+        if sstatus & SSTATUS_SPIE != 0 {
+            sstatus |= SSTATUS_SIE;
+        } else {
+            sstatus &= !SSTATUS_SIE;
+        }
+        sstatus |= SSTATUS_SPIE;
         sstatus &= !SSTATUS_SPP;
         let _ = self.write_csr(CSR_SSTATUS, sstatus);
         prev
     }
 
+    /// Machine trap entry: record the trapped mode in MPP and stack MIE into MPIE.
+    fn set_mstatus_mpp(&mut self, prev: PrivilegeMode) {
+        let mut mstatus = self.read_csr(CSR_MSTATUS).unwrap_or(0);
+        let mpp = match prev {
+            PrivilegeMode::User => 0b00,
+            PrivilegeMode::Supervisor => 0b01,
+            PrivilegeMode::Machine => 0b11,
+        };
+        mstatus = (mstatus & !MSTATUS_MPP) | (mpp << MSTATUS_MPP_SHIFT);
+        if mstatus & MSTATUS_MIE != 0 {
+            mstatus |= MSTATUS_MPIE;
+        } else {
+            mstatus &= !MSTATUS_MPIE;
+        }
+        mstatus &= !MSTATUS_MIE;
+        let _ = self.write_csr(CSR_MSTATUS, mstatus);
+    }
+
+    /// `mret` side of `set_mstatus_mpp`: return the mode in MPP, restore MIE from
+    /// MPIE, then set MPIE and reset MPP to user.
+    fn take_mstatus_mpp(&mut self) -> PrivilegeMode {
+        let mut mstatus = self.read_csr(CSR_MSTATUS).unwrap_or(0);
+        let prev = match (mstatus & MSTATUS_MPP) >> MSTATUS_MPP_SHIFT {
+            0b00 => PrivilegeMode::User,
+            0b01 => PrivilegeMode::Supervisor,
+            _ => PrivilegeMode::Machine,
+        };
+        if mstatus & MSTATUS_MPIE != 0 {
+            mstatus |= MSTATUS_MIE;
+        } else {
+            mstatus &= !MSTATUS_MIE;
+        }
+        mstatus |= MSTATUS_MPIE;
+        mstatus &= !MSTATUS_MPP;
+        let _ = self.write_csr(CSR_MSTATUS, mstatus);
+        prev
+    }
+
     fn ecall_cause(&self) -> u32 {
         match self.priv_mode {
             PrivilegeMode::User => SCAUSE_ECALL_FROM_U,
@@ -263,6 +457,7 @@ impl CPU {
                     Some(val) => val & !0x3,
                     None => return false,
                 };
+                self.set_mstatus_mpp(self.priv_mode);
                 self.priv_mode = PrivilegeMode::Machine;
                 self.set_pc(mtvec)
             }
@@ -287,6 +482,79 @@ impl CPU {
         }
     }
 
+    /// Delivers a failed user-mode access to the trap vector as a page fault
+    /// (scause 12/13/15, stval = faulting address). Returns false when the failure
+    /// was not a translation fault or there is nowhere to deliver it.
+    fn raise_page_fault(&mut self, memory: &Memory) -> bool {
+        match memory.take_fault() {
+            Some(fault) => self.deliver_page_fault(fault),
+            None => false,
+        }
+    }
+
+    /// Delivers `fault` as a page fault; see `raise_page_fault`.
+    fn deliver_page_fault(&mut self, fault: MemoryFault) -> bool {
+        if self.priv_mode != PrivilegeMode::User {
+            return false;
+        }
+        let Some(trap_mode) = self.has_trap_vector() else {
+            return false;
+        };
+        let cause = match fault.kind {
+            MemoryAccessKind::Fetch => SCAUSE_INSTRUCTION_PAGE_FAULT,
+            MemoryAccessKind::Load | MemoryAccessKind::ReservationLoad => SCAUSE_LOAD_PAGE_FAULT,
+            MemoryAccessKind::Store
+            | MemoryAccessKind::Atomic
+            | MemoryAccessKind::ReservationStore => SCAUSE_STORE_PAGE_FAULT,
+        };
+        self.log(
+            &format!(
+                "page fault at PC=0x{:08x}: scause={} stval=0x{:08x}",
+                self.pc,
+                cause,
+                fault.addr.as_u32()
+            ),
+            true,
+        );
+        if !self.trap_to_vector(trap_mode, cause, fault.addr.as_u32(), None) {
+            panic!(
+                "trap_to_vector returned false for page fault pc=0x{:08x}",
+                self.pc
+            );
+        }
+        true
+    }
+
+    /// Delivers an undecodable instruction to the trap vector as an illegal-instruction
+    /// trap (scause 2, stval = the instruction bits, sepc = its PC). Returns false when
+    /// the PC cannot be read or there is nowhere to deliver it.
+    fn raise_illegal_instruction(&mut self, memory: &Memory) -> bool {
+        let Some((bytes, len)) = self.fetch_instruction_bytes(memory) else {
+            return false;
+        };
+        let bits = match len {
+            4 => u32::from_le_bytes(bytes),
+            _ => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+        };
+        let Some(trap_mode) = self.has_trap_vector() else {
+            return false;
+        };
+        self.log(
+            &format!(
+                "illegal instruction at PC=0x{:08x}: bits=0x{:08x}",
+                self.pc, bits
+            ),
+            true,
+        );
+        if !self.trap_to_vector(trap_mode, SCAUSE_ILLEGAL_INSTRUCTION, bits, None) {
+            panic!(
+                "trap_to_vector returned false for illegal instruction pc=0x{:08x}",
+                self.pc
+            );
+        }
+        true
+    }
+
     fn has_trap_vector(&self) -> Option<TrapMode> {
         match self.priv_mode {
             PrivilegeMode::Machine => {
@@ -326,9 +594,11 @@ impl CPU {
     /// - EXECUTE: The actual operation is performed (arithmetic, memory access,
     ///   control flow, etc.)
     ///
-    /// ERROR HANDLING: If an invalid instruction is encountered, the CPU
-    /// handles it gracefully by calling unknown_instruction() which provides
-    /// debugging information and halts execution safely.
+    /// ERROR HANDLING: A PC that is unmapped or not executable raises an
+    /// instruction page fault (or halts without a trap vector). If an invalid
+    /// instruction is encountered, the CPU raises an illegal-instruction trap when
+    /// a trap vector is installed. Without one it calls unknown_instruction(),
+    /// which reports the bytes and panics.
     ///
     /// RETURN VALUE: Returns true if execution should continue, false to halt
     ///
@@ -346,11 +616,38 @@ impl CPU {
         // EDUCATIONAL: Step 2 - Execute the instruction or handle errors
         match instr {
             Some((instr, size)) => {
-                // Valid instruction found - execute it
+                // Valid instruction found - charge the fetch, then execute it
+                let fetch = self.metering.on_memory_access(
+                    MemoryAccessKind::Fetch,
+                    self.pc as usize,
+                    size as usize,
+                );
+                if !Self::can_continue(fetch) {
+                    return false;
+                }
+                if let Some(coverage) = self.coverage.as_mut() {
+                    coverage.record(self.priv_mode, self.pc);
+                }
                 self.run_instruction(instr, size, Rc::clone(&memory))
             }
             None => {
-                // No valid instruction found - handle the error
+                // The PC is unmapped or not executable: deliver an instruction page
+                // fault, or halt when there is nowhere to deliver it.
+                if let Some(fault) = memory.take_fault() {
+                    if self.deliver_page_fault(fault) {
+                        return true;
+                    }
+                    self.log(
+                        &format!("instruction fetch failed at PC=0x{:08x}", self.pc),
+                        false,
+                    );
+                    return false;
+                }
+                // No valid instruction found - trap if a handler is installed,
+                // otherwise there is no way to recover
+                if self.raise_illegal_instruction(&memory) {
+                    return true;
+                }
                 self.unknown_instruction(Rc::clone(&memory))
             }
         }
@@ -396,6 +693,17 @@ impl CPU {
             );
         }
 
+        // The hook needs `&mut self`, so take it out for the duration of the call.
+        if let Some(mut hook) = self.hook.take() {
+            let action = hook.before_instruction(self, &instr);
+            self.hook = Some(hook);
+            match action {
+                HookAction::Continue => {}
+                HookAction::Halt => return false,
+                HookAction::Skip => return self.pc_add(size as u32),
+            }
+        }
+
         if !Self::can_continue(self.metering.on_instruction(self.pc, &instr, size)) {
             return false;
         }
@@ -404,7 +712,19 @@ impl CPU {
         let old_pc = self.pc;
 
         // EDUCATIONAL: Execute the instruction
-        let result = self.execute(instr.clone(), memory);
+        // Drop any fault left over from logging so only this instruction's accesses count.
+        memory.take_fault();
+        // The flag only describes the step that set it. The PC stays on the `wfi`, so
+        // resuming executes it and halts again unless the host moves the PC past it.
+        self.waiting_for_interrupt = false;
+        let result = self.execute(instr.clone(), Rc::clone(&memory));
+        self.cycle = self.cycle.wrapping_add(1);
+        if result {
+            self.instret = self.instret.wrapping_add(1);
+        }
+        if !result && self.raise_page_fault(&memory) {
+            return true;
+        }
         if !result {
             self.log(
                 &format!(
@@ -417,8 +737,9 @@ impl CPU {
         }
 
         // EDUCATIONAL: Only increment PC if the instruction didn't change it
-        // This handles branches, jumps, and calls correctly
-        if self.pc == old_pc && !self.pc_add(size as u32) {
+        // This handles branches, jumps, and calls correctly. A `wfi` halt keeps
+        // the PC on the `wfi` so the stop position is stable.
+        if self.pc == old_pc && !self.waiting_for_interrupt && !self.pc_add(size as u32) {
             return false;
         }
         result
@@ -471,31 +792,33 @@ impl CPU {
     ///
     /// RETURN VALUE: Returns Some((instruction, size)) if successful, None if invalid
     pub fn next_instruction(&mut self, memory: Memory) -> Option<(Instruction, u8)> {
-        let pc = VirtualAddress(self.pc);
-
-        // EDUCATIONAL: Read 4 bytes from memory (enough for any instruction)
-        let bytes = memory.mem_slice(pc, VirtualAddress(self.pc.wrapping_add(4)))?;
+        // EDUCATIONAL: The fetch needs execute permission; a failed translation
+        // leaves a `Fetch` fault.
+        memory.take_fault();
+        let (bytes, len) = self.fetch_instruction_bytes(&memory)?;
+
+        // EDUCATIONAL: The decoder picks 16- or 32-bit decoding from the low bits
+        // and reports how many bytes the instruction used.
+        decode_instruction(&bytes[..len])
+    }
 
-        // EDUCATIONAL: Need at least 2 bytes for any instruction
-        if bytes.len() < 2 {
-            return None;
-        }
-
-        // EDUCATIONAL: Check if this is a compressed instruction
-        // RISC-V compressed instructions have bottom 2 bits != 0b11
-        let hword = u16::from_le_bytes([bytes[0], bytes[1]]);
-        let is_compressed = (hword & 0b11) != 0b11;
-
-        if is_compressed {
-            // EDUCATIONAL: Decode 16-bit compressed instruction
-            decode_compressed(hword).map(|inst| (inst, 2))
-        } else if bytes.len() >= 4 {
-            // EDUCATIONAL: Decode 32-bit regular instruction
-            let word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-            decode_full(word).map(|inst| (inst, 4))
-        } else {
-            None
+    /// Reads the instruction at the PC and its length. The first halfword is read
+    /// on its own and its low bits say whether a second one follows, so a compressed
+    /// instruction at the end of a page never touches the next page. The upper half
+    /// of a 32-bit instruction is translated separately, since it may sit in another
+    /// frame; when that fails the `Fetch` fault is at `pc + 2`.
+    fn fetch_instruction_bytes(&self, memory: &Memory) -> Option<([u8; 4], usize)> {
+        let upper = self.pc.wrapping_add(2);
+        let mut bytes = [0u8; 4];
+        bytes[..2]
+            .copy_from_slice(&memory.fetch_slice(VirtualAddress(self.pc), VirtualAddress(upper))?);
+        if bytes[0] & 0b11 != 0b11 {
+            return Some((bytes, 2));
         }
+        bytes[2..].copy_from_slice(
+            &memory.fetch_slice(VirtualAddress(upper), VirtualAddress(upper.wrapping_add(2)))?,
+        );
+        Some((bytes, 4))
     }
 
     /// Safely read a register with metering.
@@ -518,6 +841,17 @@ impl CPU {
         true
     }
 
+    /// Report a conditional branch at the current PC to metering.
+    fn record_branch(&mut self, offset: i32, taken: bool) -> bool {
+        let target = self.pc.wrapping_add(offset as u32);
+        Self::can_continue(self.metering.on_branch(self.pc, target, taken))
+    }
+
+    /// Report a linking jump (a call) from the current PC to metering.
+    fn record_call(&mut self, target: u32) -> bool {
+        Self::can_continue(self.metering.on_call_target(self.pc, target))
+    }
+
     /// Add to the program counter with wrapping semantics and metering.
     fn pc_add(&mut self, delta: u32) -> bool {
         let old = self.pc;
@@ -41,7 +41,10 @@ use crate::isa_compressed::CompressedOpcode;
 /// RETURNS: Some((instruction, size)) if successful, None if invalid
 /// - instruction: The decoded instruction object
 /// - size: Number of bytes consumed (2 for compressed, 4 for regular)
-pub fn decode(bytes: &[u8]) -> Option<(Instruction, u8)> {
+///
+/// Nothing is executed, so this is also the entry point for tools that walk code
+/// (disassemblers, trace dumpers) and need the same length rules as the CPU.
+pub fn decode_instruction(bytes: &[u8]) -> Option<(Instruction, u8)> {
     // EDUCATIONAL: Need at least 2 bytes to read the first 16 bits
     if bytes.len() < 2 {
         return None;
@@ -227,12 +230,16 @@ pub fn decode_full(word: u32) -> Option<Instruction> {
                 0x6 => Some(Instruction::Ori { rd, rs1, imm }),
                 0x7 => Some(Instruction::Andi { rd, rs1, imm }),
 
-                // EDUCATIONAL: Immediate shifts (use only bottom 5 bits of immediate)
-                0x1 => Some(Instruction::Slli {
-                    rd,
-                    rs1,
-                    shamt: (imm & 0x1f) as u8, // Only bottom 5 bits for shift amount
-                }),
+                // EDUCATIONAL: Immediate shifts (use only bottom 5 bits of immediate).
+                // shamt[5] lands in funct7, so shifts of 32 or more are illegal on RV32.
+                0x1 => match funct7 {
+                    0x00 => Some(Instruction::Slli {
+                        rd,
+                        rs1,
+                        shamt: (imm & 0x1f) as u8, // Only bottom 5 bits for shift amount
+                    }),
+                    _ => None,
+                },
                 0x5 => match funct7 {
                     // EDUCATIONAL: Logical vs arithmetic right shift
                     0x00 => Some(Instruction::Srli {
@@ -401,16 +408,17 @@ pub fn decode_full(word: u32) -> Option<Instruction> {
         // EDUCATIONAL: Load Upper Immediate (U-type)
         // Load 20-bit immediate into upper bits of register
         Opcode::Lui => {
-            // EDUCATIONAL: 20-bit immediate goes into bits 31:12
-            let imm = ((word >> 12) & 0xFFFFF) as i32;
+            // EDUCATIONAL: 20-bit immediate goes into bits 31:12. Keep it signed
+            // (bit 31 is its sign) so it matches what C.LUI decodes to.
+            let imm = (word as i32) >> 12;
             Some(Instruction::Lui { rd, imm })
         }
 
         // EDUCATIONAL: Add Upper Immediate to PC (U-type)
         // PC-relative addressing for position-independent code
         Opcode::Auipc => {
-            // EDUCATIONAL: 20-bit immediate added to PC (bits 31:12)
-            let imm = ((word >> 12) & 0xfffff) as i32;
+            // EDUCATIONAL: 20-bit signed immediate added to PC (bits 31:12)
+            let imm = (word as i32) >> 12;
             Some(Instruction::Auipc { rd, imm })
         }
 
@@ -428,6 +436,12 @@ pub fn decode_full(word: u32) -> Option<Instruction> {
                         1 => Some(Instruction::Ebreak),
                         0x302 => Some(Instruction::Mret),
                         0x102 => Some(Instruction::Sret),
+                        0x105 if rs1 == 0 && rd == 0 => Some(Instruction::Wfi),
+                        // SFENCE.VMA: funct7=0b0001001, rd=x0; rs2 carries the ASID.
+                        _ if funct12 >> 5 == 0x09 && rd == 0 => Some(Instruction::SfenceVma {
+                            rs1,
+                            rs2: (funct12 & 0x1f) as usize,
+                        }),
                         _ => None,
                     }
                 }
@@ -496,9 +510,22 @@ pub fn decode_full(word: u32) -> Option<Instruction> {
 /// (x8-x15) to save bits, and have special encodings for common registers
 /// like x0 (zero), x1 (ra), x2 (sp).
 ///
-/// This function currently supports a minimal set:
-/// - C.ADDI, C.LI, C.LW, C.SW, C.JAL, C.JR, C.RET
+/// Every RV32C integer encoding is covered (quadrant 0: C.ADDI4SPN, C.LW,
+/// C.SW; quadrant 1: C.NOP/C.ADDI, C.JAL, C.LI, C.ADDI16SP, C.LUI, C.SRLI,
+/// C.SRAI, C.ANDI, C.SUB/C.XOR/C.OR/C.AND, C.J, C.BEQZ, C.BNEZ; quadrant 2:
+/// C.SLLI, C.LWSP, C.JR, C.MV, C.EBREAK, C.JALR, C.ADD, C.SWSP). The
+/// floating-point forms (C.FLW, C.FSW, ...) decode to `None` since the VM
+/// does not implement the F/D extensions.
+///
+/// ILLEGAL ENCODINGS: The all-zero halfword is defined as illegal (it is what
+/// running into zeroed memory looks like), and reserved patterns such as
+/// C.JR x0, C.LUI/C.ADDI16SP with a zero immediate, and RV32 shifts with
+/// shamt[5] set return `None` so the CPU faults instead of executing them.
 pub fn decode_compressed(hword: u16) -> Option<Instruction> {
+    if hword == 0 {
+        return None; // defined illegal instruction
+    }
+
     let funct3 = (hword >> 13) & 0b111;
     let opcode = hword & 0b11;
     let rd = ((hword >> 7) & 0x1f) as usize;
@@ -530,6 +557,10 @@ pub fn decode_compressed(hword: u16) -> Option<Instruction> {
                     | ((hword >> 3) & 0x1) << 7
                     | ((hword >> 2) & 0x1) << 5) as i32;
 
+                if imm2 == 0 {
+                    return None; // nzimm must be non-zero
+                }
+
                 // Sign-extend 10-bit immediate
                 let imm = (imm2 << 22) >> 22;
 
@@ -542,6 +573,9 @@ pub fn decode_compressed(hword: u16) -> Option<Instruction> {
                 let nzimm_17 = (hword >> 12) & 0x1; // nzimm[17] from bit 12
                 let nzimm_16_12 = (hword >> 2) & 0x1F; // nzimm[16:12] from bits 6:2
                 let nzimm = (nzimm_17 << 5) | nzimm_16_12; // Combine into 6-bit nzimm[17:12]
+                if nzimm == 0 {
+                    return None; // nzimm must be non-zero
+                }
 
                 // C.LUI expands into lui rd, nzimm[17:12]
                 // The 6-bit nzimm is used as imm[17:12] of the 20-bit LUI immediate
@@ -560,10 +594,6 @@ pub fn decode_compressed(hword: u16) -> Option<Instruction> {
         CompressedOpcode::Addi4spn => {
             let _rd = 8 + ((hword >> 2) & 0b111) as i32; // rd' field
 
-            if _rd == 0 {
-                return None; // reserved
-            }
-
             let imm = ((hword >> 12) & 0b1) << 5  | // imm[5]  from bit 12
                 ((hword >> 11) & 0b1) << 4  | // imm[4]  from bit 11
                 ((hword >> 10) & 0b1) << 9  | // imm[9]  from bit 10
@@ -604,9 +634,9 @@ pub fn decode_compressed(hword: u16) -> Option<Instruction> {
         CompressedOpcode::RegOrJump => {
             let bit12 = (hword >> 12) & 0b1;
 
-            // === EBREAK: rs1 = 0 and rs2 = 0 (regardless of bit12) ===
+            // === EBREAK: bit12 = 1, rs1 = 0 and rs2 = 0; C.JR x0 (bit12 = 0) is reserved ===
             if rs1 == 0 && rs2 == 0 {
-                return Some(Instruction::Ebreak);
+                return (bit12 == 1).then_some(Instruction::Ebreak);
             }
 
             if bit12 == 0 {
@@ -631,6 +661,9 @@ pub fn decode_compressed(hword: u16) -> Option<Instruction> {
         }
 
         CompressedOpcode::Slli => {
+            if (hword >> 12) & 0x1 == 1 {
+                return None; // shamt[5] is reserved on RV32
+            }
             let shamt = ((hword >> 2) & 0b11111) as u8;
             Some(Instruction::Slli { rd, rs1, shamt }) // emulate as ADDI with left shift beforehand
         }
@@ -721,6 +754,11 @@ pub fn decode_compressed(hword: u16) -> Option<Instruction> {
             let rd = 8 + ((hword >> 7) & 0b111) as usize;
             let rs2 = 8 + ((hword >> 2) & 0b111) as usize;
 
+            // shamt[5] (and the RV64-only C.SUBW/C.ADDW group) is reserved on RV32
+            if bit12 == 1 && funct2 != 0b10 {
+                return None;
+            }
+
             match funct2 {
                 0b00 => {
                     // C.SRLI
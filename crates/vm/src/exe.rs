@@ -1,5 +1,6 @@
 use super::{
-    Instruction, Memory, MemoryAccessKind, CPU, CSR_MEPC, CSR_SATP, CSR_SEPC, SCAUSE_BREAKPOINT,
+    EcallBehavior, Instruction, Memory, MemoryAccessKind, CPU, CSR_MEPC, CSR_SATP, CSR_SEPC,
+    SCAUSE_BREAKPOINT,
 };
 use crate::console::{console_write, CONSOLE_WRITE_ID};
 use crate::instruction::CsrOp;
@@ -433,7 +434,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if lhs == rhs {
+                let taken = lhs == rhs;
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -450,7 +455,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if lhs != rhs {
+                let taken = lhs != rhs;
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -467,7 +476,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if (lhs as i32) < (rhs as i32) {
+                let taken = (lhs as i32) < (rhs as i32);
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -484,7 +497,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if (lhs as i32) >= (rhs as i32) {
+                let taken = (lhs as i32) >= (rhs as i32);
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -501,7 +518,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if lhs < rhs {
+                let taken = lhs < rhs;
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -519,7 +540,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if lhs >= rhs {
+                let taken = lhs >= rhs;
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -536,6 +561,9 @@ impl CPU {
                 // Used for function calls and long-distance jumps
                 // The return address is stored in rd (usually x1/ra)
                 let return_address = if compressed { self.pc + 2 } else { self.pc + 4 };
+                if rd != 0 && !self.record_call(self.pc.wrapping_add(offset as u32)) {
+                    return false;
+                }
                 if !self.write_reg(rd, return_address) {
                     return false;
                 }
@@ -562,6 +590,9 @@ impl CPU {
                 // For compressed instructions (c.jalr), return address should be pc + 2
                 // For regular instructions (jalr), return address should be pc + 4
                 let return_address = if compressed { self.pc + 2 } else { self.pc + 4 };
+                if rd != 0 && !self.record_call(target) {
+                    return false;
+                }
 
                 if !self.write_reg(rd, return_address) {
                     return false;
@@ -784,13 +815,22 @@ impl CPU {
                 if !Self::can_continue(self.metering.on_syscall(call_id, &args)) {
                     return false;
                 }
-                if call_id == CONSOLE_WRITE_ID {
+                if self.ecall_behavior == EcallBehavior::ExitOnA7_93 && call_id == 93 {
+                    self.exit_code = Some(match self.read_reg(Register::A0 as usize) {
+                        Some(v) => v,
+                        None => return false,
+                    });
+                    return false;
+                }
+                if call_id == CONSOLE_WRITE_ID && self.ecall_behavior != EcallBehavior::Trap {
                     let result = console_write(
                         args,
                         self.priv_mode,
                         memory,
                         self.metering.as_mut(),
                         &self.verbose_writer,
+                        &self.console_sink,
+                        self.console_level,
                     );
                     if !self.write_reg(Register::A0 as usize, result) {
                         return false;
@@ -883,18 +923,34 @@ impl CPU {
                 return false;
             }
             Instruction::Mret => {
-                let target = match self.read_csr(CSR_MEPC).or_else(|| self.read_csr(CSR_SEPC)) {
+                // EDUCATIONAL: MRET - return from a machine trap to mepc in the mode
+                // saved in mstatus.MPP. Only machine mode may execute it.
+                if self.priv_mode != super::PrivilegeMode::Machine {
+                    return self.raise_illegal_instruction(&memory);
+                }
+                let target = match self.read_csr(CSR_MEPC) {
                     Some(v) => v,
                     None => return false,
                 };
-                let prev = self.take_sstatus_spp();
+                let prev = self.take_mstatus_mpp();
                 if !self.set_pc(target) {
                     return false;
                 }
                 self.priv_mode = prev;
                 return true;
             }
+            Instruction::Wfi => {
+                // EDUCATIONAL: WFI - no interrupt will ever arrive, so stop here
+                self.waiting_for_interrupt = true;
+                return false;
+            }
             Instruction::Sret => {
+                // EDUCATIONAL: SRET - return from a supervisor trap to sepc in the mode
+                // saved in sstatus.SPP (how the kernel enters and resumes user tasks).
+                // User mode may not execute it.
+                if self.priv_mode == super::PrivilegeMode::User {
+                    return self.raise_illegal_instruction(&memory);
+                }
                 let target = match self.read_csr(CSR_SEPC) {
                     Some(v) => v,
                     None => return false,
@@ -973,7 +1029,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if val == 0 {
+                let taken = val == 0;
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -986,7 +1046,11 @@ impl CPU {
                     Some(v) => v,
                     None => return false,
                 };
-                if val != 0 {
+                let taken = val != 0;
+                if !self.record_branch(offset, taken) {
+                    return false;
+                }
+                if taken {
                     if !self.pc_add(offset as u32) {
                         return false;
                     }
@@ -1058,6 +1122,9 @@ impl CPU {
             Instruction::Fence => {
                 // FENCE is a memory barrier in hardware, but is a no-op in this VM
             }
+            Instruction::SfenceVma { .. } => {
+                // No TLB: translations are re-walked on every access, so there is nothing to flush
+            }
             Instruction::Unimp => {
                 // UNIMP is an unimplemented instruction, treat as a no-op for compatibility
             }
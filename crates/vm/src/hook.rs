@@ -0,0 +1,25 @@
+use crate::cpu::CPU;
+use crate::instruction::Instruction;
+
+/// What the CPU should do with the instruction a [`Hook`] just inspected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    /// Execute the instruction normally.
+    Continue,
+    /// Stop before executing it; `step` returns false with the PC unchanged.
+    Halt,
+    /// Do not execute it; advance the PC past it and keep running.
+    Skip,
+}
+
+/// Instruction-level tracer with full access to CPU state.
+///
+/// Unlike [`Metering`](crate::metering::Metering), which only observes and
+/// can halt, a hook runs before each instruction with `&mut CPU`, so tools can
+/// implement conditional breakpoints, watchpoints, or patch registers on the
+/// fly. The instruction was already fetched from the current PC, so a hook must
+/// not move the PC; use [`HookAction`] to stop or skip it instead. Hooks run
+/// before metering, so skipped instructions are not charged.
+pub trait Hook {
+    fn before_instruction(&mut self, cpu: &mut CPU, instr: &Instruction) -> HookAction;
+}
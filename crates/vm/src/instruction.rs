@@ -1,3 +1,5 @@
+use core::fmt;
+
 /// This enum defines the full instruction set for RV32IMAC
 /// (Integer, Multiplication, Atomic, and Compressed extensions).
 /// Each variant corresponds to a decoded instruction
@@ -261,13 +263,15 @@ pub enum Instruction {
     /// LUI: rd = imm << 12
     /// EDUCATIONAL: Load upper immediate. Loads a 20-bit immediate into bits 31-12 of rd,
     /// with bits 11-0 set to zero. Used for loading large constants and addresses.
-    /// This is a U-type instruction (upper immediate operation).
+    /// This is a U-type instruction (upper immediate operation). `imm` is the signed
+    /// 20-bit value, so `0xfffff` decodes to -1.
     Lui { rd: usize, imm: i32 },
 
     /// AUIPC: rd = pc + (imm << 12)
     /// EDUCATIONAL: Add upper immediate to PC. Adds a 20-bit immediate (shifted left by 12)
     /// to the current PC and stores the result in rd. Used for PC-relative addressing
-    /// and position-independent code. This is a U-type instruction.
+    /// and position-independent code. This is a U-type instruction. `imm` is signed as
+    /// for LUI, and the PC is that of the `auipc` itself.
     Auipc { rd: usize, imm: i32 },
 
     /// ECALL: Environment call
@@ -449,10 +453,19 @@ pub enum Instruction {
     /// This is a 16-bit version of EBREAK. Triggers a debugger breakpoint in real systems.
     Ebreak,
 
-    /// MRET: Machine-mode return (treated as a halt in this VM)
+    /// MRET: Machine-mode return to `mepc` in the mode held in `mstatus.MPP`
     Mret,
-    /// SRET: Supervisor-mode return
+    /// SRET: Supervisor-mode return to `sepc` in the mode held in `sstatus.SPP`
     Sret,
+    /// WFI: Wait for interrupt
+    /// EDUCATIONAL: Stalls the hart until an interrupt is pending. Nothing raises interrupts
+    /// in this single-threaded VM, so it halts instead of waiting forever.
+    Wfi,
+    /// SFENCE.VMA: Flush cached address translations
+    /// EDUCATIONAL: Orders page-table updates against later implicit accesses. rs1 selects a
+    /// virtual address and rs2 an ASID (x0 means "all"). Sv32 here walks the page tables on
+    /// every access, so there is nothing to invalidate and this executes as a no-op.
+    SfenceVma { rs1: usize, rs2: usize },
 
     /// C.MISC-ALU: compressed miscellaneous ALU operations
     /// EDUCATIONAL: Compressed miscellaneous ALU operations including C.SUB, C.XOR, C.OR, C.AND.
@@ -521,253 +534,241 @@ pub enum MiscAluOp {
 }
 
 impl Instruction {
-    pub fn pretty_print(&self) -> String {
-        fn reg(r: usize) -> String {
-            format!("x{r}") // or use register aliases like a0, t1, etc. if desired
-        }
-
+    /// Assembly mnemonic for this instruction (`"add"`, `"lw"`, `"c.sub"`, ...).
+    pub fn mnemonic(&self) -> &'static str {
         match self {
-            Instruction::Add { rd, rs1, rs2 } => {
-                format!("add  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Sub { rd, rs1, rs2 } => {
-                format!("sub  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Addi { rd, rs1, imm } => {
-                format!("addi {}, {}, {}", reg(*rd), reg(*rs1), imm)
-            }
+            Instruction::Add { .. } => "add",
+            Instruction::Sub { .. } => "sub",
+            Instruction::Addi { .. } => "addi",
+            Instruction::And { .. } => "and",
+            Instruction::Or { .. } => "or",
+            Instruction::Xor { .. } => "xor",
+            Instruction::Andi { .. } => "andi",
+            Instruction::Ori { .. } => "ori",
+            Instruction::Xori { .. } => "xori",
+            Instruction::Slt { .. } => "slt",
+            Instruction::Sltu { .. } => "sltu",
+            Instruction::Slti { .. } => "slti",
+            Instruction::Sltiu { .. } => "sltiu",
+            Instruction::Sll { .. } => "sll",
+            Instruction::Srl { .. } => "srl",
+            Instruction::Sra { .. } => "sra",
+            Instruction::Slli { .. } => "slli",
+            Instruction::Srli { .. } => "srli",
+            Instruction::Srai { .. } => "srai",
+            Instruction::Lw { .. } => "lw",
+            Instruction::Ld { .. } => "ld",
+            Instruction::Lb { .. } => "lb",
+            Instruction::Lbu { .. } => "lbu",
+            Instruction::Lh { .. } => "lh",
+            Instruction::Lhu { .. } => "lhu",
+            Instruction::Sh { .. } => "sh",
+            Instruction::Sw { .. } => "sw",
+            Instruction::Sb { .. } => "sb",
+            Instruction::Beq { .. } => "beq",
+            Instruction::Bne { .. } => "bne",
+            Instruction::Blt { .. } => "blt",
+            Instruction::Bge { .. } => "bge",
+            Instruction::Bltu { .. } => "bltu",
+            Instruction::Bgeu { .. } => "bgeu",
+            Instruction::Jal { .. } => "jal",
+            Instruction::Jalr { .. } => "jalr",
+            Instruction::Lui { .. } => "lui",
+            Instruction::Auipc { .. } => "auipc",
+            Instruction::Ecall => "ecall",
+            Instruction::Fence => "fence",
+            Instruction::Unimp => "unimp",
+            Instruction::Mul { .. } => "mul",
+            Instruction::Mulh { .. } => "mulh",
+            Instruction::Mulhsu { .. } => "mulhsu",
+            Instruction::Mulhu { .. } => "mulhu",
+            Instruction::Div { .. } => "div",
+            Instruction::Divu { .. } => "divu",
+            Instruction::Rem { .. } => "rem",
+            Instruction::Remu { .. } => "remu",
+            Instruction::AmoswapW { .. } => "amoswap.w",
+            Instruction::AmoaddW { .. } => "amoadd.w",
+            Instruction::AmoandW { .. } => "amoand.w",
+            Instruction::AmoorW { .. } => "amoor.w",
+            Instruction::AmoxorW { .. } => "amoxor.w",
+            Instruction::AmomaxW { .. } => "amomax.w",
+            Instruction::AmominW { .. } => "amomin.w",
+            Instruction::AmomaxuW { .. } => "amomaxu.w",
+            Instruction::AmominuW { .. } => "amominu.w",
+            Instruction::LrW { .. } => "lr.w",
+            Instruction::ScW { .. } => "sc.w",
+            Instruction::Jr { .. } => "jr",
+            Instruction::Ret => "ret",
+            Instruction::Mv { .. } => "mv",
+            Instruction::Addi16sp { .. } => "c.addi16sp",
+            Instruction::Addi4spn { .. } => "c.addi4spn",
+            Instruction::Nop => "nop",
+            Instruction::Beqz { .. } => "beqz",
+            Instruction::Bnez { .. } => "bnez",
+            Instruction::Ebreak => "ebreak",
+            Instruction::Mret => "mret",
+            Instruction::Sret => "sret",
+            Instruction::Wfi => "wfi",
+            Instruction::SfenceVma { .. } => "sfence.vma",
+            Instruction::Csr { op, imm, .. } => match (op, imm) {
+                (CsrOp::Csrrw, false) => "csrrw",
+                (CsrOp::Csrrs, false) => "csrrs",
+                (CsrOp::Csrrc, false) => "csrrc",
+                (CsrOp::Csrrw, true) => "csrrwi",
+                (CsrOp::Csrrs, true) => "csrrsi",
+                (CsrOp::Csrrc, true) => "csrrci",
+            },
+            Instruction::MiscAlu { op, .. } => match op {
+                MiscAluOp::Sub => "c.sub",
+                MiscAluOp::Xor => "c.xor",
+                MiscAluOp::Or => "c.or",
+                MiscAluOp::And => "c.and",
+            },
+        }
+    }
 
-            Instruction::And { rd, rs1, rs2 } => {
-                format!("and  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Or { rd, rs1, rs2 } => {
-                format!("or   {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Xor { rd, rs1, rs2 } => {
-                format!("xor  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
+    /// Disassembly used in execution traces; same text as the `Display` impl.
+    pub fn pretty_print(&self) -> String {
+        self.to_string()
+    }
+}
 
-            Instruction::Andi { rd, rs1, imm } => {
-                format!("andi {}, {}, {}", reg(*rd), reg(*rs1), imm)
-            }
-            Instruction::Ori { rd, rs1, imm } => {
-                format!("ori  {}, {}, {}", reg(*rd), reg(*rs1), imm)
-            }
-            Instruction::Xori { rd, rs1, imm } => {
-                format!("xori {}, {}, {}", reg(*rd), reg(*rs1), imm)
-            }
+/// Formats a register operand as `xN`.
+struct Reg(usize);
 
-            Instruction::Slt { rd, rs1, rs2 } => {
-                format!("slt  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Sltu { rd, rs1, rs2 } => {
-                format!("sltu {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Slti { rd, rs1, imm } => {
-                format!("slti {}, {}, {}", reg(*rd), reg(*rs1), imm)
-            }
-            Instruction::Sltiu { rd, rs1, imm } => {
-                format!("sltiu {}, {}, {}", reg(*rd), reg(*rs1), imm)
-            }
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "x{}", self.0)
+    }
+}
 
-            Instruction::Sll { rd, rs1, rs2 } => {
-                format!("sll  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Srl { rd, rs1, rs2 } => {
-                format!("srl  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Sra { rd, rs1, rs2 } => {
-                format!("sra  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Slli { rd, rs1, shamt } => {
-                format!("slli {}, {}, {}", reg(*rd), reg(*rs1), shamt)
-            }
-            Instruction::Srli { rd, rs1, shamt } => {
-                format!("srli {}, {}, {}", reg(*rd), reg(*rs1), shamt)
-            }
-            Instruction::Srai { rd, rs1, shamt } => {
-                format!("srai {}, {}, {}", reg(*rd), reg(*rs1), shamt)
-            }
+/// Formats a PC-relative offset with an explicit sign (`+12`, `-8`).
+struct Rel(i32);
 
-            Instruction::Lw { rd, rs1, offset } => {
-                format!("lw   {}, {}({})", reg(*rd), offset, reg(*rs1))
-            }
-            Instruction::Ld { rd, rs1, offset } => {
-                format!("ld   {}, {}({})", reg(*rd), offset, reg(*rs1))
-            }
-            Instruction::Lb { rd, rs1, offset } => {
-                format!("lb   {}, {}({})", reg(*rd), offset, reg(*rs1))
-            }
-            Instruction::Lbu { rd, rs1, offset } => {
-                format!("lbu   {}, {}({})", reg(*rd), offset, reg(*rs1))
-            }
-            Instruction::Lh { rd, rs1, offset } => {
-                format!("lh   {}, {}({})", reg(*rd), offset, reg(*rs1))
-            }
-            Instruction::Lhu { rd, rs1, offset } => {
-                format!("lhu   {}, {}({})", reg(*rd), offset, reg(*rs1))
-            }
-            Instruction::Sh { rs1, rs2, offset } => {
-                format!("sh   {}, {}({})", reg(*rs2), offset, reg(*rs1))
-            }
-            Instruction::Sw { rs1, rs2, offset } => {
-                format!("sw   {}, {}({})", reg(*rs2), offset, reg(*rs1))
-            }
-            Instruction::Sb { rs1, rs2, offset } => {
-                format!("sb   {}, {}({})", reg(*rs2), offset, reg(*rs1))
-            }
-
-            Instruction::Beq { rs1, rs2, offset } => {
-                format!("beq  {}, {}, pc+{}", reg(*rs1), reg(*rs2), offset)
-            }
-            Instruction::Bne { rs1, rs2, offset } => {
-                format!("bne  {}, {}, pc+{}", reg(*rs1), reg(*rs2), offset)
-            }
-            Instruction::Blt { rs1, rs2, offset } => {
-                format!("blt  {}, {}, pc+{}", reg(*rs1), reg(*rs2), offset)
-            }
-            Instruction::Bge { rs1, rs2, offset } => {
-                format!("bge  {}, {}, pc+{}", reg(*rs1), reg(*rs2), offset)
-            }
-            Instruction::Bltu { rs1, rs2, offset } => {
-                format!("bltu {}, {}, pc+{}", reg(*rs1), reg(*rs2), offset)
-            }
-            Instruction::Bgeu { rs1, rs2, offset } => {
-                format!("bgeu {}, {}, pc+{}", reg(*rs1), reg(*rs2), offset)
-            }
+impl fmt::Display for Rel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+}", self.0)
+    }
+}
 
-            Instruction::Jal {
-                rd,
-                offset,
-                compressed: _,
-            } => format!("jal  {}, pc+{}", reg(*rd), offset),
+/// Renders RISC-V assembly, e.g. `add x1, x2, x3`, `lw x5, 8(x6)`, `beq x1, x2, +12`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.mnemonic();
+        match self {
+            Instruction::Add { rd, rs1, rs2 }
+            | Instruction::Sub { rd, rs1, rs2 }
+            | Instruction::And { rd, rs1, rs2 }
+            | Instruction::Or { rd, rs1, rs2 }
+            | Instruction::Xor { rd, rs1, rs2 }
+            | Instruction::Slt { rd, rs1, rs2 }
+            | Instruction::Sltu { rd, rs1, rs2 }
+            | Instruction::Sll { rd, rs1, rs2 }
+            | Instruction::Srl { rd, rs1, rs2 }
+            | Instruction::Sra { rd, rs1, rs2 }
+            | Instruction::Mul { rd, rs1, rs2 }
+            | Instruction::Mulh { rd, rs1, rs2 }
+            | Instruction::Mulhsu { rd, rs1, rs2 }
+            | Instruction::Mulhu { rd, rs1, rs2 }
+            | Instruction::Div { rd, rs1, rs2 }
+            | Instruction::Divu { rd, rs1, rs2 }
+            | Instruction::Rem { rd, rs1, rs2 }
+            | Instruction::Remu { rd, rs1, rs2 } => {
+                write!(f, "{m} {}, {}, {}", Reg(*rd), Reg(*rs1), Reg(*rs2))
+            }
+
+            Instruction::Addi { rd, rs1, imm }
+            | Instruction::Andi { rd, rs1, imm }
+            | Instruction::Ori { rd, rs1, imm }
+            | Instruction::Xori { rd, rs1, imm }
+            | Instruction::Slti { rd, rs1, imm }
+            | Instruction::Sltiu { rd, rs1, imm } => {
+                write!(f, "{m} {}, {}, {}", Reg(*rd), Reg(*rs1), imm)
+            }
+            Instruction::Slli { rd, rs1, shamt }
+            | Instruction::Srli { rd, rs1, shamt }
+            | Instruction::Srai { rd, rs1, shamt } => {
+                write!(f, "{m} {}, {}, {}", Reg(*rd), Reg(*rs1), shamt)
+            }
+
+            Instruction::Lw { rd, rs1, offset }
+            | Instruction::Ld { rd, rs1, offset }
+            | Instruction::Lb { rd, rs1, offset }
+            | Instruction::Lbu { rd, rs1, offset }
+            | Instruction::Lh { rd, rs1, offset }
+            | Instruction::Lhu { rd, rs1, offset } => {
+                write!(f, "{m} {}, {}({})", Reg(*rd), offset, Reg(*rs1))
+            }
+            Instruction::Sh { rs1, rs2, offset }
+            | Instruction::Sw { rs1, rs2, offset }
+            | Instruction::Sb { rs1, rs2, offset } => {
+                write!(f, "{m} {}, {}({})", Reg(*rs2), offset, Reg(*rs1))
+            }
+
+            Instruction::Beq { rs1, rs2, offset }
+            | Instruction::Bne { rs1, rs2, offset }
+            | Instruction::Blt { rs1, rs2, offset }
+            | Instruction::Bge { rs1, rs2, offset }
+            | Instruction::Bltu { rs1, rs2, offset }
+            | Instruction::Bgeu { rs1, rs2, offset } => {
+                write!(f, "{m} {}, {}, {}", Reg(*rs1), Reg(*rs2), Rel(*offset))
+            }
+            Instruction::Beqz { rs1, offset } | Instruction::Bnez { rs1, offset } => {
+                write!(f, "{m} {}, {}", Reg(*rs1), Rel(*offset))
+            }
+
+            Instruction::Jal { rd, offset, .. } => write!(f, "{m} {}, {}", Reg(*rd), Rel(*offset)),
             Instruction::Jalr {
-                rd,
-                rs1,
-                offset,
-                compressed: _,
-            } => format!("jalr {}, {}({})", reg(*rd), offset, reg(*rs1)),
-
-            Instruction::Lui { rd, imm } => format!("lui  {}, {}", reg(*rd), imm),
-            Instruction::Auipc { rd, imm } => format!("auipc {}, {}", reg(*rd), imm),
+                rd, rs1, offset, ..
+            } => write!(f, "{m} {}, {}({})", Reg(*rd), offset, Reg(*rs1)),
+            Instruction::Jr { rs1 } => write!(f, "{m} {}", Reg(*rs1)),
 
-            Instruction::Ecall => "ecall".to_string(),
-            Instruction::Fence => "fence".to_string(),
-            Instruction::Unimp => "unimp".to_string(),
-
-            Instruction::Mul { rd, rs1, rs2 } => {
-                format!("mul  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Mulh { rd, rs1, rs2 } => {
-                format!("mulh {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Mulhsu { rd, rs1, rs2 } => {
-                format!("mulhsu {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Mulhu { rd, rs1, rs2 } => {
-                format!("mulhu {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
+            // The 20-bit upper immediate, as assemblers print it.
+            Instruction::Lui { rd, imm } | Instruction::Auipc { rd, imm } => {
+                write!(f, "{m} {}, {:#x}", Reg(*rd), imm & 0xfffff)
             }
 
-            Instruction::Div { rd, rs1, rs2 } => {
-                format!("div  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Divu { rd, rs1, rs2 } => {
-                format!("divu {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Rem { rd, rs1, rs2 } => {
-                format!("rem  {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::Remu { rd, rs1, rs2 } => {
-                format!("remu {}, {}, {}", reg(*rd), reg(*rs1), reg(*rs2))
+            Instruction::AmoswapW { rd, rs1, rs2 }
+            | Instruction::AmoaddW { rd, rs1, rs2 }
+            | Instruction::AmoandW { rd, rs1, rs2 }
+            | Instruction::AmoorW { rd, rs1, rs2 }
+            | Instruction::AmoxorW { rd, rs1, rs2 }
+            | Instruction::AmomaxW { rd, rs1, rs2 }
+            | Instruction::AmominW { rd, rs1, rs2 }
+            | Instruction::AmomaxuW { rd, rs1, rs2 }
+            | Instruction::AmominuW { rd, rs1, rs2 }
+            | Instruction::ScW { rd, rs1, rs2 } => {
+                write!(f, "{m} {}, {}, ({})", Reg(*rd), Reg(*rs2), Reg(*rs1))
             }
+            Instruction::LrW { rd, rs1 } => write!(f, "{m} {}, ({})", Reg(*rd), Reg(*rs1)),
 
-            Instruction::AmoswapW { rd, rs1, rs2 } => {
-                format!("amoswap.w {}, ({}) <- {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmoaddW { rd, rs1, rs2 } => {
-                format!("amoadd.w  {}, ({}) + {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmoandW { rd, rs1, rs2 } => {
-                format!("amoand.w  {}, ({}) & {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmoorW { rd, rs1, rs2 } => {
-                format!("amoor.w   {}, ({}) | {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmoxorW { rd, rs1, rs2 } => {
-                format!("amoxor.w  {}, ({}) ^ {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmomaxW { rd, rs1, rs2 } => {
-                format!("amomax.w  {}, ({}) max {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmominW { rd, rs1, rs2 } => {
-                format!("amomin.w  {}, ({}) min {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmomaxuW { rd, rs1, rs2 } => {
-                format!("amomaxu.w {}, ({}) maxu {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::AmominuW { rd, rs1, rs2 } => {
-                format!("amominu.w {}, ({}) minu {}", reg(*rd), reg(*rs1), reg(*rs2))
-            }
-            Instruction::LrW { rd, rs1 } => format!("lr.w   {}, ({})", reg(*rd), reg(*rs1)),
-            Instruction::ScW { rd, rs1, rs2 } => {
-                format!("sc.w   {}, ({}) <- {}", reg(*rd), reg(*rs1), reg(*rs2))
+            Instruction::Mv { rd, rs2 } => write!(f, "{m} {}, {}", Reg(*rd), Reg(*rs2)),
+            Instruction::Addi16sp { imm } => write!(f, "{m} {}, {}", Reg(2), imm),
+            Instruction::Addi4spn { rd, imm } => {
+                write!(f, "{m} {}, {}, {}", Reg(*rd), Reg(2), imm)
             }
+            Instruction::MiscAlu { rd, rs2, .. } => write!(f, "{m} {}, {}", Reg(*rd), Reg(*rs2)),
 
-            Instruction::Jr { rs1 } => format!("jr   {}", reg(*rs1)),
-            Instruction::Ret => "ret".to_string(),
-            Instruction::Mv { rd, rs2 } => format!("mv   {}, {}", reg(*rd), reg(*rs2)),
-            Instruction::Addi16sp { imm } => format!("addi16sp sp, {imm}"),
-            Instruction::Addi4spn { rd, imm } => format!("c.addi4spn {}, {}", reg(*rd), imm),
-
-            Instruction::Nop => "nop".to_string(),
-            Instruction::Beqz { rs1, offset } => format!("beqz {}, pc+{}", reg(*rs1), offset),
-            Instruction::Bnez { rs1, offset } => format!("bnez {}, pc+{}", reg(*rs1), offset),
-            Instruction::Ebreak => "ebreak".to_string(),
-            Instruction::Mret => "mret".to_string(),
-            Instruction::Sret => "sret".to_string(),
+            Instruction::SfenceVma { rs1, rs2 } => write!(f, "{m} {}, {}", Reg(*rs1), Reg(*rs2)),
             Instruction::Csr {
-                rd,
-                rs1,
-                csr,
-                op,
-                imm,
+                rd, rs1, csr, imm, ..
             } => {
-                let op_str = match op {
-                    CsrOp::Csrrw => {
-                        if *imm {
-                            "csrrwi"
-                        } else {
-                            "csrrw"
-                        }
-                    }
-                    CsrOp::Csrrs => {
-                        if *imm {
-                            "csrrsi"
-                        } else {
-                            "csrrs"
-                        }
-                    }
-                    CsrOp::Csrrc => {
-                        if *imm {
-                            "csrrci"
-                        } else {
-                            "csrrc"
-                        }
-                    }
-                };
-                let src = if *imm { format!("{rs1}") } else { reg(*rs1) };
-                format!("{} {}, {}, 0x{:03x}", op_str, reg(*rd), src, csr)
-            }
-
-            Instruction::MiscAlu { rd, rs2, op } => {
-                let op_str = match op {
-                    MiscAluOp::Sub => "c.sub",
-                    MiscAluOp::Xor => "c.xor",
-                    MiscAluOp::Or => "c.or",
-                    MiscAluOp::And => "c.and",
-                };
-                format!("{} {}, {}", op_str, reg(*rd), reg(*rs2))
-            }
+                if *imm {
+                    write!(f, "{m} {}, {:#05x}, {}", Reg(*rd), csr, rs1)
+                } else {
+                    write!(f, "{m} {}, {:#05x}, {}", Reg(*rd), csr, Reg(*rs1))
+                }
+            }
+
+            Instruction::Ecall
+            | Instruction::Fence
+            | Instruction::Unimp
+            | Instruction::Ret
+            | Instruction::Nop
+            | Instruction::Ebreak
+            | Instruction::Mret
+            | Instruction::Sret
+            | Instruction::Wfi => f.write_str(m),
         }
     }
 }
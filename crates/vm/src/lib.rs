@@ -1,6 +1,7 @@
 pub mod console;
 pub mod cpu;
 pub mod decoder;
+pub mod hook;
 pub mod instruction;
 pub mod isa;
 pub mod isa_compressed;
@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use super::VirtualAddress;
+
+/// Host-side handler behind a memory-mapped I/O region.
+///
+/// Loads and stores that fall entirely inside a registered region bypass the
+/// page tables and backing store and are forwarded here instead. `offset` is
+/// relative to the region base and `size` is the access width in bytes (1, 2 or 4).
+pub trait MmioDevice {
+    /// Serve a load; only the low `size` bytes of the returned value are used.
+    fn read(&mut self, offset: u32, size: u8) -> u32;
+    /// Handle a store of the low `size` bytes of `value`.
+    fn write(&mut self, offset: u32, size: u8, value: u32);
+}
+
+/// Shared handle to a registered device.
+pub type MmioHandle = Rc<RefCell<dyn MmioDevice>>;
+
+/// A virtual address range dispatched to a device.
+#[derive(Clone)]
+pub(crate) struct MmioRegion {
+    base: u32,
+    len: u32,
+    device: MmioHandle,
+}
+
+impl MmioRegion {
+    pub(crate) fn new(base: VirtualAddress, len: u32, device: MmioHandle) -> Option<Self> {
+        if len == 0 {
+            return None;
+        }
+        base.as_u32().checked_add(len)?;
+        Some(Self {
+            base: base.as_u32(),
+            len,
+            device,
+        })
+    }
+
+    pub(crate) fn overlaps(&self, other: &MmioRegion) -> bool {
+        self.base < other.base + other.len && other.base < self.base + self.len
+    }
+
+    /// Offset of `[addr, addr + size)` inside the region, if it fits entirely.
+    fn offset_of(&self, addr: VirtualAddress, size: u8) -> Option<u32> {
+        let offset = addr.as_u32().checked_sub(self.base)?;
+        let end = offset.checked_add(size as u32)?;
+        (end <= self.len).then_some(offset)
+    }
+
+    pub(crate) fn read(&self, addr: VirtualAddress, size: u8) -> Option<u32> {
+        let offset = self.offset_of(addr, size)?;
+        Some(self.device.borrow_mut().read(offset, size))
+    }
+
+    pub(crate) fn write(&self, addr: VirtualAddress, size: u8, value: u32) -> bool {
+        match self.offset_of(addr, size) {
+            Some(offset) => {
+                self.device.borrow_mut().write(offset, size, value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Debug for MmioRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmioRegion")
+            .field("base", &format_args!("0x{:08x}", self.base))
+            .field("len", &self.len)
+            .finish()
+    }
+}
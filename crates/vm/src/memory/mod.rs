@@ -3,8 +3,10 @@ use std::rc::Rc;
 
 use crate::metering::{MemoryAccessKind, Metering};
 
+mod mmio;
 mod sv32;
 
+pub use mmio::{MmioDevice, MmioHandle};
 pub use sv32::Sv32Memory;
 pub use types::mmu::*;
 
@@ -99,6 +101,95 @@ impl From<VirtualAddress> for usize {
     }
 }
 
+/// A data access that failed translation, kept so the CPU can raise a page fault.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemoryFault {
+    pub addr: VirtualAddress,
+    pub kind: MemoryAccessKind,
+}
+
+/// A host-side write (`Sv32Memory::try_write_bytes`) that hit a page it cannot store to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemError {
+    /// `addr` is the first byte whose page is not mapped.
+    Unmapped { addr: VirtualAddress },
+    /// `addr` is the first byte whose page is mapped but not writable.
+    ReadOnly { addr: VirtualAddress },
+}
+
+impl std::fmt::Display for MemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemError::Unmapped { addr } => write!(f, "write to unmapped address 0x{:08x}", addr.0),
+            MemError::ReadOnly { addr } => {
+                write!(f, "write to read-only address 0x{:08x}", addr.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MemError {}
+
+/// Why `Sv32Memory::map_range_with_page_size` refused a mapping.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MapError {
+    /// Sv32 leaves are 4 KiB pages or 4 MiB megapages; `size` is neither.
+    UnsupportedPageSize { size: usize },
+    /// The range start or length is not a multiple of the page size.
+    Misaligned { addr: VirtualAddress, len: usize },
+    /// `addr` falls in an L1 entry that already holds a table or a megapage.
+    Overlap { addr: VirtualAddress },
+    /// Physical memory ran out; pages mapped before this one stay mapped.
+    OutOfMemory { addr: VirtualAddress },
+}
+
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapError::UnsupportedPageSize { size } => write!(
+                f,
+                "unsupported page size {size}: Sv32 maps 4 KiB pages or 4 MiB megapages"
+            ),
+            MapError::Misaligned { addr, len } => write!(
+                f,
+                "range 0x{:08x}+0x{len:x} is not aligned to the page size",
+                addr.0
+            ),
+            MapError::Overlap { addr } => {
+                write!(f, "0x{:08x} is already mapped at the L1 level", addr.0)
+            }
+            MapError::OutOfMemory { addr } => {
+                write!(f, "out of physical memory mapping 0x{:08x}", addr.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+/// Why `Sv32Memory::try_new` refused a memory size or frame size.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryConfigError {
+    /// Sv32 frames are `PAGE_SIZE` bytes; `size` is not.
+    UnsupportedFrameSize { size: usize },
+    /// `total_size_bytes` was 0.
+    Empty,
+}
+
+impl std::fmt::Display for MemoryConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryConfigError::UnsupportedFrameSize { size } => write!(
+                f,
+                "unsupported frame size {size}: Sv32 frames are {PAGE_SIZE} bytes"
+            ),
+            MemoryConfigError::Empty => write!(f, "physical memory size must be > 0"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryConfigError {}
+
 pub trait MMU: std::fmt::Debug {
     // --- CPU-facing data access (loads/stores/fetches) ---
     fn mem(&self) -> Ref<'_, Vec<u8>>;
@@ -107,6 +198,13 @@ pub trait MMU: std::fmt::Debug {
         start: VirtualAddress,
         end: VirtualAddress,
     ) -> Option<std::cell::Ref<'_, [u8]>>;
+    /// Like `mem_slice`, but translated as an instruction fetch: every page in the
+    /// range must be executable. A failure is remembered as a `Fetch` fault.
+    fn fetch_slice(
+        &self,
+        start: VirtualAddress,
+        end: VirtualAddress,
+    ) -> Option<std::cell::Ref<'_, [u8]>>;
     fn store_u16(
         &self,
         addr: VirtualAddress,
@@ -152,10 +250,14 @@ pub trait MMU: std::fmt::Debug {
         metering: &mut dyn Metering,
         kind: MemoryAccessKind,
     ) -> Option<u32>;
+    /// Take the most recent translation failure, clearing it.
+    fn take_fault(&self) -> Option<MemoryFault>;
 }
 
 pub trait API: std::fmt::Debug {
-    fn map_range(&self, start: VirtualAddress, len: usize, perms: Perms);
+    /// Map `[start, start + len)` onto freshly allocated frames. Returns false when
+    /// physical memory runs out (pages mapped before that point stay mapped).
+    fn map_range(&self, start: VirtualAddress, len: usize, perms: Perms) -> bool;
     /// Get the current page-table root (index/identifier).
     fn current_root(&self) -> usize;
     /// Read the current satp value.
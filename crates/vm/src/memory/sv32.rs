@@ -4,17 +4,23 @@ use std::rc::Rc;
 use crate::metering::{MemoryAccessKind, MeterResult, Metering};
 
 use types::{
-    map_allocating, map_to_physical, Sv32PagePerms, Sv32PageTable, SV32_PTE_R, SV32_PTE_V,
-    SV32_PTE_W, SV32_PTE_X, SV32_SATP_PPN_MASK,
+    map_allocating, map_megapage, map_to_physical, protect_range, Sv32PagePerms, Sv32PageTable,
+    SV32_MEGAPAGE_SIZE, SV32_PTE_R, SV32_PTE_V, SV32_PTE_W, SV32_PTE_X, SV32_SATP_PPN_MASK,
+    SV32_VPN_MASK,
 };
 
-use super::{Perms, VirtualAddress, API, MMU};
+use super::mmio::MmioRegion;
+use super::{
+    MapError, MemError, MemoryConfigError, MemoryFault, MmioHandle, Perms, VirtualAddress, API,
+    MMU, PAGE_SIZE,
+};
 
 /// Software Sv32 MMU backed by a contiguous physical buffer.
 ///
 /// Design at a glance:
 /// - Physical memory is a single `Vec<u8>` (`backing`). Frames are 4 KiB slices into it.
 /// - Virtual→physical is resolved with Sv32-style page tables: L1 root (VPN1) and L2 (VPN0).
+///   An L1 entry may also be a leaf mapping a whole 4 MiB megapage.
 /// - Page tables live in guest memory; `translate` walks them using the satp root PPN.
 /// - A bump frame allocator hands out PPNs (physical page numbers) sequentially from the backing; no free list yet.
 /// - Mapping APIs (`map_page`/`map_range`) allocate tables/frames and set R/W/X/U bits.
@@ -29,7 +35,8 @@ use super::{Perms, VirtualAddress, API, MMU};
 /// - Identity mapping is not assumed; everything uses page tables even for kernel.
 #[derive(Debug)]
 pub struct Sv32Memory {
-    /// Page size in bytes (Sv32: 4 KiB).
+    /// Frame size in bytes. `PAGE_SIZE` (4 KiB) under `new`'s contract: Sv32 PPNs count
+    /// 4 KiB frames, and megapages are built from aligned runs of them.
     page_size: usize,
     /// Total number of physical frames available.
     total_pages: usize,
@@ -39,6 +46,10 @@ pub struct Sv32Memory {
     satp: Cell<u32>,
     /// Next free physical frame index for frame allocation.
     next_free_frame: Cell<usize>,
+    /// Last access that failed translation, for page-fault reporting.
+    last_fault: Cell<Option<MemoryFault>>,
+    /// Virtual ranges whose loads/stores go to host devices instead of memory.
+    mmio: RefCell<Vec<MmioRegion>>,
 }
 
 fn perms_to_sv32(perms: Perms) -> Sv32PagePerms {
@@ -51,6 +62,10 @@ fn perms_to_sv32(perms: Perms) -> Sv32PagePerms {
 }
 
 impl Sv32Memory {
+    /// Callers must pass `PAGE_SIZE` as `page_size`: Sv32 PPNs count 4 KiB frames, and
+    /// 4 MiB megapages are mapped with `map_range_with_page_size`, not by a larger frame
+    /// size. Other frame sizes are not checked here; use [`Sv32Memory::try_new`] to
+    /// validate untrusted sizes. Panics if either size is 0.
     pub fn new(total_size_bytes: usize, page_size: usize) -> Self {
         assert!(page_size != 0, "page_size must be > 0");
         assert!(total_size_bytes != 0, "total_size_bytes must be > 0");
@@ -67,12 +82,26 @@ impl Sv32Memory {
             backing: Rc::new(RefCell::new(vec![0u8; total])),
             satp: Cell::new(root_ppn as u32),
             next_free_frame: Cell::new(root_ppn + 1),
+            last_fault: Cell::new(None),
+            mmio: RefCell::new(Vec::new()),
         };
         // Zero the root page table frame so we can immediately populate it.
         mem.zero_frame(root_ppn);
         mem
     }
 
+    /// Checked [`Sv32Memory::new`]: refuses a frame size other than `PAGE_SIZE` and an
+    /// empty memory instead of building an unusable MMU.
+    pub fn try_new(total_size_bytes: usize, page_size: usize) -> Result<Self, MemoryConfigError> {
+        if page_size != PAGE_SIZE {
+            return Err(MemoryConfigError::UnsupportedFrameSize { size: page_size });
+        }
+        if total_size_bytes == 0 {
+            return Err(MemoryConfigError::Empty);
+        }
+        Ok(Self::new(total_size_bytes, page_size))
+    }
+
     fn total_size(&self) -> usize {
         self.backing.borrow().len()
     }
@@ -100,6 +129,24 @@ impl Sv32Memory {
         Some(frame)
     }
 
+    /// Allocate the 1024 contiguous, megapage-aligned frames behind one megapage and
+    /// return the first PPN. Frames skipped to reach the alignment are not reused.
+    fn allocate_megapage_frames(&self) -> Option<usize> {
+        let frames = SV32_MEGAPAGE_SIZE / self.page_size;
+        let first = self
+            .next_free_frame
+            .get()
+            .checked_next_multiple_of(frames)?;
+        let end = first.checked_add(frames)?;
+        if end > self.total_pages {
+            return None;
+        }
+        self.next_free_frame.set(end);
+        let start = first * self.page_size;
+        self.backing.borrow_mut()[start..end * self.page_size].fill(0);
+        Some(first)
+    }
+
     pub fn next_free_ppn(&self) -> usize {
         self.next_free_frame.get()
     }
@@ -134,13 +181,71 @@ impl Sv32Memory {
     }
 
     /// Map a contiguous virtual range page-by-page with the given permissions.
-    pub fn map_range(&self, start: VirtualAddress, len: usize, perms: Perms) {
+    ///
+    /// Returns false once no free frame is left for a leaf or page table, or the
+    /// range overflows the address space; pages mapped before that stay mapped.
+    pub fn map_range(&self, start: VirtualAddress, len: usize, perms: Perms) -> bool {
         let root = self.root_ppn() as u32;
-        let ok = map_allocating(self, root, start.as_u32(), len, perms_to_sv32(perms));
-        assert!(ok, "map_range failed");
+        map_allocating(self, root, start.as_u32(), len, perms_to_sv32(perms))
+    }
+
+    /// Map `[start, start + len)` with leaves of `page_size`: `PAGE_SIZE` behaves like
+    /// `map_range`, `SV32_MEGAPAGE_SIZE` maps each 4 MiB with one L1 leaf backed by
+    /// fresh contiguous frames. Megapage ranges must be 4 MiB aligned and cannot
+    /// share an L1 entry with 4 KiB mappings.
+    pub fn map_range_with_page_size(
+        &self,
+        start: VirtualAddress,
+        len: usize,
+        perms: Perms,
+        page_size: usize,
+    ) -> Result<(), MapError> {
+        match page_size {
+            PAGE_SIZE => {
+                if self.map_range(start, len, perms) {
+                    Ok(())
+                } else {
+                    Err(MapError::OutOfMemory { addr: start })
+                }
+            }
+            SV32_MEGAPAGE_SIZE => {
+                if !start.as_usize().is_multiple_of(page_size) || !len.is_multiple_of(page_size) {
+                    return Err(MapError::Misaligned { addr: start, len });
+                }
+                if start
+                    .as_usize()
+                    .checked_add(len)
+                    .is_none_or(|end| end > 1 << 32)
+                {
+                    return Err(MapError::Misaligned { addr: start, len });
+                }
+                let root = self.root_ppn() as u32;
+                for offset in (0..len).step_by(page_size) {
+                    let va = start.wrapping_add(offset as u32);
+                    let vpn1 = va.vpn1() as usize & SV32_VPN_MASK as usize;
+                    let l1_pte = self
+                        .root_base()
+                        .and_then(|base| self.read_pte(base + vpn1 * core::mem::size_of::<u32>()));
+                    if l1_pte.is_none_or(|pte| pte & SV32_PTE_V != 0) {
+                        return Err(MapError::Overlap { addr: va });
+                    }
+                    let ppn = self
+                        .allocate_megapage_frames()
+                        .ok_or(MapError::OutOfMemory { addr: va })?;
+                    let phys = (ppn * self.page_size) as u32;
+                    if !map_megapage(self, root, va.as_u32(), phys, perms_to_sv32(perms)) {
+                        return Err(MapError::Overlap { addr: va });
+                    }
+                }
+                Ok(())
+            }
+            size => Err(MapError::UnsupportedPageSize { size }),
+        }
     }
 
     /// Map a virtual range to a specific physical range without allocating new leaf frames.
+    ///
+    /// Returns false if the physical range extends past the backing memory.
     pub fn map_physical_range(
         &self,
         va_start: VirtualAddress,
@@ -148,6 +253,12 @@ impl Sv32Memory {
         len: usize,
         perms: Perms,
     ) -> bool {
+        let in_bounds = (phys_start as usize)
+            .checked_add(len)
+            .is_some_and(|end| end <= self.total_size());
+        if !in_bounds {
+            return false;
+        }
         map_to_physical(
             self,
             self.root_ppn() as u32,
@@ -158,18 +269,69 @@ impl Sv32Memory {
         )
     }
 
+    /// Route loads and stores in `[base, base + len)` to `device` instead of memory.
+    ///
+    /// The range is matched on virtual addresses before translation, so it needs no
+    /// page mapping and applies under every root. Returns false for an empty or
+    /// overflowing range, or one that overlaps an existing region.
+    pub fn register_mmio(&self, base: VirtualAddress, len: u32, device: MmioHandle) -> bool {
+        let Some(region) = MmioRegion::new(base, len, device) else {
+            return false;
+        };
+        let mut regions = self.mmio.borrow_mut();
+        if regions.iter().any(|existing| existing.overlaps(&region)) {
+            return false;
+        }
+        regions.push(region);
+        true
+    }
+
+    fn mmio_read(&self, addr: VirtualAddress, size: u8) -> Option<u32> {
+        let regions = self.mmio.borrow();
+        regions.iter().find_map(|region| region.read(addr, size))
+    }
+
+    fn mmio_write(&self, addr: VirtualAddress, size: u8, value: u32) -> bool {
+        let regions = self.mmio.borrow();
+        regions.iter().any(|region| region.write(addr, size, value))
+    }
+
+    /// Change the permissions of an already-mapped range without touching its physical frames.
+    ///
+    /// Returns false, leaving every PTE unchanged, if any page in the range is unmapped.
+    pub fn protect_range(&self, start: VirtualAddress, len: usize, perms: Perms) -> bool {
+        protect_range(
+            self,
+            self.root_ppn() as u32,
+            start.as_u32(),
+            len,
+            perms_to_sv32(perms),
+        )
+    }
+
     /// Translate a virtual address to a physical offset into `backing`, checking permissions.
     ///
     /// This emulates an Sv32 page-table walk driven by the current `satp`:
     /// - `satp` PPN selects the root L1 page table (written by the kernel in guest memory).
-    /// - We read the L1 PTE at VPN1; it must be valid and non-leaf (no superpages here).
-    /// - From that PPN we read the L2 PTE at VPN0; it must be valid and carry R/W/X bits.
+    /// - We read the L1 PTE at VPN1; it must be valid. If it carries R/W/X bits it is a
+    ///   megapage leaf covering 4 MiB (its PPN[0] must be zero, as the spec requires).
+    /// - Otherwise from that PPN we read the L2 PTE at VPN0; it must be valid and carry R/W/X bits.
     /// - Permissions are checked against the access kind; on success we return a byte offset
     ///   into the physical backing buffer.
     ///
     /// All PTE bytes we read here are what the kernel previously wrote into guest memory;
     /// the host MMU just interprets them to enforce translations.
+    ///
+    /// A failed translation is remembered (see `MMU::take_fault`).
     fn translate(&self, va: VirtualAddress, kind: MemoryAccessKind) -> Option<usize> {
+        let phys = self.walk(va, kind);
+        if phys.is_none() {
+            self.last_fault.set(Some(MemoryFault { addr: va, kind }));
+        }
+        phys
+    }
+
+    fn walk(&self, va: VirtualAddress, kind: MemoryAccessKind) -> Option<usize> {
         let root_base = self.root_base()?;
         let vpn1 = va.vpn1() as usize;
         let vpn0 = va.vpn0() as usize;
@@ -181,9 +343,16 @@ impl Sv32Memory {
             return None;
         }
 
-        // We only support two-level translation; reject L1 leaf/superpages.
         if root_pte & (SV32_PTE_R | SV32_PTE_W | SV32_PTE_X) != 0 {
-            return None;
+            // Megapage leaf: PPN[1] selects the 4 MiB frame, VPN0 and the offset index it.
+            let ppn = (root_pte >> 10) as usize;
+            if ppn & SV32_VPN_MASK as usize != 0 || !Self::leaf_allows(root_pte, kind) {
+                return None;
+            }
+            let in_megapage = va.as_usize() & (SV32_MEGAPAGE_SIZE - 1);
+            return ppn
+                .checked_mul(self.page_size)
+                .and_then(|base| base.checked_add(in_megapage));
         }
 
         let l2_ppn = (root_pte >> 10) as usize;
@@ -196,15 +365,7 @@ impl Sv32Memory {
             return None;
         }
 
-        let allowed = match kind {
-            MemoryAccessKind::Load | MemoryAccessKind::ReservationLoad => {
-                l2_pte & (SV32_PTE_R | SV32_PTE_X) != 0
-            }
-            MemoryAccessKind::Store
-            | MemoryAccessKind::Atomic
-            | MemoryAccessKind::ReservationStore => l2_pte & SV32_PTE_W != 0,
-        };
-        if !allowed {
+        if !Self::leaf_allows(l2_pte, kind) {
             return None;
         }
 
@@ -214,6 +375,19 @@ impl Sv32Memory {
             .and_then(|base| base.checked_add(offset))
     }
 
+    /// Whether a leaf PTE's R/W/X bits permit `kind`.
+    fn leaf_allows(pte: u32, kind: MemoryAccessKind) -> bool {
+        match kind {
+            MemoryAccessKind::Load | MemoryAccessKind::ReservationLoad => {
+                pte & (SV32_PTE_R | SV32_PTE_X) != 0
+            }
+            MemoryAccessKind::Store
+            | MemoryAccessKind::Atomic
+            | MemoryAccessKind::ReservationStore => pte & SV32_PTE_W != 0,
+            MemoryAccessKind::Fetch => pte & SV32_PTE_X != 0,
+        }
+    }
+
     fn meter_access(
         metering: &mut dyn Metering,
         kind: MemoryAccessKind,
@@ -226,6 +400,87 @@ impl Sv32Memory {
         )
     }
 
+    /// Borrow `[start, end)` of guest memory, translating both ends as `kind`. The
+    /// range must be physically contiguous.
+    fn slice_as(
+        &self,
+        start: VirtualAddress,
+        end: VirtualAddress,
+        kind: MemoryAccessKind,
+    ) -> Option<std::cell::Ref<'_, [u8]>> {
+        if start.as_usize() > end.as_usize() {
+            return None;
+        }
+        let len = end.as_usize().saturating_sub(start.as_usize());
+        let phys_start = self.translate(start, kind)?;
+        let last_va = VirtualAddress(end.as_u32().saturating_sub(1));
+        let phys_last = self.translate(last_va, kind)?;
+        // Ensure the range is physically contiguous.
+        if phys_last + 1 != phys_start + len {
+            return None;
+        }
+        let backing = self.backing.borrow();
+        Some(std::cell::Ref::map(backing, move |v| {
+            &v[phys_start..phys_start + len]
+        }))
+    }
+
+    /// Translate an `N`-byte access. One that crosses a page boundary translates
+    /// both pages up front and returns the second page's physical address with the
+    /// byte count in the first, so an unmapped or read-only second page faults the
+    /// whole access before any byte moves.
+    fn translate_access<const N: usize>(
+        &self,
+        addr: VirtualAddress,
+        kind: MemoryAccessKind,
+    ) -> Option<(usize, Option<(usize, usize)>)> {
+        let phys = self.translate(addr, kind)?;
+        let in_page = self.page_size - addr.offset() as usize;
+        if N <= in_page {
+            return Some((phys, None));
+        }
+        let next = self.translate(addr.wrapping_add(in_page as u32), kind)?;
+        Some((phys, Some((in_page, next))))
+    }
+
+    fn load_bytes<const N: usize>(
+        &self,
+        addr: VirtualAddress,
+        kind: MemoryAccessKind,
+    ) -> Option<[u8; N]> {
+        let (phys, split) = self.translate_access::<N>(addr, kind)?;
+        let backing = self.backing.borrow();
+        let mut out = [0u8; N];
+        match split {
+            None => out.copy_from_slice(&backing[phys..phys + N]),
+            Some((head, next)) => {
+                out[..head].copy_from_slice(&backing[phys..phys + head]);
+                out[head..].copy_from_slice(&backing[next..next + N - head]);
+            }
+        }
+        Some(out)
+    }
+
+    fn store_bytes<const N: usize>(
+        &self,
+        addr: VirtualAddress,
+        bytes: [u8; N],
+        kind: MemoryAccessKind,
+    ) -> bool {
+        let Some((phys, split)) = self.translate_access::<N>(addr, kind) else {
+            return false;
+        };
+        let mut backing = self.backing.borrow_mut();
+        match split {
+            None => backing[phys..phys + N].copy_from_slice(&bytes),
+            Some((head, next)) => {
+                backing[phys..phys + head].copy_from_slice(&bytes[..head]);
+                backing[next..next + N - head].copy_from_slice(&bytes[head..]);
+            }
+        }
+        true
+    }
+
     /// Copy a slice into physical backing, honoring translation and page boundaries.
     fn copy_into_backing(&self, start: VirtualAddress, data: &[u8], kind: MemoryAccessKind) {
         let mut remaining = data.len();
@@ -255,6 +510,84 @@ impl Sv32Memory {
     pub fn write_bytes(&self, start: VirtualAddress, data: &[u8]) {
         self.copy_into_backing(start, data, MemoryAccessKind::Store);
     }
+
+    /// Checked `write_bytes`: verifies every page of the range is mapped and writable
+    /// before copying, so nothing is written on error. The error names the first byte
+    /// that could not be stored.
+    pub fn try_write_bytes(&self, start: VirtualAddress, data: &[u8]) -> Result<(), MemError> {
+        let mut checked = 0usize;
+        while checked < data.len() {
+            let va = start.wrapping_add(checked as u32);
+            if self.walk(va, MemoryAccessKind::Store).is_none() {
+                return Err(match self.walk(va, MemoryAccessKind::Load) {
+                    Some(_) => MemError::ReadOnly { addr: va },
+                    None => MemError::Unmapped { addr: va },
+                });
+            }
+            checked += self.page_size - va.offset() as usize;
+        }
+        self.copy_into_backing(start, data, MemoryAccessKind::Store);
+        Ok(())
+    }
+
+    /// Read a NUL-terminated UTF-8 string at `start`, scanning at most `max_len` bytes
+    /// for the terminator (which is not part of the result). Returns `None` when a byte
+    /// is unmapped, no NUL falls within the cap, or the bytes are not UTF-8. The bytes
+    /// are copied page by page, so the string may span non-contiguous frames.
+    pub fn read_cstr(&self, start: VirtualAddress, max_len: usize) -> Option<String> {
+        let mut bytes = Vec::new();
+        while bytes.len() < max_len {
+            let va = start.wrapping_add(bytes.len() as u32);
+            let phys = self.walk(va, MemoryAccessKind::Load)?;
+            let in_page = (self.page_size - va.offset() as usize).min(max_len - bytes.len());
+            let backing = self.backing.borrow();
+            let chunk = &backing[phys..phys + in_page];
+            match chunk.iter().position(|&b| b == 0) {
+                Some(nul) => {
+                    bytes.extend_from_slice(&chunk[..nul]);
+                    return String::from_utf8(bytes).ok();
+                }
+                None => bytes.extend_from_slice(chunk),
+            }
+        }
+        None
+    }
+
+    /// xxd-style dump of `[start, start + len)` for diagnostics: 16 bytes per line as
+    /// `address: hex pairs  ascii`. Unmapped bytes show as `--` in the hex columns and
+    /// `~` in the ASCII column; nothing is metered and no fault is recorded.
+    pub fn hexdump(&self, start: VirtualAddress, len: usize) -> String {
+        let backing = self.backing.borrow();
+        let mut out = String::new();
+        for line_start in (0..len).step_by(16) {
+            let line_va = start.wrapping_add(line_start as u32);
+            let mut hex = String::new();
+            let mut ascii = String::new();
+            for i in 0..(len - line_start).min(16) {
+                if i > 0 && i % 2 == 0 {
+                    hex.push(' ');
+                }
+                let va = line_va.wrapping_add(i as u32);
+                match self.walk(va, MemoryAccessKind::Load) {
+                    Some(phys) => {
+                        let byte = backing[phys];
+                        hex.push_str(&format!("{byte:02x}"));
+                        ascii.push(if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        });
+                    }
+                    None => {
+                        hex.push_str("--");
+                        ascii.push('~');
+                    }
+                }
+            }
+            out.push_str(&format!("{:08x}: {hex:<39}  {ascii}\n", line_va.as_u32()));
+        }
+        out
+    }
 }
 
 impl Sv32PageTable for Sv32Memory {
@@ -289,21 +622,15 @@ impl MMU for Sv32Memory {
         start: VirtualAddress,
         end: VirtualAddress,
     ) -> Option<std::cell::Ref<'_, [u8]>> {
-        if start.as_usize() > end.as_usize() {
-            return None;
-        }
-        let len = end.as_usize().saturating_sub(start.as_usize());
-        let phys_start = self.translate(start, MemoryAccessKind::Load)?;
-        let last_va = VirtualAddress(end.as_u32().saturating_sub(1));
-        let phys_last = self.translate(last_va, MemoryAccessKind::Load)?;
-        // Ensure the range is physically contiguous.
-        if phys_last + 1 != phys_start + len {
-            return None;
-        }
-        let backing = self.backing.borrow();
-        Some(std::cell::Ref::map(backing, move |v| {
-            &v[phys_start..phys_start + len]
-        }))
+        self.slice_as(start, end, MemoryAccessKind::Load)
+    }
+
+    fn fetch_slice(
+        &self,
+        start: VirtualAddress,
+        end: VirtualAddress,
+    ) -> Option<std::cell::Ref<'_, [u8]>> {
+        self.slice_as(start, end, MemoryAccessKind::Fetch)
     }
 
     fn store_u16(
@@ -316,13 +643,10 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 2) {
             return false;
         }
-        if let Some(offset) = self.translate(addr, kind) {
-            let mut backing = self.backing.borrow_mut();
-            backing[offset..offset + 2].copy_from_slice(&val.to_le_bytes());
-        } else {
-            return false;
+        if self.mmio_write(addr, 2, val as u32) {
+            return true;
         }
-        true
+        self.store_bytes(addr, val.to_le_bytes(), kind)
     }
 
     fn store_u32(
@@ -335,13 +659,10 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 4) {
             return false;
         }
-        if let Some(offset) = self.translate(addr, kind) {
-            let mut backing = self.backing.borrow_mut();
-            backing[offset..offset + 4].copy_from_slice(&val.to_le_bytes());
-        } else {
-            return false;
+        if self.mmio_write(addr, 4, val) {
+            return true;
         }
-        true
+        self.store_bytes(addr, val.to_le_bytes(), kind)
     }
 
     fn store_u8(
@@ -354,6 +675,9 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 1) {
             return false;
         }
+        if self.mmio_write(addr, 1, val as u32) {
+            return true;
+        }
         if let Some(offset) = self.translate(addr, kind) {
             let mut backing = self.backing.borrow_mut();
             backing[offset] = val;
@@ -372,11 +696,10 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 4) {
             return None;
         }
-        let backing = self.backing.borrow();
-        let offset = self.translate(addr, kind)?;
-        Some(u32::from_le_bytes(
-            backing[offset..offset + 4].try_into().unwrap(),
-        ))
+        if let Some(value) = self.mmio_read(addr, 4) {
+            return Some(value);
+        }
+        self.load_bytes(addr, kind).map(u32::from_le_bytes)
     }
 
     fn load_byte(
@@ -388,6 +711,9 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 1) {
             return None;
         }
+        if let Some(value) = self.mmio_read(addr, 1) {
+            return Some(value as u8);
+        }
         let backing = self.backing.borrow();
         let offset = self.translate(addr, kind)?;
         Some(backing[offset])
@@ -402,11 +728,10 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 2) {
             return None;
         }
-        let backing = self.backing.borrow();
-        let offset = self.translate(addr, kind)?;
-        Some(u16::from_le_bytes(
-            backing[offset..offset + 2].try_into().unwrap(),
-        ))
+        if let Some(value) = self.mmio_read(addr, 2) {
+            return Some(value as u16);
+        }
+        self.load_bytes(addr, kind).map(u16::from_le_bytes)
     }
 
     fn load_word(
@@ -418,17 +743,20 @@ impl MMU for Sv32Memory {
         if !Self::meter_access(metering, kind, addr, 4) {
             return None;
         }
-        let backing = self.backing.borrow();
-        let offset = self.translate(addr, kind)?;
-        Some(u32::from_le_bytes(
-            backing[offset..offset + 4].try_into().unwrap(),
-        ))
+        if let Some(value) = self.mmio_read(addr, 4) {
+            return Some(value);
+        }
+        self.load_bytes(addr, kind).map(u32::from_le_bytes)
+    }
+
+    fn take_fault(&self) -> Option<MemoryFault> {
+        self.last_fault.take()
     }
 }
 
 impl API for Sv32Memory {
-    fn map_range(&self, start: VirtualAddress, len: usize, perms: Perms) {
-        Sv32Memory::map_range(self, start, len, perms);
+    fn map_range(&self, start: VirtualAddress, len: usize, perms: Perms) -> bool {
+        Sv32Memory::map_range(self, start, len, perms)
     }
 
     fn current_root(&self) -> usize {
@@ -1,3 +1,7 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use crate::cpu::PrivilegeMode;
 use crate::instruction::Instruction;
 
@@ -16,6 +20,8 @@ pub enum MemoryAccessKind {
     Atomic,
     ReservationLoad,
     ReservationStore,
+    /// Instruction fetch at the current PC.
+    Fetch,
 }
 
 /// Pluggable metering interface. Implementors can account for gas or other resource
@@ -70,6 +76,16 @@ pub trait Metering: std::fmt::Debug {
     fn on_call(&mut self, _input_bytes: usize) -> MeterResult {
         MeterResult::Continue
     }
+
+    /// Called for every conditional branch, whether or not it is taken.
+    fn on_branch(&mut self, _pc: u32, _target: u32, _taken: bool) -> MeterResult {
+        MeterResult::Continue
+    }
+
+    /// Called when `jal`/`jalr` writes a link register (i.e. a function call).
+    fn on_call_target(&mut self, _pc: u32, _target: u32) -> MeterResult {
+        MeterResult::Continue
+    }
 }
 
 /// Default metering that performs no accounting.
@@ -77,3 +93,127 @@ pub trait Metering: std::fmt::Debug {
 pub struct NoopMeter;
 
 impl Metering for NoopMeter {}
+
+/// Memory access tallies grouped by kind.
+///
+/// LR/SC reservation accesses are counted with the other atomics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccessCounts {
+    pub loads: u64,
+    pub stores: u64,
+    pub atomics: u64,
+    pub fetches: u64,
+}
+
+impl MemoryAccessCounts {
+    /// Count one access of `kind`.
+    pub fn record(&mut self, kind: MemoryAccessKind) {
+        let slot = match kind {
+            MemoryAccessKind::Load => &mut self.loads,
+            MemoryAccessKind::Store => &mut self.stores,
+            MemoryAccessKind::Atomic
+            | MemoryAccessKind::ReservationLoad
+            | MemoryAccessKind::ReservationStore => &mut self.atomics,
+            MemoryAccessKind::Fetch => &mut self.fetches,
+        };
+        *slot = slot.saturating_add(1);
+    }
+
+    /// Data accesses only (everything except instruction fetches).
+    pub fn data_accesses(&self) -> u64 {
+        self.loads
+            .saturating_add(self.stores)
+            .saturating_add(self.atomics)
+    }
+}
+
+/// Metering that tallies memory accesses per kind.
+///
+/// Clones share the same counters, so keep a clone to read the tallies after
+/// handing one to `VM::set_metering`.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryProfiler {
+    counts: Rc<Cell<MemoryAccessCounts>>,
+}
+
+impl MemoryProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of the tallies recorded so far.
+    pub fn counts(&self) -> MemoryAccessCounts {
+        self.counts.get()
+    }
+}
+
+impl Metering for MemoryProfiler {
+    fn on_memory_access(
+        &mut self,
+        kind: MemoryAccessKind,
+        _addr: usize,
+        _bytes: usize,
+    ) -> MeterResult {
+        let mut counts = self.counts.get();
+        counts.record(kind);
+        self.counts.set(counts);
+        MeterResult::Continue
+    }
+}
+
+/// Metering that counts taken back-edges (backward conditional branches) and call targets.
+///
+/// Back-edges are keyed by the branch PC, so the hottest entries point at loop
+/// latches. Clones share the same tables, like `MemoryProfiler`.
+#[derive(Debug, Default, Clone)]
+pub struct BranchProfiler {
+    back_edges: Rc<RefCell<HashMap<u32, u64>>>,
+    calls: Rc<RefCell<HashMap<u32, u64>>>,
+}
+
+impl BranchProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Times the branch at `pc` was taken backwards.
+    pub fn back_edge_count(&self, pc: u32) -> u64 {
+        self.back_edges.borrow().get(&pc).copied().unwrap_or(0)
+    }
+
+    /// Times `target` was entered through a linking jump.
+    pub fn call_count(&self, target: u32) -> u64 {
+        self.calls.borrow().get(&target).copied().unwrap_or(0)
+    }
+
+    /// The `n` most-taken back-edges as `(pc, count)`, hottest first (ties by PC).
+    pub fn top_back_edges(&self, n: usize) -> Vec<(u32, u64)> {
+        let mut edges: Vec<(u32, u64)> = self
+            .back_edges
+            .borrow()
+            .iter()
+            .map(|(&pc, &count)| (pc, count))
+            .collect();
+        edges.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        edges.truncate(n);
+        edges
+    }
+}
+
+impl Metering for BranchProfiler {
+    fn on_branch(&mut self, pc: u32, target: u32, taken: bool) -> MeterResult {
+        if taken && target <= pc {
+            let mut edges = self.back_edges.borrow_mut();
+            let count = edges.entry(pc).or_insert(0);
+            *count = count.saturating_add(1);
+        }
+        MeterResult::Continue
+    }
+
+    fn on_call_target(&mut self, _pc: u32, target: u32) -> MeterResult {
+        let mut calls = self.calls.borrow_mut();
+        let count = calls.entry(target).or_insert(0);
+        *count = count.saturating_add(1);
+        MeterResult::Continue
+    }
+}
@@ -1,9 +1,42 @@
-use crate::cpu::CPU;
+use crate::cpu::{PrivilegeMode, CPU};
+use crate::hook::Hook;
 use crate::memory::{Memory, API};
 use crate::metering::Metering;
 use crate::registers::Register;
 use std::rc::Rc;
 
+/// Why a bounded run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// The CPU stopped on its own (halt, ebreak outside a trap vector, fault).
+    Halted,
+    /// The caller-supplied stop condition matched.
+    Stopped,
+    /// `max_steps` instructions ran without the program stopping.
+    StepLimit,
+    /// The CPU executed `wfi`; no interrupt source exists, so it halted there.
+    Wfi,
+}
+
+/// Result of `VM::run_with_limit` / `VM::run_until`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Number of `step` calls made, including the one that halted.
+    pub steps: usize,
+    pub exit: ExitReason,
+    /// Program counter when execution stopped (past the halting instruction on `Halted`,
+    /// on the `wfi` itself for `Wfi`).
+    pub pc: u32,
+}
+
+/// Architectural CPU state captured after a run, see `VM::final_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VmState {
+    pub regs: [u32; 32],
+    pub pc: u32,
+    pub priv_mode: PrivilegeMode,
+}
+
 /// Represents a complete RISC-V virtual machine.
 ///
 /// EDUCATIONAL PURPOSE: This struct encapsulates all the components needed
@@ -41,6 +74,9 @@ impl VM {
     pub fn set_metering(&mut self, metering: Box<dyn Metering>) {
         self.cpu.set_metering(metering);
     }
+    pub fn set_hook(&mut self, hook: Box<dyn Hook>) {
+        self.cpu.set_hook(hook);
+    }
     pub fn set_reg_u32(&mut self, reg: Register, data: u32) {
         self.cpu.regs[reg as usize] = data;
     }
@@ -154,7 +190,47 @@ impl VM {
     /// with code loaded and registers set up. For a complete VM, you'd typically
     /// call this after setting up the initial state.
     pub fn raw_run(&mut self) {
+        self.run_with_limit(usize::MAX);
+    }
+
+    /// Snapshot of the register file, PC and privilege mode, e.g. after `raw_run`.
+    pub fn final_state(&self) -> VmState {
+        VmState {
+            regs: self.cpu.regs,
+            pc: self.cpu.pc,
+            priv_mode: self.cpu.priv_mode,
+        }
+    }
+
+    /// Runs until the CPU halts or `max_steps` instructions have been stepped.
+    pub fn run_with_limit(&mut self, max_steps: usize) -> RunSummary {
+        self.run_until(max_steps, |_| false)
+    }
+
+    /// Like `run_with_limit`, but also checks `stop` after every successful step
+    /// (e.g. to watch a `tohost` word) and reports `ExitReason::Stopped` when it matches.
+    pub fn run_until(&mut self, max_steps: usize, mut stop: impl FnMut(&VM) -> bool) -> RunSummary {
+        let mut steps = 0usize;
         // EDUCATIONAL: Main execution loop - fetch, decode, execute
-        while self.cpu.step(Rc::clone(&self.memory)) {}
+        let exit = loop {
+            if steps == max_steps {
+                break ExitReason::StepLimit;
+            }
+            steps += 1;
+            if !self.cpu.step(Rc::clone(&self.memory)) {
+                if self.cpu.waiting_for_interrupt {
+                    break ExitReason::Wfi;
+                }
+                break ExitReason::Halted;
+            }
+            if stop(self) {
+                break ExitReason::Stopped;
+            }
+        };
+        RunSummary {
+            steps,
+            exit,
+            pc: self.cpu.pc,
+        }
     }
 }
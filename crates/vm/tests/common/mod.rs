@@ -0,0 +1,34 @@
+// Each test binary includes this module and uses only some of its helpers.
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::vm::VM;
+
+/// Where `vm_with_program` places the program and starts the PC.
+pub const CODE_BASE: u32 = 0x1000;
+
+/// Opcode of the register-register (`OP`) instructions.
+const OP: u32 = 0x33;
+
+/// Encodes an `OP` instruction such as `add`, `sll` or `div`.
+pub fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | OP
+}
+
+/// Memory with the first four pages mapped kernel RWX and `words` at `CODE_BASE`.
+pub fn memory_with_program(words: &[u32]) -> Rc<Sv32Memory> {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(memory.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rwx_kernel()));
+    let code: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+    memory.write_bytes(VirtualAddress(CODE_BASE), &code);
+    memory
+}
+
+/// A VM over `memory_with_program(words)` with the PC pointing at the program.
+pub fn vm_with_program(words: &[u32]) -> VM {
+    let mut vm = VM::new(memory_with_program(words));
+    vm.cpu.pc = CODE_BASE;
+    vm
+}
@@ -0,0 +1,251 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use types::boot::LogLevel;
+use vm::console::{console_write, parse_level_tag, ConsoleSink, CONSOLE_WRITE_ID};
+use vm::cpu::{PrivilegeMode, CPU};
+use vm::memory::{Memory, Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::metering::NoopMeter;
+use vm::registers::Register;
+
+const FMT_ADDR: u32 = 0x1000;
+const ARGS_ADDR: u32 = 0x2000;
+const STR_ADDR: u32 = 0x3000;
+
+#[derive(Default)]
+struct LineCollector {
+    lines: Vec<(String, PrivilegeMode)>,
+    tags: Vec<(LogLevel, Option<String>)>,
+    max_level: Option<LogLevel>,
+}
+
+impl ConsoleSink for LineCollector {
+    fn on_line(
+        &mut self,
+        line: &str,
+        level: LogLevel,
+        category: Option<&str>,
+        caller_mode: PrivilegeMode,
+    ) {
+        self.lines.push((line.to_string(), caller_mode));
+        self.tags.push((level, category.map(str::to_string)));
+    }
+
+    fn max_level(&self) -> LogLevel {
+        self.max_level.unwrap_or(LogLevel::Debug)
+    }
+}
+
+#[derive(Default)]
+struct Capture {
+    buffer: String,
+}
+
+impl Write for Capture {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.buffer.push_str(s);
+        Ok(())
+    }
+}
+
+fn render(fmt: &str, args: &[u32]) -> String {
+    render_with_str(fmt, args, "")
+}
+
+fn render_with_str(fmt: &str, args: &[u32], s: &str) -> String {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(memory.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rw_kernel()));
+    memory.write_bytes(VirtualAddress(FMT_ADDR), fmt.as_bytes());
+    let arg_bytes: Vec<u8> = args.iter().flat_map(|a| a.to_le_bytes()).collect();
+    memory.write_bytes(VirtualAddress(ARGS_ADDR), &arg_bytes);
+    memory.write_bytes(VirtualAddress(STR_ADDR), s.as_bytes());
+
+    let capture = Rc::new(RefCell::new(Capture::default()));
+    let writer: Option<Rc<RefCell<dyn Write>>> = Some(capture.clone());
+    let memory: Memory = memory;
+    console_write(
+        [
+            FMT_ADDR,
+            fmt.len() as u32,
+            ARGS_ADDR,
+            arg_bytes.len() as u32,
+            0,
+            0,
+        ],
+        PrivilegeMode::Supervisor,
+        memory,
+        &mut NoopMeter,
+        &writer,
+        &None,
+        LogLevel::Debug,
+    );
+    let out = capture.borrow().buffer.clone();
+    out.trim_end_matches('\n').to_string()
+}
+
+#[test]
+fn plain_specifiers_are_unchanged() {
+    assert_eq!(render("v=%d", &[42]), "v=42");
+    assert_eq!(render("v=%d", &[(-7i32) as u32]), "v=-7");
+    assert_eq!(render("v=%x", &[0xbeef]), "v=0000beef");
+    assert_eq!(render("100%%", &[]), "100%");
+}
+
+#[test]
+fn width_pads_with_spaces() {
+    assert_eq!(render("[%5d]", &[42]), "[   42]");
+    assert_eq!(render("[%4x]", &[0xab]), "[  ab]");
+    assert_eq!(render("[%2d]", &[12345]), "[12345]");
+}
+
+#[test]
+fn zero_flag_pads_numbers() {
+    assert_eq!(render("[%08x]", &[0xbeef]), "[0000beef]");
+    assert_eq!(render("[%04x]", &[0xbeef]), "[beef]");
+    assert_eq!(render("[%05d]", &[42]), "[00042]");
+    assert_eq!(render("[%05d]", &[(-42i32) as u32]), "[-0042]");
+}
+
+#[test]
+fn left_flag_justifies_strings_and_numbers() {
+    assert_eq!(
+        render_with_str("[%-10s]", &[STR_ADDR, 3], "abc"),
+        "[abc       ]"
+    );
+    assert_eq!(render_with_str("[%6s]", &[STR_ADDR, 3], "abc"), "[   abc]");
+    assert_eq!(render("[%-5d]", &[7]), "[7    ]");
+    assert_eq!(render("[%-05d]", &[7]), "[7    ]");
+}
+
+#[test]
+fn widths_consume_the_same_args() {
+    assert_eq!(
+        render_with_str("%3d|%-4s|%02x", &[1, STR_ADDR, 2, 0xf], "hi"),
+        "  1|hi  |0f"
+    );
+}
+
+#[test]
+fn console_sink_receives_lines_with_caller_mode() {
+    const ECALL: u32 = 0x0000_0073;
+    let sv32 = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(sv32.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rwx_kernel()));
+    sv32.write_bytes(VirtualAddress(0), &ECALL.to_le_bytes());
+    let fmt = "n=%d";
+    sv32.write_bytes(VirtualAddress(FMT_ADDR), fmt.as_bytes());
+    let memory: Memory = sv32.clone();
+
+    let capture = Rc::new(RefCell::new(Capture::default()));
+    let sink = Rc::new(RefCell::new(LineCollector::default()));
+    let mut cpu = CPU::new();
+    cpu.set_verbose_writer(capture.clone());
+    cpu.set_console_sink(sink.clone());
+
+    for (value, mode) in [(1u32, PrivilegeMode::Supervisor), (2, PrivilegeMode::User)] {
+        sv32.write_bytes(VirtualAddress(ARGS_ADDR), &value.to_le_bytes());
+        cpu.pc = 0;
+        cpu.priv_mode = mode;
+        cpu.regs[Register::A7 as usize] = CONSOLE_WRITE_ID;
+        cpu.regs[Register::A1 as usize] = FMT_ADDR;
+        cpu.regs[Register::A2 as usize] = fmt.len() as u32;
+        cpu.regs[Register::A3 as usize] = ARGS_ADDR;
+        cpu.regs[Register::A4 as usize] = 4;
+        assert!(cpu.step(Rc::clone(&memory)));
+    }
+
+    assert_eq!(
+        sink.borrow().lines,
+        vec![
+            ("n=1".to_string(), PrivilegeMode::Supervisor),
+            ("n=2".to_string(), PrivilegeMode::User),
+        ]
+    );
+    assert!(capture.borrow().buffer.is_empty());
+}
+
+#[test]
+fn level_tags_are_parsed_and_stripped() {
+    assert_eq!(
+        parse_level_tag("<D|erc20> G: minting"),
+        (LogLevel::Debug, Some("erc20"), "G: minting")
+    );
+    assert_eq!(
+        parse_level_tag("<E|dex> swap failed"),
+        (LogLevel::Error, Some("dex"), "swap failed")
+    );
+    assert_eq!(parse_level_tag("plain"), (LogLevel::Info, None, "plain"));
+    assert_eq!(
+        parse_level_tag("<X|a> b"),
+        (LogLevel::Info, None, "<X|a> b")
+    );
+    assert_eq!(
+        parse_level_tag("<D|open"),
+        (LogLevel::Info, None, "<D|open")
+    );
+}
+
+#[test]
+fn sink_level_filters_tagged_guest_lines() {
+    let sv32 = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(sv32.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rw_kernel()));
+    let sink = Rc::new(RefCell::new(LineCollector {
+        max_level: Some(LogLevel::Info),
+        ..LineCollector::default()
+    }));
+    let sink_dyn: Option<Rc<RefCell<dyn ConsoleSink>>> = Some(sink.clone());
+
+    for fmt in ["<D|erc20> minting", "<I|erc20> init called", "untagged"] {
+        sv32.write_bytes(VirtualAddress(FMT_ADDR), fmt.as_bytes());
+        let memory: Memory = sv32.clone();
+        console_write(
+            [FMT_ADDR, fmt.len() as u32, ARGS_ADDR, 0, 0, 0],
+            PrivilegeMode::User,
+            memory,
+            &mut NoopMeter,
+            &None,
+            &sink_dyn,
+            LogLevel::Debug,
+        );
+    }
+
+    let sink = sink.borrow();
+    assert_eq!(
+        sink.lines,
+        vec![
+            ("init called".to_string(), PrivilegeMode::User),
+            ("untagged".to_string(), PrivilegeMode::User),
+        ]
+    );
+    assert_eq!(
+        sink.tags,
+        vec![
+            (LogLevel::Info, Some("erc20".to_string())),
+            (LogLevel::Info, None),
+        ]
+    );
+}
+
+#[test]
+fn console_level_filters_tagged_lines_without_a_sink() {
+    let sv32 = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(sv32.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rw_kernel()));
+    let capture = Rc::new(RefCell::new(Capture::default()));
+    let writer: Option<Rc<RefCell<dyn Write>>> = Some(capture.clone());
+
+    for fmt in ["<D|erc20> minting", "<I|erc20> init called", "untagged"] {
+        sv32.write_bytes(VirtualAddress(FMT_ADDR), fmt.as_bytes());
+        let memory: Memory = sv32.clone();
+        console_write(
+            [FMT_ADDR, fmt.len() as u32, ARGS_ADDR, 0, 0, 0],
+            PrivilegeMode::User,
+            memory,
+            &mut NoopMeter,
+            &writer,
+            &None,
+            LogLevel::Info,
+        );
+    }
+
+    assert_eq!(capture.borrow().buffer, "init called\nuntagged\n");
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::vm_with_program;
+use vm::vm::ExitReason;
+
+#[test]
+fn cycle_delta_matches_instructions_executed() {
+    let mut vm = vm_with_program(&[
+        0xc000_22f3, // csrr t0, cycle
+        0x0050_0313, // addi t1, zero, 5
+        0xfff3_0313, // loop: addi t1, t1, -1
+        0xfe03_1ee3, // bnez t1, loop
+        0xc000_23f3, // csrr t2, cycle
+        0xc020_2e73, // csrr t3, instret
+        0x0010_0073, // ebreak
+    ]);
+    let summary = vm.run_with_limit(100);
+    assert_eq!(summary.exit, ExitReason::Halted);
+
+    // The first read, the setup addi, and five loop iterations of two instructions.
+    assert_eq!(vm.cpu.regs[7] - vm.cpu.regs[5], 1 + 1 + 5 * 2);
+    // Nothing faulted, so every instruction before the instret read retired.
+    assert_eq!(vm.cpu.regs[28], vm.cpu.regs[7] + 1);
+    assert_eq!(vm.cpu.cycle_count(), summary.steps as u64);
+    // The halting ebreak executes but does not retire.
+    assert_eq!(vm.cpu.instret_count(), summary.steps as u64 - 1);
+}
+
+#[test]
+fn mcycle_is_writable_and_cycle_is_not() {
+    let mut vm = vm_with_program(&[
+        0x0640_0293, // addi t0, zero, 100
+        0xb002_9073, // csrw mcycle, t0
+        0xc000_1073, // csrw cycle, zero
+        0xc000_23f3, // csrr t2, cycle
+        0x0010_0073, // ebreak
+    ]);
+    assert_eq!(vm.run_with_limit(100).exit, ExitReason::Halted);
+    // The write lands, then the ignored user write and the read itself each add one.
+    assert_eq!(vm.cpu.regs[7], 100 + 2);
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use vm::cpu::PrivilegeMode;
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::vm::VM;
+
+const CODE_BASE: u32 = 0x1000;
+
+// addi t0, zero, 1 ; beq t0, t0, +8 ; addi t1, zero, 2 (skipped) ; ebreak
+const PROGRAM: [u32; 4] = [0x0010_0293, 0x0052_8463, 0x0020_0313, 0x0010_0073];
+
+fn vm_in(mode: PrivilegeMode) -> VM {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(memory.map_range(
+        VirtualAddress(0),
+        2 * PAGE_SIZE,
+        Perms::new(true, true, true, true)
+    ));
+    let code: Vec<u8> = PROGRAM.iter().flat_map(|w| w.to_le_bytes()).collect();
+    memory.write_bytes(VirtualAddress(CODE_BASE), &code);
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = CODE_BASE;
+    vm.cpu.priv_mode = mode;
+    vm
+}
+
+fn taken_path() -> BTreeSet<u32> {
+    [CODE_BASE, CODE_BASE + 4, CODE_BASE + 12]
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn coverage_is_off_by_default() {
+    let mut vm = vm_in(PrivilegeMode::Supervisor);
+    vm.run_with_limit(10);
+    assert!(vm.cpu.coverage.is_none());
+}
+
+#[test]
+fn coverage_records_executed_pcs_only() {
+    let mut vm = vm_in(PrivilegeMode::Supervisor);
+    vm.cpu.enable_coverage();
+    vm.run_with_limit(10);
+    let coverage = vm.cpu.coverage.take().unwrap();
+    assert_eq!(coverage.privileged, taken_path());
+    assert!(coverage.user.is_empty());
+}
+
+#[test]
+fn user_mode_pcs_are_kept_apart() {
+    let mut vm = vm_in(PrivilegeMode::User);
+    vm.cpu.enable_coverage();
+    vm.run_with_limit(10);
+    let coverage = vm.cpu.coverage.take().unwrap();
+    assert_eq!(coverage.user, taken_path());
+    assert!(coverage.privileged.is_empty());
+}
@@ -0,0 +1,92 @@
+use vm::memory::{MemoryFault, Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE};
+use vm::metering::{MemoryAccessKind, NoopMeter};
+
+const BASE: u32 = 0x2000;
+const NEXT: u32 = BASE + PAGE_SIZE as u32;
+/// A word starting two bytes before the end of the page at `BASE`.
+const STRADDLE: u32 = NEXT - 2;
+
+#[test]
+fn straddling_store_into_unmapped_page_faults_without_partial_write() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    assert!(memory.map_range(VirtualAddress(BASE), PAGE_SIZE, Perms::rw_kernel()));
+
+    let addr = VirtualAddress(STRADDLE);
+    assert!(!memory.store_u32(addr, 0xdead_beef, &mut meter, MemoryAccessKind::Store));
+    assert_eq!(
+        memory.take_fault(),
+        Some(MemoryFault {
+            addr: VirtualAddress(NEXT),
+            kind: MemoryAccessKind::Store
+        })
+    );
+    assert!(!memory.store_u16(
+        VirtualAddress(NEXT - 1),
+        0xbeef,
+        &mut meter,
+        MemoryAccessKind::Store
+    ));
+    // The mapped half was left untouched.
+    for va in [STRADDLE, STRADDLE + 1] {
+        assert_eq!(
+            memory.load_byte(VirtualAddress(va), &mut meter, MemoryAccessKind::Load),
+            Some(0)
+        );
+    }
+    assert_eq!(
+        memory.load_u32(addr, &mut meter, MemoryAccessKind::Load),
+        None
+    );
+    assert_eq!(
+        memory.load_halfword(VirtualAddress(NEXT - 1), &mut meter, MemoryAccessKind::Load),
+        None
+    );
+}
+
+#[test]
+fn straddling_store_into_read_only_page_faults_without_partial_write() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    assert!(memory.map_range(VirtualAddress(BASE), 2 * PAGE_SIZE, Perms::rw_kernel()));
+    let read_only = Perms::new(true, false, false, false);
+    assert!(memory.protect_range(VirtualAddress(NEXT), PAGE_SIZE, read_only));
+
+    let addr = VirtualAddress(STRADDLE);
+    assert!(!memory.store_u32(addr, 0xdead_beef, &mut meter, MemoryAccessKind::Store));
+    assert_eq!(
+        memory.load_u32(addr, &mut meter, MemoryAccessKind::Load),
+        Some(0),
+        "loads still see both pages, and neither was written"
+    );
+}
+
+#[test]
+fn straddling_access_uses_each_pages_own_frame() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    // Map the second page first so the two frames are not physically adjacent.
+    assert!(memory.map_range(VirtualAddress(NEXT), PAGE_SIZE, Perms::rw_kernel()));
+    assert!(memory.map_range(VirtualAddress(BASE), PAGE_SIZE, Perms::rw_kernel()));
+
+    let addr = VirtualAddress(STRADDLE);
+    assert!(memory.store_u32(addr, 0x0403_0201, &mut meter, MemoryAccessKind::Store));
+    assert_eq!(
+        memory.load_byte(VirtualAddress(NEXT), &mut meter, MemoryAccessKind::Load),
+        Some(3)
+    );
+    assert_eq!(
+        memory.load_u32(addr, &mut meter, MemoryAccessKind::Load),
+        Some(0x0403_0201)
+    );
+    assert!(memory.store_u16(
+        VirtualAddress(NEXT - 1),
+        0xbbaa,
+        &mut meter,
+        MemoryAccessKind::Store
+    ));
+    assert_eq!(
+        memory.load_word(addr, &mut meter, MemoryAccessKind::Load),
+        Some(0x04bb_aa01)
+    );
+}
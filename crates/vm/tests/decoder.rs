@@ -0,0 +1,328 @@
+use std::rc::Rc;
+
+use vm::cpu::{CSR_SCAUSE, CSR_SEPC, CSR_STVAL, CSR_STVEC};
+use vm::decoder::{decode_compressed, decode_full, decode_instruction};
+use vm::instruction::{Instruction, MiscAluOp};
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::vm::VM;
+
+#[test]
+fn all_zero_halfword_is_illegal() {
+    assert_eq!(decode_compressed(0x0000), None);
+}
+
+#[test]
+fn reserved_compressed_encodings_are_illegal() {
+    let reserved: [(u16, &str); 9] = [
+        (0x0004, "c.addi4spn with nzuimm = 0"),
+        (0x8000, "reserved quadrant 0 funct3 = 100"),
+        (0x8002, "c.jr x0"),
+        (0x4002, "c.lwsp x0"),
+        (0x6281, "c.lui with nzimm = 0"),
+        (0x6101, "c.addi16sp with nzimm = 0"),
+        (0x1286, "c.slli with shamt[5] set on RV32"),
+        (0x9005, "c.srli with shamt[5] set on RV32"),
+        (0x9c01, "c.subw (RV64 only)"),
+    ];
+    for (hword, what) in reserved {
+        assert_eq!(decode_compressed(hword), None, "{what} ({hword:#06x})");
+    }
+}
+
+#[test]
+fn neighbouring_legal_encodings_still_decode() {
+    assert_eq!(decode_compressed(0x9002), Some(Instruction::Ebreak));
+    assert_eq!(
+        decode_compressed(0x6285),
+        Some(Instruction::Lui { rd: 5, imm: 1 })
+    );
+    assert_eq!(
+        decode_compressed(0x0286),
+        Some(Instruction::Slli {
+            rd: 5,
+            rs1: 5,
+            shamt: 1
+        })
+    );
+}
+
+fn assert_expands(cases: &[(u16, Instruction, &str)]) {
+    for (hword, expected, what) in cases {
+        assert_eq!(
+            decode_compressed(*hword).as_ref(),
+            Some(expected),
+            "{what} ({hword:#06x})"
+        );
+    }
+}
+
+#[test]
+fn quadrant0_encodings_expand() {
+    assert_expands(&[
+        (
+            0x0800,
+            Instruction::Addi4spn { rd: 8, imm: 16 },
+            "c.addi4spn s0, sp, 16",
+        ),
+        (
+            0x41c8,
+            Instruction::Lw {
+                rd: 10,
+                rs1: 11,
+                offset: 4,
+            },
+            "c.lw a0, 4(a1)",
+        ),
+        (
+            0xc588,
+            Instruction::Sw {
+                rs1: 11,
+                rs2: 10,
+                offset: 8,
+            },
+            "c.sw a0, 8(a1)",
+        ),
+    ]);
+}
+
+#[test]
+fn quadrant1_encodings_expand() {
+    assert_expands(&[
+        (
+            0x0001,
+            Instruction::Addi {
+                rd: 0,
+                rs1: 0,
+                imm: 0,
+            },
+            "c.nop",
+        ),
+        (
+            0x157d,
+            Instruction::Addi {
+                rd: 10,
+                rs1: 10,
+                imm: -1,
+            },
+            "c.addi a0, -1",
+        ),
+        (
+            0x2009,
+            Instruction::Jal {
+                rd: 1,
+                offset: 2,
+                compressed: true,
+            },
+            "c.jal 2",
+        ),
+        (
+            0x4515,
+            Instruction::Addi {
+                rd: 10,
+                rs1: 0,
+                imm: 5,
+            },
+            "c.li a0, 5",
+        ),
+        (0x717d, Instruction::Addi16sp { imm: -16 }, "c.addi16sp -16"),
+        (0x6505, Instruction::Lui { rd: 10, imm: 1 }, "c.lui a0, 0x1"),
+        (
+            0x7501,
+            Instruction::Lui { rd: 10, imm: -32 },
+            "c.lui a0, 0xfffe0",
+        ),
+        (
+            0x8005,
+            Instruction::Srli {
+                rd: 8,
+                rs1: 8,
+                shamt: 1,
+            },
+            "c.srli s0, 1",
+        ),
+        (
+            0x8405,
+            Instruction::Srai {
+                rd: 8,
+                rs1: 8,
+                shamt: 1,
+            },
+            "c.srai s0, 1",
+        ),
+        (
+            0x987d,
+            Instruction::Andi {
+                rd: 8,
+                rs1: 8,
+                imm: -1,
+            },
+            "c.andi s0, -1",
+        ),
+        (
+            0x8c05,
+            Instruction::MiscAlu {
+                rd: 8,
+                rs2: 9,
+                op: MiscAluOp::Sub,
+            },
+            "c.sub s0, s1",
+        ),
+        (
+            0x8c25,
+            Instruction::MiscAlu {
+                rd: 8,
+                rs2: 9,
+                op: MiscAluOp::Xor,
+            },
+            "c.xor s0, s1",
+        ),
+        (
+            0x8c45,
+            Instruction::MiscAlu {
+                rd: 8,
+                rs2: 9,
+                op: MiscAluOp::Or,
+            },
+            "c.or s0, s1",
+        ),
+        (
+            0x8c65,
+            Instruction::MiscAlu {
+                rd: 8,
+                rs2: 9,
+                op: MiscAluOp::And,
+            },
+            "c.and s0, s1",
+        ),
+        (
+            0xbffd,
+            Instruction::Jal {
+                rd: 0,
+                offset: -2,
+                compressed: true,
+            },
+            "c.j -2",
+        ),
+        (
+            0xc401,
+            Instruction::Beqz { rs1: 8, offset: 8 },
+            "c.beqz s0, 8",
+        ),
+        (
+            0xfc7d,
+            Instruction::Bnez { rs1: 8, offset: -2 },
+            "c.bnez s0, -2",
+        ),
+    ]);
+}
+
+#[test]
+fn quadrant2_encodings_expand() {
+    assert_expands(&[
+        (
+            0x40b2,
+            Instruction::Lw {
+                rd: 1,
+                rs1: 2,
+                offset: 12,
+            },
+            "c.lwsp ra, 12(sp)",
+        ),
+        (
+            0xc606,
+            Instruction::Sw {
+                rs1: 2,
+                rs2: 1,
+                offset: 12,
+            },
+            "c.swsp ra, 12(sp)",
+        ),
+        (0x8502, Instruction::Jr { rs1: 10 }, "c.jr a0"),
+        (0x8082, Instruction::Ret, "c.ret"),
+        (0x852e, Instruction::Mv { rd: 10, rs2: 11 }, "c.mv a0, a1"),
+        (
+            0x9502,
+            Instruction::Jalr {
+                rd: 1,
+                rs1: 10,
+                offset: 0,
+                compressed: true,
+            },
+            "c.jalr a0",
+        ),
+        (
+            0x952e,
+            Instruction::Add {
+                rd: 10,
+                rs1: 10,
+                rs2: 11,
+            },
+            "c.add a0, a1",
+        ),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "Unknown or invalid instruction at PC = 0x00001000")]
+fn running_into_zeroed_memory_faults() {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(memory.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rwx_kernel()));
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = 0x1000;
+    vm.run_with_limit(100);
+}
+
+#[test]
+fn undecodable_word_traps_as_illegal_instruction() {
+    const HANDLER: u32 = 0x2000;
+    const ILLEGAL: u32 = 0xffff_ffff;
+    assert_eq!(decode_full(ILLEGAL), None);
+
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(memory.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rwx_kernel()));
+    // addi t0, zero, 1 ; <illegal>
+    let code: Vec<u8> = [0x0010_0293u32, ILLEGAL]
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .collect();
+    memory.write_bytes(VirtualAddress(0x1000), &code);
+    // Handler: addi t1, zero, 2
+    memory.write_bytes(VirtualAddress(HANDLER), &0x0020_0313u32.to_le_bytes());
+
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = 0x1000;
+    vm.cpu.csrs.insert(CSR_STVEC, HANDLER);
+    vm.run_with_limit(3);
+
+    assert_eq!(vm.cpu.regs[5], 1);
+    assert_eq!(vm.cpu.regs[6], 2, "handler ran after the trap");
+    assert_eq!(vm.cpu.pc, HANDLER + 4);
+    assert_eq!(vm.cpu.csrs[&CSR_SCAUSE], 2);
+    assert_eq!(vm.cpu.csrs[&CSR_SEPC], 0x1004, "sepc is the faulting PC");
+    assert_eq!(vm.cpu.csrs[&CSR_STVAL], ILLEGAL);
+}
+
+#[test]
+fn decode_instruction_reports_compressed_length() {
+    // c.ebreak followed by the first half of another instruction.
+    assert_eq!(
+        decode_instruction(&[0x02, 0x90, 0x13, 0x00]),
+        Some((Instruction::Ebreak, 2))
+    );
+}
+
+#[test]
+fn decode_instruction_reports_full_width_length() {
+    assert_eq!(
+        decode_instruction(&0x0010_0073u32.to_le_bytes()),
+        Some((Instruction::Ebreak, 4))
+    );
+}
+
+#[test]
+fn decode_instruction_rejects_truncated_buffers() {
+    assert_eq!(decode_instruction(&[]), None);
+    assert_eq!(decode_instruction(&[0x02]), None);
+    // Low bits 0b11 announce a 32-bit instruction, but only half of it is present.
+    assert_eq!(decode_instruction(&[0x73, 0x00, 0x10]), None);
+}
@@ -0,0 +1,121 @@
+use vm::decoder::{decode_compressed, decode_full};
+use vm::instruction::{CsrOp, Instruction, MiscAluOp};
+
+fn full(word: u32) -> String {
+    decode_full(word)
+        .unwrap_or_else(|| panic!("{word:#010x} should decode"))
+        .to_string()
+}
+
+#[test]
+fn r_type() {
+    // add x1, x2, x3
+    assert_eq!(full(0x003100b3), "add x1, x2, x3");
+    // mul x10, x11, x12
+    assert_eq!(full(0x02c58533), "mul x10, x11, x12");
+}
+
+#[test]
+fn i_type() {
+    // addi x5, x6, -1
+    assert_eq!(full(0xfff30293), "addi x5, x6, -1");
+    // lw x5, 8(x6)
+    assert_eq!(full(0x00832283), "lw x5, 8(x6)");
+    // jalr x1, 0(x5)
+    assert_eq!(full(0x000280e7), "jalr x1, 0(x5)");
+}
+
+#[test]
+fn s_type() {
+    // sw x7, -4(x2)
+    assert_eq!(full(0xfe712e23), "sw x7, -4(x2)");
+}
+
+#[test]
+fn b_type() {
+    // beq x1, x2, +12
+    assert_eq!(full(0x00208663), "beq x1, x2, +12");
+    // bne x1, x0, -8
+    assert_eq!(full(0xfe009ce3), "bne x1, x0, -8");
+}
+
+#[test]
+fn u_and_j_type() {
+    // lui x5, 0x12345
+    assert_eq!(full(0x123452b7), "lui x5, 0x12345");
+    // auipc x1, 0x0
+    assert_eq!(full(0x00000097), "auipc x1, 0x0");
+    // jal x1, +16
+    assert_eq!(full(0x010000ef), "jal x1, +16");
+}
+
+#[test]
+fn system_and_csr() {
+    assert_eq!(full(0x00000073), "ecall");
+    assert_eq!(full(0x10500073), "wfi");
+    let csrrw = Instruction::Csr {
+        rd: 5,
+        rs1: 6,
+        csr: 0x105,
+        op: CsrOp::Csrrw,
+        imm: false,
+    };
+    assert_eq!(csrrw.mnemonic(), "csrrw");
+    assert_eq!(csrrw.to_string(), "csrrw x5, 0x105, x6");
+    let csrrsi = Instruction::Csr {
+        rd: 0,
+        rs1: 2,
+        csr: 0x100,
+        op: CsrOp::Csrrs,
+        imm: true,
+    };
+    assert_eq!(csrrsi.to_string(), "csrrsi x0, 0x100, 2");
+}
+
+#[test]
+fn atomics() {
+    let amo = Instruction::AmoaddW {
+        rd: 5,
+        rs1: 10,
+        rs2: 6,
+    };
+    assert_eq!(amo.to_string(), "amoadd.w x5, x6, (x10)");
+    assert_eq!(
+        Instruction::LrW { rd: 5, rs1: 10 }.to_string(),
+        "lr.w x5, (x10)"
+    );
+}
+
+#[test]
+fn compressed() {
+    // c.lui x5, 0x1
+    assert_eq!(
+        decode_compressed(0x6285).map(|i| i.to_string()),
+        Some("lui x5, 0x1".to_string())
+    );
+    assert_eq!(
+        decode_compressed(0x9002).map(|i| i.to_string()),
+        Some("ebreak".to_string())
+    );
+    let sub = Instruction::MiscAlu {
+        rd: 8,
+        rs2: 9,
+        op: MiscAluOp::Sub,
+    };
+    assert_eq!(sub.mnemonic(), "c.sub");
+    assert_eq!(sub.to_string(), "c.sub x8, x9");
+    assert_eq!(
+        Instruction::Mv { rd: 10, rs2: 11 }.to_string(),
+        "mv x10, x11"
+    );
+}
+
+#[test]
+fn pretty_print_matches_display() {
+    let instr = Instruction::Beq {
+        rs1: 1,
+        rs2: 2,
+        offset: 12,
+    };
+    assert_eq!(instr.pretty_print(), instr.to_string());
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::{r_type, vm_with_program};
+
+const MULDIV: u32 = 0x01;
+const DIV: u32 = 4;
+const DIVU: u32 = 5;
+const REM: u32 = 6;
+const REMU: u32 = 7;
+
+const MIN: u32 = i32::MIN as u32;
+const MINUS_ONE: u32 = u32::MAX;
+
+/// Runs `op rd, x5, x6` for each `funct3` with `x5 = lhs`, `x6 = rhs` and returns
+/// the four results in DIV, DIVU, REM, REMU order.
+fn divide(lhs: u32, rhs: u32) -> [u32; 4] {
+    let code: Vec<u32> = [DIV, DIVU, REM, REMU]
+        .iter()
+        .enumerate()
+        .map(|(i, &funct3)| r_type(MULDIV, 6, 5, funct3, 10 + i as u32))
+        .collect();
+    let mut vm = vm_with_program(&code);
+    vm.cpu.regs[5] = lhs;
+    vm.cpu.regs[6] = rhs;
+    vm.run_with_limit(4);
+    [
+        vm.cpu.regs[10],
+        vm.cpu.regs[11],
+        vm.cpu.regs[12],
+        vm.cpu.regs[13],
+    ]
+}
+
+#[test]
+fn division_by_zero_follows_the_spec() {
+    // Quotients are all ones, remainders are the dividend; nothing traps.
+    assert_eq!(divide(7, 0), [MINUS_ONE, u32::MAX, 7, 7]);
+    assert_eq!(divide(MIN, 0), [MINUS_ONE, u32::MAX, MIN, MIN]);
+    assert_eq!(divide(0, 0), [MINUS_ONE, u32::MAX, 0, 0]);
+}
+
+#[test]
+fn signed_overflow_follows_the_spec() {
+    // i32::MIN / -1 overflows: the quotient is the dividend and the remainder 0.
+    // The unsigned forms see 0x8000_0000 / 0xffff_ffff.
+    assert_eq!(divide(MIN, MINUS_ONE), [MIN, 0, 0, MIN]);
+}
+
+#[test]
+fn signed_division_truncates_toward_zero() {
+    let minus = |v: i32| v as u32;
+    assert_eq!(divide(minus(-7), 2), [minus(-3), 0x7fff_fffc, minus(-1), 1]);
+    assert_eq!(divide(7, minus(-2)), [minus(-3), 0, 1, 7]);
+}
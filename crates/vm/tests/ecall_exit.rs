@@ -0,0 +1,60 @@
+mod common;
+
+use common::{vm_with_program, CODE_BASE};
+use vm::console::CONSOLE_WRITE_ID;
+use vm::cpu::{EcallBehavior, PrivilegeMode, CSR_MCAUSE, CSR_MEPC, CSR_MTVAL, CSR_MTVEC};
+use vm::vm::ExitReason;
+
+const VECTOR: u32 = 0x2000;
+const SCAUSE_ECALL_FROM_S: u32 = 9;
+
+// addi a7, zero, 93 ; addi a0, zero, <code> ; ecall ; addi t0, zero, 1
+fn exit_program(code: u32) -> [u32; 4] {
+    [
+        0x05d0_0893,
+        (code << 20) | 0x0000_0513,
+        0x0000_0073,
+        0x0010_0293,
+    ]
+}
+
+#[test]
+fn exit_ecall_halts_with_a0_as_exit_code() {
+    let mut vm = vm_with_program(&exit_program(0));
+    vm.cpu.ecall_behavior = EcallBehavior::ExitOnA7_93;
+    let summary = vm.run_with_limit(100);
+    assert_eq!(summary.exit, ExitReason::Halted);
+    assert_eq!(summary.steps, 3);
+    assert_eq!(vm.cpu.exit_code, Some(0));
+    assert_eq!(vm.cpu.regs[5], 0, "nothing runs after the exit ecall");
+}
+
+#[test]
+fn exit_ecall_reports_failure_codes() {
+    let mut vm = vm_with_program(&exit_program(7));
+    vm.cpu.ecall_behavior = EcallBehavior::ExitOnA7_93;
+    vm.run_with_limit(100);
+    assert_eq!(vm.cpu.exit_code, Some(7));
+}
+
+#[test]
+#[should_panic(expected = "ecall without trap vector for id=93")]
+fn syscall_mode_does_not_treat_93_as_exit() {
+    let mut vm = vm_with_program(&exit_program(0));
+    vm.run_with_limit(100);
+}
+
+#[test]
+fn trap_mode_sends_console_writes_to_the_vector() {
+    // addi a7, zero, CONSOLE_WRITE_ID ; ecall
+    let mut vm = vm_with_program(&[(CONSOLE_WRITE_ID << 20) | 0x0000_0893, 0x0000_0073]);
+    vm.cpu.ecall_behavior = EcallBehavior::Trap;
+    vm.cpu.csrs.insert(CSR_MTVEC, VECTOR);
+    let summary = vm.run_with_limit(2);
+    assert_eq!(summary.exit, ExitReason::StepLimit);
+    assert_eq!(summary.pc, VECTOR);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::Machine);
+    assert_eq!(vm.cpu.csrs[&CSR_MCAUSE], SCAUSE_ECALL_FROM_S);
+    assert_eq!(vm.cpu.csrs[&CSR_MEPC], CODE_BASE + 4);
+    assert_eq!(vm.cpu.csrs[&CSR_MTVAL], 0);
+}
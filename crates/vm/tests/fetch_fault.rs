@@ -0,0 +1,152 @@
+use std::rc::Rc;
+
+use vm::cpu::PrivilegeMode;
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::vm::{ExitReason, VM};
+
+const CODE_BASE: u32 = 0x1000;
+const VECTOR: u32 = 0x1200;
+/// Mapped read/write for the user, but not executable.
+const DATA_PAGE: u32 = 0x2000;
+/// Never mapped.
+const UNMAPPED: u32 = 0x3000;
+
+// jalr x0, 0(t0)
+const JUMP_T0: u32 = 0x0002_8067;
+
+const CSR_STVEC: u16 = 0x105;
+const CSR_SEPC: u16 = 0x141;
+const CSR_SCAUSE: u16 = 0x142;
+const CSR_STVAL: u16 = 0x143;
+const SCAUSE_INSTRUCTION_PAGE_FAULT: u32 = 12;
+
+/// A user-mode VM that jumps to `target` from `CODE_BASE`, with `stvec` at `VECTOR`
+/// when `with_vector` is set.
+fn vm_jumping_to(target: u32, with_vector: bool) -> VM {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    let user_rwx = Perms::new(true, true, true, true);
+    let user_rw = Perms::new(true, true, false, true);
+    assert!(memory.map_range(VirtualAddress(CODE_BASE), PAGE_SIZE, user_rwx));
+    assert!(memory.map_range(VirtualAddress(DATA_PAGE), PAGE_SIZE, user_rw));
+    memory.write_bytes(VirtualAddress(CODE_BASE), &JUMP_T0.to_le_bytes());
+    // A valid instruction on the data page, so only the missing X bit stops it.
+    memory.write_bytes(VirtualAddress(DATA_PAGE), &0x0010_0293u32.to_le_bytes());
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = CODE_BASE;
+    vm.cpu.priv_mode = PrivilegeMode::User;
+    vm.cpu.regs[5] = target;
+    if with_vector {
+        vm.cpu.csrs.insert(CSR_STVEC, VECTOR);
+    }
+    vm
+}
+
+fn assert_instruction_page_fault(vm: &VM, target: u32) {
+    assert_eq!(vm.cpu.pc, VECTOR);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::Supervisor);
+    assert_eq!(vm.cpu.csrs[&CSR_SCAUSE], SCAUSE_INSTRUCTION_PAGE_FAULT);
+    assert_eq!(vm.cpu.csrs[&CSR_STVAL], target);
+    assert_eq!(vm.cpu.csrs[&CSR_SEPC], target);
+}
+
+#[test]
+fn jump_to_unmapped_pc_is_an_instruction_page_fault() {
+    let mut vm = vm_jumping_to(UNMAPPED, true);
+    let summary = vm.run_with_limit(2);
+    assert_eq!(summary.exit, ExitReason::StepLimit);
+    assert_instruction_page_fault(&vm, UNMAPPED);
+}
+
+#[test]
+fn jump_to_non_executable_page_is_an_instruction_page_fault() {
+    let mut vm = vm_jumping_to(DATA_PAGE, true);
+    vm.run_with_limit(2);
+    assert_instruction_page_fault(&vm, DATA_PAGE);
+    assert_eq!(
+        vm.cpu.regs[5], DATA_PAGE,
+        "the data page instruction did not run"
+    );
+}
+
+#[test]
+fn fetch_fault_without_a_vector_halts() {
+    let mut vm = vm_jumping_to(UNMAPPED, false);
+    let summary = vm.run_with_limit(10);
+    assert_eq!(summary.exit, ExitReason::Halted);
+    assert_eq!(summary.pc, UNMAPPED);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::User);
+}
+
+#[test]
+fn undecodable_word_after_a_jump_is_still_an_illegal_instruction() {
+    let mut vm = vm_jumping_to(CODE_BASE + 0x100, true);
+    vm.run_with_limit(2);
+    assert_eq!(vm.cpu.pc, VECTOR);
+    assert_eq!(vm.cpu.csrs[&CSR_SCAUSE], 2, "scause = illegal instruction");
+    assert_eq!(vm.cpu.csrs[&CSR_SEPC], CODE_BASE + 0x100);
+}
+
+/// Page below the boundary the split-fetch tests straddle.
+const LOW_PAGE: u32 = 0x1000;
+const PAGE_BOUNDARY: u32 = 0x2000;
+
+/// A user-mode VM with `stvec` at `VECTOR`, the PC two bytes below `PAGE_BOUNDARY`
+/// and `halves` written there. The page above the boundary is mapped first when
+/// `map_upper` is set, so the two pages land in non-adjacent frames.
+fn vm_at_page_end(halves: &[u16], map_upper: bool) -> VM {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    let user_rwx = Perms::new(true, true, true, true);
+    if map_upper {
+        assert!(memory.map_range(VirtualAddress(PAGE_BOUNDARY), PAGE_SIZE, user_rwx));
+    }
+    assert!(memory.map_range(VirtualAddress(LOW_PAGE), PAGE_SIZE, user_rwx));
+    let mut va = PAGE_BOUNDARY - 2;
+    for half in halves {
+        memory.write_bytes(VirtualAddress(va), &half.to_le_bytes());
+        va += 2;
+    }
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = PAGE_BOUNDARY - 2;
+    vm.cpu.priv_mode = PrivilegeMode::User;
+    vm.cpu.csrs.insert(CSR_STVEC, VECTOR);
+    vm
+}
+
+#[test]
+fn compressed_instruction_at_a_page_end_runs_with_the_next_page_unmapped() {
+    // c.nop
+    let mut vm = vm_at_page_end(&[0x0001], false);
+    assert!(vm.cpu.step(Rc::clone(&vm.memory)));
+    assert_eq!(vm.cpu.pc, PAGE_BOUNDARY);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::User);
+
+    // The next fetch is the one that faults, at the boundary itself.
+    vm.run_with_limit(1);
+    assert_instruction_page_fault(&vm, PAGE_BOUNDARY);
+}
+
+#[test]
+fn full_instruction_split_across_non_adjacent_frames_runs() {
+    // addi t0, x0, 1 with its upper half on the next page.
+    let mut vm = vm_at_page_end(&[0x0293, 0x0010], true);
+    let split = vm.memory.mem_slice(
+        VirtualAddress(PAGE_BOUNDARY - 2),
+        VirtualAddress(PAGE_BOUNDARY + 2),
+    );
+    assert!(split.is_none(), "the two pages must not be adjacent frames");
+
+    assert!(vm.cpu.step(Rc::clone(&vm.memory)));
+    assert_eq!(vm.cpu.regs[5], 1);
+    assert_eq!(vm.cpu.pc, PAGE_BOUNDARY + 2);
+}
+
+#[test]
+fn full_instruction_with_an_unmapped_upper_half_faults_at_its_second_halfword() {
+    // The low half of addi t0, x0, 1; the page holding the rest is unmapped.
+    let mut vm = vm_at_page_end(&[0x0293], false);
+    vm.run_with_limit(1);
+    assert_eq!(vm.cpu.pc, VECTOR);
+    assert_eq!(vm.cpu.csrs[&CSR_SCAUSE], SCAUSE_INSTRUCTION_PAGE_FAULT);
+    assert_eq!(vm.cpu.csrs[&CSR_STVAL], PAGE_BOUNDARY);
+    assert_eq!(vm.cpu.csrs[&CSR_SEPC], PAGE_BOUNDARY - 2);
+}
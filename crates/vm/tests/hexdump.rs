@@ -0,0 +1,23 @@
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE};
+
+#[test]
+fn hexdump_formats_mapped_and_unmapped_bytes() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    assert!(memory.map_range(VirtualAddress(0x1000), PAGE_SIZE, Perms::rw_kernel()));
+    memory.write_bytes(VirtualAddress(0x1000), b"Hello, aVM!\n\x00\x01\xfe\x7f");
+
+    // Starts 8 bytes below the mapping, so the first half line is unmapped.
+    let dump = memory.hexdump(VirtualAddress(0x0ff8), 24);
+    let expected = concat!(
+        "00000ff8: ---- ---- ---- ---- 4865 6c6c 6f2c 2061  ~~~~~~~~Hello, a\n",
+        "00001008: 564d 210a 0001 fe7f                      VM!.....\n",
+    );
+    assert_eq!(dump, expected);
+    assert!(memory.take_fault().is_none());
+}
+
+#[test]
+fn hexdump_of_nothing_is_empty() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    assert_eq!(memory.hexdump(VirtualAddress(0x1000), 0), "");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{vm_with_program, CODE_BASE};
+use vm::cpu::CPU;
+use vm::hook::{Hook, HookAction};
+use vm::instruction::Instruction;
+use vm::vm::ExitReason;
+
+const A0: usize = 10;
+
+/// Conditional breakpoint: stop once a0 reaches a target value.
+struct BreakOnA0(u32);
+
+impl Hook for BreakOnA0 {
+    fn before_instruction(&mut self, cpu: &mut CPU, _instr: &Instruction) -> HookAction {
+        if cpu.regs[A0] == self.0 {
+            HookAction::Halt
+        } else {
+            HookAction::Continue
+        }
+    }
+}
+
+/// Skips the instruction at one PC.
+struct SkipAt(u32);
+
+impl Hook for SkipAt {
+    fn before_instruction(&mut self, cpu: &mut CPU, _instr: &Instruction) -> HookAction {
+        if cpu.pc == self.0 {
+            HookAction::Skip
+        } else {
+            HookAction::Continue
+        }
+    }
+}
+
+#[test]
+fn hook_halts_when_a0_reaches_target_mid_loop() {
+    // loop: addi a0, a0, 1 ; j loop
+    let mut vm = vm_with_program(&[0x0015_0513, 0xffdf_f06f]);
+    vm.set_hook(Box::new(BreakOnA0(7)));
+    let summary = vm.run_with_limit(1000);
+
+    assert_eq!(summary.exit, ExitReason::Halted);
+    assert_eq!(vm.cpu.regs[A0], 7);
+    assert_eq!(vm.cpu.pc, CODE_BASE + 4, "halted before executing the jump");
+}
+
+#[test]
+fn skipped_instruction_is_not_executed() {
+    // addi a0, zero, 1 ; addi a0, a0, 1 ; ebreak
+    let mut vm = vm_with_program(&[0x0010_0513, 0x0015_0513, 0x0010_0073]);
+    vm.set_hook(Box::new(SkipAt(CODE_BASE + 4)));
+    let summary = vm.run_with_limit(100);
+
+    assert_eq!(summary.exit, ExitReason::Halted);
+    assert_eq!(vm.cpu.regs[A0], 1);
+}
@@ -0,0 +1,36 @@
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE};
+
+const PAGES: usize = 16;
+
+#[test]
+fn mapping_past_physical_memory_fails() {
+    let memory = Sv32Memory::new(PAGES * PAGE_SIZE, PAGE_SIZE);
+    // Frame 0, the root and the L2 table take frames too, so PAGES leaves never fit.
+    assert!(!memory.map_range(VirtualAddress(0), PAGES * PAGE_SIZE, Perms::rw_kernel()));
+    assert!(!memory.map_range(VirtualAddress(0x40_0000), PAGE_SIZE, Perms::rw_kernel()));
+}
+
+#[test]
+fn pages_mapped_before_exhaustion_do_not_alias() {
+    let memory = Sv32Memory::new(PAGES * PAGE_SIZE, PAGE_SIZE);
+    assert!(!memory.map_range(VirtualAddress(0), 2 * PAGES * PAGE_SIZE, Perms::rw_kernel()));
+
+    // Reserved frame 0, the root and one L2 table leave PAGES - 3 distinct leaves.
+    let mapped = PAGES - 3;
+    for page in 0..mapped {
+        memory.write_bytes(VirtualAddress((page * PAGE_SIZE) as u32), &[page as u8]);
+    }
+    for page in 0..mapped {
+        let va = VirtualAddress((page * PAGE_SIZE) as u32);
+        let byte = memory.mem_slice(va, va.checked_add(1).unwrap()).unwrap()[0];
+        assert_eq!(byte, page as u8);
+    }
+}
+
+#[test]
+fn physical_range_past_backing_is_rejected() {
+    let memory = Sv32Memory::new(PAGES * PAGE_SIZE, PAGE_SIZE);
+    let va = VirtualAddress(0x80_0000);
+    assert!(!memory.map_physical_range(va, 0, (PAGES + 1) * PAGE_SIZE, Perms::rw_kernel()));
+    assert!(memory.map_physical_range(va, 0, PAGES * PAGE_SIZE, Perms::rw_kernel()));
+}
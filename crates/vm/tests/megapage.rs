@@ -0,0 +1,229 @@
+use vm::memory::{
+    MapError, MemoryConfigError, Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE,
+    SV32_MEGAPAGE_SIZE,
+};
+use vm::metering::{MemoryAccessKind, NoopMeter};
+
+const MEMORY: usize = 16 * 1024 * 1024;
+const MEGA: u32 = 0x0040_0000;
+
+#[test]
+fn megapage_translates_the_whole_range() {
+    let memory = Sv32Memory::new(MEMORY, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    memory
+        .map_range_with_page_size(
+            VirtualAddress(MEGA),
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE,
+        )
+        .unwrap();
+
+    // One word per 4 KiB page, including both ends of the megapage.
+    let pages = (SV32_MEGAPAGE_SIZE / PAGE_SIZE) as u32;
+    for page in 0..pages {
+        let addr = VirtualAddress(MEGA + page * PAGE_SIZE as u32);
+        assert!(memory.store_u32(addr, page, &mut meter, MemoryAccessKind::Store));
+    }
+    let last = VirtualAddress(MEGA + SV32_MEGAPAGE_SIZE as u32 - 4);
+    assert!(memory.store_u32(last, 0xfeed_f00d, &mut meter, MemoryAccessKind::Store));
+    for page in 0..pages {
+        let addr = VirtualAddress(MEGA + page * PAGE_SIZE as u32);
+        assert_eq!(
+            memory.load_u32(addr, &mut meter, MemoryAccessKind::Load),
+            Some(page)
+        );
+    }
+    assert_eq!(
+        memory.load_u32(last, &mut meter, MemoryAccessKind::Load),
+        Some(0xfeed_f00d)
+    );
+
+    // Accesses straddling a 4 KiB boundary stay inside the megapage.
+    let straddle = VirtualAddress(MEGA + PAGE_SIZE as u32 - 2);
+    assert!(memory.store_u32(straddle, 0x1122_3344, &mut meter, MemoryAccessKind::Store));
+    assert_eq!(
+        memory.load_u32(straddle, &mut meter, MemoryAccessKind::Load),
+        Some(0x1122_3344)
+    );
+
+    // Neighbouring megapages are untouched.
+    let below = VirtualAddress(MEGA - 4);
+    let above = VirtualAddress(MEGA + SV32_MEGAPAGE_SIZE as u32);
+    assert_eq!(
+        memory.load_u32(below, &mut meter, MemoryAccessKind::Load),
+        None
+    );
+    assert!(memory.take_fault().is_some());
+    assert_eq!(
+        memory.load_u32(above, &mut meter, MemoryAccessKind::Load),
+        None
+    );
+}
+
+#[test]
+fn megapage_respects_permissions() {
+    let memory = Sv32Memory::new(MEMORY, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    memory
+        .map_range_with_page_size(
+            VirtualAddress(MEGA),
+            SV32_MEGAPAGE_SIZE,
+            Perms::new(true, false, false, false),
+            SV32_MEGAPAGE_SIZE,
+        )
+        .unwrap();
+    let addr = VirtualAddress(MEGA + 0x1234);
+    assert_eq!(
+        memory.load_u32(addr, &mut meter, MemoryAccessKind::Load),
+        Some(0)
+    );
+    assert!(!memory.store_u32(addr, 1, &mut meter, MemoryAccessKind::Store));
+}
+
+#[test]
+fn small_pages_across_an_l1_boundary_still_work() {
+    let memory = Sv32Memory::new(MEMORY, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    memory
+        .map_range_with_page_size(
+            VirtualAddress(MEGA),
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE,
+        )
+        .unwrap();
+
+    // Two 4 KiB pages on either side of the 0x00C0_0000 L1 boundary.
+    let boundary = 0x00C0_0000u32;
+    memory
+        .map_range_with_page_size(
+            VirtualAddress(boundary - PAGE_SIZE as u32),
+            2 * PAGE_SIZE,
+            Perms::rw_kernel(),
+            PAGE_SIZE,
+        )
+        .unwrap();
+    let straddle = VirtualAddress(boundary - 2);
+    assert!(memory.store_u32(straddle, 0xcafe_babe, &mut meter, MemoryAccessKind::Store));
+    assert_eq!(
+        memory.load_u32(straddle, &mut meter, MemoryAccessKind::Load),
+        Some(0xcafe_babe)
+    );
+}
+
+#[test]
+fn megapage_cannot_share_an_l1_entry() {
+    let memory = Sv32Memory::new(MEMORY, PAGE_SIZE);
+    let mega = VirtualAddress(MEGA);
+    memory
+        .map_range_with_page_size(
+            mega,
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE,
+        )
+        .unwrap();
+    assert!(!memory.map_range(VirtualAddress(MEGA + 0x1000), PAGE_SIZE, Perms::rw_kernel()));
+    assert_eq!(
+        memory.map_range_with_page_size(
+            mega,
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE
+        ),
+        Err(MapError::Overlap { addr: mega })
+    );
+
+    let small = VirtualAddress(0x0080_0000);
+    assert!(memory.map_range(small, PAGE_SIZE, Perms::rw_kernel()));
+    assert_eq!(
+        memory.map_range_with_page_size(
+            small,
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE
+        ),
+        Err(MapError::Overlap { addr: small })
+    );
+}
+
+#[test]
+fn unsupported_or_misaligned_megapages_are_rejected() {
+    let memory = Sv32Memory::new(MEMORY, PAGE_SIZE);
+    let mega = VirtualAddress(MEGA);
+    assert_eq!(
+        memory.map_range_with_page_size(mega, 8192, Perms::rw_kernel(), 8192),
+        Err(MapError::UnsupportedPageSize { size: 8192 })
+    );
+    let err = memory
+        .map_range_with_page_size(mega, 8192, Perms::rw_kernel(), 8192)
+        .unwrap_err();
+    assert!(err.to_string().contains("4 MiB megapages"));
+
+    let unaligned = VirtualAddress(MEGA + PAGE_SIZE as u32);
+    assert_eq!(
+        memory.map_range_with_page_size(
+            unaligned,
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE
+        ),
+        Err(MapError::Misaligned {
+            addr: unaligned,
+            len: SV32_MEGAPAGE_SIZE
+        })
+    );
+    assert_eq!(
+        memory.map_range_with_page_size(mega, PAGE_SIZE, Perms::rw_kernel(), SV32_MEGAPAGE_SIZE),
+        Err(MapError::Misaligned {
+            addr: mega,
+            len: PAGE_SIZE
+        })
+    );
+}
+
+#[test]
+fn megapage_allocation_fails_when_memory_runs_out() {
+    // 4 MiB of backing cannot hold an aligned megapage next to the reserved frames.
+    let memory = Sv32Memory::new(SV32_MEGAPAGE_SIZE, PAGE_SIZE);
+    let mega = VirtualAddress(MEGA);
+    assert_eq!(
+        memory.map_range_with_page_size(
+            mega,
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE
+        ),
+        Err(MapError::OutOfMemory { addr: mega })
+    );
+}
+
+#[test]
+fn try_new_rejects_non_sv32_frame_sizes() {
+    assert_eq!(
+        Sv32Memory::try_new(MEMORY, 8192).unwrap_err(),
+        MemoryConfigError::UnsupportedFrameSize { size: 8192 }
+    );
+    assert_eq!(
+        Sv32Memory::try_new(MEMORY, SV32_MEGAPAGE_SIZE).unwrap_err(),
+        MemoryConfigError::UnsupportedFrameSize {
+            size: SV32_MEGAPAGE_SIZE
+        }
+    );
+    assert_eq!(
+        Sv32Memory::try_new(0, PAGE_SIZE).unwrap_err(),
+        MemoryConfigError::Empty
+    );
+
+    let memory = Sv32Memory::try_new(MEMORY, PAGE_SIZE).unwrap();
+    memory
+        .map_range_with_page_size(
+            VirtualAddress(MEGA),
+            SV32_MEGAPAGE_SIZE,
+            Perms::rw_kernel(),
+            SV32_MEGAPAGE_SIZE,
+        )
+        .unwrap();
+}
@@ -0,0 +1,76 @@
+use std::rc::Rc;
+
+use vm::cpu::CPU;
+use vm::memory::{Memory, Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::metering::{BranchProfiler, MemoryAccessCounts, MemoryProfiler};
+
+// addi t0, zero, 0x100 ; addi t1, zero, 42 ; sw t1, 0(t0) ; lw t2, 0(t0) ; lw s0, 0(t0)
+const PROGRAM: [u32; 5] = [
+    0x1000_0293,
+    0x02a0_0313,
+    0x0062_a023,
+    0x0002_a383,
+    0x0002_a403,
+];
+
+#[test]
+fn memory_profiler_tallies_accesses_by_kind() {
+    let sv32 = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(sv32.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rwx_kernel()));
+    let code: Vec<u8> = PROGRAM.iter().flat_map(|w| w.to_le_bytes()).collect();
+    sv32.write_bytes(VirtualAddress(0x1000), &code);
+    let memory: Memory = sv32;
+
+    let profiler = MemoryProfiler::new();
+    let mut cpu = CPU::new();
+    cpu.set_metering(Box::new(profiler.clone()));
+    cpu.pc = 0x1000;
+    for _ in 0..PROGRAM.len() {
+        assert!(cpu.step(Rc::clone(&memory)));
+    }
+
+    assert_eq!(cpu.regs[7], 42);
+    assert_eq!(cpu.regs[8], 42);
+    assert_eq!(
+        profiler.counts(),
+        MemoryAccessCounts {
+            loads: 2,
+            stores: 1,
+            atomics: 0,
+            fetches: PROGRAM.len() as u64,
+        }
+    );
+    assert_eq!(profiler.counts().data_accesses(), 3);
+}
+
+// addi t0, zero, 5 ; loop: addi t0, t0, -1 ; bne t0, zero, loop ; jal ra, func ; ebreak ; nop
+// func: ret
+const LOOP_AND_CALL: [u32; 7] = [
+    0x0050_0293,
+    0xfff2_8293,
+    0xfe02_9ee3,
+    0x00c0_00ef,
+    0x0010_0073,
+    0x0000_0013,
+    0x0000_8067,
+];
+
+#[test]
+fn branch_profiler_reports_loop_back_edge_and_calls() {
+    let sv32 = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    assert!(sv32.map_range(VirtualAddress(0), 4 * PAGE_SIZE, Perms::rwx_kernel()));
+    let code: Vec<u8> = LOOP_AND_CALL.iter().flat_map(|w| w.to_le_bytes()).collect();
+    sv32.write_bytes(VirtualAddress(0x1000), &code);
+    let memory: Memory = sv32;
+
+    let profiler = BranchProfiler::new();
+    let mut cpu = CPU::new();
+    cpu.set_metering(Box::new(profiler.clone()));
+    cpu.pc = 0x1000;
+    while cpu.step(Rc::clone(&memory)) {}
+
+    assert_eq!(cpu.regs[5], 0);
+    assert_eq!(profiler.top_back_edges(3), vec![(0x1008, 4)]);
+    assert_eq!(profiler.call_count(0x1018), 1);
+    assert_eq!(profiler.call_count(0x1010), 0, "ret is not a call");
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use common::{memory_with_program, CODE_BASE};
+use vm::memory::{MmioDevice, VirtualAddress};
+use vm::vm::{ExitReason, VM};
+
+const MMIO_BASE: u32 = 0x8000_0000;
+// lui t1, 0x80000 ; li t3, 4
+// loop: lw t2, 0(t1) ; add t0, t0, t2 ; addi t3, t3, -1 ; bnez t3, loop
+// sw t0, 4(t1) ; ebreak
+const ACCUMULATE: [u32; 8] = [
+    0x8000_0337,
+    0x0040_0e13,
+    0x0003_2383,
+    0x0072_82b3,
+    0xfffe_0e13,
+    0xfe0e_1ae3,
+    0x0053_2223,
+    0x0010_0073,
+];
+
+/// Returns 1, 2, 3, ... on successive reads and records every write.
+#[derive(Default)]
+struct Counter {
+    reads: u32,
+    writes: Vec<(u32, u8, u32)>,
+}
+
+impl MmioDevice for Counter {
+    fn read(&mut self, _offset: u32, _size: u8) -> u32 {
+        self.reads += 1;
+        self.reads
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        self.writes.push((offset, size, value));
+    }
+}
+
+#[test]
+fn guest_accumulates_device_reads() {
+    let memory = memory_with_program(&ACCUMULATE);
+    let device = Rc::new(RefCell::new(Counter::default()));
+    assert!(memory.register_mmio(VirtualAddress(MMIO_BASE), 8, device.clone()));
+
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = CODE_BASE;
+    let summary = vm.run_with_limit(100);
+
+    assert_eq!(summary.exit, ExitReason::Halted);
+    assert_eq!(vm.cpu.regs[5], 1 + 2 + 3 + 4);
+    let device = device.borrow();
+    assert_eq!(device.reads, 4);
+    assert_eq!(device.writes, vec![(4, 4, 10)]);
+}
+
+#[test]
+fn overlapping_or_empty_regions_are_rejected() {
+    let memory = memory_with_program(&[]);
+    let device = Rc::new(RefCell::new(Counter::default()));
+    assert!(memory.register_mmio(VirtualAddress(MMIO_BASE), 0x100, device.clone()));
+    assert!(!memory.register_mmio(VirtualAddress(MMIO_BASE + 0xfc), 8, device.clone()));
+    assert!(!memory.register_mmio(VirtualAddress(0x9000_0000), 0, device.clone()));
+    assert!(!memory.register_mmio(VirtualAddress(0xffff_fff0), 0x20, device.clone()));
+    assert!(memory.register_mmio(VirtualAddress(MMIO_BASE + 0x100), 4, device));
+}
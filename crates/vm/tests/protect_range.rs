@@ -0,0 +1,52 @@
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE};
+use vm::metering::{MemoryAccessKind, NoopMeter};
+
+const BASE: u32 = 0x2000;
+
+fn rw_memory() -> Sv32Memory {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    assert!(memory.map_range(VirtualAddress(BASE), 2 * PAGE_SIZE, Perms::rw_kernel()));
+    memory.write_bytes(VirtualAddress(BASE), &[0xaa, 0xbb, 0xcc, 0xdd]);
+    memory
+}
+
+#[test]
+fn read_only_range_rejects_stores_but_keeps_contents() {
+    let memory = rw_memory();
+    let mut meter = NoopMeter;
+    let read_only = Perms::new(true, false, false, false);
+    assert!(memory.protect_range(VirtualAddress(BASE), 2 * PAGE_SIZE, read_only));
+
+    assert!(!memory.store_u32(
+        VirtualAddress(BASE),
+        0x1234_5678,
+        &mut meter,
+        MemoryAccessKind::Store
+    ));
+    assert!(!memory.store_u8(
+        VirtualAddress(BASE + PAGE_SIZE as u32),
+        1,
+        &mut meter,
+        MemoryAccessKind::Store
+    ));
+    assert_eq!(
+        memory.load_u32(VirtualAddress(BASE), &mut meter, MemoryAccessKind::Load),
+        Some(0xddcc_bbaa)
+    );
+
+    assert!(memory.protect_range(VirtualAddress(BASE), 4, Perms::rw_kernel()));
+    assert!(memory.store_u8(VirtualAddress(BASE), 7, &mut meter, MemoryAccessKind::Store));
+    assert_eq!(
+        memory.load_byte(VirtualAddress(BASE), &mut meter, MemoryAccessKind::Load),
+        Some(7)
+    );
+}
+
+#[test]
+fn partially_unmapped_range_is_rejected_untouched() {
+    let memory = rw_memory();
+    let mut meter = NoopMeter;
+    let read_only = Perms::new(true, false, false, false);
+    assert!(!memory.protect_range(VirtualAddress(BASE), 3 * PAGE_SIZE, read_only));
+    assert!(memory.store_u8(VirtualAddress(BASE), 1, &mut meter, MemoryAccessKind::Store));
+}
@@ -0,0 +1,77 @@
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE};
+
+const BASE: u32 = 0x1000;
+
+fn memory_with(bytes: &[u8], at: u32) -> Sv32Memory {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    assert!(memory.map_range(VirtualAddress(BASE), 2 * PAGE_SIZE, Perms::rw_kernel()));
+    memory.write_bytes(VirtualAddress(at), bytes);
+    memory
+}
+
+#[test]
+fn reads_up_to_the_terminator() {
+    let memory = memory_with(b"out of pages\0trailing", BASE);
+    let s = memory.read_cstr(VirtualAddress(BASE), 64).unwrap();
+    assert_eq!(&*s, "out of pages");
+    drop(s);
+
+    // The NUL may sit exactly at the last byte the cap allows.
+    assert_eq!(
+        &*memory.read_cstr(VirtualAddress(BASE), 13).unwrap(),
+        "out of pages"
+    );
+    assert_eq!(
+        &*memory.read_cstr(VirtualAddress(BASE + 12), 1).unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn strings_may_cross_a_page_boundary() {
+    let at = BASE + PAGE_SIZE as u32 - 3;
+    let memory = memory_with(b"abcdef\0", at);
+    assert_eq!(
+        &*memory.read_cstr(VirtualAddress(at), 16).unwrap(),
+        "abcdef"
+    );
+}
+
+#[test]
+fn strings_may_span_non_contiguous_frames() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    let next = BASE + PAGE_SIZE as u32;
+    // Map an unrelated page in between so the two string pages get non-adjacent frames.
+    assert!(memory.map_range(VirtualAddress(BASE), PAGE_SIZE, Perms::rw_kernel()));
+    assert!(memory.map_range(VirtualAddress(0x8000), PAGE_SIZE, Perms::rw_kernel()));
+    assert!(memory.map_range(VirtualAddress(next), PAGE_SIZE, Perms::rw_kernel()));
+    let at = next - 3;
+    memory.write_bytes(VirtualAddress(at), "ab\u{e9}cd\0".as_bytes());
+    assert!(memory
+        .mem_slice(VirtualAddress(at), VirtualAddress(at + 6))
+        .is_none());
+
+    assert_eq!(
+        memory.read_cstr(VirtualAddress(at), 16).as_deref(),
+        Some("ab\u{e9}cd")
+    );
+}
+
+#[test]
+fn missing_terminator_within_the_cap_is_none() {
+    let memory = memory_with(b"no terminator here", BASE);
+    assert!(memory.read_cstr(VirtualAddress(BASE), 8).is_none());
+    assert!(memory.read_cstr(VirtualAddress(BASE), 0).is_none());
+}
+
+#[test]
+fn unmapped_or_invalid_bytes_are_none() {
+    // Runs off the end of the mapping before any NUL.
+    let end = BASE + 2 * PAGE_SIZE as u32;
+    let memory = memory_with(b"abcd", end - 4);
+    assert!(memory.read_cstr(VirtualAddress(end - 4), 64).is_none());
+    assert!(memory.read_cstr(VirtualAddress(0x8000), 64).is_none());
+
+    memory.write_bytes(VirtualAddress(BASE), &[0xff, 0xfe, 0]);
+    assert!(memory.read_cstr(VirtualAddress(BASE), 64).is_none());
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{vm_with_program, CODE_BASE};
+use vm::cpu::PrivilegeMode;
+use vm::vm::{ExitReason, RunSummary, VmState};
+
+// addi t0, zero, 1 ; addi t0, t0, 1 ; ebreak
+const HALTING: [u32; 3] = [0x0010_0293, 0x0012_8293, 0x0010_0073];
+// loop: addi t0, t0, 1 ; j loop
+const SPINNING: [u32; 2] = [0x0012_8293, 0xffdf_f06f];
+
+#[test]
+fn short_program_reports_steps_and_clean_halt() {
+    let mut vm = vm_with_program(&HALTING);
+    let summary = vm.run_with_limit(100);
+    assert_eq!(
+        summary,
+        RunSummary {
+            steps: 3,
+            exit: ExitReason::Halted,
+            pc: CODE_BASE + 12,
+        }
+    );
+    assert_eq!(vm.cpu.regs[5], 2);
+}
+
+#[test]
+fn step_limit_stops_a_spinning_program() {
+    let mut vm = vm_with_program(&SPINNING);
+    let summary = vm.run_with_limit(10);
+    assert_eq!(summary.steps, 10);
+    assert_eq!(summary.exit, ExitReason::StepLimit);
+    assert_eq!(vm.cpu.regs[5], 5);
+}
+
+#[test]
+fn run_until_reports_stop_condition() {
+    let mut vm = vm_with_program(&SPINNING);
+    let summary = vm.run_until(1000, |vm| vm.cpu.regs[5] == 3);
+    assert_eq!(summary.exit, ExitReason::Stopped);
+    assert_eq!(summary.steps, 5);
+    assert_eq!(summary.pc, CODE_BASE + 4);
+}
+
+#[test]
+fn wfi_halts_with_a_stable_pc() {
+    // addi t0, zero, 1 ; wfi ; addi t0, t0, 1
+    let mut vm = vm_with_program(&[0x0010_0293, 0x1050_0073, 0x0012_8293]);
+    let wfi_pc = CODE_BASE + 4;
+    assert_eq!(
+        vm.run_with_limit(100),
+        RunSummary {
+            steps: 2,
+            exit: ExitReason::Wfi,
+            pc: wfi_pc,
+        }
+    );
+
+    // Nothing wakes the hart, so resuming stops on the same `wfi` again.
+    let again = vm.run_with_limit(100);
+    assert_eq!(
+        (again.steps, again.exit, again.pc),
+        (1, ExitReason::Wfi, wfi_pc)
+    );
+    assert_eq!(vm.cpu.regs[5], 1);
+}
+
+#[test]
+fn stepping_past_a_wfi_resumes_normally() {
+    // addi t0, zero, 1 ; wfi ; addi t0, t0, 1 ; ebreak
+    let mut vm = vm_with_program(&[0x0010_0293, 0x1050_0073, 0x0012_8293, 0x0010_0073]);
+    assert_eq!(vm.run_with_limit(100).exit, ExitReason::Wfi);
+
+    // The host skips the `wfi`; later instructions advance the PC again and the
+    // final halt is an ordinary one.
+    vm.cpu.pc += 4;
+    assert_eq!(
+        vm.run_with_limit(100),
+        RunSummary {
+            steps: 2,
+            exit: ExitReason::Halted,
+            pc: CODE_BASE + 16,
+        }
+    );
+    assert_eq!(vm.cpu.regs[5], 2);
+    assert!(!vm.cpu.waiting_for_interrupt);
+}
+
+#[test]
+fn final_state_reports_registers_after_raw_run() {
+    // addi a0, zero, 42 ; ebreak
+    let mut vm = vm_with_program(&[0x02a0_0513, 0x0010_0073]);
+    vm.raw_run();
+    let state = vm.final_state();
+    assert_eq!(state.regs[10], 42);
+    assert_eq!(
+        state,
+        VmState {
+            regs: vm.cpu.regs,
+            pc: CODE_BASE + 8,
+            priv_mode: PrivilegeMode::Supervisor,
+        }
+    );
+}
+
+#[test]
+fn wfi_resumes_once_the_host_steps_past_it() {
+    // addi t0, zero, 1 ; wfi ; addi t0, t0, 1 ; ebreak
+    let mut vm = vm_with_program(&[0x0010_0293, 0x1050_0073, 0x0012_8293, 0x0010_0073]);
+    let wfi_pc = CODE_BASE + 4;
+    assert_eq!(vm.run_with_limit(100).exit, ExitReason::Wfi);
+    assert!(vm.cpu.waiting_for_interrupt);
+
+    // Stand in for a wake-up: skip the `wfi` and the hart runs the next instruction.
+    vm.cpu.pc = wfi_pc + 4;
+    let resumed = vm.run_with_limit(1);
+    assert_eq!(
+        (resumed.steps, resumed.exit, resumed.pc),
+        (1, ExitReason::StepLimit, wfi_pc + 8)
+    );
+    assert!(!vm.cpu.waiting_for_interrupt);
+    assert_eq!(vm.cpu.regs[5], 2);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{r_type, vm_with_program};
+use vm::decoder::decode_full;
+use vm::instruction::Instruction;
+
+const OP_IMM: u32 = 0x13;
+
+fn i_type(imm: u32, rs1: u32, funct3: u32, rd: u32) -> u32 {
+    (imm << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | OP_IMM
+}
+
+#[test]
+fn immediate_shifts_of_32_or_more_are_illegal() {
+    // slli / srli / srai x5, x5, shamt
+    for (funct3, funct7, what) in [(1, 0x00, "slli"), (5, 0x00, "srli"), (5, 0x20, "srai")] {
+        let shifted = |shamt: u32| i_type((funct7 << 5) | shamt, 5, funct3, 5);
+        assert!(decode_full(shifted(31)).is_some(), "{what} by 31");
+        assert_eq!(decode_full(shifted(32)), None, "{what} by 32");
+        assert_eq!(decode_full(shifted(33)), None, "{what} by 33");
+    }
+    assert_eq!(
+        decode_full(i_type(31, 5, 1, 5)),
+        Some(Instruction::Slli {
+            rd: 5,
+            rs1: 5,
+            shamt: 31
+        })
+    );
+}
+
+#[test]
+fn register_shifts_use_the_low_five_bits() {
+    let mut vm = vm_with_program(&[
+        i_type(0xff0, 0, 0, 5),    // addi x5, x0, -16
+        i_type(32, 0, 0, 6),       // addi x6, x0, 32
+        i_type(33, 0, 0, 7),       // addi x7, x0, 33
+        r_type(0x00, 6, 5, 1, 10), // sll x10, x5, x6
+        r_type(0x00, 7, 5, 1, 11), // sll x11, x5, x7
+        r_type(0x00, 6, 5, 5, 12), // srl x12, x5, x6
+        r_type(0x00, 7, 5, 5, 13), // srl x13, x5, x7
+        r_type(0x20, 6, 5, 5, 14), // sra x14, x5, x6
+        r_type(0x20, 7, 5, 5, 15), // sra x15, x5, x7
+    ]);
+    vm.run_with_limit(9);
+
+    // A shift by 32 is a shift by 0, and 33 is a shift by 1.
+    let lhs = 0xffff_fff0u32;
+    assert_eq!(vm.cpu.regs[10], lhs, "sll by 32");
+    assert_eq!(vm.cpu.regs[11], lhs << 1, "sll by 33");
+    assert_eq!(vm.cpu.regs[12], lhs, "srl by 32");
+    assert_eq!(vm.cpu.regs[13], lhs >> 1, "srl by 33");
+    assert_eq!(vm.cpu.regs[14], lhs, "sra by 32");
+    assert_eq!(vm.cpu.regs[15], ((lhs as i32) >> 1) as u32, "sra by 33");
+}
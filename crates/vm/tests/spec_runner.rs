@@ -3,9 +3,10 @@
 
 use std::io::Read;
 use std::path::Path;
+use vm::cpu::EcallBehavior;
 use vm::memory::{Perms, Sv32Memory, VirtualAddress, API, MMU, PAGE_SIZE};
 use vm::registers::Register;
-use vm::vm::VM;
+use vm::vm::{ExitReason, VM};
 
 const DEFAULT_VM_SIZE: usize = 16 * 1024 * 1024;
 const STACK_SIZE: usize = 256 * 1024;
@@ -120,10 +121,13 @@ fn run_single_test(elf_path: &str) -> Result<(), Box<dyn std::error::Error>> {
         min_base + map_len,
         map_len
     );
-    memory.map_range(
-        VirtualAddress(min_base as u32),
-        map_len,
-        Perms::rwx_kernel(),
+    assert!(
+        memory.map_range(
+            VirtualAddress(min_base as u32),
+            map_len,
+            Perms::rwx_kernel(),
+        ),
+        "failed to map test image"
     );
 
     let mut image = vec![0u8; image_size];
@@ -155,44 +159,46 @@ fn run_single_test(elf_path: &str) -> Result<(), Box<dyn std::error::Error>> {
 
     let mut vm = VM::new(memory.clone());
     vm.cpu.verbose = false;
+    vm.cpu.ecall_behavior = EcallBehavior::ExitOnA7_93;
     vm.cpu.pc = entry_point;
     vm.set_reg_u32(Register::Sp, stack_top);
     let root_satp = memory.satp();
 
     println!("Running test...");
-    let mut steps = 0usize;
-    loop {
-        if !vm.cpu.step(memory.clone()) {
-            break;
-        }
-        steps += 1;
+    let mut tohost_value = Ok(0);
+    let summary = vm.run_until(MAX_STEPS, |_| {
         if memory.satp() == 0 {
             memory.set_satp(root_satp);
         }
-        if steps > MAX_STEPS {
-            return Err("execution limit reached without tohost signal".into());
-        }
-        let tohost_value = read_tohost_value(memory.as_ref(), tohost_addr)?;
-        if tohost_value != 0 {
+        tohost_value = read_tohost_value(memory.as_ref(), tohost_addr);
+        !matches!(tohost_value, Ok(0))
+    });
+    match summary.exit {
+        ExitReason::Stopped => {
+            let tohost_value = tohost_value?;
             if tohost_value == 1 {
                 println!("Test completed.");
                 return Ok(());
             }
             return Err(format!("test failed (tohost=0x{tohost_value:x})").into());
         }
+        ExitReason::StepLimit => {
+            return Err("execution limit reached without tohost signal".into());
+        }
+        ExitReason::Wfi => {
+            return Err(format!("halted on wfi at pc=0x{:08x}", summary.pc).into());
+        }
+        ExitReason::Halted => {}
     }
 
-    let exit_id = vm.cpu.regs[Register::A7 as usize];
-    if exit_id == 93 {
-        let exit_code = vm.cpu.regs[Register::A0 as usize];
-        if exit_code == 0 {
+    match vm.cpu.exit_code {
+        Some(0) => {
             println!("Test completed.");
-            return Ok(());
+            Ok(())
         }
-        return Err(format!("test failed (ecall exit code={exit_code})").into());
+        Some(exit_code) => Err(format!("test failed (ecall exit code={exit_code})").into()),
+        None => Err("execution halted without tohost signal".into()),
     }
-
-    Err("execution halted without tohost signal".into())
 }
 
 fn read_tohost_value(
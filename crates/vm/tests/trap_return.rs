@@ -0,0 +1,124 @@
+use std::rc::Rc;
+
+use vm::cpu::PrivilegeMode;
+use vm::memory::{Perms, Sv32Memory, VirtualAddress, PAGE_SIZE};
+use vm::vm::VM;
+
+const CODE_BASE: u32 = 0x1000;
+const TARGET: u32 = 0x1100;
+const VECTOR: u32 = 0x1200;
+
+const SRET: u32 = 0x1020_0073;
+const MRET: u32 = 0x3020_0073;
+// csrr a0, sstatus
+const READ_SSTATUS: u32 = 0x1000_2573;
+// csrr a0, mstatus
+const READ_MSTATUS: u32 = 0x3000_2573;
+// csrr a1, scause
+const READ_SCAUSE: u32 = 0x1420_25f3;
+
+const SSTATUS_SIE: u32 = 1 << 1;
+const SSTATUS_SPIE: u32 = 1 << 5;
+const SSTATUS_SPP: u32 = 1 << 8;
+const MSTATUS_MPIE: u32 = 1 << 7;
+const MSTATUS_MPP: u32 = 0b11 << 11;
+
+// csrrw x0, csr, rs1
+fn csrw(csr: u32, rs1: u32) -> u32 {
+    (csr << 20) | (rs1 << 15) | (1 << 12) | 0x73
+}
+
+// lui rd, hi ; addi rd, rd, lo (hi rounded so the sign-extended lo adds back up)
+fn li(rd: u32, value: u32) -> [u32; 2] {
+    let hi = value.wrapping_add(0x800) & !0xfff;
+    let lo = value.wrapping_sub(hi) & 0xfff;
+    [
+        hi | (rd << 7) | 0x37,
+        (lo << 20) | (rd << 15) | (rd << 7) | 0x13,
+    ]
+}
+
+/// Point `epc` at `TARGET`, the trap vector at `VECTOR`, load `status`, then `xret`.
+fn trap_return(epc: u32, status_csr: u32, status: u32, xret: u32) -> Vec<u32> {
+    let mut words = Vec::new();
+    words.extend(li(5, TARGET));
+    words.push(csrw(epc, 5));
+    words.extend(li(6, VECTOR));
+    words.push(csrw(0x105, 6));
+    words.extend(li(7, status));
+    words.push(csrw(status_csr, 7));
+    words.push(xret);
+    words
+}
+
+fn vm_with(mode: PrivilegeMode, code: &[u32], target: &[u32], vector: &[u32]) -> VM {
+    let memory = Rc::new(Sv32Memory::new(64 * 1024, PAGE_SIZE));
+    let user_rwx = Perms::new(true, true, true, true);
+    assert!(memory.map_range(VirtualAddress(0), 4 * PAGE_SIZE, user_rwx));
+    let bytes = |words: &[u32]| -> Vec<u8> { words.iter().flat_map(|w| w.to_le_bytes()).collect() };
+    memory.write_bytes(VirtualAddress(CODE_BASE), &bytes(code));
+    memory.write_bytes(VirtualAddress(TARGET), &bytes(target));
+    memory.write_bytes(VirtualAddress(VECTOR), &bytes(vector));
+    let mut vm = VM::new(memory);
+    vm.cpu.pc = CODE_BASE;
+    vm.cpu.priv_mode = mode;
+    vm
+}
+
+#[test]
+fn sret_with_spp_clear_enters_user_mode_at_sepc() {
+    let code = trap_return(0x141, 0x100, SSTATUS_SPIE, SRET);
+    let mut vm = vm_with(PrivilegeMode::Supervisor, &code, &[READ_SSTATUS], &[]);
+    vm.run_with_limit(code.len());
+    assert_eq!(vm.cpu.pc, TARGET);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::User);
+
+    vm.run_with_limit(1);
+    // SIE comes back from SPIE, SPIE is set and SPP is left pointing at user mode.
+    let sstatus = vm.cpu.regs[10];
+    assert_eq!(sstatus & SSTATUS_SPP, 0);
+    assert_ne!(sstatus & SSTATUS_SIE, 0);
+    assert_ne!(sstatus & SSTATUS_SPIE, 0);
+}
+
+#[test]
+fn sret_with_spp_set_stays_in_supervisor_mode() {
+    let code = trap_return(0x141, 0x100, SSTATUS_SPP, SRET);
+    let mut vm = vm_with(PrivilegeMode::Supervisor, &code, &[READ_SSTATUS], &[]);
+    vm.run_with_limit(code.len() + 1);
+    assert_eq!(vm.cpu.pc, TARGET + 4);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::Supervisor);
+    assert_eq!(vm.cpu.regs[10] & (SSTATUS_SPP | SSTATUS_SIE), 0);
+}
+
+#[test]
+fn sret_from_user_mode_is_an_illegal_instruction() {
+    let code = trap_return(0x141, 0x100, 0, SRET);
+    let mut vm = vm_with(PrivilegeMode::Supervisor, &code, &[SRET], &[READ_SCAUSE]);
+    vm.run_with_limit(code.len() + 2);
+    assert_eq!(vm.cpu.pc, VECTOR + 4);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::Supervisor);
+    assert_eq!(vm.cpu.regs[11], 2, "scause = illegal instruction");
+}
+
+#[test]
+fn mret_returns_to_the_mode_in_mpp_at_mepc() {
+    let supervisor = 0b01 << 11;
+    let code = trap_return(0x341, 0x300, supervisor, MRET);
+    let mut vm = vm_with(PrivilegeMode::Machine, &code, &[READ_MSTATUS], &[]);
+    vm.run_with_limit(code.len() + 1);
+    assert_eq!(vm.cpu.pc, TARGET + 4);
+    assert_eq!(vm.cpu.priv_mode, PrivilegeMode::Supervisor);
+    let mstatus = vm.cpu.regs[10];
+    assert_eq!(mstatus & MSTATUS_MPP, 0);
+    assert_ne!(mstatus & MSTATUS_MPIE, 0);
+}
+
+#[test]
+fn mret_below_machine_mode_is_an_illegal_instruction() {
+    let code = trap_return(0x341, 0x300, 0, MRET);
+    let mut vm = vm_with(PrivilegeMode::Supervisor, &code, &[], &[READ_SCAUSE]);
+    vm.run_with_limit(code.len() + 1);
+    assert_eq!(vm.cpu.pc, VECTOR + 4);
+    assert_eq!(vm.cpu.regs[11], 2, "scause = illegal instruction");
+}
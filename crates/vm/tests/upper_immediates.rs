@@ -0,0 +1,62 @@
+mod common;
+
+use common::{vm_with_program, CODE_BASE};
+use vm::decoder::{decode_compressed, decode_full};
+use vm::instruction::Instruction;
+
+const LUI: u32 = 0x37;
+const AUIPC: u32 = 0x17;
+
+fn u_type(imm20: u32, rd: u32, opcode: u32) -> u32 {
+    (imm20 << 12) | (rd << 7) | opcode
+}
+
+#[test]
+fn upper_immediates_decode_signed() {
+    assert_eq!(
+        decode_full(u_type(0x80000, 5, LUI)),
+        Some(Instruction::Lui {
+            rd: 5,
+            imm: -0x80000
+        })
+    );
+    assert_eq!(
+        decode_full(u_type(0xfffff, 5, AUIPC)),
+        Some(Instruction::Auipc { rd: 5, imm: -1 })
+    );
+    assert_eq!(
+        decode_full(u_type(0x7ffff, 5, LUI)),
+        Some(Instruction::Lui {
+            rd: 5,
+            imm: 0x7ffff
+        })
+    );
+    // lui x10, 0xfffe0 and c.lui a0, 0xfffe0 decode to the same instruction.
+    assert_eq!(
+        decode_full(u_type(0xfffe0, 10, LUI)),
+        decode_compressed(0x7501)
+    );
+}
+
+#[test]
+fn upper_immediates_with_bit_19_set() {
+    let mut vm = vm_with_program(&[
+        u_type(0x80000, 5, LUI),    // lui x5, 0x80000
+        u_type(0xfffff, 6, LUI),    // lui x6, 0xfffff
+        u_type(0x80000, 7, AUIPC),  // auipc x7, 0x80000 (pc CODE_BASE + 8)
+        u_type(0xfffff, 28, AUIPC), // auipc x28, 0xfffff (pc CODE_BASE + 0xc)
+        u_type(0x00001, 29, AUIPC), // auipc x29, 0x1 (pc CODE_BASE + 0x10)
+    ]);
+    vm.run_with_limit(5);
+
+    assert_eq!(vm.cpu.regs[5], 0x8000_0000, "lui 0x80000");
+    assert_eq!(vm.cpu.regs[6], 0xffff_f000, "lui 0xfffff");
+    // auipc adds to its own PC, wrapping like any 32-bit add.
+    assert_eq!(vm.cpu.regs[7], CODE_BASE + 8 + 0x8000_0000, "auipc 0x80000");
+    assert_eq!(
+        vm.cpu.regs[28],
+        (CODE_BASE + 0xc).wrapping_add(0xffff_f000),
+        "auipc 0xfffff"
+    );
+    assert_eq!(vm.cpu.regs[29], CODE_BASE + 0x10 + 0x1000, "auipc 0x1");
+}
@@ -0,0 +1,61 @@
+use vm::memory::{MemError, Perms, Sv32Memory, VirtualAddress, MMU, PAGE_SIZE};
+use vm::metering::{MemoryAccessKind, NoopMeter};
+
+const BASE: u32 = 0x2000;
+
+#[test]
+fn write_into_unmapped_range_reports_first_unmapped_byte() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    assert!(memory.map_range(VirtualAddress(BASE), PAGE_SIZE, Perms::rw_kernel()));
+
+    // Straddles the mapped page and the unmapped one after it.
+    let start = BASE + PAGE_SIZE as u32 - 2;
+    assert_eq!(
+        memory.try_write_bytes(VirtualAddress(start), &[1, 2, 3, 4]),
+        Err(MemError::Unmapped {
+            addr: VirtualAddress(BASE + PAGE_SIZE as u32)
+        })
+    );
+    // Nothing is written when any page is rejected.
+    assert_eq!(
+        memory.load_byte(VirtualAddress(start), &mut meter, MemoryAccessKind::Load),
+        Some(0)
+    );
+}
+
+#[test]
+fn write_into_read_only_page_is_rejected() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    assert!(memory.map_range(VirtualAddress(BASE), PAGE_SIZE, Perms::rw_kernel()));
+    let read_only = Perms::new(true, false, false, false);
+    assert!(memory.protect_range(VirtualAddress(BASE), PAGE_SIZE, read_only));
+
+    assert_eq!(
+        memory.try_write_bytes(VirtualAddress(BASE + 8), &[0xaa]),
+        Err(MemError::ReadOnly {
+            addr: VirtualAddress(BASE + 8)
+        })
+    );
+}
+
+#[test]
+fn write_into_mapped_range_succeeds() {
+    let memory = Sv32Memory::new(64 * 1024, PAGE_SIZE);
+    let mut meter = NoopMeter;
+    assert!(memory.map_range(VirtualAddress(BASE), 2 * PAGE_SIZE, Perms::rw_kernel()));
+
+    let start = BASE + PAGE_SIZE as u32 - 2;
+    assert_eq!(
+        memory.try_write_bytes(VirtualAddress(start), &[1, 2, 3, 4]),
+        Ok(())
+    );
+    assert_eq!(
+        memory.load_u32(VirtualAddress(start), &mut meter, MemoryAccessKind::Load),
+        Some(0x0403_0201)
+    );
+    assert_eq!(
+        memory.try_write_bytes(VirtualAddress(0x9000_0000), &[]),
+        Ok(())
+    );
+}
@@ -2,7 +2,7 @@
 /// 
 /// This module uses the existing infrastructure from the compiler and VM crates:
 /// - `compiler::parse_elf_from_bytes` for ELF parsing
-/// - `vm::decoder::decode` for RISC-V instruction decoding
+/// - `vm::decoder::decode_instruction` for RISC-V instruction decoding
 /// - `vm::instruction::Instruction` for instruction representation
 /// 
 /// The purpose is to extract instructions from ELF files in the same way
@@ -43,7 +43,7 @@ pub fn decode_elf(path: &Path) -> Result<Vec<ElfInstruction>> {
             let remaining = &code[offset..];
             
             // Use VM's decoder to decode the instruction
-            if let Some((instruction, size)) = decoder::decode(remaining) {
+            if let Some((instruction, size)) = decoder::decode_instruction(remaining) {
                 let raw = match size {
                     2 => {
                         // 16-bit compressed instruction
@@ -137,8 +137,8 @@ fn format_instruction(inst: &Instruction) -> String {
         Jalr { rd, rs1, offset, compressed: _ } => format!("jalr x{}, {}(x{})", rd, offset, rs1),
         
         // Upper immediate instructions
-        Lui { rd, imm } => format!("lui x{}, 0x{:x}", rd, imm),
-        Auipc { rd, imm } => format!("auipc x{}, 0x{:x}", rd, imm),
+        Lui { rd, imm } => format!("lui x{}, 0x{:x}", rd, imm & 0xfffff),
+        Auipc { rd, imm } => format!("auipc x{}, 0x{:x}", rd, imm & 0xfffff),
         
         // System instructions
         Ecall => "ecall".to_string(),